@@ -0,0 +1,386 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+// Canon's CR3 format is based on ISO BMFF. The EXIF data is stored in the
+// CMT boxes that are located in a `uuid` box inside of `moov`, each holding
+// a standalone TIFF structure with a single IFD:
+// - CMT1: IFD0
+// - CMT2: ExifIFD
+// - CMT3: Canon MakerNote
+// - CMT4: GPS IFD
+// See: https://github.com/lclevy/canon_cr3
+
+use std::io::BufReader;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+
+use crate::endian::Endian;
+use crate::exif_tag::ExifTag;
+use crate::general_file_io::open_read_file;
+use crate::general_file_io::open_write_file;
+use crate::heif::box_header::BoxHeader;
+use crate::heif::box_type::BoxType;
+use crate::ifd::ExifTagGroup;
+use crate::ifd::ImageFileDirectory;
+use crate::io_error;
+use crate::metadata::Metadata;
+use crate::u8conversion::*;
+
+/// The usertype of the `uuid` box in `moov` that contains the CMT boxes
+const CANON_UUID: [u8; 16] = [
+    0x85, 0xc0, 0xb6, 0x87, 0x82, 0x0f, 0x11, 0xe0,
+    0x81, 0x11, 0xf4, 0xce, 0x46, 0x2b, 0x6a, 0x48
+];
+
+/// The CMT boxes that hold an IFD and the group that it gets decoded as
+const CMT_IFD_BOXES: [(&[u8; 4], ExifTagGroup); 3] = [
+    (b"CMT1", ExifTagGroup::GENERIC),
+    (b"CMT2", ExifTagGroup::EXIF),
+    (b"CMT4", ExifTagGroup::GPS),
+];
+
+/// The CMT box with the maker note, which is provided as `MakerNote` tag
+const CMT_MAKER_NOTE_BOX: &[u8; 4] = b"CMT3";
+
+/// The type and location of a box, where `content_start` is the position
+/// right after its header
+struct
+BoxLocation
+{
+    box_type:      BoxType,
+    content_start: u64,
+    end:           u64,
+}
+
+/// Reads the headers of the boxes located between the start and end
+/// position, without reading their content
+fn
+read_boxes
+<T: Read + Seek>
+(
+    cursor: &mut T,
+    start:  u64,
+    end:    u64,
+)
+-> Result<Vec<BoxLocation>, std::io::Error>
+{
+    let mut boxes    = Vec::new();
+    let mut position = start;
+
+    while position + 8 <= end
+    {
+        cursor.seek(SeekFrom::Start(position))?;
+        let header = BoxHeader::read_box_header(cursor)?;
+
+        // A size of 0 indicates that the box extends to the end
+        // See also: ISO/IEC 14496-12:2015, § 4.2
+        let box_end = match header.get_box_size()
+        {
+            0    => end,
+            size => position + size,
+        };
+
+        if box_end > end || box_end < position + header.get_header_size()
+        {
+            return io_error!(InvalidData, format!("Invalid size of box {:?} in CR3 file!", header.get_box_type()));
+        }
+
+        boxes.push(BoxLocation {
+            box_type:      header.get_box_type(),
+            content_start: position + header.get_header_size(),
+            end:           box_end,
+        });
+        position = box_end;
+    }
+
+    return Ok(boxes);
+}
+
+/// Locates the CMT boxes by going through `moov` and Canon's `uuid` box
+fn
+find_cmt_boxes
+<T: Read + Seek>
+(
+    cursor: &mut T
+)
+-> Result<Vec<BoxLocation>, std::io::Error>
+{
+    let file_length = cursor.seek(SeekFrom::End(0))?;
+
+    let Some(moov) = read_boxes(cursor, 0, file_length)?
+        .into_iter()
+        .find(|location| location.box_type.to_4_bytes() == b"moov")
+    else {
+        return io_error!(InvalidData, "Could not find moov box in CR3 file!");
+    };
+
+    let Some(canon_box) = read_boxes(cursor, moov.content_start, moov.end)?
+        .into_iter()
+        .find(|location| location.box_type == BoxType::uuid { usertype: CANON_UUID })
+    else {
+        return io_error!(InvalidData, "Could not find Canon uuid box in CR3 file!");
+    };
+
+    return read_boxes(cursor, canon_box.content_start, canon_box.end);
+}
+
+/// Decodes the TIFF structure of a CMT box and returns its endianness and
+/// the IFD as the given group. Other IFDs that the TIFF structure may link
+/// to are ignored, as they are not expected in a CR3 file.
+fn
+decode_cmt_box
+<T: Read + Seek>
+(
+    cursor:   &mut T,
+    location: &BoxLocation,
+    group:    ExifTagGroup,
+)
+-> Result<(Endian, ImageFileDirectory), std::io::Error>
+{
+    let mut header = [0u8; 8];
+    cursor.seek(SeekFrom::Start(location.content_start))?;
+    cursor.read_exact(&mut header)?;
+
+    let endian = match header[0..2]
+    {
+        [0x49, 0x49] => Endian::Little,
+        [0x4d, 0x4d] => Endian::Big,
+        _            => return io_error!(InvalidData, format!("Illegal endian information in CMT box: {:?}", &header[0..2]))
+    };
+
+    if from_u8_vec_res_macro!(u16, &header[2..4], &endian)? != 0x002a
+    {
+        return io_error!(InvalidData, "Invalid TIFF header in CMT box!");
+    }
+
+    let ifd_offset = from_u8_vec_res_macro!(u32, &header[4..8], &endian)?;
+    cursor.seek(SeekFrom::Start(location.content_start + ifd_offset as u64))?;
+
+    let mut ifds = Vec::new();
+    ImageFileDirectory::decode_ifd(
+        cursor,
+        location.content_start,
+        &endian,
+        &group,
+        0,
+        &mut ifds,
+        false
+    )?;
+
+    let Some(index) = ifds.iter().position(|ifd| ifd.get_ifd_type() == group) else {
+        return io_error!(Other, format!("Could not decode {group:?} IFD of CMT box!"));
+    };
+
+    return Ok((endian, ifds.swap_remove(index)));
+}
+
+fn
+read_box_content
+<T: Read + Seek>
+(
+    cursor:   &mut T,
+    location: &BoxLocation,
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut content = vec![0u8; (location.end - location.content_start) as usize];
+    cursor.seek(SeekFrom::Start(location.content_start))?;
+    cursor.read_exact(&mut content)?;
+    return Ok(content);
+}
+
+/// Gets the tags of an IFD that get written to a CMT box, i.e. all except
+/// the `MakerNote`, which has a CMT box of its own, sorted by their hex value
+fn
+get_cmt_tags
+(
+    ifd: Option<&ImageFileDirectory>
+)
+-> Vec<ExifTag>
+{
+    let Some(ifd) = ifd else { return Vec::new(); };
+    let mut tags = ifd.get_tags()
+        .iter()
+        .filter(|tag| !matches!(tag, ExifTag::MakerNote(_)))
+        .cloned()
+        .collect::<Vec<ExifTag>>();
+    tags.sort_by_key(|tag| tag.as_u16());
+    return tags;
+}
+
+fn
+generic_read_metadata
+<T: Read + Seek>
+(
+    cursor: &mut T
+)
+-> Result<Metadata, std::io::Error>
+{
+    let cmt_boxes = find_cmt_boxes(cursor)?;
+
+    let mut endian = None;
+    let mut ifds   = Vec::new();
+
+    for (box_type, group) in CMT_IFD_BOXES
+    {
+        let Some(location) = cmt_boxes.iter().find(|location| location.box_type.to_4_bytes() == box_type) else {
+            continue;
+        };
+
+        let (cmt_endian, ifd) = decode_cmt_box(cursor, location, group)?;
+        endian.get_or_insert(cmt_endian);
+        ifds.push(ifd);
+    }
+
+    if let Some(location) = cmt_boxes.iter().find(|location| location.box_type.to_4_bytes() == CMT_MAKER_NOTE_BOX)
+    {
+        let maker_note = ExifTag::MakerNote(read_box_content(cursor, location)?);
+        match ifds.iter_mut().find(|ifd| ifd.get_ifd_type() == ExifTagGroup::EXIF)
+        {
+            Some(exif_ifd) => exif_ifd.set_tag(maker_note),
+            None           => ifds.push(ImageFileDirectory::new_with_tags(vec![maker_note], ExifTagGroup::EXIF, 0)),
+        }
+    }
+
+    return Ok(Metadata::new_from_decoded_ifds(endian.unwrap_or(Endian::Little), ifds));
+}
+
+/// Writes the metadata to the CMT boxes. As changing the size of a box would
+/// require updating the chunk offsets of all tracks, the new data has to fit
+/// into the existing boxes, which get padded with zeros if necessary.
+/// Boxes whose data is unchanged are not touched. The maker note in CMT3 is
+/// only written if the `MakerNote` tag is present.
+/// Everything gets prepared first, so the file is only modified if all boxes
+/// can be written.
+fn
+generic_write_metadata
+<T: Read + Write + Seek>
+(
+    cursor:   &mut T,
+    metadata: &Metadata
+)
+-> Result<(), std::io::Error>
+{
+    let cmt_boxes  = find_cmt_boxes(cursor)?;
+    let mut writes = Vec::new();
+
+    if metadata.get_ifds().iter().any(|ifd| ifd.get_ifd_type() == ExifTagGroup::INTEROP)
+    {
+        log::warn!("CR3 files have no box for the Interop IFD, its tags are not written!");
+    }
+
+    for (box_type, group) in CMT_IFD_BOXES
+    {
+        let tags = get_cmt_tags(metadata.get_ifd(group, 0));
+
+        let Some(location) = cmt_boxes.iter().find(|location| location.box_type.to_4_bytes() == box_type) else {
+            if tags.is_empty()
+            {
+                continue;
+            }
+            return io_error!(
+                Unsupported,
+                format!("CR3 file has no {} box for the {group:?} tags and adding it is not supported!", String::from_utf8_lossy(box_type))
+            );
+        };
+
+        let (endian, ifd) = decode_cmt_box(cursor, location, group)?;
+        if get_cmt_tags(Some(&ifd)) == tags
+        {
+            continue;
+        }
+
+        // Encode the tags as IFD0 of a standalone TIFF structure
+        let cmt_metadata = Metadata::new_from_decoded_ifds(
+            endian,
+            vec![ImageFileDirectory::new_with_tags(tags, ExifTagGroup::GENERIC, 0)]
+        );
+        writes.push((location, cmt_metadata.encode()?));
+    }
+
+    if let Some(ExifTag::MakerNote(maker_note)) = metadata.get_tag(&ExifTag::MakerNote(Vec::new())).next()
+    {
+        if let Some(location) = cmt_boxes.iter().find(|location| location.box_type.to_4_bytes() == CMT_MAKER_NOTE_BOX)
+        {
+            if read_box_content(cursor, location)? != *maker_note
+            {
+                writes.push((location, maker_note.clone()));
+            }
+        }
+    }
+
+    // Trailing zeros of the data (e.g. due to alignment) don't have to fit 
+    // into the box, as it gets padded with zeros anyway
+    for (location, data) in writes.iter_mut()
+    {
+        let box_length = location.end - location.content_start;
+        data.truncate(data.iter().rposition(|byte| *byte != 0x00).map_or(0, |index| index + 1));
+        if data.len() as u64 > box_length
+        {
+            return io_error!(
+                Unsupported,
+                format!(
+                    "New data for {} needs {} bytes but the box only has {} bytes!",
+                    String::from_utf8_lossy(&location.box_type.to_4_bytes()),
+                    data.len(),
+                    box_length
+                )
+            );
+        }
+        data.resize(box_length as usize, 0x00);
+    }
+
+    for (location, data) in writes
+    {
+        cursor.seek(SeekFrom::Start(location.content_start))?;
+        cursor.write_all(&data)?;
+    }
+
+    return Ok(());
+}
+
+pub(crate) fn
+read_metadata
+(
+    file_buffer: &[u8]
+)
+-> Result<Metadata, std::io::Error>
+{
+    return generic_read_metadata(&mut Cursor::new(file_buffer));
+}
+
+pub(crate) fn
+file_read_metadata
+(
+    path: &Path
+)
+-> Result<Metadata, std::io::Error>
+{
+    return generic_read_metadata(&mut BufReader::new(open_read_file(path)?));
+}
+
+pub(crate) fn
+write_metadata
+(
+    file_buffer: &mut Vec<u8>,
+    metadata:    &Metadata
+)
+-> Result<(), std::io::Error>
+{
+    return generic_write_metadata(&mut Cursor::new(file_buffer), metadata);
+}
+
+pub(crate) fn
+file_write_metadata
+(
+    path:     &Path,
+    metadata: &Metadata
+)
+-> Result<(), std::io::Error>
+{
+    return generic_write_metadata(&mut open_write_file(path)?, metadata);
+}
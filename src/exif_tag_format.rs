@@ -83,6 +83,9 @@ ExifTagFormat
 			0x000a  => Some(ExifTagFormat::RATIONAL64S),
 			0x000b  => Some(ExifTagFormat::FLOAT),
 			0x000c  => Some(ExifTagFormat::DOUBLE),
+			// IFD offsets (TIFF Technical Note 1), e.g. used for the SubIFDs
+			// of camera raw files, are stored just like INT32U values
+			0x000d  => Some(ExifTagFormat::INT32U),
 			_       => None,
 		}
 	}
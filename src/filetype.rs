@@ -5,6 +5,7 @@ use std::io;
 use std::io::ErrorKind;
 use std::io::Read;
 use std::io::Seek;
+use std::io::SeekFrom;
 use std::path::Path;
 use std::str::FromStr;
 
@@ -22,11 +23,44 @@ FileExtension
     TIFF,
     WEBP,
     HEIF,
+    DNG,        // Camera raw formats that are based on TIFF, see `is_camera_raw`
+    CR2,
+    NEF,
+    ARW,
+    ORF,
+    RW2,
+    PEF,
+    CR3,        // Canon's ISO BMFF based raw format
+    MP4,        // Videos, which only support the QuickTime metadata
+    MOV,
 }
 
 impl
 FileExtension
 {
+    /// Checks if this is one of the camera raw formats that are based on 
+    /// TIFF, either with a slightly different header (CR2, ORF, RW2) or as
+    /// an ordinary TIFF file with additional IFDs (DNG, NEF, ARW, PEF). 
+    /// The latter can't be told apart from TIFF by their content.
+    pub fn
+    is_camera_raw
+    (
+        &self
+    )
+    -> bool
+    {
+        return matches!(
+            self, 
+            FileExtension::DNG | 
+            FileExtension::CR2 | 
+            FileExtension::NEF | 
+            FileExtension::ARW | 
+            FileExtension::ORF | 
+            FileExtension::RW2 | 
+            FileExtension::PEF
+        );
+    }
+
     pub fn
     auto_detect
     <T: Seek + Read>
@@ -57,6 +91,23 @@ FileExtension
                 return Some(FileExtension::JPEG);
             }
 
+            // Canon CR2, a TIFF with "CR" right after the TIFF header
+            [0x49, 0x49, 0x2A, 0x00, _, _, _, _, 0x43, 0x52, ..] => {
+                return Some(FileExtension::CR2);
+            }
+
+            // Olympus ORF, a TIFF with "RO", "OR" or "SR" as magic number
+            [0x49, 0x49, 0x52, 0x4F, ..] |
+            [0x4D, 0x4D, 0x4F, 0x52, ..] |
+            [0x49, 0x49, 0x53, 0x52, ..] => {
+                return Some(FileExtension::ORF);
+            }
+
+            // Panasonic RW2, a TIFF with 0x55 as magic number
+            [0x49, 0x49, 0x55, 0x00, ..] => {
+                return Some(FileExtension::RW2);
+            }
+
             // TIFF, little endian
             [0x49, 0x49, 0x2A, 0x00, ..] => {
                 return Some(FileExtension::TIFF);
@@ -87,19 +138,15 @@ FileExtension
                 return Some(FileExtension::JXL);
             }
 
-            // HEIC/HEIF/AVIF
+            // HEIC/HEIF/AVIF and other ISO BMFF based files, which are 
+            // distinguished by the brands listed in the ftyp box
             // length       f     t     y     p 
-              [_, _, _, _, 0x66, 0x74, 0x79, 0x70, 0x68, 0x65, 0x69, 0x63, ..]  // heic
-            | [_, _, _, _, 0x66, 0x74, 0x79, 0x70, 0x68, 0x65, 0x69, 0x66, ..]  // heif
-            | [_, _, _, _, 0x66, 0x74, 0x79, 0x70, 0x61, 0x76, 0x69, 0x66, ..]  // avif
-            => 
+            [_, _, _, _, 0x66, 0x74, 0x79, 0x70, ..] => 
             {
-                return Some(FileExtension::HEIF)
+                cursor.seek(SeekFrom::Start(0)).ok()?;
+                return IsoBmffType::auto_detect(cursor)?.get_file_extension();
             }
 
-            // TODO: Other HEIF formats, e.g. ftypmif1, see also:
-            // https://www.loc.gov/preservation/digital/formats/fdd/fdd000526.shtml
-
             _ => { 
                 return None;
             }
@@ -107,6 +154,172 @@ FileExtension
     }
 }
 
+/// The kind of an ISO base media file format (ISO/IEC 14496-12) based file,
+/// as determined by the major and compatible brands of its `ftyp` box
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum
+IsoBmffType
+{
+    HEIF,           // HEIF image, e.g. brands heic, heix, mif1
+    HEIF_SEQUENCE,  // HEIF image sequence, e.g. brands hevc, msf1
+    AVIF,
+    AVIF_SEQUENCE,
+    JXL,
+    CR3,
+    MP4,
+    MOV,
+}
+
+// Brands that identify the kind of file on their own
+// See: https://mp4ra.org/registered-types/brands
+const SPECIFIC_BRANDS: [(&[u8; 4], IsoBmffType); 18] = [
+    (b"avif", IsoBmffType::AVIF),
+    (b"avio", IsoBmffType::AVIF),
+    (b"avis", IsoBmffType::AVIF_SEQUENCE),
+    (b"heic", IsoBmffType::HEIF),
+    (b"heix", IsoBmffType::HEIF),
+    (b"heim", IsoBmffType::HEIF),
+    (b"heis", IsoBmffType::HEIF),
+    (b"heif", IsoBmffType::HEIF),
+    (b"hevc", IsoBmffType::HEIF_SEQUENCE),
+    (b"hevx", IsoBmffType::HEIF_SEQUENCE),
+    (b"hevm", IsoBmffType::HEIF_SEQUENCE),
+    (b"hevs", IsoBmffType::HEIF_SEQUENCE),
+    (b"jxl ", IsoBmffType::JXL),
+    (b"crx ", IsoBmffType::CR3),
+    (b"qt  ", IsoBmffType::MOV),
+    (b"M4V ", IsoBmffType::MP4),
+    (b"M4A ", IsoBmffType::MP4),
+    (b"f4v ", IsoBmffType::MP4),
+];
+
+// Brands that are shared by several kinds of files, so they are only used
+// if none of the brands is specific
+const GENERIC_BRANDS: [(&[u8; 4], IsoBmffType); 16] = [
+    (b"mif1", IsoBmffType::HEIF),
+    (b"mif2", IsoBmffType::HEIF),
+    (b"miaf", IsoBmffType::HEIF),
+    (b"msf1", IsoBmffType::HEIF_SEQUENCE),
+    (b"isom", IsoBmffType::MP4),
+    (b"iso2", IsoBmffType::MP4),
+    (b"iso4", IsoBmffType::MP4),
+    (b"iso5", IsoBmffType::MP4),
+    (b"iso6", IsoBmffType::MP4),
+    (b"mp41", IsoBmffType::MP4),
+    (b"mp42", IsoBmffType::MP4),
+    (b"avc1", IsoBmffType::MP4),
+    (b"dash", IsoBmffType::MP4),
+    (b"mmp4", IsoBmffType::MP4),
+    (b"3gp4", IsoBmffType::MP4),
+    (b"3gp5", IsoBmffType::MP4),
+];
+
+impl
+IsoBmffType
+{
+    /// Determines the kind of file based on the major brand and the list of
+    /// compatible brands of the `ftyp` box. Brands that identify the kind of
+    /// file on their own take precedence over generic brands like `mif1` or 
+    /// `isom`, with the major brand being checked first in both cases. 
+    pub fn
+    from_brands
+    (
+        major_brand:       &[u8; 4],
+        compatible_brands: &[[u8; 4]],
+    )
+    -> Option<Self>
+    {
+        let brands = std::iter::once(major_brand).chain(compatible_brands.iter());
+
+        for table in [&SPECIFIC_BRANDS[..], &GENERIC_BRANDS[..]]
+        {
+            for brand in brands.clone()
+            {
+                if let Some((_, file_type)) = table.iter().find(|(known, _)| *known == brand)
+                {
+                    return Some(*file_type);
+                }
+            }
+        }
+
+        return None;
+    }
+
+    /// Determines the kind of file by parsing its `ftyp` box, which has to 
+    /// be the first box - or the second one in case of a JXL, which starts
+    /// with its signature box. 
+    pub fn
+    auto_detect
+    <T: Seek + Read>
+    (
+        cursor: &mut T
+    )
+    -> Option<Self>
+    {
+        let mut header = [0u8; 8];
+        cursor.read_exact(&mut header).ok()?;
+
+        // Skip the JXL signature box
+        if header == [0x00, 0x00, 0x00, 0x0C, 0x4A, 0x58, 0x4C, 0x20]
+        {
+            cursor.seek(SeekFrom::Current(4)).ok()?;
+            cursor.read_exact(&mut header).ok()?;
+        }
+
+        if &header[4..8] != b"ftyp"
+        {
+            return None;
+        }
+
+        // The ftyp box consists of the major brand, the minor version and
+        // the list of compatible brands, each using 4 bytes
+        // See ISO/IEC 14496-12:2015, § 4.3
+        let box_size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        if box_size < 16
+        {
+            return None;
+        }
+
+        let mut content = Vec::new();
+        cursor.take(box_size - 8).read_to_end(&mut content).ok()?;
+        if content.len() as u64 != box_size - 8
+        {
+            return None;
+        }
+
+        let major_brand       = content[0..4].try_into().ok()?;
+        let compatible_brands = content[8..]
+            .chunks_exact(4)
+            .filter_map(|brand| brand.try_into().ok())
+            .collect::<Vec<[u8; 4]>>();
+
+        return Self::from_brands(major_brand, &compatible_brands);
+    }
+
+    /// Gets the file extension that is used for reading and writing metadata
+    /// of this kind of file
+    pub fn
+    get_file_extension
+    (
+        &self
+    )
+    -> Option<FileExtension>
+    {
+        return match self
+        {
+            IsoBmffType::HEIF
+            | IsoBmffType::HEIF_SEQUENCE
+            | IsoBmffType::AVIF
+            | IsoBmffType::AVIF_SEQUENCE => Some(FileExtension::HEIF),
+            IsoBmffType::JXL             => Some(FileExtension::JXL),
+            IsoBmffType::CR3             => Some(FileExtension::CR3),
+            IsoBmffType::MP4             => Some(FileExtension::MP4),
+            IsoBmffType::MOV             => Some(FileExtension::MOV),
+        };
+    }
+}
+
 impl 
 FromStr 
 for 
@@ -135,6 +348,26 @@ FileExtension
                 => Ok(FileExtension::TIFF),
             "webp" 
                 => Ok(FileExtension::WEBP),
+            "dng"
+                => Ok(FileExtension::DNG),
+            "cr2"
+                => Ok(FileExtension::CR2),
+            "nef" | "nrw"
+                => Ok(FileExtension::NEF),
+            "arw"
+                => Ok(FileExtension::ARW),
+            "orf"
+                => Ok(FileExtension::ORF),
+            "rw2"
+                => Ok(FileExtension::RW2),
+            "pef"
+                => Ok(FileExtension::PEF),
+            "cr3"
+                => Ok(FileExtension::CR3),
+            "mp4" | "m4v"
+                => Ok(FileExtension::MP4),
+            "mov" | "qt"
+                => Ok(FileExtension::MOV),
             _ => io_error!(Unsupported, format!("Unknown file type: {}", input)),
         }
     }
@@ -184,6 +417,17 @@ mod tests
             ("tif",  FileExtension::TIFF),
             ("tiff", FileExtension::TIFF),
             ("webp", FileExtension::WEBP),
+            ("dng",  FileExtension::DNG),
+            ("CR2",  FileExtension::CR2),
+            ("nef",  FileExtension::NEF),
+            ("arw",  FileExtension::ARW),
+            ("orf",  FileExtension::ORF),
+            ("rw2",  FileExtension::RW2),
+            ("pef",  FileExtension::PEF),
+            ("cr3",  FileExtension::CR3),
+            ("mp4",  FileExtension::MP4),
+            ("m4v",  FileExtension::MP4),
+            ("MOV",  FileExtension::MOV),
         ];
 
         for (input, expected) in table 
@@ -193,4 +437,69 @@ mod tests
             assert_eq!(result.unwrap(), expected, "Parsed value mismatch for '{input}'");
         }
     }
+
+    #[test]
+    fn iso_bmff_brands() 
+    {
+        type BrandCase = (&'static [u8; 4], Vec<[u8; 4]>, Option<IsoBmffType>);
+
+        let table: Vec<BrandCase> = vec![
+            (b"heic", vec![*b"mif1", *b"heic"],           Some(IsoBmffType::HEIF)),
+            (b"mif1", vec![*b"mif1", *b"heix"],           Some(IsoBmffType::HEIF)),
+            (b"mif1", vec![*b"mif1", *b"miaf"],           Some(IsoBmffType::HEIF)),
+            (b"mif1", vec![*b"avif", *b"mif1", *b"miaf"], Some(IsoBmffType::AVIF)),
+            (b"msf1", vec![*b"msf1", *b"hevc"],           Some(IsoBmffType::HEIF_SEQUENCE)),
+            (b"msf1", vec![*b"msf1", *b"iso8"],           Some(IsoBmffType::HEIF_SEQUENCE)),
+            (b"avis", vec![*b"avif", *b"avis", *b"msf1"], Some(IsoBmffType::AVIF_SEQUENCE)),
+            (b"jxl ", vec![*b"jxl "],                     Some(IsoBmffType::JXL)),
+            (b"crx ", vec![*b"crx ", *b"isom"],           Some(IsoBmffType::CR3)),
+            (b"isom", vec![*b"isom", *b"iso2", *b"mp41"], Some(IsoBmffType::MP4)),
+            (b"qt  ", vec![*b"qt  "],                     Some(IsoBmffType::MOV)),
+            (b"abcd", vec![*b"efgh"],                     None),
+        ];
+
+        for (major_brand, compatible_brands, expected) in table 
+        {
+            assert_eq!(
+                IsoBmffType::from_brands(major_brand, &compatible_brands), 
+                expected,
+                "Brand mismatch for '{}'", String::from_utf8_lossy(major_brand)
+            );
+        }
+    }
+
+    #[test]
+    fn iso_bmff_auto_detect() 
+    {
+        // ftyp box with major brand mif1, minor version 0, compatible 
+        // brands mif1 and heic, followed by the start of a meta box
+        let mut data = vec![0x00, 0x00, 0x00, 0x18];
+        data.extend(b"ftypmif1\0\0\0\0mif1heic\0\0\0\x08meta");
+
+        assert_eq!(IsoBmffType::auto_detect(&mut std::io::Cursor::new(&data)), Some(IsoBmffType::HEIF));
+        assert_eq!(FileExtension::auto_detect(&mut std::io::Cursor::new(&data)), Some(FileExtension::HEIF));
+
+        // Truncated ftyp box
+        assert_eq!(IsoBmffType::auto_detect(&mut std::io::Cursor::new(&data[..20])), None);
+    }
+
+    #[test]
+    fn raw_auto_detect() 
+    {
+        let table = vec![
+            (b"II*\0\x10\0\0\0CR\x02\0", Some(FileExtension::CR2)),
+            (b"IIRO\x08\0\0\0\0\0\0\0", Some(FileExtension::ORF)),
+            (b"MMOR\0\0\0\x08\0\0\0\0", Some(FileExtension::ORF)),
+            (b"IIU\0\x18\0\0\0\0\0\0\0", Some(FileExtension::RW2)),
+            (b"II*\0\x08\0\0\0\0\0\0\0", Some(FileExtension::TIFF)),
+        ];
+
+        for (data, expected) in table 
+        {
+            assert_eq!(FileExtension::auto_detect(&mut std::io::Cursor::new(data)), expected);
+        }
+
+        assert!(FileExtension::NEF.is_camera_raw());
+        assert!(!FileExtension::TIFF.is_camera_raw());
+    }
 }
\ No newline at end of file
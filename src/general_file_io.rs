@@ -34,6 +34,9 @@ macro_rules! io_error_plain {
 
 use std::fs::File;
 use std::fs::OpenOptions;
+use std::io::Read;
+use std::io::Seek;
+use std::io::Write;
 use std::path::Path;
 
 pub(crate) fn
@@ -72,4 +75,27 @@ open_write_file
 		.open(path)
 }
 
+/// Loads the entire file into memory, applies the modification and writes
+/// the result back, possibly truncating the file if it got shorter
+pub(crate) fn
+modify_file
+(
+	path:         &Path,
+	modification: impl FnOnce(&mut Vec<u8>) -> Result<(), std::io::Error>
+)
+-> Result<(), std::io::Error>
+{
+	let mut file = open_write_file(path)?;
+	let mut file_buffer: Vec<u8> = Vec::new();
+	file.read_to_end(&mut file_buffer)?;
+
+	modification(&mut file_buffer)?;
+
+	file.seek(std::io::SeekFrom::Start(0))?;
+	file.write_all(&file_buffer)?;
+	file.set_len(file_buffer.len() as u64)?;
+
+	return Ok(());
+}
+
 pub(crate) use io_error;
\ No newline at end of file
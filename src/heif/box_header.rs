@@ -86,13 +86,39 @@ BoxHeader
         }
     }
 
-    pub(super) fn
+    pub(crate) fn
     read_box_header
     <T: Seek + Read>
     (
         cursor: &mut T
     )
     -> Result<Self, std::io::Error>
+    {
+        return Self::read_box_header_internal(cursor, false);
+    }
+
+    /// Reads the header of a box that never extends the full box, regardless
+    /// of its type. This is the case for e.g. the references in an iref box,
+    /// which use box types like `iloc` that otherwise denote full boxes.
+    pub(crate) fn
+    read_simple_box_header
+    <T: Seek + Read>
+    (
+        cursor: &mut T
+    )
+    -> Result<Self, std::io::Error>
+    {
+        return Self::read_box_header_internal(cursor, true);
+    }
+
+    fn
+    read_box_header_internal
+    <T: Seek + Read>
+    (
+        cursor:        &mut T,
+        always_simple: bool,
+    )
+    -> Result<Self, std::io::Error>
     {
         // Read in the size
         let box_size = read_be_u32(cursor)?;
@@ -109,7 +135,7 @@ BoxHeader
             flags:       None,
         };
 
-        if box_type.extends_fullbox()
+        if !always_simple && box_type.extends_fullbox()
         {
             header.version = Some(read_1_bytes(cursor)?[0]);
             header.flags   = Some(read_3_bytes(cursor)?);
@@ -156,7 +182,7 @@ BoxHeader
         return Ok(header);
     }
 
-    pub(super) fn
+    pub(crate) fn
     get_box_size
     (
         &self
@@ -166,7 +192,7 @@ BoxHeader
         return self.box_size;
     }
 
-    pub(super) fn
+    pub(crate) fn
     get_box_type
     (
         &self
@@ -176,7 +202,7 @@ BoxHeader
         return self.box_type.clone();
     }
 
-    pub(super) fn
+    pub(crate) fn
     get_header_size
     (
         &self
@@ -186,7 +212,7 @@ BoxHeader
         return self.header_size;
     }
 
-    pub(super) fn
+    pub(crate) fn
     set_box_size
     (
         &mut self,
@@ -196,6 +222,16 @@ BoxHeader
         self.box_size = new_size;
     }
 
+    pub(crate) fn
+    set_box_type
+    (
+        &mut self,
+        new_type: BoxType
+    )
+    {
+        self.box_type = new_type;
+    }
+
     pub(super) fn
     set_box_type_via_string
     (
@@ -231,7 +267,33 @@ BoxHeader
         self.version = new_version;
     }
 
+    /// Gets the 24 bit flags of a full box header
     pub(super) fn
+    get_flags
+    (
+        &self
+    )
+    -> u32
+    {
+        let flags = self.flags.expect("BoxHeader: flags are not set");
+        return (flags[0] as u32) << 16 | (flags[1] as u32) << 8 | flags[2] as u32;
+    }
+
+    pub(super) fn
+    set_flags
+    (
+        &mut self,
+        new_flags: u32
+    )
+    {
+        self.flags = Some([
+            (new_flags >> 16) as u8,
+            (new_flags >>  8) as u8,
+            (new_flags      ) as u8,
+        ]);
+    }
+
+    pub(crate) fn
     serialize
     (
         &self
@@ -254,9 +316,9 @@ BoxHeader
         serialized.extend(self.box_type.to_4_bytes());
 
         // Serialize version and flags (if present)
-        if self.box_type.extends_fullbox()
+        if let Some(version) = self.version
         {
-            serialized.push(self.version.expect("BoxHeader: version is not set when serializing"));
+            serialized.push(version);
             for flag in self.flags.expect("BoxHeader: flags not set when serializing")
             {
                 serialized.push(flag);
@@ -18,6 +18,14 @@ BoxType
     iprp,
     ipco,
     ipma,
+    colr,
+    ispe,
+    cdsc,
+    dimg,
+    pixi,
+    irot,
+    imir,
+    clap,
     mdat,
     idat,
     pdin,
@@ -98,15 +106,17 @@ BoxType
 impl
 BoxType
 {
-    pub(super) fn
+    pub(crate) fn
     from_4_bytes
     (
         bytes: [u8; 4]
     )
     -> BoxType
     {
-        let box_type_str = std::str::from_utf8(&bytes).unwrap_or("");
-        match box_type_str
+        // Box types are interpreted as Latin-1, as QuickTime uses types like
+        // `©xyz` that are not valid UTF-8
+        let box_type_str = bytes.iter().map(|byte| *byte as char).collect::<String>();
+        match box_type_str.as_str()
         {
             "ftyp" => BoxType::ftyp,
             "meta" => BoxType::meta, 
@@ -120,6 +130,14 @@ BoxType
             "iprp" => BoxType::iprp, 
             "ipco" => BoxType::ipco, 
             "ipma" => BoxType::ipma, 
+            "colr" => BoxType::colr, 
+            "ispe" => BoxType::ispe, 
+            "cdsc" => BoxType::cdsc, 
+            "dimg" => BoxType::dimg, 
+            "pixi" => BoxType::pixi, 
+            "irot" => BoxType::irot, 
+            "imir" => BoxType::imir, 
+            "clap" => BoxType::clap, 
             "mdat" => BoxType::mdat, 
             "idat" => BoxType::idat, 
             "pdin" => BoxType::pdin,
@@ -194,17 +212,22 @@ BoxType
             "hmhd" => BoxType::hmhd,
             "sthd" => BoxType::sthd,
             "uuid" => BoxType::uuid { usertype: [0u8; 16] },
-            _      => BoxType::unknown { box_type: String::from(box_type_str) }
+            _      => BoxType::unknown { box_type: box_type_str.clone() }
         }
     }
 
-    pub(super) fn
+    pub(crate) fn
     to_4_bytes
     (
         &self
     )
     -> Vec<u8>
     {
+        if let BoxType::unknown { box_type } = self
+        {
+            return box_type.chars().map(|character| character as u8).collect();
+        }
+
         match self
         {
             BoxType::ftyp => "ftyp",
@@ -219,6 +242,14 @@ BoxType
             BoxType::iprp => "iprp", 
             BoxType::ipco => "ipco", 
             BoxType::ipma => "ipma", 
+            BoxType::colr => "colr", 
+            BoxType::ispe => "ispe", 
+            BoxType::cdsc => "cdsc", 
+            BoxType::dimg => "dimg", 
+            BoxType::pixi => "pixi", 
+            BoxType::irot => "irot", 
+            BoxType::imir => "imir", 
+            BoxType::clap => "clap", 
             BoxType::mdat => "mdat", 
             BoxType::idat => "idat", 
             BoxType::pdin => "pdin",
@@ -312,6 +343,7 @@ BoxType
             BoxType::iloc |
             BoxType::pitm |
             BoxType::iref |
+            BoxType::ipma |
             BoxType::pdin |
             BoxType::mvhd |
             BoxType::tkhd |
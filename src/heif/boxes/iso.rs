@@ -20,6 +20,41 @@ IsoBox
 impl
 IsoBox
 {
+    /// Creates a new box with the given header and data. The size stored
+    /// in the header gets adjusted to match the data.
+    pub(crate) fn
+    new
+    (
+        mut header: BoxHeader,
+        data:       Vec<u8>
+    )
+    -> IsoBox
+    {
+        header.set_box_size(header.get_header_size() + data.len() as u64);
+        return IsoBox { header, data };
+    }
+
+    pub(crate) fn
+    get_data
+    (
+        &self
+    )
+    -> &[u8]
+    {
+        return &self.data;
+    }
+
+    pub(crate) fn
+    set_data
+    (
+        &mut self,
+        data: Vec<u8>
+    )
+    {
+        self.header.set_box_size(self.header.get_header_size() + data.len() as u64);
+        self.data = data;
+    }
+
     fn
     construct_from_cursor_unboxed
     <T: Seek + Read>
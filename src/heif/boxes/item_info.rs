@@ -1,6 +1,7 @@
 // Copyright © 2025-2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
 // See https://github.com/TechnikTobi/little_exif#license for licensing details
 
+use std::io::Cursor;
 use std::io::Read;
 use std::io::Seek;
 
@@ -8,6 +9,7 @@ use crate::endian::Endian;
 use crate::general_file_io::io_error;
 use crate::u8conversion::U8conversion;
 use crate::u8conversion::to_u8_vec_macro;
+use crate::util::read_4_bytes;
 use crate::util::read_be_u16;
 use crate::util::read_be_u32;
 use crate::util::read_null_terminated_string;
@@ -23,10 +25,12 @@ use crate::heif::boxes::ParsableIsoBox;
 // 000001:     24 bits of flags
 // 0019:       item ID (16 bits)
 // 0000:       item protection index (16 bits)
-// 6876633100: item name, a null terminated string, here: "hvc1"
-// theoretically, after this point there would be two other strings, the
-// content_type and the optional content_encoding, however, the practical
-// examples did *not* have any of this
+// 68766331:   item type (4 bytes), here: "hvc1"
+// 00:         item name, a null terminated string, here: empty
+// for items of type `mime`, two other null terminated strings follow, the
+// content_type and the optional content_encoding (e.g. for XMP data the
+// content type is "application/rdf+xml")
+// See: ISO/IEC 14496-12:2015, § 8.11.6.2
 
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -36,7 +40,9 @@ ItemInfoEntryBox
     pub(self)  header:                BoxHeader,
     pub(crate) item_id:               u16,
     pub(crate) item_protection_index: u16,
+    pub(crate) item_type:             String,
     pub(crate) item_name:             String,
+    pub(crate) content_type:          Option<String>,
     pub(crate) additional_data:       Vec<u8>,
 }
 
@@ -71,13 +77,11 @@ ItemInfoEntryBox
     {
         let item_id               = read_be_u16(cursor)?;
         let item_protection_index = read_be_u16(cursor)?;
-        let item_name             = read_null_terminated_string(cursor)?;
 
         // Determine how much data is left for this entry
         let data_read_so_far = header.get_header_size() 
-            + 2                           // item_id
-            + 2                           // item_protection_index
-            + item_name.len() as u64 + 1; // string len + null terminator
+            + 2  // item_id
+            + 2; // item_protection_index
 
         if data_read_so_far > header.get_box_size()
         {
@@ -93,17 +97,54 @@ ItemInfoEntryBox
 
         let data_left_to_read = header.get_box_size() - data_read_so_far;
 
-        let mut additional_data: Vec<u8> = Vec::new();
-        additional_data.try_reserve_exact(data_left_to_read as usize)?;
-        cursor.take(data_left_to_read as u64).read_to_end(&mut additional_data)?;
+        let mut entry_data: Vec<u8> = Vec::new();
+        entry_data.try_reserve_exact(data_left_to_read as usize)?;
+        cursor.take(data_left_to_read).read_to_end(&mut entry_data)?;
 
-        log::trace!("Successfully read in ItemInfoEntryBox with ID: {item_id}, Name: {item_name}");
+        // Read the strings from the data of this entry only, so that a missing
+        // null terminator can't make them reach into the following boxes
+        let mut entry_cursor = Cursor::new(entry_data);
+
+        // Versions 0 and 1 don't have an item type, instead the content type
+        // always follows after the item name
+        let item_type = if header.get_version() >= 2
+        {
+            String::from_utf8(read_4_bytes(&mut entry_cursor)?.to_vec()).map_err(
+                |_| std::io::Error::new(
+                    std::io::ErrorKind::InvalidData, 
+                    "Could not convert item type of ItemInfoEntryBox to UTF-8 string!"
+                )
+            )?
+        }
+        else
+        {
+            String::new()
+        };
+
+        let item_name = read_null_terminated_string(&mut entry_cursor)?;
+
+        let has_content_type = header.get_version() < 2 || item_type == "mime";
+        let content_type     = if has_content_type && (entry_cursor.position() as usize) < entry_cursor.get_ref().len()
+        {
+            Some(read_null_terminated_string(&mut entry_cursor)?)
+        }
+        else
+        {
+            None
+        };
+
+        // Whatever is left (e.g. the content encoding) is kept as is
+        let additional_data = entry_cursor.get_ref()[entry_cursor.position() as usize..].to_vec();
+
+        log::trace!("Successfully read in ItemInfoEntryBox with ID: {item_id}, Type: {item_type}, Name: {item_name}");
 
         return Ok(ItemInfoEntryBox {
             header,
             item_id,
             item_protection_index,
+            item_type,
             item_name,
+            content_type,
             additional_data,
         });
     }
@@ -134,15 +175,21 @@ ItemInfoEntryBox
 impl
 ItemInfoBox
 {
+    /// XMP data is stored as item of type `mime` with the content type 
+    /// `application/rdf+xml`
     pub fn
-    get_exif_item
+    get_xmp_item
     (
         &self
     )
     -> Option<&ItemInfoEntryBox>
     {
         return self.items.iter()
-            .find(|item| item.item_name == "Exif")
+            .find(|item| 
+                item.item_type == "mime"
+                &&
+                item.content_type.as_deref() == Some("application/rdf+xml")
+            )
     }
 
     /// Creates a new item in this item information box and returns by how many
@@ -151,8 +198,8 @@ ItemInfoBox
     create_new_item_info_entry
     (
         &mut self,
-        iloc_id: u32,
-        name:    &str,
+        iloc_id:   u32,
+        item_type: &str,
     )
     -> u64
     {
@@ -161,7 +208,9 @@ ItemInfoBox
                 header:                BoxHeader::new_exif_info_entry_box_header(), 
                 item_id:               iloc_id as u16, 
                 item_protection_index: 0, 
-                item_name:             name.to_string(), 
+                item_type:             item_type.to_string(), 
+                item_name:             String::new(), 
+                content_type:          None,
                 additional_data:       Vec::new()
             }
         );
@@ -238,8 +287,17 @@ ItemInfoEntryBox
         
         serialized.extend(to_u8_vec_macro!(u16, &self.item_id,               &Endian::Big).iter());
         serialized.extend(to_u8_vec_macro!(u16, &self.item_protection_index, &Endian::Big).iter());
+        if self.header.get_version() >= 2
+        {
+            serialized.extend(self.item_type.bytes());
+        }
         serialized.extend(self.item_name.bytes());
         serialized.push(0x00); // null terminator for item name string
+        if let Some(content_type) = &self.content_type
+        {
+            serialized.extend(content_type.bytes());
+            serialized.push(0x00); // null terminator for content type string
+        }
         serialized.extend(&self.additional_data);
 
         return serialized;
@@ -287,4 +345,52 @@ ItemInfoBox
     fn as_any_mut     (&mut self) -> &mut dyn std::any::Any {      self        }
     fn get_header     (&    self) -> &        BoxHeader     { &    self.header }
     fn get_header_mut (&mut self) -> &mut     BoxHeader     { &mut self.header }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn
+    item_info_entries_round_trip()
+    -> Result<(), std::io::Error>
+    {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x65, 0x69, 0x69, 0x6e, 0x66,                     // size 0x65, iinf
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x03,                                 // version 0, flags, 3 entries
+            0x00, 0x00, 0x00, 0x15, 0x69, 0x6e, 0x66, 0x65,                     // size 0x15, infe
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00,                     // version 2, flags, ID 1, protection index 0
+            0x68, 0x76, 0x63, 0x31, 0x00,                                       // type "hvc1", empty name
+            0x00, 0x00, 0x00, 0x15, 0x69, 0x6e, 0x66, 0x65,                     // size 0x15, infe
+            0x02, 0x00, 0x00, 0x01, 0x00, 0x02, 0x00, 0x00,                     // version 2, flags, ID 2, protection index 0
+            0x45, 0x78, 0x69, 0x66, 0x00,                                       // type "Exif", empty name
+            0x00, 0x00, 0x00, 0x2d, 0x69, 0x6e, 0x66, 0x65,                     // size 0x2d, infe
+            0x02, 0x00, 0x00, 0x01, 0x00, 0x03, 0x00, 0x00,                     // version 2, flags, ID 3, protection index 0
+            0x6d, 0x69, 0x6d, 0x65, 0x58, 0x4d, 0x50, 0x00,                     // type "mime", name "XMP"
+            0x61, 0x70, 0x70, 0x6c, 0x69, 0x63, 0x61, 0x74,                     // content type "application/rdf+xml"
+            0x69, 0x6f, 0x6e, 0x2f, 0x72, 0x64, 0x66, 0x2b,
+            0x78, 0x6d, 0x6c, 0x00,
+            0x00,                                                               // empty content encoding
+        ];
+
+        let mut cursor   = Cursor::new(&data);
+        let header       = BoxHeader::read_box_header(&mut cursor)?;
+        let iinf_generic = ItemInfoBox::construct_from_cursor(&mut cursor, header)?;
+        let iinf         = iinf_generic.as_any().downcast_ref::<ItemInfoBox>().unwrap();
+
+        let item_types = iinf.items.iter().map(|item| item.item_type.as_str()).collect::<Vec<_>>();
+        assert_eq!(item_types, ["hvc1", "Exif", "mime"]);
+        assert_eq!(iinf.items[0].content_type, None);
+
+        let xmp_item = iinf.get_xmp_item().unwrap();
+        assert_eq!(xmp_item.item_id,         3);
+        assert_eq!(xmp_item.item_name,       "XMP");
+        assert_eq!(xmp_item.additional_data, [0x00]);
+
+        assert_eq!(iinf.serialize(), data);
+
+        Ok(())
+    }
+}
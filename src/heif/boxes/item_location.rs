@@ -40,7 +40,7 @@ ItemLocationBox
 
 
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum
 ItemConstructionMethod
 {
@@ -366,26 +366,107 @@ ItemLocationBox
             )
     }
 
-    // Returns the ID of the new entry and by how many bytes this box got longer
+    pub(crate) fn
+    get_item_location_entry_mut
+    (
+        &mut self,
+        item_id: u16
+    )
+    -> Result<&mut ItemLocationEntry, std::io::Error>
+    {
+        self.items.iter_mut()
+            .find(|item| item.item_id == item_id as u32)
+            .ok_or(
+                io_error_plain!(
+                    Other, 
+                    format!("ItemLocationEntry with item_id {} not found!", item_id)
+                )
+            )
+    }
+
+    /// Updates the size stored in the header after the entries changed. If
+    /// an offset or length can't be represented with the current field 
+    /// sizes (e.g. as they are zero), the field sizes get increased. 
+    pub(crate) fn
+    update_box_size
+    (
+        &mut self
+    )
+    {
+        let required_size = |values: &mut dyn Iterator<Item = u64>, current_size: u8| {
+            let max_value = values.max().unwrap_or(0);
+            if max_value > u32::MAX as u64 { return 8; }
+            if max_value > 0 && current_size == 0 { return 4; }
+            return current_size;
+        };
+
+        self.offset_size = required_size(
+            &mut self.items.iter().flat_map(|item| item.extents.iter().map(|extent| extent.extent_offset)),
+            self.offset_size
+        );
+        self.length_size = required_size(
+            &mut self.items.iter().flat_map(|item| item.extents.iter().map(|extent| extent.extent_length)),
+            self.length_size
+        );
+        self.base_offset_size = required_size(
+            &mut self.items.iter().map(|item| item.base_offset),
+            self.base_offset_size
+        );
+
+        let new_box_size = self.serialize().len() as u64;
+        self.header.set_box_size(new_box_size);
+    }
+
+    /// Shifts the extents of all items with the given construction method
+    /// that start after the given position by `delta` bytes. The extent 
+    /// given via `skip` (item ID and index of extent) is left untouched. 
+    pub(crate) fn
+    shift_extents_after
+    (
+        &mut self,
+        construction_method: ItemConstructionMethod,
+        position:            u64,
+        delta:               i64,
+        skip:                (u32, usize),
+    )
+    {
+        for item in &mut self.items
+        {
+            if item.get_construction_method() != construction_method
+            {
+                continue;
+            }
+
+            for (index, extent) in item.extents.iter_mut().enumerate()
+            {
+                if (item.item_id, index) == skip
+                {
+                    continue;
+                }
+
+                if item.base_offset + extent.extent_offset > position
+                {
+                    extent.extent_offset = (extent.extent_offset as i64 + delta) as u64;
+                }
+            }
+        }
+    }
+
+    /// Creates a new entry for the item with the given ID that is stored in
+    /// the file and returns by how many bytes this box got longer
     pub(crate) fn
     create_new_item_location_entry
     (
         &mut self,
+        item_id:     u32,
         data_start:  u64,
         data_length: u64
     )
-    -> (u32, u64)
+    -> u64
     {
-        // Determine largest iloc ID so far
-        let old_largest_id = self.items
-            .iter()
-            .map(|x| x.item_id)
-            .max()
-            .unwrap_or(0);
-
         self.items.push(ItemLocationEntry 
             {
-                item_id:                          old_largest_id + 1, 
+                item_id, 
                 reserved_and_construction_method: 0, 
                 data_reference_index:             0, 
                 base_offset:                      0,
@@ -405,16 +486,10 @@ ItemLocationBox
 
         // Due to the addition of a new item, the size in the header needs to 
         // be adjusted as well
-        // TODO: make this more efficient by only computing how much memory is
-        // needed, not by actually serializing (and thus, allocating memory)
         let old_box_size = self.header.get_box_size();
-        let new_box_size = self.serialize().len() as u64;
-        self.header.set_box_size(new_box_size);
+        self.update_box_size();
 
-        return (
-            self.items.last_mut().expect("No items present after insertion").item_id,
-            new_box_size - old_box_size
-        );
+        return self.header.get_box_size() - old_box_size;
     }
 
     pub(crate) fn
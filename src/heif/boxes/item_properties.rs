@@ -0,0 +1,744 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+
+use crate::endian::Endian;
+use crate::general_file_io::io_error;
+use crate::u8conversion::U8conversion;
+use crate::u8conversion::to_u8_vec_macro;
+use crate::util::read_1_bytes;
+use crate::util::read_be_u16;
+use crate::util::read_be_u32;
+
+use crate::heif::box_type::BoxType;
+use crate::heif::box_header::BoxHeader;
+use crate::heif::boxes::GenericIsoBox;
+use crate::heif::boxes::ParsableIsoBox;
+use crate::heif::boxes::iso::IsoBox;
+use crate::metadata::heif_io::HeifItemProperty;
+use crate::rational::iR64;
+use crate::rational::uR64;
+
+use super::read_box_based_on_header;
+use super::read_next_box;
+
+/// The item properties box `iprp` that contains the property container box
+/// `ipco` and one or more property association boxes `ipma`.
+/// See ISO/IEC 23008-12:2017, § 9.3
+pub struct
+ItemPropertiesBox
+{
+    header:                 BoxHeader,
+    pub(crate) other_boxes: Vec<Box<dyn GenericIsoBox>>,
+}
+
+/// The item property container box `ipco`, which stores the properties
+/// (e.g. `ispe` or `colr`) that get referenced by their 1-based index
+pub struct
+ItemPropertyContainerBox
+{
+    header:                BoxHeader,
+    pub(crate) properties: Vec<Box<dyn GenericIsoBox>>,
+}
+
+/// The item property association box `ipma`
+pub struct
+ItemPropertyAssociationBox
+{
+    header:             BoxHeader,
+    pub(crate) entries: Vec<ItemPropertyAssociationEntry>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct
+ItemPropertyAssociationEntry
+{
+    pub(crate) item_id:      u32,
+        // only if version == 1, if version == 0 this is u16
+
+    pub(crate) associations: Vec<PropertyAssociation>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct
+PropertyAssociation
+{
+    pub(crate) essential:      bool,
+    pub(crate) property_index: u16,
+        // 1-based index into the ipco box, 0 means no property
+        // only 7 bit if the lowest flag is not set, otherwise 15 bit
+}
+
+type ChildBoxes = Vec<Box<dyn GenericIsoBox>>;
+
+/// Reads the remaining bytes of a box and tries to parse the contained child
+/// boxes. If this fails, the box is kept as generic `IsoBox` so that files
+/// with unexpected content can still be processed.
+fn
+read_child_boxes
+<T: Seek + Read>
+(
+    cursor: &mut T,
+    header: &BoxHeader
+)
+-> Result<Result<ChildBoxes, Vec<u8>>, std::io::Error>
+{
+    let Some(remaining_bytes) = header.get_box_size().checked_sub(header.get_header_size()) else {
+        return io_error!(
+            InvalidData,
+            format!(
+                "Box size {} is smaller than header size {} for box type {:?}",
+                header.get_box_size(),
+                header.get_header_size(),
+                header.get_box_type()
+            )
+        );
+    };
+
+    let mut box_bytes: Vec<u8> = Vec::new();
+    box_bytes.try_reserve_exact(remaining_bytes as usize)?;
+    cursor.take(remaining_bytes).read_to_end(&mut box_bytes)?;
+
+    let mut local_cursor = Cursor::new(&box_bytes);
+    let mut child_boxes  = Vec::new();
+    while local_cursor.position() < box_bytes.len() as u64
+    {
+        let child_box = BoxHeader::read_box_header(&mut local_cursor)
+            .and_then(|child_header| read_box_based_on_header(&mut local_cursor, child_header));
+
+        match child_box
+        {
+            Ok(child_box) => child_boxes.push(child_box),
+            Err(e)        => {
+                log::debug!("Could not parse children of {:?}: {}", header.get_box_type(), e);
+                return Ok(Err(box_bytes));
+            }
+        }
+    }
+
+    return Ok(Ok(child_boxes));
+}
+
+impl
+ItemPropertiesBox
+{
+    pub(crate) fn
+    get_property_container_box
+    (
+        &self
+    )
+    -> Result<&ItemPropertyContainerBox, std::io::Error>
+    {
+        match self.other_boxes.iter().find(|b| b.get_header().get_box_type() == BoxType::ipco) {
+            Some(b) => match b.as_any().downcast_ref::<ItemPropertyContainerBox>() {
+                Some(unboxed) => Ok(unboxed),
+                None => io_error!(
+                    InvalidData,
+                    "Found ipco box but could not downcast to ItemPropertyContainerBox"
+                ),
+            },
+            None => io_error!(
+                NotFound,
+                "No ipco box found in ItemPropertiesBox"
+            ),
+        }
+    }
+
+    pub(crate) fn
+    get_property_container_box_mut
+    (
+        &mut self
+    )
+    -> Result<&mut ItemPropertyContainerBox, std::io::Error>
+    {
+        match self.other_boxes.iter_mut().find(|b| b.get_header().get_box_type() == BoxType::ipco) {
+            Some(b) => match b.as_any_mut().downcast_mut::<ItemPropertyContainerBox>() {
+                Some(unboxed) => Ok(unboxed),
+                None => io_error!(
+                    InvalidData,
+                    "Found ipco box but could not downcast to ItemPropertyContainerBox"
+                ),
+            },
+            None => io_error!(
+                NotFound,
+                "No ipco box found in ItemPropertiesBox"
+            ),
+        }
+    }
+
+    /// Gets all property association boxes. Usually there is only one, but
+    /// the standard allows one per version/flags combination
+    pub(crate) fn
+    get_property_association_boxes
+    (
+        &self
+    )
+    -> Vec<&ItemPropertyAssociationBox>
+    {
+        return self.other_boxes.iter()
+            .filter_map(|b| b.as_any().downcast_ref::<ItemPropertyAssociationBox>())
+            .collect();
+    }
+
+    pub(crate) fn
+    get_property_association_boxes_mut
+    (
+        &mut self
+    )
+    -> Vec<&mut ItemPropertyAssociationBox>
+    {
+        return self.other_boxes.iter_mut()
+            .filter_map(|b| b.as_any_mut().downcast_mut::<ItemPropertyAssociationBox>())
+            .collect();
+    }
+
+    /// Gets the 1-based indices of the properties associated with the item
+    pub(crate) fn
+    get_associated_property_indices
+    (
+        &self,
+        item_id: u32
+    )
+    -> Vec<u16>
+    {
+        return self.get_property_association_boxes()
+            .iter()
+            .flat_map(|ipma| ipma.entries.iter())
+            .filter(|entry| entry.item_id == item_id)
+            .flat_map(|entry| entry.associations.iter())
+            .map(|association| association.property_index)
+            .collect();
+    }
+
+    /// Removes the properties with the given 1-based indices from the `ipco`
+    /// box and updates the associations accordingly
+    pub(crate) fn
+    remove_properties
+    (
+        &mut self,
+        removed_indices: &[u16],
+    )
+    -> Result<(), std::io::Error>
+    {
+        let mut index = 0u16;
+        self.get_property_container_box_mut()?.properties.retain(|_| {
+            index += 1;
+            !removed_indices.contains(&index)
+        });
+
+        for ipma in self.get_property_association_boxes_mut()
+        {
+            for entry in &mut ipma.entries
+            {
+                entry.associations.retain(|association| 
+                    !removed_indices.contains(&association.property_index)
+                );
+
+                for association in &mut entry.associations
+                {
+                    association.property_index -= removed_indices.iter()
+                        .filter(|removed_index| **removed_index < association.property_index)
+                        .count() as u16;
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Recomputes the sizes of this box and its child boxes after modifying
+    /// their content
+    pub(crate) fn
+    update_box_sizes
+    (
+        &mut self
+    )
+    {
+        for other_box in &mut self.other_boxes
+        {
+            let new_size = other_box.serialize().len() as u64;
+            other_box.get_header_mut().set_box_size(new_size);
+        }
+
+        let new_size = self.serialize().len() as u64;
+        self.header.set_box_size(new_size);
+    }
+}
+
+impl
+ItemPropertyAssociationBox
+{
+    fn
+    construct_from_cursor_unboxed
+    <T: Seek + Read>
+    (
+        cursor: &mut T,
+        header:  BoxHeader
+    )
+    -> Result<Self, std::io::Error>
+    {
+        let large_ids     = header.get_version() >= 1;
+        let large_indices = header.get_flags() & 1 == 1;
+
+        let entry_count = read_be_u32(cursor)?;
+        let mut entries = Vec::new();
+
+        for _ in 0..entry_count
+        {
+            let item_id = if large_ids
+            {
+                read_be_u32(cursor)?
+            }
+            else
+            {
+                read_be_u16(cursor)? as u32
+            };
+
+            let association_count = read_1_bytes(cursor)?[0];
+            let mut associations  = Vec::new();
+
+            for _ in 0..association_count
+            {
+                let association = if large_indices
+                {
+                    let value = read_be_u16(cursor)?;
+                    PropertyAssociation {
+                        essential:      value & 0x8000 != 0,
+                        property_index: value & 0x7fff,
+                    }
+                }
+                else
+                {
+                    let value = read_1_bytes(cursor)?[0];
+                    PropertyAssociation {
+                        essential:      value & 0x80 != 0,
+                        property_index: (value & 0x7f) as u16,
+                    }
+                };
+
+                associations.push(association);
+            }
+
+            entries.push(ItemPropertyAssociationEntry { item_id, associations });
+        }
+
+        return Ok(ItemPropertyAssociationBox { header, entries });
+    }
+
+    /// Associates the property with the given 1-based index with the item,
+    /// switching to 15 bit indices if required
+    pub(crate) fn
+    add_association
+    (
+        &mut self,
+        item_id:        u32,
+        essential:      bool,
+        property_index: u16
+    )
+    {
+        self.insert_association(item_id, None, essential, property_index);
+    }
+
+    /// Same as `add_association`, but inserts the association at the given
+    /// position of the associations of the item instead of appending it
+    pub(crate) fn
+    insert_association
+    (
+        &mut self,
+        item_id:        u32,
+        position:       Option<usize>,
+        essential:      bool,
+        property_index: u16
+    )
+    {
+        self.use_large_indices_if_required(property_index);
+
+        let association = PropertyAssociation { essential, property_index };
+
+        match self.entries.iter_mut().find(|entry| entry.item_id == item_id)
+        {
+            Some(entry) => {
+                let position = position.unwrap_or(entry.associations.len()).min(entry.associations.len());
+                entry.associations.insert(position, association);
+            },
+            None        => self.entries.push(ItemPropertyAssociationEntry {
+                item_id,
+                associations: vec![association]
+            }),
+        }
+    }
+
+    /// Replaces the association of the item with the property at the old 
+    /// 1-based index with one to the property at the new index
+    pub(crate) fn
+    replace_association
+    (
+        &mut self,
+        item_id:   u32,
+        old_index: u16,
+        new_index: u16,
+        essential: bool,
+    )
+    {
+        self.use_large_indices_if_required(new_index);
+
+        for entry in self.entries.iter_mut().filter(|entry| entry.item_id == item_id)
+        {
+            for association in entry.associations.iter_mut().filter(|association| association.property_index == old_index)
+            {
+                association.property_index = new_index;
+                association.essential      = essential;
+            }
+        }
+    }
+
+    fn
+    use_large_indices_if_required
+    (
+        &mut self,
+        property_index: u16
+    )
+    {
+        if property_index > 0x7f
+        {
+            let flags = self.header.get_flags();
+            self.header.set_flags(flags | 1);
+        }
+    }
+}
+
+/// Decodes an item property, see ISO/IEC 23008-12:2017, § 6.5. Properties 
+/// of unknown type or with unexpected content are returned as 
+/// `HeifItemProperty::Other`. 
+pub(crate) fn
+decode_item_property
+(
+    property: &dyn GenericIsoBox
+)
+-> HeifItemProperty
+{
+    let box_type = property.get_header().get_box_type();
+    let data     = property.as_any().downcast_ref::<IsoBox>()
+        .map(|iso_box| iso_box.get_data())
+        .unwrap_or_default();
+
+    let read_u16 = |position: usize| data.get(position..position+2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]));
+    let read_u32 = |position: usize| data.get(position..position+4)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+
+    // Note that the version and flags of the full boxes `ispe` and `pixi` 
+    // are part of the data as these types are not known to be full boxes
+    let decoded = match box_type
+    {
+        BoxType::ispe => read_u32(4).zip(read_u32(8))
+            .map(|(width, height)| HeifItemProperty::ImageSpatialExtents { width, height }),
+
+        BoxType::pixi => data.get(4)
+            .and_then(|channel_count| data.get(5..5 + *channel_count as usize))
+            .map(|bits| HeifItemProperty::PixelInformation { bits_per_channel: bits.to_vec() }),
+
+        BoxType::colr => match data.get(0..4)
+        {
+            Some(b"nclx") => read_u16(4).zip(read_u16(6)).zip(read_u16(8)).zip(data.get(10))
+                .map(|(((colour_primaries, transfer_characteristics), matrix_coefficients), flags)| 
+                    HeifItemProperty::NclxColor {
+                        colour_primaries,
+                        transfer_characteristics,
+                        matrix_coefficients,
+                        full_range: flags & 0x80 != 0,
+                    }
+                ),
+            Some(b"prof") => Some(HeifItemProperty::IccColor { restricted: false, icc_profile: data[4..].to_vec() }),
+            Some(b"rICC") => Some(HeifItemProperty::IccColor { restricted: true,  icc_profile: data[4..].to_vec() }),
+            _             => None,
+        },
+
+        BoxType::irot => data.first().map(|angle| HeifItemProperty::Rotation { angle: angle & 0x03 }),
+
+        BoxType::imir => data.first().map(|axis| HeifItemProperty::Mirror { axis: axis & 0x01 }),
+
+        BoxType::clap => (0..8).map(|index| read_u32(index * 4)).collect::<Option<Vec<u32>>>()
+            .map(|values| HeifItemProperty::CleanAperture {
+                width:             uR64 { nominator: values[0],        denominator: values[1]        },
+                height:            uR64 { nominator: values[2],        denominator: values[3]        },
+                horizontal_offset: iR64 { nominator: values[4] as i32, denominator: values[5] as i32 },
+                vertical_offset:   iR64 { nominator: values[6] as i32, denominator: values[7] as i32 },
+            }),
+
+        _ => None,
+    };
+
+    // The data of other properties starts right after the box type
+    return decoded.unwrap_or_else(|| HeifItemProperty::Other { 
+        box_type: String::from_utf8_lossy(&box_type.to_4_bytes()).to_string(), 
+        data:     property.serialize().get(8..).unwrap_or_default().to_vec(),
+    });
+}
+
+/// Encodes an item property as box that can be stored in the `ipco` box
+pub(crate) fn
+encode_item_property
+(
+    property: &HeifItemProperty
+)
+-> Result<Box<dyn GenericIsoBox>, std::io::Error>
+{
+    let (box_type, data) = match property
+    {
+        HeifItemProperty::ImageSpatialExtents { width, height } => {
+            let mut data = vec![0, 0, 0, 0];
+            data.extend(width.to_be_bytes());
+            data.extend(height.to_be_bytes());
+            ("ispe", data)
+        },
+
+        HeifItemProperty::PixelInformation { bits_per_channel } => {
+            if bits_per_channel.len() > u8::MAX as usize
+            {
+                return io_error!(InvalidInput, "Too many channels for pixi property!");
+            }
+            let mut data = vec![0, 0, 0, 0, bits_per_channel.len() as u8];
+            data.extend(bits_per_channel);
+            ("pixi", data)
+        },
+
+        HeifItemProperty::NclxColor { colour_primaries, transfer_characteristics, matrix_coefficients, full_range } => {
+            let mut data = b"nclx".to_vec();
+            data.extend(colour_primaries.to_be_bytes());
+            data.extend(transfer_characteristics.to_be_bytes());
+            data.extend(matrix_coefficients.to_be_bytes());
+            data.push(if *full_range { 0x80 } else { 0x00 });
+            ("colr", data)
+        },
+
+        HeifItemProperty::IccColor { restricted, icc_profile } => {
+            let mut data = if *restricted { b"rICC".to_vec() } else { b"prof".to_vec() };
+            data.extend(icc_profile);
+            ("colr", data)
+        },
+
+        HeifItemProperty::Rotation { angle } => {
+            if *angle > 3
+            {
+                return io_error!(InvalidInput, "Rotation angle of irot property must be in the range 0-3!");
+            }
+            ("irot", vec![*angle])
+        },
+
+        HeifItemProperty::Mirror { axis } => {
+            if *axis > 1
+            {
+                return io_error!(InvalidInput, "Axis of imir property must be either 0 or 1!");
+            }
+            ("imir", vec![*axis])
+        },
+
+        HeifItemProperty::CleanAperture { width, height, horizontal_offset, vertical_offset } => {
+            let mut data = Vec::new();
+            for value in [
+                width.nominator,                    width.denominator,
+                height.nominator,                   height.denominator,
+                horizontal_offset.nominator as u32, horizontal_offset.denominator as u32,
+                vertical_offset.nominator   as u32, vertical_offset.denominator   as u32,
+            ]
+            {
+                data.extend(value.to_be_bytes());
+            }
+            ("clap", data)
+        },
+
+        HeifItemProperty::Other { box_type, data } => {
+            if box_type.len() != 4
+            {
+                return io_error!(InvalidInput, format!("Invalid property box type: {}", box_type));
+            }
+            (box_type.as_str(), data.clone())
+        },
+    };
+
+    // Parse the encoded box so that e.g. the version and flags of full boxes
+    // are handled the same way as for properties read from a file
+    let mut encoded = (8 + data.len() as u32).to_be_bytes().to_vec();
+    encoded.extend(box_type.as_bytes());
+    encoded.extend(data);
+
+    return read_next_box(&mut Cursor::new(&encoded));
+}
+
+impl
+ParsableIsoBox
+for
+ItemPropertiesBox
+{
+    fn
+    construct_from_cursor
+    <T: Seek + Read>
+    (
+        cursor: &mut T,
+        header:  BoxHeader
+    )
+    -> Result<Box<dyn GenericIsoBox>, std::io::Error>
+    {
+        match read_child_boxes(cursor, &header)?
+        {
+            Ok(other_boxes) => return Ok(Box::new(ItemPropertiesBox { header, other_boxes })),
+            Err(data)       => return Ok(Box::new(IsoBox::new(header, data))),
+        }
+    }
+}
+
+impl
+ParsableIsoBox
+for
+ItemPropertyContainerBox
+{
+    fn
+    construct_from_cursor
+    <T: Seek + Read>
+    (
+        cursor: &mut T,
+        header:  BoxHeader
+    )
+    -> Result<Box<dyn GenericIsoBox>, std::io::Error>
+    {
+        match read_child_boxes(cursor, &header)?
+        {
+            Ok(properties) => return Ok(Box::new(ItemPropertyContainerBox { header, properties })),
+            Err(data)      => return Ok(Box::new(IsoBox::new(header, data))),
+        }
+    }
+}
+
+impl
+ParsableIsoBox
+for
+ItemPropertyAssociationBox
+{
+    fn
+    construct_from_cursor
+    <T: Seek + Read>
+    (
+        cursor: &mut T,
+        header:  BoxHeader
+    )
+    -> Result<Box<dyn GenericIsoBox>, std::io::Error>
+    {
+        return Ok(Box::new(ItemPropertyAssociationBox::construct_from_cursor_unboxed(
+            cursor,
+            header
+        )?));
+    }
+}
+
+impl
+GenericIsoBox
+for
+ItemPropertiesBox
+{
+    fn
+    serialize
+    (
+        &self
+    )
+    -> Vec<u8>
+    {
+        let mut serialized = self.header.serialize();
+        for other_box in &self.other_boxes
+        {
+            serialized.extend(other_box.serialize());
+        }
+        return serialized;
+    }
+
+    fn as_any         (&    self) -> &    dyn std::any::Any {      self        }
+    fn as_any_mut     (&mut self) -> &mut dyn std::any::Any {      self        }
+    fn get_header     (&    self) -> &        BoxHeader     { &    self.header }
+    fn get_header_mut (&mut self) -> &mut     BoxHeader     { &mut self.header }
+}
+
+impl
+GenericIsoBox
+for
+ItemPropertyContainerBox
+{
+    fn
+    serialize
+    (
+        &self
+    )
+    -> Vec<u8>
+    {
+        let mut serialized = self.header.serialize();
+        for property in &self.properties
+        {
+            serialized.extend(property.serialize());
+        }
+        return serialized;
+    }
+
+    fn as_any         (&    self) -> &    dyn std::any::Any {      self        }
+    fn as_any_mut     (&mut self) -> &mut dyn std::any::Any {      self        }
+    fn get_header     (&    self) -> &        BoxHeader     { &    self.header }
+    fn get_header_mut (&mut self) -> &mut     BoxHeader     { &mut self.header }
+}
+
+impl
+GenericIsoBox
+for
+ItemPropertyAssociationBox
+{
+    fn
+    serialize
+    (
+        &self
+    )
+    -> Vec<u8>
+    {
+        let large_ids     = self.header.get_version() >= 1;
+        let large_indices = self.header.get_flags() & 1 == 1;
+
+        let mut serialized = self.header.serialize();
+        serialized.extend(to_u8_vec_macro!(u32, &(self.entries.len() as u32), &Endian::Big).iter());
+
+        for entry in &self.entries
+        {
+            if large_ids
+            {
+                serialized.extend(to_u8_vec_macro!(u32, & entry.item_id,         &Endian::Big).iter());
+            }
+            else
+            {
+                serialized.extend(to_u8_vec_macro!(u16, &(entry.item_id as u16), &Endian::Big).iter());
+            }
+
+            serialized.push(entry.associations.len() as u8);
+
+            for association in &entry.associations
+            {
+                if large_indices
+                {
+                    let value = association.property_index & 0x7fff
+                        | if association.essential { 0x8000 } else { 0 };
+                    serialized.extend(to_u8_vec_macro!(u16, &value, &Endian::Big).iter());
+                }
+                else
+                {
+                    let value = association.property_index as u8 & 0x7f
+                        | if association.essential { 0x80 } else { 0 };
+                    serialized.push(value);
+                }
+            }
+        }
+
+        return serialized;
+    }
+
+    fn as_any         (&    self) -> &    dyn std::any::Any {      self        }
+    fn as_any_mut     (&mut self) -> &mut dyn std::any::Any {      self        }
+    fn get_header     (&    self) -> &        BoxHeader     { &    self.header }
+    fn get_header_mut (&mut self) -> &mut     BoxHeader     { &mut self.header }
+}
@@ -11,6 +11,7 @@ use crate::util::read_be_u16;
 use crate::util::read_be_u32;
 
 use crate::heif::box_header::BoxHeader;
+use crate::heif::box_type::BoxType;
 use crate::heif::boxes::GenericIsoBox;
 use crate::heif::boxes::ParsableIsoBox;
 use crate::io_error;
@@ -48,7 +49,7 @@ SingleItemTypeReferenceBox
     )
     -> Result<Self, std::io::Error>
     {
-        let     header     = BoxHeader::read_box_header(cursor)?;
+        let     header     = BoxHeader::read_simple_box_header(cursor)?;
         let mut to_item_ID = Vec::new();
 
         // Depending on the version stored in the header of the iref box,
@@ -152,6 +153,49 @@ ItemReferenceBox
         return Ok(ItemReferenceBox { header, references });
     }
 
+    /// Gets the IDs of the items that the given item references with the
+    /// given type of reference (e.g. `iloc`), in the stored order
+    #[allow(non_snake_case)]
+    pub(crate) fn
+    get_references
+    (
+        &self,
+        reference_type: BoxType,
+        from_item_ID:   u32,
+    )
+    -> Vec<u32>
+    {
+        return self.references.iter()
+            .filter(|reference| {
+                reference.header.get_box_type() == reference_type
+                &&
+                reference.from_item_ID == from_item_ID
+            })
+            .flat_map(|reference| reference.to_item_ID.iter().copied())
+            .collect();
+    }
+
+    /// Removes all references of the given type from the given item and 
+    /// updates the size of this box accordingly
+    #[allow(non_snake_case)]
+    pub(crate) fn
+    remove_references
+    (
+        &mut self,
+        reference_type: BoxType,
+        from_item_ID:   u32,
+    )
+    {
+        self.references.retain(|reference| {
+            reference.header.get_box_type() != reference_type
+            ||
+            reference.from_item_ID != from_item_ID
+        });
+
+        let new_iref_box_size = self.serialize().len() as u64;
+        self.header.set_box_size(new_iref_box_size);
+    }
+
     #[allow(non_snake_case)]
     pub(crate) fn
     create_new_single_item_reference_box
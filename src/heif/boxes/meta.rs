@@ -19,6 +19,8 @@ use crate::heif::boxes::ParsableIsoBox;
 use crate::heif::boxes::item_info::ItemInfoBox;
 use crate::heif::boxes::item_location::ItemLocationBox;
 use crate::heif::boxes::item_reference::ItemReferenceBox;
+use crate::heif::boxes::item_properties::ItemPropertiesBox;
+use crate::heif::boxes::iso::IsoBox;
 
 use super::read_box_based_on_header;
 
@@ -123,6 +125,170 @@ MetaBox
         return None;
     }
 
+    pub(crate) fn
+    get_item_reference_box_mut
+    (
+        &mut self
+    )
+    -> Option<&mut ItemReferenceBox>
+    {
+        if let Some(found_box) = self.other_boxes.iter_mut()
+            .find(|b| b.get_header().get_box_type() == BoxType::iref)
+        {
+            return found_box.as_any_mut().downcast_mut::<ItemReferenceBox>();
+        }
+        return None;
+    }
+
+    /// Gets the `idat` box that stores the data of items with construction
+    /// method 1, see ISO/IEC 14496-12:2015, § 8.11.11
+    pub(crate) fn
+    get_item_data_box
+    (
+        &self
+    )
+    -> Result<&IsoBox, std::io::Error>
+    {
+        match self.other_boxes.iter().find(|b| b.get_header().get_box_type() == BoxType::idat) {
+            Some(b) => match b.as_any().downcast_ref::<IsoBox>() {
+                Some(unboxed) => Ok(unboxed),
+                None => io_error!(
+                    InvalidData,
+                    "Found idat box but could not downcast to IsoBox"
+                ),
+            },
+            None => io_error!(
+                NotFound,
+                "No idat box found in MetaBox"
+            ),
+        }
+    }
+
+    pub(crate) fn
+    get_item_data_box_mut
+    (
+        &mut self
+    )
+    -> Result<&mut IsoBox, std::io::Error>
+    {
+        match self.other_boxes.iter_mut().find(|b| b.get_header().get_box_type() == BoxType::idat) {
+            Some(b) => match b.as_any_mut().downcast_mut::<IsoBox>() {
+                Some(unboxed) => Ok(unboxed),
+                None => io_error!(
+                    InvalidData,
+                    "Found idat box but could not downcast to IsoBox"
+                ),
+            },
+            None => io_error!(
+                NotFound,
+                "No idat box found in MetaBox"
+            ),
+        }
+    }
+
+    pub(crate) fn
+    get_item_properties_box
+    (
+        &self
+    )
+    -> Result<&ItemPropertiesBox, std::io::Error>
+    {
+        match self.other_boxes.iter().find(|b| b.get_header().get_box_type() == BoxType::iprp) {
+            Some(b) => match b.as_any().downcast_ref::<ItemPropertiesBox>() {
+                Some(unboxed) => Ok(unboxed),
+                None => io_error!(
+                    InvalidData,
+                    "Found iprp box but could not downcast to ItemPropertiesBox"
+                ),
+            },
+            None => io_error!(
+                NotFound,
+                "No iprp box found in MetaBox"
+            ),
+        }
+    }
+
+    pub(crate) fn
+    get_item_properties_box_mut
+    (
+        &mut self
+    )
+    -> Result<&mut ItemPropertiesBox, std::io::Error>
+    {
+        match self.other_boxes.iter_mut().find(|b| b.get_header().get_box_type() == BoxType::iprp) {
+            Some(b) => match b.as_any_mut().downcast_mut::<ItemPropertiesBox>() {
+                Some(unboxed) => Ok(unboxed),
+                None => io_error!(
+                    InvalidData,
+                    "Found iprp box but could not downcast to ItemPropertiesBox"
+                ),
+            },
+            None => io_error!(
+                NotFound,
+                "No iprp box found in MetaBox"
+            ),
+        }
+    }
+
+    /// Gets the ID of the primary item as stored in the `pitm` box
+    /// See ISO/IEC 14496-12:2015, § 8.11.4
+    pub(crate) fn
+    get_primary_item_id
+    (
+        &self
+    )
+    -> Option<u32>
+    {
+        let pitm = self.other_boxes.iter()
+            .find(|b| b.get_header().get_box_type() == BoxType::pitm)?
+            .as_any()
+            .downcast_ref::<IsoBox>()?;
+
+        let data = pitm.get_data();
+        if pitm.get_header().get_version() == 0
+        {
+            return Some(u16::from_be_bytes(data.get(0..2)?.try_into().ok()?) as u32);
+        }
+        return Some(u32::from_be_bytes(data.get(0..4)?.try_into().ok()?));
+    }
+
+    /// Gets an item ID that is not used by any item yet, i.e. that is not 
+    /// used in the item location, item information, item reference or 
+    /// primary item box
+    pub(crate) fn
+    get_unused_item_id
+    (
+        &self
+    )
+    -> Result<u32, std::io::Error>
+    {
+        let mut largest_id = self.get_primary_item_id().unwrap_or(0);
+
+        for item in &self.get_item_location_box()?.items
+        {
+            largest_id = largest_id.max(item.item_id);
+        }
+
+        for item in &self.get_item_info_box()?.items
+        {
+            largest_id = largest_id.max(item.item_id as u32);
+        }
+
+        if let Some(iref) = self.get_item_reference_box()
+        {
+            for reference in &iref.references
+            {
+                largest_id = largest_id.max(reference.from_item_ID);
+                for to_item_id in &reference.to_item_ID
+                {
+                    largest_id = largest_id.max(*to_item_id);
+                }
+            }
+        }
+
+        return Ok(largest_id + 1);
+    }
+
     pub(crate) fn
     create_new_item_reference_box_if_none_exists_yet
     (
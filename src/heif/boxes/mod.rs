@@ -14,11 +14,15 @@ pub(super) mod meta;
 pub(super) mod item_info;
 pub(super) mod item_location;
 pub(super) mod item_reference;
+pub(super) mod item_properties;
 
 use iso::IsoBox;
 use meta::MetaBox;
 use item_info::ItemInfoBox;
 use item_location::ItemLocationBox;
+use item_properties::ItemPropertiesBox;
+use item_properties::ItemPropertyContainerBox;
+use item_properties::ItemPropertyAssociationBox;
 
 #[allow(dead_code)]
 pub trait 
@@ -57,11 +61,14 @@ read_box_based_on_header
 {
     return match header.get_box_type()
     {
-        BoxType::meta => MetaBox::         construct_from_cursor(cursor, header),
-        BoxType::iinf => ItemInfoBox::     construct_from_cursor(cursor, header),
-        BoxType::iloc => ItemLocationBox:: construct_from_cursor(cursor, header),
-        BoxType::iref => ItemReferenceBox::construct_from_cursor(cursor, header),
-        _             => IsoBox::         construct_from_cursor(cursor, header)
+        BoxType::meta => MetaBox::                   construct_from_cursor(cursor, header),
+        BoxType::iinf => ItemInfoBox::               construct_from_cursor(cursor, header),
+        BoxType::iloc => ItemLocationBox::           construct_from_cursor(cursor, header),
+        BoxType::iref => ItemReferenceBox::          construct_from_cursor(cursor, header),
+        BoxType::iprp => ItemPropertiesBox::         construct_from_cursor(cursor, header),
+        BoxType::ipco => ItemPropertyContainerBox::  construct_from_cursor(cursor, header),
+        BoxType::ipma => ItemPropertyAssociationBox::construct_from_cursor(cursor, header),
+        _             => IsoBox::                    construct_from_cursor(cursor, header)
     };
 }
 
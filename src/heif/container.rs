@@ -14,13 +14,21 @@ use crate::heif::boxes::meta::MetaBox;
 use crate::heif::read_next_box;
 
 use crate::metadata::Metadata;
+use crate::metadata::heif_io::HeifAssociatedProperty;
+use crate::metadata::heif_io::HeifImageItem;
+use crate::metadata::heif_io::HeifItemProperty;
 use crate::util::insert_multiple_at;
 use crate::util::range_remove;
 use crate::util::read_be_u32;
 
+use super::box_header::BoxHeader;
 use super::boxes::GenericIsoBox;
+use super::boxes::iso::IsoBox;
 use super::boxes::item_info::ItemInfoBox;
+use super::boxes::item_properties::decode_item_property;
+use super::boxes::item_properties::encode_item_property;
 use super::boxes::item_location::ItemLocationBox;
+use super::boxes::item_location::ItemLocationEntryExtentEntry;
 
 pub struct
 HeifContainer
@@ -28,6 +36,13 @@ HeifContainer
     boxes: Vec<Box<dyn GenericIsoBox>>
 }
 
+// Item types of coded and derived images
+// See ISO/IEC 23008-12:2017, § 6.6.2 and Annexes B-E, as well as the AV1 and
+// uncompressed codec specifications
+const IMAGE_ITEM_TYPES: [&str; 11] = [
+    "hvc1", "av01", "avc1", "jpeg", "j2k1", "vvc1", "unci", "grid", "iovl", "iden", "tmap"
+];
+
 // General structure of ISO container
 // 
 // ┏━━━━━━┓
@@ -132,64 +147,502 @@ HeifContainer
 
     
 
+    /// Gets the ID of the EXIF item that describes the primary item. EXIF
+    /// items of other images are not considered, as their data does not 
+    /// belong to the primary image.
     fn
     get_item_id_exif_data
     (
         &self
     )
     -> Result<u16, std::io::Error>
+    {
+        let Some(primary_item_id) = self.get_meta_box()?.get_primary_item_id() else {
+            return io_error!(NotFound, "No primary item found!");
+        };
+
+        return self.get_item_id_exif_data_of_image(primary_item_id);
+    }
+
+    /// Gets the ID of the EXIF item that describes the given image item via
+    /// a `cdsc` item reference. For the primary item, an EXIF item without 
+    /// any `cdsc` reference is accepted as well. 
+    fn
+    get_item_id_exif_data_of_image
+    (
+        &self,
+        image_item_id: u32,
+    )
+    -> Result<u16, std::io::Error>
+    {
+        let meta         = self.get_meta_box()?;
+        let exif_items   = meta.get_item_info_box()?.items.iter()
+            .filter(|item| item.item_type == "Exif")
+            .map(|item| item.item_id)
+            .collect::<Vec<u16>>();
+        let descriptions = meta.get_item_reference_box()
+            .map(|iref| iref.references.iter()
+                .filter(|reference| reference.get_header().get_box_type() == BoxType::cdsc)
+                .collect::<Vec<_>>()
+            )
+            .unwrap_or_default();
+
+        for exif_item_id in &exif_items
+        {
+            if descriptions.iter().any(|reference| 
+                reference.from_item_ID == *exif_item_id as u32
+                &&
+                reference.to_item_ID.contains(&image_item_id)
+            )
+            {
+                return Ok(*exif_item_id);
+            }
+        }
+
+        if meta.get_primary_item_id() == Some(image_item_id)
+        {
+            if let Some(exif_item_id) = exif_items.iter().find(|exif_item_id| 
+                descriptions.iter().all(|reference| reference.from_item_ID != **exif_item_id as u32)
+            )
+            {
+                return Ok(*exif_item_id);
+            }
+        }
+
+        return io_error!(NotFound, format!("No EXIF item found for image item {}!", image_item_id));
+    }
+
+    /// Lists the image items, without the input images of derived images 
+    /// (e.g. the tiles of a grid) as these are not meant to be displayed
+    /// on their own. The dimensions are taken from the `ispe` property. 
+    pub(super) fn
+    get_image_items
+    (
+        &self
+    )
+    -> Result<Vec<HeifImageItem>, std::io::Error>
+    {
+        let meta            = self.get_meta_box()?;
+        let primary_item_id = meta.get_primary_item_id();
+
+        let derivation_inputs = meta.get_item_reference_box()
+            .map(|iref| iref.references.iter()
+                .filter(|reference| reference.get_header().get_box_type() == BoxType::dimg)
+                .flat_map(|reference| reference.to_item_ID.iter().copied())
+                .collect::<Vec<u32>>()
+            )
+            .unwrap_or_default();
+
+        let mut image_items = Vec::new();
+        for item in &meta.get_item_info_box()?.items
+        {
+            let item_id = item.item_id as u32;
+            if !IMAGE_ITEM_TYPES.contains(&item.item_type.as_str()) || derivation_inputs.contains(&item_id)
+            {
+                continue;
+            }
+
+            let (width, height) = match self.get_image_spatial_extents(item_id)
+            {
+                Some((width, height)) => (Some(width), Some(height)),
+                None                  => (None, None),
+            };
+
+            image_items.push(HeifImageItem {
+                item_id,
+                item_type:  item.item_type.clone(),
+                width,
+                height,
+                is_primary: primary_item_id == Some(item_id),
+            });
+        }
+
+        return Ok(image_items);
+    }
+
+    /// Gets the width and height of an image item from its `ispe` property
+    /// See ISO/IEC 23008-12:2017, § 6.5.3
+    fn
+    get_image_spatial_extents
+    (
+        &self,
+        item_id: u32,
+    )
+    -> Option<(u32, u32)>
+    {
+        return self.get_item_properties(item_id).ok()?
+            .into_iter()
+            .find_map(|associated| match associated.property
+            {
+                HeifItemProperty::ImageSpatialExtents { width, height } => Some((width, height)),
+                _                                                      => None,
+            });
+    }
+
+    /// Checks that an item with the given ID exists
+    fn
+    check_item_exists
+    (
+        &self,
+        item_id: u32,
+    )
+    -> Result<(), std::io::Error>
+    {
+        if !self.get_meta_box()?.get_item_info_box()?.items.iter().any(|item| item.item_id as u32 == item_id)
+        {
+            return io_error!(NotFound, format!("No item with ID {}!", item_id));
+        }
+        return Ok(());
+    }
+
+    /// Gets the properties associated with the given item, in the order of
+    /// their association
+    pub(super) fn
+    get_item_properties
+    (
+        &self,
+        item_id: u32,
+    )
+    -> Result<Vec<HeifAssociatedProperty>, std::io::Error>
+    {
+        self.check_item_exists(item_id)?;
+
+        let iprp = match self.get_meta_box()?.get_item_properties_box()
+        {
+            Ok(iprp)                                             => iprp,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e)                                               => return Err(e),
+        };
+        let ipco = iprp.get_property_container_box()?;
+
+        let associations = iprp.get_property_association_boxes()
+            .into_iter()
+            .flat_map(|ipma| ipma.entries.iter())
+            .filter(|entry| entry.item_id == item_id)
+            .flat_map(|entry| entry.associations.iter());
+
+        let mut properties = Vec::new();
+        for association in associations
+        {
+            // Property indices are 1-based, 0 means "no property"
+            let Some(property) = ipco.properties.get((association.property_index as usize).wrapping_sub(1)) else {
+                continue;
+            };
+
+            properties.push(HeifAssociatedProperty {
+                property:  decode_item_property(property.as_ref()),
+                essential: association.essential,
+            });
+        }
+
+        return Ok(properties);
+    }
+
+    /// Writes the property for the given item, replacing the associated 
+    /// property of the same kind. If that property is shared with other 
+    /// items, a new property gets added instead. 
+    pub(super) fn
+    generic_write_item_property
+    (
+        &mut self,
+        file_buffer: &mut Vec<u8>,
+        item_id:     u32,
+        property:    &HeifItemProperty,
+        essential:   bool,
+    )
+    -> Result<(), std::io::Error>
+    {
+        self.check_item_exists(item_id)?;
+
+        let old_meta_size = self.get_meta_box()?.serialize().len() as u64;
+        let new_property  = encode_item_property(property)?;
+
+        let iprp = self.get_meta_box_mut()?.get_item_properties_box_mut()?;
+        let ipco = iprp.get_property_container_box()?;
+
+        // The associated properties of the item as 1-based index and the 
+        // decoded property
+        let associated = iprp.get_associated_property_indices(item_id)
+            .into_iter()
+            .filter_map(|index| ipco.properties.get((index as usize).wrapping_sub(1))
+                .map(|property| (index, decode_item_property(property.as_ref())))
+            )
+            .collect::<Vec<_>>();
+
+        let existing_index = associated.iter()
+            .find(|(_, associated_property)| is_same_kind_of_property(associated_property, property))
+            .map(|(index, _)| *index);
+
+        let is_shared = |index: u16| iprp.get_property_association_boxes()
+            .iter()
+            .flat_map(|ipma| ipma.entries.iter())
+            .any(|entry| 
+                entry.item_id != item_id 
+                && 
+                entry.associations.iter().any(|association| association.property_index == index)
+            );
+
+        match existing_index
+        {
+            Some(index) if !is_shared(index) => {
+                iprp.get_property_container_box_mut()?.properties[index as usize - 1] = new_property;
+                for ipma in iprp.get_property_association_boxes_mut()
+                {
+                    ipma.replace_association(item_id, index, index, essential);
+                }
+            },
+
+            Some(index) => {
+                let ipco = iprp.get_property_container_box_mut()?;
+                ipco.properties.push(new_property);
+                let new_index = ipco.properties.len() as u16;
+
+                for ipma in iprp.get_property_association_boxes_mut()
+                {
+                    ipma.replace_association(item_id, index, new_index, essential);
+                }
+            },
+
+            None => {
+                let ipco = iprp.get_property_container_box_mut()?;
+                ipco.properties.push(new_property);
+                let new_index = ipco.properties.len() as u16;
+
+                // Descriptive properties need to be associated before the 
+                // transformative ones, see ISO/IEC 23008-12:2017, § 6.5.1
+                let position = match is_transformative_property(property)
+                {
+                    true  => None,
+                    false => associated.iter().position(|(_, associated_property)| is_transformative_property(associated_property)),
+                };
+
+                let mut ipmas = iprp.get_property_association_boxes_mut();
+                let ipma_index = ipmas.iter()
+                    .position(|ipma| ipma.entries.iter().any(|entry| entry.item_id == item_id))
+                    .unwrap_or(0);
+
+                match ipmas.get_mut(ipma_index)
+                {
+                    Some(ipma) => ipma.insert_association(item_id, position, essential, new_index),
+                    None       => return io_error!(NotFound, "No ipma box found in iprp box!"),
+                }
+            },
+        }
+
+        iprp.update_box_sizes();
+        return self.update_after_meta_box_change(file_buffer, old_meta_size);
+    }
+
+    /// Removes the association of the properties with the given box type 
+    /// from the given item. Properties that are not associated with any item
+    /// afterwards get removed as well. 
+    pub(super) fn
+    generic_remove_item_property
+    (
+        &mut self,
+        file_buffer: &mut Vec<u8>,
+        item_id:     u32,
+        box_type:    &str,
+    )
+    -> Result<(), std::io::Error>
+    {
+        self.check_item_exists(item_id)?;
+
+        let old_meta_size = self.get_meta_box()?.serialize().len() as u64;
+
+        let iprp = match self.get_meta_box_mut()?.get_item_properties_box_mut()
+        {
+            Ok(iprp)                                             => iprp,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e)                                               => return Err(e),
+        };
+        let ipco = iprp.get_property_container_box()?;
+
+        let removed_indices = iprp.get_associated_property_indices(item_id)
+            .into_iter()
+            .filter(|index| ipco.properties.get((*index as usize).wrapping_sub(1))
+                .map(|property| property.get_header().get_box_type().to_4_bytes() == box_type.as_bytes())
+                .unwrap_or(false)
+            )
+            .collect::<Vec<u16>>();
+
+        if removed_indices.is_empty()
+        {
+            return Ok(());
+        }
+
+        for ipma in iprp.get_property_association_boxes_mut()
+        {
+            for entry in ipma.entries.iter_mut().filter(|entry| entry.item_id == item_id)
+            {
+                entry.associations.retain(|association| 
+                    !removed_indices.contains(&association.property_index)
+                );
+            }
+        }
+
+        // Properties that are still associated with other items are kept
+        let unused_indices = removed_indices.into_iter()
+            .filter(|index| !iprp.get_property_association_boxes()
+                .iter()
+                .flat_map(|ipma| ipma.entries.iter())
+                .flat_map(|entry| entry.associations.iter())
+                .any(|association| association.property_index == *index)
+            )
+            .collect::<Vec<u16>>();
+
+        iprp.remove_properties(&unused_indices)?;
+        iprp.update_box_sizes();
+        return self.update_after_meta_box_change(file_buffer, old_meta_size);
+    }
+
+    fn
+    get_item_id_xmp_data
+    (
+        &self
+    )
+    -> Result<u16, std::io::Error>
     {
         if let Ok(meta) = self.get_meta_box() 
         {
-            if let Some(item) = meta.get_item_info_box()?.get_exif_item() {
+            if let Some(item) = meta.get_item_info_box()?.get_xmp_item() {
                 return Ok(item.item_id);
             }
         }
 
-        return io_error!(Other, "No EXIF item found!");
+        return io_error!(Other, "No XMP item found!");
     }
 
+    /// Gets the absolute position and the length of the given extent of an
+    /// item that is stored in the file
     fn
-    get_exif_data_pos_and_len
+    get_extent_pos_and_len
     (
         &self,
-        exif_item_id: u16,
+        item_id:      u16,
+        extent_index: usize,
     )
     -> Result<(u64, u64), std::io::Error>
     {
-        let exif_item = match self.get_meta_box() 
+        let item = self.get_meta_box()?.get_item_location_box()?.get_item_location_entry(item_id)?;
+
+        if item.get_construction_method() != ItemConstructionMethod::FILE
         {
-            Ok(meta) => meta.get_item_location_box()?.get_item_location_entry(exif_item_id)?,
-            Err(e) => return Err(e),
-        };
+            return io_error!(Other, "Expected item to be stored in the file!");
+        }
+
+        match item.extents.get(extent_index)
+        {
+            Some(extent) => return Ok((
+                item.base_offset + extent.extent_offset,
+                extent.extent_length
+            )),
+            None => return io_error!(Other, format!("Item {} has no extent #{}", item_id, extent_index)),
+        }
+    }
 
-        let exif_extents = &exif_item.extents;
-        if exif_extents.len() != 1 
+    /// Reads the data of the item with the given ID, which may consist of 
+    /// multiple extents. Depending on the construction method, the extents
+    /// refer to the file (0), the `idat` box (1) or the data of the items 
+    /// referenced via `iloc` item references (2).
+    /// See ISO/IEC 14496-12:2015, § 8.11.3
+    fn
+    get_item_data
+    <T: Seek + Read>
+    (
+        &self,
+        cursor:  &mut T,
+        item_id: u16,
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        return self.get_item_data_with_parents(cursor, item_id, &mut Vec::new());
+    }
+
+    /// The list of parents is used for detecting circular item references
+    fn
+    get_item_data_with_parents
+    <T: Seek + Read>
+    (
+        &self,
+        cursor:  &mut T,
+        item_id: u16,
+        parents: &mut Vec<u16>,
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        let meta = self.get_meta_box()?;
+        let item = meta.get_item_location_box()?.get_item_location_entry(item_id)?;
+
+        if item.data_reference_index != 0
         {
-            return io_error!(Other, "Expected exactly one EXIF extent info entry");
+            return io_error!(Unsupported, format!("Data of item {} is stored in another file!", item_id));
         }
 
-        match exif_item.get_construction_method()
+        let mut data = Vec::new();
+        match item.get_construction_method()
         {
-            super::boxes::item_location::ItemConstructionMethod::FILE => {
-                if let Some(first) = exif_extents.first() {
-                    return Ok((
-                        first.extent_offset + exif_item.base_offset,
-                        first.extent_length
-                    ));
-                } else {
-                    return io_error!(Other, "Expected one EXIF extent");
+            ItemConstructionMethod::FILE => {
+                for extent in &item.extents
+                {
+                    cursor.seek(std::io::SeekFrom::Start(item.base_offset + extent.extent_offset))?;
+
+                    // A length of zero refers to the entire remaining data
+                    if extent.extent_length == 0
+                    {
+                        cursor.read_to_end(&mut data)?;
+                        continue;
+                    }
+
+                    let mut extent_data = Vec::new();
+                    extent_data.try_reserve_exact(extent.extent_length as usize)?;
+                    cursor.take(extent.extent_length).read_to_end(&mut extent_data)?;
+
+                    if extent_data.len() as u64 != extent.extent_length
+                    {
+                        return io_error!(UnexpectedEof, format!("Extent of item {} exceeds end of file!", item_id));
+                    }
+                    data.extend(extent_data);
                 }
             },
 
-            super::boxes::item_location::ItemConstructionMethod::IDAT => {
-                return io_error!(Other, "HEIF: item constr. method 'IDAT' currently not supported. Please create a new ticket at https://github.com/TechnikTobi/little_exif with an example image file");
+            ItemConstructionMethod::IDAT => {
+                let idat_data = meta.get_item_data_box()?.get_data();
+                for extent in &item.extents
+                {
+                    data.extend(get_extent_slice(idat_data, item.base_offset, extent)?);
+                }
             },
 
-            super::boxes::item_location::ItemConstructionMethod::ITEM => {
-                return io_error!(Other, "HEIF: item constr. method 'ITEM' currently not supported. Please create a new ticket at https://github.com/TechnikTobi/little_exif with an example image file");
+            ItemConstructionMethod::ITEM => {
+                if parents.contains(&item_id)
+                {
+                    return io_error!(InvalidData, format!("Circular item reference for item {}!", item_id));
+                }
+                parents.push(item_id);
+
+                // The extent index is the 1-based index of the 'iloc' item 
+                // reference that provides the data of the extent
+                let source_item_ids = meta.get_item_reference_box()
+                    .map(|iref| iref.get_references(BoxType::iloc, item_id as u32))
+                    .unwrap_or_default();
+
+                for extent in &item.extents
+                {
+                    let reference_index = extent.extent_index.unwrap_or(1) as usize;
+                    let Some(source_item_id) = source_item_ids.get(reference_index.wrapping_sub(1)) else {
+                        return io_error!(InvalidData, format!("Item {} has no item reference #{}!", item_id, reference_index));
+                    };
+
+                    let source_data = self.get_item_data_with_parents(cursor, *source_item_id as u16, parents)?;
+                    data.extend(get_extent_slice(&source_data, item.base_offset, extent)?);
+                }
+
+                parents.pop();
             },
         }
+
+        return Ok(data);
     }
 
     pub(super) fn
@@ -201,18 +654,50 @@ HeifContainer
     )
     -> Result<Vec<u8>, std::io::Error>
     {
-        // Locate exif data
-        let exif_item_id    = self.get_item_id_exif_data()?;
-        let (start, length) = self.get_exif_data_pos_and_len(exif_item_id)?;
+        let exif_item_id = self.get_item_id_exif_data()?;
+        return self.get_exif_data_of_item(cursor, exif_item_id);
+    }
 
-        // Reset cursor to start of exif data
-        cursor.seek(std::io::SeekFrom::Start(start))?;
+    /// Gets the EXIF data that describes the given image item
+    pub(super) fn
+    get_image_exif_data
+    <T: Seek + Read>
+    (
+        &self,
+        cursor:        &mut T,
+        image_item_id: u32,
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        let exif_item_id = self.get_item_id_exif_data_of_image(image_item_id)?;
+        return self.get_exif_data_of_item(cursor, exif_item_id);
+    }
 
-        // Read in the first 4 bytes, which gives the offset to the start
-        // of the TIFF header and seek to that
-        let exif_tiff_header_offset = read_be_u32(cursor)? as usize;
+    fn
+    get_exif_data_of_item
+    <T: Seek + Read>
+    (
+        &self,
+        cursor:       &mut T,
+        exif_item_id: u16,
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        // Read exif data
+        let exif_item_data = self.get_item_data(cursor, exif_item_id)?;
+        let length         = exif_item_data.len() as u64;
 
-        cursor.seek(std::io::SeekFrom::Current(exif_tiff_header_offset as i64))?;
+        // The first 4 bytes give the offset to the start of the TIFF header
+        if length < 4
+        {
+            return io_error!(InvalidData, format!("EXIF data length ({}) is too small", length));
+        }
+        let exif_tiff_header_offset = u32::from_be_bytes([
+            exif_item_data[0], 
+            exif_item_data[1], 
+            exif_item_data[2], 
+            exif_item_data[3]
+        ]) as usize;
 
         if length < 4 + exif_tiff_header_offset as u64
         {
@@ -226,13 +711,9 @@ HeifContainer
             );
         }
 
-        // Read in the remaining bytes
-        let mut exif_buffer = vec![0u8; 
-            length as usize 
-            - 4                       // the 4 bytes that store the offset
-            - exif_tiff_header_offset // the actual offset
-        ];
-        cursor.read_exact(&mut exif_buffer)?;
+        // The remaining bytes, without the 4 bytes that store the offset and
+        // the actual offset
+        let mut exif_buffer = exif_item_data[4 + exif_tiff_header_offset..].to_vec();
 
         // Stick a EXIF_HEADER in the front
         let mut full_exif_data = EXIF_HEADER.to_vec();
@@ -241,48 +722,231 @@ HeifContainer
         return Ok(full_exif_data);
     }
 
-    /// Constructs a new version of the exif data area of the HEIF file
+    pub(super) fn
+    get_xmp_data
+    <T: Seek + Read>
+    (
+        &self,
+        cursor: &mut T,
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        let xmp_item_id = self.get_item_id_xmp_data()?;
+        return self.get_item_data(cursor, xmp_item_id);
+    }
+
+    /// Replaces the data of the existing XMP item. Creating a new XMP item
+    /// is currently not supported.
+    pub(super) fn
+    generic_write_xmp
+    (
+        &mut self,
+        file_buffer: &mut Vec<u8>,
+        xmp_data:    &[u8],
+    )
+    -> Result<(), std::io::Error>
+    {
+        let xmp_item_id = self.get_item_id_xmp_data()?;
+        return self.replace_item_data(file_buffer, xmp_item_id, xmp_data.to_vec());
+    }
+
+    /// Gets the ICC profile stored in a `colr` item property. Prefers the
+    /// profile associated with the primary item and falls back to the first
+    /// ICC `colr` property in the `ipco` box otherwise.
+    pub(super) fn
+    get_icc_profile
+    (
+        &self
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        let meta = self.get_meta_box()?;
+        let iprp = meta.get_item_properties_box()?;
+        let ipco = iprp.get_property_container_box()?;
+
+        // Property indices are 1-based, 0 means "no property"
+        let mut candidates: Vec<usize> = match meta.get_primary_item_id()
+        {
+            Some(primary_item_id) => iprp.get_associated_property_indices(primary_item_id)
+                .iter()
+                .filter(|index| **index > 0)
+                .map(|index| *index as usize - 1)
+                .collect(),
+            None => Vec::new(),
+        };
+        candidates.extend(0..ipco.properties.len());
+
+        for index in candidates
+        {
+            if let Some(icc_profile) = ipco.properties.get(index).and_then(|p| get_icc_colr_data(p.as_ref()))
+            {
+                return Ok(icc_profile.to_vec());
+            }
+        }
+
+        return io_error!(NotFound, "No ICC profile found!");
+    }
+
+    /// Writes the ICC profile to all `colr` item properties that store an ICC
+    /// profile. If there is no such property yet, a new one gets created and
+    /// associated with the primary item.
+    pub(super) fn
+    generic_write_icc_profile
+    (
+        &mut self,
+        file_buffer: &mut Vec<u8>,
+        icc_profile: &[u8],
+    )
+    -> Result<(), std::io::Error>
+    {
+        let old_meta_size = self.get_meta_box()?.serialize().len() as u64;
+        let primary_id    = self.get_meta_box()?.get_primary_item_id();
+
+        let mut colr_data = b"prof".to_vec();
+        colr_data.extend(icc_profile);
+
+        let iprp = self.get_meta_box_mut()?.get_item_properties_box_mut()?;
+        let ipco = iprp.get_property_container_box_mut()?;
+
+        let mut replaced = false;
+        for property in &mut ipco.properties
+        {
+            if get_icc_colr_data(property.as_ref()).is_none()
+            {
+                continue;
+            }
+
+            if let Some(colr) = property.as_any_mut().downcast_mut::<IsoBox>()
+            {
+                colr.set_data(colr_data.clone());
+                replaced = true;
+            }
+        }
+
+        if !replaced
+        {
+            let Some(primary_id) = primary_id else {
+                return io_error!(NotFound, "No primary item to associate ICC profile with!");
+            };
+
+            let mut colr_header = BoxHeader::new_simple_box_header();
+            colr_header.set_box_type_via_string("colr");
+            ipco.properties.push(Box::new(IsoBox::new(colr_header, colr_data)));
+
+            let new_index = ipco.properties.len() as u16;
+            match iprp.get_property_association_boxes_mut().into_iter().next()
+            {
+                Some(ipma) => ipma.add_association(primary_id, false, new_index),
+                None       => return io_error!(NotFound, "No ipma box found in iprp box!"),
+            }
+        }
+
+        iprp.update_box_sizes();
+        return self.update_after_meta_box_change(file_buffer, old_meta_size);
+    }
+
+    /// Removes all `colr` item properties that store an ICC profile and
+    /// updates the property associations accordingly
+    pub(super) fn
+    generic_remove_icc_profile
+    (
+        &mut self,
+        file_buffer: &mut Vec<u8>,
+    )
+    -> Result<(), std::io::Error>
+    {
+        let old_meta_size = self.get_meta_box()?.serialize().len() as u64;
+
+        let iprp = match self.get_meta_box_mut()?.get_item_properties_box_mut()
+        {
+            Ok(iprp)                                             => iprp,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e)                                               => return Err(e),
+        };
+        let ipco = iprp.get_property_container_box()?;
+
+        // 1-based indices of the removed properties
+        let removed_indices: Vec<u16> = ipco.properties.iter()
+            .enumerate()
+            .filter(|(_, property)| get_icc_colr_data(property.as_ref()).is_some())
+            .map(|(index, _)| index as u16 + 1)
+            .collect();
+
+        if removed_indices.is_empty()
+        {
+            return Ok(());
+        }
+
+        iprp.remove_properties(&removed_indices)?;
+        iprp.update_box_sizes();
+        return self.update_after_meta_box_change(file_buffer, old_meta_size);
+    }
+
+    /// Updates the size of the meta box after its content changed, shifts 
+    /// the item locations accordingly and writes all boxes to the buffer
+    fn
+    update_after_meta_box_change
+    (
+        &mut self,
+        file_buffer:   &mut Vec<u8>,
+        old_meta_size: u64,
+    )
+    -> Result<(), std::io::Error>
+    {
+        let meta          = self.get_meta_box_mut()?;
+        let new_meta_size = meta.serialize().len() as u64;
+        meta.get_header_mut().set_box_size(new_meta_size);
+
+        // Same limitation as when creating a new EXIF item: This assumes that
+        // the item data is located after the meta box
+        meta.get_item_location_box_mut()?.add_to_extents(
+            new_meta_size as i64 - old_meta_size as i64
+        );
+
+        file_buffer.clear();
+        for iso_box in &self.boxes
+        {
+            file_buffer.extend(iso_box.serialize());
+        }
+
+        return Ok(());
+    }
+
+    /// Constructs a new version of the exif data area of the HEIF file, based
+    /// on the data of the previous exif data area
     /// the i64 tells us the delta in bytes. If negative, the new area is
     /// shorter than the old one, positive if longer
     #[allow(unused_assignments)]
     fn
     construct_new_exif_data_area
-    <T: Seek + Read>
     (
         &self,
-        cursor:   &mut T,
-        metadata: &Metadata,
+        exif_buffer: &[u8],
+        metadata:    &Metadata,
     )
     -> Result<(Vec<u8>, i64), std::io::Error>
     {
         // The buffer containing the new metadata that gets returned
         let mut new_exif_buffer;
 
-        // Try to locate the old exif data. 
-        let exif_item_id = self.get_item_id_exif_data()?;
-
-        // Determine the start and length of the previous exif data area
-        let (start, length) = self.get_exif_data_pos_and_len(exif_item_id)?;
+        let length = exif_buffer.len();
 
         // If the length is zero, we assume that this is a previously newly 
         // created exif data area, which requires special handling.
         // If the length is non-zero, there has been exif data before:
         if length > 0
         {
-            // Reset cursor to start of exif data
-            cursor.seek(std::io::SeekFrom::Start(start))?;
-
-            // Read in all of this area
-            let mut exif_buffer = vec![0u8; length as usize];
-            cursor.read_exact(&mut exif_buffer)?;
-
             // Decode the first 4 bytes, which tells us where to cut off the old 
             // data and replace with the new one
-            let mut local_cursor            = Cursor::new(exif_buffer[0..4].to_vec());
+            let mut local_cursor            = Cursor::new(exif_buffer);
             let     exif_tiff_header_offset = read_be_u32(&mut local_cursor)?;
 
             // Cut off data, starting at the old TIFF header and replace with new
-            new_exif_buffer = exif_buffer[0..exif_tiff_header_offset as usize + 4].to_vec();
+            new_exif_buffer = match exif_buffer.get(0..exif_tiff_header_offset as usize + 4)
+            {
+                Some(exif_header) => exif_header.to_vec(),
+                None              => return io_error!(InvalidData, "EXIF data is too short for its TIFF header offset!"),
+            };
         }
         else
         {
@@ -344,150 +1008,414 @@ HeifContainer
     {
         // Find out where old exif is located, needed to determine which iloc
         // entries need to be updated
-        let mut id = self.get_item_id_exif_data();
-
-        // Check that the ID is okay - if not, there is no exif area yet and
-        // we need to create one!
-        if id.is_err()
-        {
-            // What we need to do at this point is
-            // - Create a new item location entry that points to the EXIF data
-            // - Create an item information entry that tells us that the iloc 
-            //   entry points to EXIF data
-            // - Create an item reference entry that links the EXIF data to the
-            //   image/iloc ID #1 -> is this always #1?
-
-            // Where to put the new exif area
-            let new_exif_start = self.get_start_address_for_new_exif_area();
-
-            // If there is no iref box yet, create one so we can find one,
-            // and get the size delta of the new box for extents
-            let mut iref_size_delta = self.get_meta_box_mut()?.create_new_item_reference_box_if_none_exists_yet();
-
-            // Acquire the item location, the item information and the item 
-            // reference boxes that are inside the meta box. For some reason, 
-            // this is not trivial - using e.g. get_item_location_box_mut() 
-            // does not work due to (according to the borrow checker) multiple 
-            // mutable usages of self
-            let mut iloc_opt = None;
-            let mut iinf_opt = None;
-            let mut iref_opt = None;
-
-            let meta_mut_ref = self.get_meta_box_mut()?;
-            for other_box in &mut meta_mut_ref.other_boxes
+        let id = match self.get_item_id_exif_data()
+        {
+            Ok(id) => id,
+
+            // If there is no exif area yet, we need to create one for the 
+            // primary item!
+            Err(_) => match self.get_meta_box()?.get_primary_item_id()
             {
-                if other_box.get_header().get_box_type() == BoxType::iloc
-                {
-                    iloc_opt = other_box
-                        .as_any_mut()
-                        .downcast_mut::<ItemLocationBox>();
-                }
-                else if other_box.get_header().get_box_type() == BoxType::iinf
-                {
-                    iinf_opt = other_box
-                        .as_any_mut()
-                        .downcast_mut::<ItemInfoBox>();
-                }
-                else if other_box.get_header().get_box_type() == BoxType::iref
-                {
-                    iref_opt = other_box
-                        .as_any_mut()
-                        .downcast_mut::<ItemReferenceBox>();
-                }
+                Some(primary_item_id) => self.create_new_exif_item(primary_item_id)?,
+                None => return io_error!(NotFound, "No primary item to link new EXIF data with!"),
+            },
+        };
+
+        return self.write_exif_item(file_buffer, metadata, id);
+    }
+
+    /// Writes the metadata to the EXIF item that describes the given image 
+    /// item, which gets created if there is none yet
+    pub(super) fn
+    generic_write_image_metadata
+    (
+        &mut self,
+        file_buffer:   &mut Vec<u8>,
+        metadata:      &Metadata,
+        image_item_id: u32,
+    )
+    -> Result<(), std::io::Error>
+    {
+        if !self.get_image_items()?.iter().any(|item| item.item_id == image_item_id)
+        {
+            return io_error!(NotFound, format!("No image item with ID {}!", image_item_id));
+        }
+
+        let id = match self.get_item_id_exif_data_of_image(image_item_id)
+        {
+            Ok(id) => id,
+            Err(_) => self.create_new_exif_item(image_item_id)?,
+        };
+
+        return self.write_exif_item(file_buffer, metadata, id);
+    }
+
+    fn
+    write_exif_item
+    (
+        &mut self,
+        file_buffer: &mut Vec<u8>,
+        metadata:    &Metadata,
+        id:          u16,
+    )
+    -> Result<(), std::io::Error>
+    {
+
+        // Read in the previous exif data area, which is empty for a newly
+        // created exif item. Note that an extent length of zero in the file 
+        // would otherwise refer to all remaining data of the file. 
+        let old_exif_area = match self.get_meta_box()?.get_item_location_box()?.get_item_location_entry(id)?.extents.as_slice()
+        {
+            [extent] if extent.extent_length == 0 => Vec::new(),
+            _ => self.get_item_data(&mut Cursor::new(&*file_buffer), id)?,
+        };
+
+        // Construct new exif data area
+        let (new_exif_area, _) = self.construct_new_exif_data_area(
+            &old_exif_area, 
+            metadata
+        )?;
+
+        return self.replace_item_data(file_buffer, id, new_exif_area);
+    }
+
+    /// Creates a new, empty exif item, consisting of
+    /// - a new item location entry that points to the end of the `mdat` box
+    ///   (which gets created if the file does not have one yet)
+    /// - an item information entry that tells us that the iloc entry points 
+    ///   to EXIF data
+    /// - an item reference entry that links the EXIF data to the given image
+    ///   item, see ISO/IEC 23008-12:2017, § A.2.1
+    /// and returns the ID of the new item
+    fn
+    create_new_exif_item
+    (
+        &mut self,
+        image_item_id: u32,
+    )
+    -> Result<u16, std::io::Error>
+    {
+        // Item information entries of version 2 only store 16 bit item IDs
+        let new_item_id = self.get_meta_box()?.get_unused_item_id()?;
+        if new_item_id > u16::MAX as u32
+        {
+            return io_error!(Unsupported, "Can't create new EXIF item as there are too many items!");
+        }
+
+        // Where to put the new exif area
+        if self.get_start_address_for_new_exif_area() == u64::MAX
+        {
+            let mut mdat_header = BoxHeader::new_simple_box_header();
+            mdat_header.set_box_type_via_string("mdat");
+            self.boxes.push(Box::new(IsoBox::new(mdat_header, Vec::new())));
+        }
+        let new_exif_start = self.get_start_address_for_new_exif_area();
+
+        // If there is no iref box yet, create one so we can find one,
+        // and get the size delta of the new box for extents
+        let mut iref_size_delta = self.get_meta_box_mut()?.create_new_item_reference_box_if_none_exists_yet();
+
+        // Acquire the item location, the item information and the item 
+        // reference boxes that are inside the meta box. For some reason, 
+        // this is not trivial - using e.g. get_item_location_box_mut() 
+        // does not work due to (according to the borrow checker) multiple 
+        // mutable usages of self
+        let mut iloc_opt = None;
+        let mut iinf_opt = None;
+        let mut iref_opt = None;
+
+        let meta_mut_ref = self.get_meta_box_mut()?;
+        for other_box in &mut meta_mut_ref.other_boxes
+        {
+            if other_box.get_header().get_box_type() == BoxType::iloc
+            {
+                iloc_opt = other_box
+                    .as_any_mut()
+                    .downcast_mut::<ItemLocationBox>();
+            }
+            else if other_box.get_header().get_box_type() == BoxType::iinf
+            {
+                iinf_opt = other_box
+                    .as_any_mut()
+                    .downcast_mut::<ItemInfoBox>();
+            }
+            else if other_box.get_header().get_box_type() == BoxType::iref
+            {
+                iref_opt = other_box
+                    .as_any_mut()
+                    .downcast_mut::<ItemReferenceBox>();
             }
+        }
 
-            let iloc = match iloc_opt {
-                Some(v) => v,
-                None => return io_error!(Other, "iloc box should exist"),
-            };
-            let iinf = match iinf_opt {
-                Some(v) => v,
-                None => return io_error!(Other, "iinf box should exist"),
-            };
-            let iref = match iref_opt {
-                Some(v) => v,
-                None => return io_error!(Other, "iref box should exist"),
+        let iloc = match iloc_opt {
+            Some(v) => v,
+            None => return io_error!(Other, "iloc box should exist"),
+        };
+        let iinf = match iinf_opt {
+            Some(v) => v,
+            None => return io_error!(Other, "iinf box should exist"),
+        };
+        let iref = match iref_opt {
+            Some(v) => v,
+            None => return io_error!(Other, "iref box should exist"),
+        };
+
+        // Note that the given `new_exif_start` value is based on old
+        // length values (which change due to adding a new item to both the
+        // iloc and iinf boxes) - but this does not matter as this will 
+        // be updated anyway later by `add_to_extents`
+        // This way, we don't need any exception during the update procedure
+        let iloc_size_delta = iloc.create_new_item_location_entry(
+            new_item_id,
+            new_exif_start,
+            0
+        );
+        let iinf_size_delta = iinf.create_new_item_info_entry(
+            new_item_id, 
+            "Exif"
+        );
+        iref_size_delta += iref.create_new_single_item_reference_box(
+            "cdsc",
+            new_item_id, 
+            vec![image_item_id]
+        );
+
+        // Fix the extents in the iloc box
+        iloc.add_to_extents(
+            (iloc_size_delta + iinf_size_delta + iref_size_delta) as i64
+        );
+
+        // Fix up the size of the meta box as well
+        let new_box_size = self.get_meta_box()?.serialize().len() as u64;
+        self.get_meta_box_mut()?.get_header_mut().set_box_size(new_box_size);
+
+        // No change to the mdat data at this point as we set up the
+        // iloc item so that the exif area currently has a length of zero
+
+        // Now we have a valid exif area with an iloc ID!
+        return Ok(new_item_id as u16);
+    }
+
+    /// Replaces the data of the item with the given ID with the new data.
+    /// Depending on the construction method of the item, the data gets 
+    /// replaced in the file or the `idat` box. Items that are constructed 
+    /// from other items get changed to store their data in the file. Data 
+    /// that is spread across multiple extents ends up in a single extent. 
+    fn
+    replace_item_data
+    (
+        &mut self,
+        file_buffer:   &mut Vec<u8>,
+        item_id:       u16,
+        new_data_area: Vec<u8>,
+    )
+    -> Result<(), std::io::Error>
+    {
+        let construction_method = self.get_meta_box()?
+            .get_item_location_box()?
+            .get_item_location_entry(item_id)?
+            .get_construction_method();
+
+        match construction_method
+        {
+            ItemConstructionMethod::FILE => (),
+
+            ItemConstructionMethod::IDAT 
+                => return self.replace_idat_item_data(file_buffer, item_id, new_data_area),
+
+            ItemConstructionMethod::ITEM 
+                => self.relocate_item_to_file(file_buffer, item_id)?,
+        }
+
+        let extent_count = self.get_meta_box()?
+            .get_item_location_box()?
+            .get_item_location_entry(item_id)?
+            .extents.len();
+
+        if extent_count == 0
+        {
+            return io_error!(InvalidData, format!("Item {} has no extents!", item_id));
+        }
+
+        if extent_count > 1
+        {
+            // Remove the data of all but the first extent, starting with the 
+            // last one. As the boxes of this container still contain the old
+            // data after each replacement, the container needs to be 
+            // constructed again. 
+            for extent_index in (1..extent_count).rev()
+            {
+                self.replace_extent_data(file_buffer, item_id, extent_index, Vec::new())?;
+                *self = HeifContainer::construct_from_cursor_unboxed(&mut Cursor::new(&*file_buffer))?;
+            }
+
+            // Afterwards, remove the now empty extents themselves
+            let old_meta_size = self.get_meta_box()?.serialize().len() as u64;
+            let iloc          = self.get_meta_box_mut()?.get_item_location_box_mut()?;
+            let item          = iloc.get_item_location_entry_mut(item_id)?;
+            item.extents.truncate(1);
+            item.extent_count = 1;
+            iloc.update_box_size();
+            self.update_after_meta_box_change(file_buffer, old_meta_size)?;
+        }
+
+        return self.replace_extent_data(file_buffer, item_id, 0, new_data_area);
+    }
+
+    /// Replaces the data of an item that is stored in the `idat` box. The 
+    /// new data gets placed at the location of the first extent, the other
+    /// extents get removed. 
+    fn
+    replace_idat_item_data
+    (
+        &mut self,
+        file_buffer:   &mut Vec<u8>,
+        item_id:       u16,
+        new_data_area: Vec<u8>,
+    )
+    -> Result<(), std::io::Error>
+    {
+        let old_meta_size = self.get_meta_box()?.serialize().len() as u64;
+        let mut idat_data = self.get_meta_box()?.get_item_data_box()?.get_data().to_vec();
+        let mut new_data  = Some(new_data_area);
+
+        let iloc         = self.get_meta_box_mut()?.get_item_location_box_mut()?;
+        let extent_count = iloc.get_item_location_entry(item_id)?.extents.len();
+
+        if extent_count == 0
+        {
+            return io_error!(InvalidData, format!("Item {} has no extents!", item_id));
+        }
+
+        // Starting with the last extent, so the first one gets the new data
+        for extent_index in (0..extent_count).rev()
+        {
+            let item   = iloc.get_item_location_entry(item_id)?;
+            let extent = &item.extents[extent_index];
+            let start  = item.base_offset + extent.extent_offset;
+            let end    = match extent.extent_length
+            {
+                0      => idat_data.len() as u64,
+                length => start + length,
             };
 
-            // Note that the given `new_exif_start` value is based on old
-            // length values (which change due to adding a new item to both the
-            // iloc and iinf boxes) - but this does not matter as this will 
-            // be updated anyway later by `add_to_extents`
-            // This way, we don't need any exception during the update procedure
-            let (new_iloc_id, iloc_size_delta) = iloc.create_new_item_location_entry(
-                new_exif_start,
-                0
-            );
-            let               iinf_size_delta  = iinf.create_new_item_info_entry(
-                new_iloc_id, 
-                "Exif"
-            );
-                              iref_size_delta += iref.create_new_single_item_reference_box(
-                "cdsc",             // TODO: Check if this is always this type?
-                new_iloc_id, 
-                vec![1]             // TODO: Check if this is always item #1?
-            );
+            if start > end || end > idat_data.len() as u64
+            {
+                return io_error!(InvalidData, format!("Extent of item {} exceeds idat box!", item_id));
+            }
+
+            let replacement = if extent_index == 0 { new_data.take().unwrap_or_default() } else { Vec::new() };
+            let delta       = replacement.len() as i64 - (end - start) as i64;
+            let new_length  = replacement.len() as u64;
 
-            // Fix the extents in the iloc box
-            iloc.add_to_extents(
-                (iloc_size_delta + iinf_size_delta + iref_size_delta) as i64
+            idat_data.splice(start as usize..end as usize, replacement);
+
+            iloc.shift_extents_after(
+                ItemConstructionMethod::IDAT, 
+                start, 
+                delta, 
+                (item_id as u32, extent_index)
             );
+            iloc.get_item_location_entry_mut(item_id)?.extents[extent_index].extent_length = new_length;
+        }
 
-            // Fix up the size of the meta box as well
-            let new_box_size = self.get_meta_box()?.serialize().len() as u64;
-            self.get_meta_box_mut()?.get_header_mut().set_box_size(new_box_size);
+        let item = iloc.get_item_location_entry_mut(item_id)?;
+        item.extents.truncate(1);
+        item.extent_count = 1;
+        iloc.update_box_size();
 
-            // No change to the mdat data at this point as we set up the
-            // iloc item so that the exif area currently has a length of zero
+        self.get_meta_box_mut()?.get_item_data_box_mut()?.set_data(idat_data);
+        return self.update_after_meta_box_change(file_buffer, old_meta_size);
+    }
 
-            // Now we have a valid exif area with an iloc ID!
-            id = Ok(new_iloc_id as u16);
+    /// Changes the construction method of an item that is constructed from
+    /// other items to 'file', pointing to a new, empty data area at the end
+    /// of the mdat box. The `iloc` references to the items that previously
+    /// provided the data get removed. 
+    fn
+    relocate_item_to_file
+    (
+        &mut self,
+        file_buffer: &mut Vec<u8>,
+        item_id:     u16,
+    )
+    -> Result<(), std::io::Error>
+    {
+        let old_meta_size = self.get_meta_box()?.serialize().len() as u64;
+
+        // As with a new EXIF item, the start address is based on the old 
+        // length of the meta box, which gets fixed by `add_to_extents`
+        let new_data_start = self.get_start_address_for_new_exif_area();
+        if new_data_start == u64::MAX
+        {
+            return io_error!(Other, "No mdat box found for storing the item data!");
         }
 
-        // Get position and length of current exif area
-        let (old_exif_pos, old_exif_len) = match &id {
-            Ok(idv) => self.get_exif_data_pos_and_len(*idv)?,
-            Err(_) => (0, 0),
-        };
+        let meta = self.get_meta_box_mut()?;
+        if let Some(iref) = meta.get_item_reference_box_mut()
+        {
+            iref.remove_references(BoxType::iloc, item_id as u32);
+        }
+
+        let iloc = meta.get_item_location_box_mut()?;
+        let item = iloc.get_item_location_entry_mut(item_id)?;
+        item.reserved_and_construction_method &= 0xfff0;
+        item.base_offset  = 0;
+        item.extent_count = 1;
+        item.extents      = vec![ItemLocationEntryExtentEntry {
+            extent_index:  Some(0),
+            extent_offset: new_data_start,
+            extent_length: 0,
+        }];
+        iloc.update_box_size();
+
+        return self.update_after_meta_box_change(file_buffer, old_meta_size);
+    }
+
+    /// Replaces the data of the given extent of an item that is stored in 
+    /// the file with the new data. This takes care of updating the location 
+    /// information of all other items and the size of the box that contains
+    /// the data.
+    fn
+    replace_extent_data
+    (
+        &mut self,
+        file_buffer:       &mut Vec<u8>,
+        item_id:           u16,
+        extent_index:      usize,
+        mut new_data_area: Vec<u8>,
+    )
+    -> Result<(), std::io::Error>
+    {
+        // Get position and length of current data area
+        let (old_data_pos, old_data_len) = self.get_extent_pos_and_len(item_id, extent_index)?;
+        let delta = new_data_area.len() as i64 - old_data_len as i64;
 
         // Get cursor for file
         let mut cursor = Cursor::new(file_buffer);
 
-        // Construct new exif data area
-        let (mut new_exif_area, delta) = self.construct_new_exif_data_area(
-            &mut cursor, 
-            metadata
-        )?;
-
         let meta_mut = self.get_meta_box_mut()?;
         for item in &mut meta_mut.get_item_location_box_mut()?.items
         {
-            // First, check if any extent of this item has the same offset as
-            // the old exif data area. In that case, there must be only one
-            // extent - other cases can't be handled right now
-            if item.extents.iter()
-                .any(|extent| {
-                    item.base_offset + extent.extent_offset == old_exif_pos
-                })
+            if item.item_id == item_id as u32
             {
-                assert!(item.extents.len() == 1, "Expect to have exactly one extent info for EXIF!");
-
-                // In case of the EXIF extent information we need to update
-                // the length information, not the offset!
-                let first_extent = match item.extents.first() {
-                    Some(f) => f,
-                    None => return io_error!(Other, "Expected one extent for EXIF"),
-                };
-                let new_ext_len = (
-                    first_extent.extent_length as i64
-                    + delta
-                ) as u64;
-                match item.extents.first_mut() {
-                    Some(fm) => fm.extent_length = new_ext_len,
-                    None => return io_error!(Other, "Expected one extent for EXIF (mut)"),
+                // In case of the replaced extent information we need to update
+                // the length information, not the offset! Other extents of
+                // this item that are located after it get moved.
+                let base_offset = item.base_offset;
+                for (index, extent) in item.extents.iter_mut().enumerate()
+                {
+                    if index == extent_index
+                    {
+                        extent.extent_length = new_data_area.len() as u64;
+                    }
+                    else if base_offset + extent.extent_offset > old_data_pos
+                    {
+                        extent.extent_offset = (extent.extent_offset as i64 + delta) as u64;
+                    }
                 }
 
-                 continue;
+                continue;
             }
 
             if item.get_construction_method() == ItemConstructionMethod::IDAT
@@ -517,11 +1445,11 @@ HeifContainer
             {
                 // Potentially modify the entire base offset 
                 // however, we can only do that if all complete offsets
-                // point to an area after the exif data area
+                // point to an area after the replaced data area
                 // So we need to check that first:
                 if item.extents.iter()
                     .all(|extent| {
-                        item.base_offset + extent.extent_offset >= old_exif_pos
+                        item.base_offset + extent.extent_offset >= old_data_pos
                     })
                 {
                     item.base_offset = (item.base_offset as i64 + delta) as u64;
@@ -535,7 +1463,7 @@ HeifContainer
             {
                 let complete_offset = item.base_offset + extent.extent_offset;
 
-                if complete_offset > old_exif_pos
+                if complete_offset > old_data_pos
                 {
                     extent.extent_offset = (extent.extent_offset as i64 + delta) as u64;
                 }
@@ -544,51 +1472,51 @@ HeifContainer
 
         // Now we clear the vec and write the boxes to it
         // Keep track of how many bytes were written so we know when to 
-        // replace old exif data with new
+        // replace old data with new
         cursor.get_mut().clear();
 
         let mut written_bytes    = 0usize;
-        let mut new_exif_written = false;
-        let     end_of_old_exif  = (old_exif_pos + old_exif_len) as usize;
+        let mut new_data_written = false;
+        let     end_of_old_data  = (old_data_pos + old_data_len) as usize;
 
         for iso_box in &mut self.boxes
         {
             let mut serialized = iso_box.serialize();
 
-            // If this box encompasses the exif data area, update its size and
+            // If this box encompasses the replaced data area, update its size and
             // serialize it again
             // TODO: As this is not the cleanest approach (e.g. what if the
-            // exif area is not in this top level box but some nested box? 
+            // data area is not in this top level box but some nested box? 
             // -> requires update of size fields of all boxes "downward") some
             // other solution needs to be found for this
             // In the meantime, this should work for the majority of HEIFs
             if 
-                written_bytes + serialized.len() >= end_of_old_exif 
+                written_bytes + serialized.len() >= end_of_old_data 
                 && 
-                !new_exif_written
+                !new_data_written
             {
                 let new_size = (iso_box.get_header().get_box_size() as i64 + delta) as u64;
                 iso_box.get_header_mut().set_box_size(new_size);
                 serialized = iso_box.serialize();
 
-                // Write the serialized box with the OLD exif data
+                // Write the serialized box with the OLD data
                 cursor.get_mut().extend(&serialized);
 
-                // Remove old exif data
+                // Remove old data
                 range_remove(
                     cursor.get_mut(), 
-                    old_exif_pos as usize, 
-                    (old_exif_pos + old_exif_len) as usize
+                    old_data_pos as usize, 
+                    (old_data_pos + old_data_len) as usize
                 );
 
-                // Insert new exif data
+                // Insert new data
                 insert_multiple_at(
                     cursor.get_mut(),
-                    old_exif_pos as usize, 
-                    &mut new_exif_area
+                    old_data_pos as usize, 
+                    &mut new_data_area
                 );
 
-                new_exif_written = true;
+                new_data_written = true;
             }
             else
             {
@@ -661,3 +1589,87 @@ HeifContainer
         return self.generic_write_metadata(cursor.get_mut(), &new_metadata);
     }
 }
+
+
+/// Returns the ICC profile if the given property is a `colr` box with the 
+/// colour type `prof` (unrestricted) or `rICC` (restricted ICC profile)
+/// See ISO/IEC 14496-12:2015, § 12.1.5
+fn
+get_icc_colr_data
+(
+    property: &dyn GenericIsoBox
+)
+-> Option<&[u8]>
+{
+    if property.get_header().get_box_type() != BoxType::colr
+    {
+        return None;
+    }
+
+    let data = property.as_any().downcast_ref::<IsoBox>()?.get_data();
+    match data.get(0..4)
+    {
+        Some(b"prof") | Some(b"rICC") => return Some(&data[4..]),
+        _                             => return None,
+    }
+}
+
+/// Gets the data of the extent from the given source, i.e. the data of the
+/// `idat` box or of another item. A length of zero refers to the entire 
+/// remaining data. 
+fn
+get_extent_slice
+<'a>
+(
+    source:      &'a [u8],
+    base_offset: u64,
+    extent:      &ItemLocationEntryExtentEntry,
+)
+-> Result<&'a [u8], std::io::Error>
+{
+    let start = base_offset + extent.extent_offset;
+    let end   = match extent.extent_length
+    {
+        0      => source.len() as u64,
+        length => start + length,
+    };
+
+    match source.get(start as usize..end as usize)
+    {
+        Some(slice) => return Ok(slice),
+        None        => return io_error!(InvalidData, "Item extent exceeds its data source!"),
+    }
+}
+
+/// Checks if the two properties are of the same kind, i.e. have the same box
+/// type and - for `colr` properties - both store either nclx or ICC data
+fn
+is_same_kind_of_property
+(
+    a: &HeifItemProperty,
+    b: &HeifItemProperty,
+)
+-> bool
+{
+    if let (HeifItemProperty::Other { box_type: a_type, .. }, HeifItemProperty::Other { box_type: b_type, .. }) = (a, b)
+    {
+        return a_type == b_type;
+    }
+    return std::mem::discriminant(a) == std::mem::discriminant(b);
+}
+
+/// Transformative properties change the reconstructed image, in the order
+/// of their association. See ISO/IEC 23008-12:2017, § 6.5.1
+fn
+is_transformative_property
+(
+    property: &HeifItemProperty,
+)
+-> bool
+{
+    return matches!(property, 
+        HeifItemProperty::CleanAperture { .. } 
+        | HeifItemProperty::Rotation    { .. } 
+        | HeifItemProperty::Mirror      { .. }
+    );
+}
@@ -6,8 +6,8 @@
 // top level, the Image File Format standard 23008-12 tells us that files with
 // the brand `mif1` do *not* require such a box. 
 
-mod box_type;
-mod box_header;
+pub(crate) mod box_type;
+pub(crate) mod box_header;
 mod boxes;
 mod container;
 
@@ -21,7 +21,13 @@ use crate::general_file_io::open_read_file;
 use crate::general_file_io::open_write_file;
 
 use crate::general_file_io::EXIF_HEADER;
+use crate::xmp::sync_xmp_of_image;
 use crate::metadata::Metadata;
+use crate::metadata::heif_io::HeifAssociatedProperty;
+use crate::metadata::heif_io::HeifImageItem;
+use crate::metadata::heif_io::HeifItemProperty;
+use crate::metadata::write_options::WriteOptions;
+use crate::metadata::xmp_io::XmpSyncMode;
 
 use crate::heif::boxes::read_next_box;
 use crate::heif::container::HeifContainer;
@@ -66,21 +72,39 @@ pub(crate) fn
 write_metadata
 (
 	file_buffer: &mut Vec<u8>,
-	metadata:    &Metadata
+	metadata:    &Metadata,
+	options:     &WriteOptions
 )
 -> Result<(), std::io::Error> 
 {
-    let mut cursor    = Cursor::new(file_buffer);
+    let mut cursor    = Cursor::new(&mut *file_buffer);
     let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
 
-    return container.generic_write_metadata(cursor.get_mut(), metadata);
+    container.generic_write_metadata(cursor.get_mut(), metadata)?;
+
+    // Keep the XMP data in sync with the new EXIF data, if requested
+    // The container gets constructed again when reading the XMP data as
+    // writing the EXIF data might have changed its location
+    if options.xmp_sync_mode != XmpSyncMode::Disabled
+    {
+        sync_xmp_of_image(
+            file_buffer, 
+            metadata, 
+            options.xmp_sync_mode, 
+            &|buffer| read_xmp(buffer), 
+            &write_xmp
+        )?;
+    }
+
+    return Ok(());
 }
 
 pub(crate) fn
 file_write_metadata
 (
     path:     &Path,
-    metadata: &Metadata
+    metadata: &Metadata,
+    options:  &WriteOptions
 )
 -> Result<(), std::io::Error>
 {
@@ -90,20 +114,101 @@ file_write_metadata
     let mut file_buffer: Vec<u8> = Vec::new();
     file.read_to_end(&mut file_buffer)?;
 
-    let mut cursor    = Cursor::new(file_buffer);
-    let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
-
-    container.generic_write_metadata(cursor.get_mut(), metadata)?;
+    write_metadata(&mut file_buffer, metadata, options)?;
 
     // Seek back to start, write the file and adjust its length, possibly 
     // truncating the file if new contents are shorter
     file.seek(std::io::SeekFrom::Start(0))?;
-    file.write_all(cursor.get_ref())?;
-    file.set_len(cursor.get_ref().len() as u64)?;
+    file.write_all(&file_buffer)?;
+    file.set_len(file_buffer.len() as u64)?;
 
     return Ok(());
 }
 
+pub(crate) fn
+read_image_items
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<HeifImageItem>, std::io::Error>
+{
+    let mut cursor    = Cursor::new(file_buffer);
+    let     container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+    return container.get_image_items();
+}
+
+pub(crate) fn
+read_image_metadata
+(
+    file_buffer: &[u8],
+    item_id:     u32,
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut cursor    = Cursor::new(file_buffer);
+    let     container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+    return container.get_image_exif_data(&mut cursor, item_id);
+}
+
+pub(crate) fn
+write_image_metadata
+(
+    file_buffer: &mut Vec<u8>,
+    metadata:    &Metadata,
+    item_id:     u32,
+)
+-> Result<(), std::io::Error>
+{
+    let mut cursor    = Cursor::new(&mut *file_buffer);
+    let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+
+    return container.generic_write_image_metadata(file_buffer, metadata, item_id);
+}
+
+pub(crate) fn
+read_item_properties
+(
+    file_buffer: &[u8],
+    item_id:     u32,
+)
+-> Result<Vec<HeifAssociatedProperty>, std::io::Error>
+{
+    let mut cursor    = Cursor::new(file_buffer);
+    let     container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+    return container.get_item_properties(item_id);
+}
+
+pub(crate) fn
+write_item_property
+(
+    file_buffer: &mut Vec<u8>,
+    item_id:     u32,
+    property:    &HeifItemProperty,
+    essential:   bool,
+)
+-> Result<(), std::io::Error>
+{
+    let mut cursor    = Cursor::new(&mut *file_buffer);
+    let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+
+    return container.generic_write_item_property(file_buffer, item_id, property, essential);
+}
+
+pub(crate) fn
+remove_item_property
+(
+    file_buffer: &mut Vec<u8>,
+    item_id:     u32,
+    box_type:    &str,
+)
+-> Result<(), std::io::Error>
+{
+    let mut cursor    = Cursor::new(&mut *file_buffer);
+    let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+
+    return container.generic_remove_item_property(file_buffer, item_id, box_type);
+}
+
 /// Encodes the given metadata into a vector of bytes that can be used as
 /// an exif box in an HEIF file.
 pub(crate) fn 
@@ -166,3 +271,105 @@ file_clear_metadata
 
     return Ok(());
 }
+
+
+
+
+pub(crate) fn
+read_xmp
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut cursor    = Cursor::new(file_buffer);
+    let     container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+    return container.get_xmp_data(&mut cursor);
+}
+
+pub(crate) fn
+file_read_xmp
+(
+    path: &Path
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut file      = open_read_file(path)?;
+    let     container = HeifContainer::construct_from_cursor_unboxed(&mut file)?;
+    return container.get_xmp_data(&mut file);
+}
+
+pub(crate) fn
+write_xmp
+(
+    file_buffer: &mut Vec<u8>,
+    xmp_data:    &[u8],
+)
+-> Result<(), std::io::Error>
+{
+    let mut cursor    = Cursor::new(&mut *file_buffer);
+    let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+
+    return container.generic_write_xmp(file_buffer, xmp_data);
+}
+
+pub(crate) fn
+file_write_xmp
+(
+    path:     &Path,
+    xmp_data: &[u8],
+)
+-> Result<(), std::io::Error>
+{
+    let mut file = open_write_file(path)?;
+    let mut file_buffer: Vec<u8> = Vec::new();
+    file.read_to_end(&mut file_buffer)?;
+
+    write_xmp(&mut file_buffer, xmp_data)?;
+
+    file.seek(std::io::SeekFrom::Start(0))?;
+    file.write_all(&file_buffer)?;
+    file.set_len(file_buffer.len() as u64)?;
+
+    return Ok(());
+}
+
+
+pub(crate) fn
+read_icc_profile
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut cursor    = Cursor::new(file_buffer);
+    let     container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+    return container.get_icc_profile();
+}
+
+pub(crate) fn
+write_icc_profile
+(
+    file_buffer: &mut Vec<u8>,
+    icc_profile: &[u8],
+)
+-> Result<(), std::io::Error>
+{
+    let mut cursor    = Cursor::new(&mut *file_buffer);
+    let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+
+    return container.generic_write_icc_profile(file_buffer, icc_profile);
+}
+
+pub(crate) fn
+remove_icc_profile
+(
+    file_buffer: &mut Vec<u8>,
+)
+-> Result<(), std::io::Error>
+{
+    let mut cursor    = Cursor::new(&mut *file_buffer);
+    let mut container = HeifContainer::construct_from_cursor_unboxed(&mut cursor)?;
+
+    return container.generic_remove_icc_profile(file_buffer);
+}
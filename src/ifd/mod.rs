@@ -4,9 +4,9 @@
 pub mod get;
 pub mod set;
 
-use std::io::Cursor;
 use std::io::Read;
 use std::io::Seek;
+use std::io::SeekFrom;
 use std::vec;
 
 use crate::endian::*;
@@ -96,26 +96,34 @@ ImageFileDirectory
 
     /// If everything goes Ok and there is enough data to unpack, this returns
     /// the offset to the next generic IFD that needs to be processed.
+    /// The data referenced by `StripOffsets` is only read if `read_strip_data`
+    /// is set - otherwise, the strip tags are skipped. This allows decoding 
+    /// e.g. camera raw files without reading the (large) image data. 
     pub(crate) fn
     decode_ifd
+    <T: Read + Seek>
     (
-        data_cursor:         &mut Cursor<&Vec<u8>>,
+        data_cursor:         &mut T,
         data_begin_position:      u64,                                          // Stays the same for all calls to this function while decoding
         endian:              &    Endian,
         group:               &    ExifTagGroup,
         generic_ifd_nr:           u32,                                          // Reuse value for recursive calls; only gets incremented by caller
         insert_into:         &mut Vec<ImageFileDirectory>,                      // Stays the same for all calls to this function while decoding
+        read_strip_data:          bool,                                         // Stays the same for all calls to this function while decoding
     )
     -> Result<Option<u32>, std::io::Error>
     {
         ////////////////////////////////////////////////////////////////////////
         // PREPARATION 
 
-        // Backup the entry position where this IFD started
-        let data_cursor_entry_position = data_cursor.position();
+        // Backup the entry position where this IFD started and determine the
+        // total length of the data
+        let data_cursor_entry_position = data_cursor.stream_position()?;
+        let data_length                = data_cursor.seek(SeekFrom::End(0))?;
+        data_cursor.seek(SeekFrom::Start(data_cursor_entry_position))?;
 
         // Check if there is enough data to decode an IFD
-        if (data_cursor.get_ref().len() as i64 - data_cursor_entry_position as i64) < 6i64
+        if (data_length as i64 - data_cursor_entry_position as i64) < 6i64
         {
             return Ok(None);
         }
@@ -131,8 +139,8 @@ ImageFileDirectory
             + IFD_ENTRY_LENGTH as usize * number_of_entries as usize 
             + IFD_END_NO_LINK.len();
         let available = (0
-            + data_cursor.get_ref().len() as i64 
-            - data_cursor_entry_position  as i64) as usize;
+            + data_length                as i64 
+            - data_cursor_entry_position as i64) as usize;
 
         if required > available
         {
@@ -198,9 +206,9 @@ ImageFileDirectory
                 let hex_offset = from_u8_vec_res_macro!(u32, &entry_buffer[8..12], endian)?;
 
                 // Backup current position & go to offset position
-                let backup_position = data_cursor.position();
-                data_cursor.set_position(data_begin_position);
-                data_cursor.seek(std::io::SeekFrom::Current(hex_offset as i64))?;
+                let backup_position = data_cursor.stream_position()?;
+                data_cursor.seek(SeekFrom::Start(data_begin_position))?;
+                data_cursor.seek(SeekFrom::Current(hex_offset as i64))?;
 
                 // Read the raw data
                 let mut raw_data_buffer = vec![0u8; byte_count as usize];
@@ -208,7 +216,7 @@ ImageFileDirectory
                 raw_data = raw_data_buffer.to_vec();
             
                 // Rewind the cursor to the start of the next entry
-                data_cursor.set_position(backup_position);
+                data_cursor.seek(SeekFrom::Start(backup_position))?;
             }
             else
             {
@@ -260,11 +268,11 @@ ImageFileDirectory
             {
                 // Compute the offset to the SubIFD and save the current position
                 let offset          = from_u8_vec_res_macro!(u32, &raw_data, endian)? as usize;
-                let backup_position = data_cursor.position();
+                let backup_position = data_cursor.stream_position()?;
 
                 // Go to the SubIFD offset and decode that
-                data_cursor.set_position(data_begin_position);
-                data_cursor.seek(std::io::SeekFrom::Current(offset as i64))?;
+                data_cursor.seek(SeekFrom::Start(data_begin_position))?;
+                data_cursor.seek(SeekFrom::Current(offset as i64))?;
 
                 let subifd_decode_result = Self::decode_ifd(
                     data_cursor,
@@ -273,6 +281,7 @@ ImageFileDirectory
                     &subifd_group,
                     generic_ifd_nr,
                     insert_into,
+                    read_strip_data,
                 );
 
                 // Check that this actually worked
@@ -294,7 +303,7 @@ ImageFileDirectory
                     // So, for now we just assume that `subifd_result` is not
                     // of relevance until evidence suggests otherwise.
                     
-                    data_cursor.set_position(backup_position);
+                    data_cursor.seek(SeekFrom::Start(backup_position))?;
                     continue;
                 }
                 else if let Err(decode_err) = subifd_decode_result
@@ -357,7 +366,14 @@ ImageFileDirectory
         // At this stage we have decoded the tags themselves. 
         // However, the data offset tags need further processing (i.e. their 
         // data needs to be read as well)
-        if let (Some(strip_tags_0), Some(strip_tags_1)) = strip_tags 
+        if !read_strip_data
+        {
+            if strip_tags.0.is_some() || strip_tags.1.is_some()
+            {
+                log::debug!("Skipping strip data of IFD {group:?} {generic_ifd_nr}");
+            }
+        }
+        else if let (Some(strip_tags_0), Some(strip_tags_1)) = strip_tags 
         {
             // 0 -> offsets
             // 1 -> byte counts
@@ -372,15 +388,15 @@ ImageFileDirectory
                     strip_tags_1.get_tag_type()
                 )
             {
-                let backup_position = data_cursor.position();
+                let backup_position = data_cursor.stream_position()?;
 
                 let mut strip_data = Vec::new();
 
                 // Gather the data from the offsets
                 for (offset, byte_count) in offsets.iter().zip(byte_counts.iter())
                 {
-                    data_cursor.set_position(data_begin_position);
-                    data_cursor.seek(std::io::SeekFrom::Current(*offset as i64))?;
+                    data_cursor.seek(SeekFrom::Start(data_begin_position))?;
+                    data_cursor.seek(SeekFrom::Current(*offset as i64))?;
 
                     let mut data_buffer = vec![0u8; *byte_count as usize];
                     data_cursor.read_exact(&mut data_buffer)?;
@@ -394,7 +410,7 @@ ImageFileDirectory
                 tags.push(ExifTag::StripByteCounts(byte_counts));
 
                 // Restore backup position
-                data_cursor.set_position(backup_position);
+                data_cursor.seek(SeekFrom::Start(backup_position))?;
             }
         }
 
@@ -413,15 +429,15 @@ ImageFileDirectory
                     thumbnail_info_1.get_tag_type()
                 )
             {
-                let backup_position = data_cursor.position();
+                let backup_position = data_cursor.stream_position()?;
 
                 if offset.len() == 1 && length.len() == 1
                 {
                     let mut thumbnail_data = vec![0u8; length[0] as usize];
 
                     // Gather the data at the offset
-                    data_cursor.set_position(data_begin_position);
-                    data_cursor.seek(std::io::SeekFrom::Current(offset[0] as i64))?;
+                    data_cursor.seek(SeekFrom::Start(data_begin_position))?;
+                    data_cursor.seek(SeekFrom::Current(offset[0] as i64))?;
                     data_cursor.read_exact(&mut thumbnail_data)?;
 
                     // Push ThumbnailOffset tag to tags vector
@@ -436,7 +452,7 @@ ImageFileDirectory
                 }
 
                 // Restore backup position
-                data_cursor.set_position(backup_position);
+                data_cursor.seek(SeekFrom::Start(backup_position))?;
             }
         }
 
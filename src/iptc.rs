@@ -0,0 +1,534 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! IPTC-IIM data sets, as usually stored inside a Photoshop image resource
+//! (see [`ImageResources`](../photoshop/struct.ImageResources.html)).
+//! Each data set consists of the tag marker 0x1C, a record number, a data set
+//! number, the length of the data and the data itself. The application
+//! record (record 2) contains the commonly used fields like the caption or
+//! the keywords, the character set is specified by data set 1:90.
+
+use crate::endian::Endian;
+use crate::general_file_io::io_error;
+use crate::u8conversion::*;
+
+const IPTC_TAG_MARKER: u8 = 0x1c;
+
+const ENVELOPE_RECORD:    u8 = 1;
+const APPLICATION_RECORD: u8 = 2;
+
+/// Data set 1:90, specifying the character set of the following records
+const CODED_CHARACTER_SET: u8 = 90;
+
+/// ISO 2022 escape sequence that indicates UTF-8 encoded data
+const UTF8_ESCAPE_SEQUENCE: [u8; 3] = [0x1b, 0x25, 0x47];
+
+/// Data sets of the application record (record 2) with text values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum
+IptcTag
+{
+    ObjectName,                     // 2:05
+    EditStatus,                     // 2:07
+    Urgency,                        // 2:10
+    Category,                       // 2:15
+    SupplementalCategories,         // 2:20, repeatable
+    Keywords,                       // 2:25, repeatable
+    SpecialInstructions,            // 2:40
+    DateCreated,                    // 2:55, CCYYMMDD
+    TimeCreated,                    // 2:60, HHMMSS±HHMM
+    DigitalCreationDate,            // 2:62
+    DigitalCreationTime,            // 2:63
+    OriginatingProgram,             // 2:65
+    ProgramVersion,                 // 2:70
+    Byline,                         // 2:80, repeatable
+    BylineTitle,                    // 2:85, repeatable
+    City,                           // 2:90
+    SubLocation,                    // 2:92
+    ProvinceState,                  // 2:95
+    CountryCode,                    // 2:100
+    CountryName,                    // 2:101
+    OriginalTransmissionReference,  // 2:103
+    Headline,                       // 2:105
+    Credit,                         // 2:110
+    Source,                         // 2:115
+    CopyrightNotice,                // 2:116
+    Contact,                        // 2:118, repeatable
+    Caption,                        // 2:120
+    Writer,                         // 2:122, repeatable
+    Unknown(u8),
+}
+
+impl
+IptcTag
+{
+    /// Gets the number of the data set in the application record
+    pub fn
+    as_u8
+    (
+        &self
+    )
+    -> u8
+    {
+        match *self
+        {
+            IptcTag::ObjectName                    => 5,
+            IptcTag::EditStatus                    => 7,
+            IptcTag::Urgency                       => 10,
+            IptcTag::Category                      => 15,
+            IptcTag::SupplementalCategories        => 20,
+            IptcTag::Keywords                      => 25,
+            IptcTag::SpecialInstructions           => 40,
+            IptcTag::DateCreated                   => 55,
+            IptcTag::TimeCreated                   => 60,
+            IptcTag::DigitalCreationDate           => 62,
+            IptcTag::DigitalCreationTime           => 63,
+            IptcTag::OriginatingProgram            => 65,
+            IptcTag::ProgramVersion                => 70,
+            IptcTag::Byline                        => 80,
+            IptcTag::BylineTitle                   => 85,
+            IptcTag::City                          => 90,
+            IptcTag::SubLocation                   => 92,
+            IptcTag::ProvinceState                 => 95,
+            IptcTag::CountryCode                   => 100,
+            IptcTag::CountryName                   => 101,
+            IptcTag::OriginalTransmissionReference => 103,
+            IptcTag::Headline                      => 105,
+            IptcTag::Credit                        => 110,
+            IptcTag::Source                        => 115,
+            IptcTag::CopyrightNotice               => 116,
+            IptcTag::Contact                       => 118,
+            IptcTag::Caption                       => 120,
+            IptcTag::Writer                        => 122,
+            IptcTag::Unknown(dataset)              => dataset,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+IptcDataSet
+{
+    record:  u8,
+    dataset: u8,
+    data:    Vec<u8>,
+}
+
+impl
+IptcDataSet
+{
+    pub fn
+    get_record
+    (
+        &self
+    )
+    -> u8
+    {
+        return self.record;
+    }
+
+    pub fn
+    get_dataset
+    (
+        &self
+    )
+    -> u8
+    {
+        return self.dataset;
+    }
+
+    pub fn
+    get_data
+    (
+        &self
+    )
+    -> &[u8]
+    {
+        return &self.data;
+    }
+
+    /// Data sets 2:00 (record version) and 2:200 onwards (e.g. previews)
+    /// contain binary data, all other application data sets contain text
+    fn
+    is_text
+    (
+        &self
+    )
+    -> bool
+    {
+        return self.record == APPLICATION_RECORD
+            && self.dataset != 0
+            && self.dataset < 200;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct
+IptcData
+{
+    datasets: Vec<IptcDataSet>,
+}
+
+impl
+IptcData
+{
+    pub fn
+    new
+    ()
+    -> IptcData
+    {
+        return IptcData { datasets: Vec::new() };
+    }
+
+    pub fn
+    decode
+    (
+        data: &[u8]
+    )
+    -> Result<IptcData, std::io::Error>
+    {
+        let mut datasets = Vec::new();
+        let mut position = 0;
+
+        while position + 5 <= data.len()
+        {
+            if data[position] != IPTC_TAG_MARKER
+            {
+                // Trailing padding bytes
+                if data[position..].iter().all(|byte| *byte == 0x00)
+                {
+                    break;
+                }
+
+                return io_error!(
+                    InvalidData,
+                    format!("Expected IPTC tag marker at position {}!", position)
+                );
+            }
+
+            let record  = data[position + 1];
+            let dataset = data[position + 2];
+            let mut length = from_u8_vec_res_macro!(u16, &data[position+3..position+5], &Endian::Big)? as usize;
+            position += 5;
+
+            // Extended data set: The lower 15 bits give the number of bytes
+            // that make up the actual length of the data
+            if length & 0x8000 != 0
+            {
+                let length_of_length = length & 0x7fff;
+                if length_of_length > 4 || position + length_of_length > data.len()
+                {
+                    return io_error!(InvalidData, "Invalid extended IPTC data set length!");
+                }
+
+                length = data[position..position + length_of_length]
+                    .iter()
+                    .fold(0usize, |acc, byte| (acc << 8) | *byte as usize);
+                position += length_of_length;
+            }
+
+            if position + length > data.len()
+            {
+                return io_error!(
+                    UnexpectedEof,
+                    format!("IPTC data set {}:{} exceeds end of data!", record, dataset)
+                );
+            }
+
+            datasets.push(IptcDataSet {
+                record,
+                dataset,
+                data: data[position..position + length].to_vec()
+            });
+            position += length;
+        }
+
+        return Ok(IptcData { datasets });
+    }
+
+    pub fn
+    encode
+    (
+        &self
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        let mut encoded = Vec::new();
+
+        for dataset in &self.datasets
+        {
+            encoded.push(IPTC_TAG_MARKER);
+            encoded.push(dataset.record);
+            encoded.push(dataset.dataset);
+
+            if dataset.data.len() < 0x8000
+            {
+                encoded.extend(to_u8_vec_macro!(u16, &(dataset.data.len() as u16), &Endian::Big));
+            }
+            else if dataset.data.len() <= u32::MAX as usize
+            {
+                encoded.extend(to_u8_vec_macro!(u16, &0x8004u16,                   &Endian::Big));
+                encoded.extend(to_u8_vec_macro!(u32, &(dataset.data.len() as u32), &Endian::Big));
+            }
+            else
+            {
+                return io_error!(InvalidInput, "IPTC data set is too long!");
+            }
+
+            encoded.extend(dataset.data.iter());
+        }
+
+        return Ok(encoded);
+    }
+
+    pub fn
+    iter
+    (
+        &self
+    )
+    -> std::slice::Iter<'_, IptcDataSet>
+    {
+        return self.datasets.iter();
+    }
+
+    /// Checks whether data set 1:90 declares the data as UTF-8 encoded
+    pub fn
+    is_utf8
+    (
+        &self
+    )
+    -> bool
+    {
+        return self.datasets.iter().any(|dataset|
+            dataset.record  == ENVELOPE_RECORD     &&
+            dataset.dataset == CODED_CHARACTER_SET &&
+            dataset.data    == UTF8_ESCAPE_SEQUENCE
+        );
+    }
+
+    /// Decodes text according to the character set of this IPTC data.
+    /// If no UTF-8 character set is declared, the text still gets decoded as
+    /// UTF-8 if it is valid UTF-8 (as quite a few applications don't set
+    /// 1:90), otherwise it is interpreted as ISO 8859-1.
+    fn
+    decode_text
+    (
+        &self,
+        data: &[u8]
+    )
+    -> String
+    {
+        if self.is_utf8()
+        {
+            return String::from_utf8_lossy(data).to_string();
+        }
+
+        if let Ok(text) = std::str::from_utf8(data)
+        {
+            return text.to_string();
+        }
+
+        return data.iter().map(|byte| *byte as char).collect();
+    }
+
+    /// Gets all values of the given tag, e.g. all keywords
+    pub fn
+    get_strings
+    (
+        &self,
+        tag: IptcTag
+    )
+    -> Vec<String>
+    {
+        return self.datasets.iter()
+            .filter(|dataset|
+                dataset.record  == APPLICATION_RECORD &&
+                dataset.dataset == tag.as_u8()
+            )
+            .map(|dataset| self.decode_text(&dataset.data))
+            .collect();
+    }
+
+    /// Gets the first value of the given tag
+    pub fn
+    get_string
+    (
+        &self,
+        tag: IptcTag
+    )
+    -> Option<String>
+    {
+        return self.get_strings(tag).into_iter().next();
+    }
+
+    /// Replaces all values of the given tag with the given value
+    pub fn
+    set_string
+    (
+        &mut self,
+        tag:   IptcTag,
+        value: &str
+    )
+    {
+        self.set_strings(tag, &[value]);
+    }
+
+    /// Replaces all values of the given tag with the given values, e.g. for
+    /// repeatable tags like `Keywords`. If any value is not plain ASCII, the
+    /// data gets converted to UTF-8 (including data set 1:90).
+    pub fn
+    set_strings
+    <S: AsRef<str>>
+    (
+        &mut self,
+        tag:    IptcTag,
+        values: &[S]
+    )
+    {
+        self.remove(tag);
+        for value in values
+        {
+            self.add_string(tag, value.as_ref());
+        }
+    }
+
+    /// Adds another value for the given tag, keeping the existing ones
+    pub fn
+    add_string
+    (
+        &mut self,
+        tag:   IptcTag,
+        value: &str
+    )
+    {
+        if !value.is_ascii() && !self.is_utf8()
+        {
+            self.convert_to_utf8();
+        }
+
+        // The application record should start with the record version
+        if !self.datasets.iter().any(|dataset| dataset.record == APPLICATION_RECORD)
+        {
+            self.insert_dataset(APPLICATION_RECORD, 0, vec![0x00, 0x04]);
+        }
+
+        self.insert_dataset(APPLICATION_RECORD, tag.as_u8(), value.as_bytes().to_vec());
+    }
+
+    /// Removes all values of the given tag
+    pub fn
+    remove
+    (
+        &mut self,
+        tag: IptcTag
+    )
+    {
+        self.datasets.retain(|dataset|
+            dataset.record  != APPLICATION_RECORD ||
+            dataset.dataset != tag.as_u8()
+        );
+    }
+
+    /// Inserts a new data set behind all data sets with lower or equal
+    /// record and data set numbers, as the records must be in ascending order
+    fn
+    insert_dataset
+    (
+        &mut self,
+        record:  u8,
+        dataset: u8,
+        data:    Vec<u8>
+    )
+    {
+        let position = self.datasets.iter()
+            .position(|other| (other.record, other.dataset) > (record, dataset))
+            .unwrap_or(self.datasets.len());
+
+        self.datasets.insert(position, IptcDataSet { record, dataset, data });
+    }
+
+    /// Re-encodes all text data sets as UTF-8 and declares this via 1:90
+    fn
+    convert_to_utf8
+    (
+        &mut self
+    )
+    {
+        let converted = self.datasets.iter()
+            .map(|dataset|
+                if dataset.is_text()
+                {
+                    self.decode_text(&dataset.data).into_bytes()
+                }
+                else
+                {
+                    dataset.data.clone()
+                }
+            )
+            .collect::<Vec<Vec<u8>>>();
+
+        for (dataset, data) in self.datasets.iter_mut().zip(converted)
+        {
+            dataset.data = data;
+        }
+
+        self.datasets.retain(|dataset|
+            dataset.record  != ENVELOPE_RECORD ||
+            dataset.dataset != CODED_CHARACTER_SET
+        );
+        self.insert_dataset(ENVELOPE_RECORD, CODED_CHARACTER_SET, UTF8_ESCAPE_SEQUENCE.to_vec());
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn
+    latin1_to_utf8_conversion()
+    -> Result<(), std::io::Error>
+    {
+        // 2:00 record version, 2:120 caption "Caf\xe9" in ISO 8859-1
+        let data = vec![
+            0x1c, 0x02, 0x00, 0x00, 0x02, 0x00, 0x04,
+            0x1c, 0x02, 0x78, 0x00, 0x04, 0x43, 0x61, 0x66, 0xe9,
+        ];
+
+        let mut iptc = IptcData::decode(&data)?;
+        assert!(!iptc.is_utf8());
+        assert_eq!(iptc.get_string(IptcTag::Caption), Some("Café".to_string()));
+        assert_eq!(iptc.encode()?, data);
+
+        iptc.set_strings(IptcTag::Keywords, &["Straße", "Wien"]);
+        assert!(iptc.is_utf8());
+
+        let decoded = IptcData::decode(&iptc.encode()?)?;
+        assert_eq!(decoded.get_string(IptcTag::Caption), Some("Café".to_string()));
+        assert_eq!(decoded.get_strings(IptcTag::Keywords), vec!["Straße", "Wien"]);
+
+        // 1:90 comes first, the keywords (2:25) in front of the caption (2:120)
+        let order = decoded.iter()
+            .map(|dataset| (dataset.get_record(), dataset.get_dataset()))
+            .collect::<Vec<(u8, u8)>>();
+        assert_eq!(order, vec![(1, 90), (2, 0), (2, 25), (2, 25), (2, 120)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn
+    extended_dataset_length()
+    -> Result<(), std::io::Error>
+    {
+        let mut iptc = IptcData::new();
+        iptc.set_string(IptcTag::Caption, &"x".repeat(0x9000));
+
+        let encoded = iptc.encode()?;
+        assert_eq!(encoded[7..13], [0x1c, 0x02, 0x78, 0x80, 0x04, 0x00]);
+        assert_eq!(IptcData::decode(&encoded)?, iptc);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,531 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! The JFIF APP0 segment of JPEG files, which stores the version, the pixel
+//! density and an optional uncompressed thumbnail, as well as the JFXX APP0
+//! extension segment that may follow it with a JPEG, palette or RGB
+//! thumbnail. See the JPEG File Interchange Format specification, v1.02.
+
+use crate::endian::Endian;
+use crate::exif_tag::ExifTag;
+use crate::general_file_io::io_error;
+use crate::ifd::ExifTagGroup;
+use crate::metadata::Metadata;
+use crate::rational::uR64;
+use crate::u8conversion::*;
+
+/// The identifier at the start of the JFIF APP0 segment
+pub(crate) const JFIF_IDENTIFIER: &[u8] = b"JFIF\0";
+
+/// The identifier at the start of the JFXX APP0 extension segment
+pub(crate) const JFXX_IDENTIFIER: &[u8] = b"JFXX\0";
+
+// Maximum length of the data in a segment after the identifier
+const MAX_DATA_LENGTH: usize = 0xffff - 2 - 5;
+
+/// Units of the pixel density stored in the JFIF segment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum
+JfifUnits
+{
+    /// The density only specifies the pixel aspect ratio
+    NoUnits           = 0,
+    DotsPerInch       = 1,
+    DotsPerCentimeter = 2,
+}
+
+impl
+JfifUnits
+{
+    fn
+    from_u8
+    (
+        value: u8
+    )
+    -> Result<JfifUnits, std::io::Error>
+    {
+        match value
+        {
+            0 => return Ok(JfifUnits::NoUnits),
+            1 => return Ok(JfifUnits::DotsPerInch),
+            2 => return Ok(JfifUnits::DotsPerCentimeter),
+            _ => return io_error!(InvalidData, format!("Unknown JFIF density units: {}", value)),
+        }
+    }
+
+    /// Converts the units to the value of the EXIF tag `ResolutionUnit`,
+    /// where 1 stands for no absolute unit, 2 for inch and 3 for centimeter
+    pub fn
+    as_exif_resolution_unit
+    (
+        &self
+    )
+    -> u16
+    {
+        return *self as u16 + 1;
+    }
+
+    pub fn
+    from_exif_resolution_unit
+    (
+        value: u16
+    )
+    -> Option<JfifUnits>
+    {
+        match value
+        {
+            1 => return Some(JfifUnits::NoUnits),
+            2 => return Some(JfifUnits::DotsPerInch),
+            3 => return Some(JfifUnits::DotsPerCentimeter),
+            _ => return None,
+        }
+    }
+}
+
+/// The content of the JFIF APP0 segment
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+Jfif
+{
+    version:          (u8, u8),
+    units:            JfifUnits,
+    x_density:        u16,
+    y_density:        u16,
+    thumbnail_width:  u8,
+    thumbnail_height: u8,
+    thumbnail_data:   Vec<u8>,  // RGB, 3 bytes per pixel
+}
+
+impl
+Default
+for
+Jfif
+{
+    fn
+    default
+    ()
+    -> Self
+    {
+        return Jfif::new();
+    }
+}
+
+impl
+Jfif
+{
+    /// Creates a new JFIF v1.02 segment content with an 1:1 aspect ratio
+    /// and no thumbnail
+    pub fn
+    new
+    ()
+    -> Jfif
+    {
+        return Jfif {
+            version:          (1, 2),
+            units:            JfifUnits::NoUnits,
+            x_density:        1,
+            y_density:        1,
+            thumbnail_width:  0,
+            thumbnail_height: 0,
+            thumbnail_data:   Vec::new(),
+        };
+    }
+
+    /// Decodes the data of the JFIF APP0 segment, which must *not* start
+    /// with the `JFIF\0` identifier
+    pub(crate) fn
+    decode
+    (
+        data: &[u8]
+    )
+    -> Result<Jfif, std::io::Error>
+    {
+        if data.len() < 9
+        {
+            return io_error!(InvalidData, "JFIF segment is too short!");
+        }
+
+        let thumbnail_width  = data[7];
+        let thumbnail_height = data[8];
+        let thumbnail_length = 3 * thumbnail_width as usize * thumbnail_height as usize;
+
+        if data.len() < 9 + thumbnail_length
+        {
+            return io_error!(InvalidData, "JFIF thumbnail exceeds end of segment!");
+        }
+
+        return Ok(Jfif {
+            version:          (data[0], data[1]),
+            units:            JfifUnits::from_u8(data[2])?,
+            x_density:        from_u8_vec_res_macro!(u16, &data[3..5], &Endian::Big)?,
+            y_density:        from_u8_vec_res_macro!(u16, &data[5..7], &Endian::Big)?,
+            thumbnail_width,
+            thumbnail_height,
+            thumbnail_data:   data[9..9 + thumbnail_length].to_vec(),
+        });
+    }
+
+    /// Encodes the content of the JFIF APP0 segment, without the identifier
+    pub(crate) fn
+    encode
+    (
+        &self
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        let mut encoded = vec![self.version.0, self.version.1, self.units as u8];
+        encoded.extend(to_u8_vec_macro!(u16, &self.x_density, &Endian::Big));
+        encoded.extend(to_u8_vec_macro!(u16, &self.y_density, &Endian::Big));
+        encoded.push(self.thumbnail_width);
+        encoded.push(self.thumbnail_height);
+        encoded.extend(self.thumbnail_data.iter());
+
+        if encoded.len() > MAX_DATA_LENGTH
+        {
+            return io_error!(InvalidInput, "JFIF thumbnail is too large for APP0 segment!");
+        }
+
+        return Ok(encoded);
+    }
+
+    /// Gets the major and minor version, e.g. (1, 2) for v1.02
+    pub fn
+    get_version
+    (
+        &self
+    )
+    -> (u8, u8)
+    {
+        return self.version;
+    }
+
+    pub fn
+    set_version
+    (
+        &mut self,
+        major: u8,
+        minor: u8
+    )
+    {
+        self.version = (major, minor);
+    }
+
+    pub fn
+    get_units
+    (
+        &self
+    )
+    -> JfifUnits
+    {
+        return self.units;
+    }
+
+    /// Gets the horizontal and vertical pixel density
+    pub fn
+    get_density
+    (
+        &self
+    )
+    -> (u16, u16)
+    {
+        return (self.x_density, self.y_density);
+    }
+
+    /// Sets the units and the horizontal and vertical pixel density, which
+    /// must not be zero
+    pub fn
+    set_density
+    (
+        &mut self,
+        units:     JfifUnits,
+        x_density: u16,
+        y_density: u16
+    )
+    -> Result<(), std::io::Error>
+    {
+        if x_density == 0 || y_density == 0
+        {
+            return io_error!(InvalidInput, "JFIF density must not be zero!");
+        }
+
+        self.units     = units;
+        self.x_density = x_density;
+        self.y_density = y_density;
+        return Ok(());
+    }
+
+    /// Gets the width, height and RGB pixel data of the thumbnail, if any
+    pub fn
+    get_thumbnail
+    (
+        &self
+    )
+    -> Option<(u8, u8, &[u8])>
+    {
+        if self.thumbnail_data.is_empty()
+        {
+            return None;
+        }
+        return Some((self.thumbnail_width, self.thumbnail_height, &self.thumbnail_data));
+    }
+
+    /// Sets the uncompressed thumbnail, consisting of 3 bytes (RGB) per
+    /// pixel. Note that the entire segment must not exceed 64kB, so the
+    /// thumbnail can have at most 21841 pixels.
+    pub fn
+    set_thumbnail
+    (
+        &mut self,
+        width:  u8,
+        height: u8,
+        data:   Vec<u8>
+    )
+    -> Result<(), std::io::Error>
+    {
+        if data.len() != 3 * width as usize * height as usize
+        {
+            return io_error!(InvalidInput, "JFIF thumbnail data does not match its dimensions!");
+        }
+
+        if 9 + data.len() > MAX_DATA_LENGTH
+        {
+            return io_error!(InvalidInput, "JFIF thumbnail is too large for APP0 segment!");
+        }
+
+        self.thumbnail_width  = width;
+        self.thumbnail_height = height;
+        self.thumbnail_data   = data;
+        return Ok(());
+    }
+
+    pub fn
+    remove_thumbnail
+    (
+        &mut self
+    )
+    {
+        self.thumbnail_width  = 0;
+        self.thumbnail_height = 0;
+        self.thumbnail_data.clear();
+    }
+
+    /// Updates the density with the EXIF tags `XResolution`, `YResolution`
+    /// and `ResolutionUnit` of the given metadata. Returns `false` if these
+    /// tags are not present or can't be represented in the JFIF segment.
+    pub fn
+    set_density_from_metadata
+    (
+        &mut self,
+        metadata: &Metadata
+    )
+    -> bool
+    {
+        let Some((x_resolution, y_resolution, unit)) = get_exif_resolution(metadata) else {
+            return false;
+        };
+
+        let Some(units) = JfifUnits::from_exif_resolution_unit(unit) else {
+            return false;
+        };
+
+        let x_density = x_resolution.round();
+        let y_density = y_resolution.round();
+        if
+            !(1.0..=u16::MAX as f64).contains(&x_density)
+            ||
+            !(1.0..=u16::MAX as f64).contains(&y_density)
+        {
+            return false;
+        }
+
+        self.units     = units;
+        self.x_density = x_density as u16;
+        self.y_density = y_density as u16;
+        return true;
+    }
+
+    /// Sets the EXIF tags `XResolution`, `YResolution` and `ResolutionUnit`
+    /// of the given metadata based on the density, e.g. if the image does not
+    /// provide this information via EXIF.
+    pub fn
+    set_resolution_of_metadata
+    (
+        &self,
+        metadata: &mut Metadata
+    )
+    {
+        metadata.set_tag(ExifTag::XResolution(vec![uR64 { nominator: self.x_density as u32, denominator: 1 }]));
+        metadata.set_tag(ExifTag::YResolution(vec![uR64 { nominator: self.y_density as u32, denominator: 1 }]));
+        metadata.set_tag(ExifTag::ResolutionUnit(vec![self.units.as_exif_resolution_unit()]));
+    }
+}
+
+/// Gets the values of `XResolution`, `YResolution` and `ResolutionUnit`. If
+/// the unit is missing, the EXIF default "inch" is assumed.
+fn
+get_exif_resolution
+(
+    metadata: &Metadata
+)
+-> Option<(f64, f64, u16)>
+{
+    let x_resolution = match metadata.get_tag_by_hex(0x011a, Some(ExifTagGroup::GENERIC)).next()
+    {
+        Some(ExifTag::XResolution(values)) => f64::from(values.first()?.clone()),
+        _                                  => return None,
+    };
+
+    let y_resolution = match metadata.get_tag_by_hex(0x011b, Some(ExifTagGroup::GENERIC)).next()
+    {
+        Some(ExifTag::YResolution(values)) => f64::from(values.first()?.clone()),
+        _                                  => return None,
+    };
+
+    let unit = match metadata.get_tag_by_hex(0x0128, Some(ExifTagGroup::GENERIC)).next()
+    {
+        Some(ExifTag::ResolutionUnit(values)) => *values.first()?,
+        _                                     => 2,
+    };
+
+    return Some((x_resolution, y_resolution, unit));
+}
+
+/// The thumbnail stored in the JFXX APP0 extension segment
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum
+JfxxThumbnail
+{
+    /// A JPEG encoded thumbnail, including its SOI and EOI markers
+    Jpeg(Vec<u8>),
+
+    /// One byte per pixel, referring to the 256 RGB entries of the palette
+    Palette { width: u8, height: u8, palette: Vec<u8>, pixels: Vec<u8> },
+
+    /// Three bytes (RGB) per pixel
+    Rgb { width: u8, height: u8, pixels: Vec<u8> },
+}
+
+impl
+JfxxThumbnail
+{
+    /// Decodes the data of the JFXX APP0 segment, which must *not* start
+    /// with the `JFXX\0` identifier
+    pub(crate) fn
+    decode
+    (
+        data: &[u8]
+    )
+    -> Result<JfxxThumbnail, std::io::Error>
+    {
+        let Some((extension_code, data)) = data.split_first() else {
+            return io_error!(InvalidData, "JFXX segment is too short!");
+        };
+
+        match (extension_code, data)
+        {
+            (0x10, _)
+                => return Ok(JfxxThumbnail::Jpeg(data.to_vec())),
+
+            (0x11, [width, height, rest @ ..])
+                if rest.len() >= 768 + *width as usize * *height as usize
+                => return Ok(JfxxThumbnail::Palette {
+                    width:   *width,
+                    height:  *height,
+                    palette: rest[..768].to_vec(),
+                    pixels:  rest[768..768 + *width as usize * *height as usize].to_vec(),
+                }),
+
+            (0x13, [width, height, rest @ ..])
+                if rest.len() >= 3 * *width as usize * *height as usize
+                => return Ok(JfxxThumbnail::Rgb {
+                    width:   *width,
+                    height:  *height,
+                    pixels:  rest[..3 * *width as usize * *height as usize].to_vec(),
+                }),
+
+            (0x11, _) | (0x13, _)
+                => return io_error!(InvalidData, "JFXX thumbnail exceeds end of segment!"),
+
+            _
+                => return io_error!(InvalidData, format!("Unknown JFXX extension code: 0x{:02x}", extension_code)),
+        }
+    }
+
+    /// Encodes the content of the JFXX APP0 segment, without the identifier
+    pub(crate) fn
+    encode
+    (
+        &self
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        let mut encoded = Vec::new();
+
+        match self
+        {
+            JfxxThumbnail::Jpeg(data) => {
+                encoded.push(0x10);
+                encoded.extend(data.iter());
+            },
+
+            JfxxThumbnail::Palette { width, height, palette, pixels } => {
+                if palette.len() != 768 || pixels.len() != *width as usize * *height as usize
+                {
+                    return io_error!(InvalidInput, "JFXX palette thumbnail data does not match its dimensions!");
+                }
+                encoded.extend([0x11, *width, *height]);
+                encoded.extend(palette.iter());
+                encoded.extend(pixels.iter());
+            },
+
+            JfxxThumbnail::Rgb { width, height, pixels } => {
+                if pixels.len() != 3 * *width as usize * *height as usize
+                {
+                    return io_error!(InvalidInput, "JFXX RGB thumbnail data does not match its dimensions!");
+                }
+                encoded.extend([0x13, *width, *height]);
+                encoded.extend(pixels.iter());
+            },
+        }
+
+        if encoded.len() > MAX_DATA_LENGTH
+        {
+            return io_error!(InvalidInput, "JFXX thumbnail is too large for APP0 segment!");
+        }
+
+        return Ok(encoded);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn
+    encode_decode_round_trip()
+    -> Result<(), std::io::Error>
+    {
+        let data = vec![
+            0x01, 0x02, 0x01, 0x01, 0x2c, 0x01, 0x2c,                           // v1.02, 300x300 dpi
+            0x01, 0x02, 0xff, 0x00, 0x00, 0x00, 0xff, 0x00,                     // 1x2 thumbnail
+        ];
+
+        let mut jfif = Jfif::decode(&data)?;
+        assert_eq!(jfif.get_version(), (1, 2));
+        assert_eq!(jfif.get_units(), JfifUnits::DotsPerInch);
+        assert_eq!(jfif.get_density(), (300, 300));
+        assert_eq!(jfif.get_thumbnail(), Some((1, 2, &data[9..])));
+        assert_eq!(jfif.encode()?, data);
+
+        jfif.remove_thumbnail();
+        assert_eq!(jfif.encode()?, [&data[..7], &[0, 0]].concat());
+
+        let thumbnail = JfxxThumbnail::Rgb { width: 1, height: 1, pixels: vec![1, 2, 3] };
+        assert_eq!(JfxxThumbnail::decode(&thumbnail.encode()?)?, thumbnail);
+        assert!(JfxxThumbnail::decode(&[0x13, 2, 2, 0]).is_err());
+
+        Ok(())
+    }
+}
@@ -12,18 +12,99 @@ use std::path::Path;
 
 use crate::endian::Endian;
 use crate::metadata::Metadata;
+use crate::metadata::jpg_io::OversizeExifError;
+use crate::metadata::jpg_io::OversizeExifPolicy;
+use crate::metadata::write_options::WriteOptions;
+use crate::metadata::xmp_io::XmpSyncMode;
+use crate::exif_tag_format::ExifTagFormat;
+use crate::ifd::ExifTagGroup;
 use crate::u8conversion::*;
 use crate::general_file_io::*;
+use crate::io_error_plain;
+use crate::xmp::sync_xmp_of_image;
+use crate::photoshop::ImageResources;
+use crate::photoshop::PHOTOSHOP_HEADER;
+use crate::jfif::Jfif;
+use crate::jfif::JfxxThumbnail;
+use crate::jfif::JFIF_IDENTIFIER;
+use crate::jfif::JFXX_IDENTIFIER;
+use crate::mpf::MpEntry;
+use crate::mpf::MpIndex;
+use crate::mpf::MPF_IDENTIFIER;
+use crate::motion_photo::MotionPhoto;
+use crate::motion_photo::MotionPhotoFormat;
+use crate::motion_photo::SamsungTrailer;
 
 pub(crate) const JPG_SIGNATURE: [u8; 2] = [0xff, 0xd8];
 
 const JPG_MARKER_PREFIX: u8  = 0xff;
+const JPG_APP0_MARKER:   u16 = 0xffe0;
 const JPG_APP1_MARKER:   u16 = 0xffe1;
+const JPG_APP2_MARKER:   u16 = 0xffe2;
+const JPG_APP13_MARKER:  u16 = 0xffed;
+const JPG_COM_MARKER:    u16 = 0xfffe;
+
+const XMP_STANDARD_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+const XMP_EXTENDED_HEADER: &[u8] = b"http://ns.adobe.com/xmp/extension/\0";
+const ICC_PROFILE_HEADER:  &[u8] = b"ICC_PROFILE\0";
+
+// Maximum length of the data in a segment, as the length field (which 
+// includes its own two bytes) can't exceed 0xffff
+const MAX_SEGMENT_DATA_LENGTH:       usize = 0xffff - 2;
+
+// Maximum length of the TIFF encoded EXIF data in a single APP1 segment
+const MAX_EXIF_DATA_LENGTH:          usize = MAX_SEGMENT_DATA_LENGTH - EXIF_HEADER.len();
+
+// An ICC profile is split into chunks, each stored in its own APP2 segment
+// following the header, the sequence number and the number of chunks
+const MAX_ICC_PROFILE_CHUNK_LENGTH:  usize = MAX_SEGMENT_DATA_LENGTH - ICC_PROFILE_HEADER.len() - 2;
+
+// The extended XMP is split into chunks, each stored in its own APP1 segment
+// following the header, a 32 byte GUID, the full length and the offset
+const MAX_EXTENDED_XMP_CHUNK_LENGTH: usize = 65400;
 
 
 
 fn
 encode_metadata_jpg
+(
+    exif_vec: &[u8],
+    metadata: &Metadata,
+    policy:   OversizeExifPolicy,
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    if exif_vec.len() <= MAX_EXIF_DATA_LENGTH
+    {
+        return Ok(encode_exif_segment(exif_vec));
+    }
+
+    match policy
+    {
+        OversizeExifPolicy::Error
+            => return Err(oversize_exif_error(exif_vec.len())),
+
+        OversizeExifPolicy::MultiSegment
+            => return Ok(exif_vec
+                .chunks(MAX_EXIF_DATA_LENGTH)
+                .flat_map(encode_exif_segment)
+                .collect()),
+
+        OversizeExifPolicy::RemoveThumbnail | OversizeExifPolicy::RemoveLargeUndefTags
+            => {
+                let reduced_exif_vec = reduce_metadata(metadata, policy)?;
+                if reduced_exif_vec.len() > MAX_EXIF_DATA_LENGTH
+                {
+                    return Err(oversize_exif_error(reduced_exif_vec.len()));
+                }
+                return Ok(encode_exif_segment(&reduced_exif_vec));
+            },
+    }
+}
+
+/// Encodes a single APP1 segment containing (a part of) the EXIF data
+fn
+encode_exif_segment
 (
     exif_vec: &[u8],
 )
@@ -46,6 +127,68 @@ encode_metadata_jpg
     return jpg_exif;
 }
 
+fn
+oversize_exif_error
+(
+    exif_length: usize
+)
+-> std::io::Error
+{
+    return std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        OversizeExifError { length: EXIF_HEADER.len() + exif_length }
+    );
+}
+
+/// Removes data from a copy of the metadata according to the oversize policy
+/// and returns the encoded result, which may still be too large
+fn
+reduce_metadata
+(
+    metadata: &Metadata,
+    policy:   OversizeExifPolicy,
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut reduced_metadata = metadata.clone();
+    if reduced_metadata.get_ifd(ExifTagGroup::GENERIC, 1).is_some()
+    {
+        reduced_metadata.remove_thumbnail();
+        log::warn!("EXIF data too large for JPEG APP1 segment, removed thumbnail");
+    }
+
+    let mut reduced_exif_vec = reduced_metadata.encode()?;
+
+    if policy != OversizeExifPolicy::RemoveLargeUndefTags
+    {
+        return Ok(reduced_exif_vec);
+    }
+
+    while reduced_exif_vec.len() > MAX_EXIF_DATA_LENGTH
+    {
+        let endian = reduced_metadata.get_endian();
+
+        // Find the largest UNDEF tag across all IFDs
+        let Some((tag_hex, tag_group, tag_length)) = reduced_metadata
+            .get_ifds()
+            .iter()
+            .flat_map(|ifd| ifd.get_tags().iter())
+            .filter(|tag| tag.format() == ExifTagFormat::UNDEF)
+            .map(|tag| (tag.as_u16(), tag.get_group(), tag.value_as_u8_vec(&endian).len()))
+            .max_by_key(|(_, _, tag_length)| *tag_length)
+        else
+        {
+            break;
+        };
+
+        log::warn!("EXIF data too large for JPEG APP1 segment, removed tag 0x{:04x} with {} bytes", tag_hex, tag_length);
+        reduced_metadata.remove_tag_by_hex_group(tag_hex, tag_group);
+        reduced_exif_vec = reduced_metadata.encode()?;
+    }
+
+    return Ok(reduced_exif_vec);
+}
+
 
 
 fn
@@ -100,6 +243,8 @@ clear_metadata
 }
 
 
+/// Removes all segments with the given marker (and prefix, if specified)
+/// from the primary image, keeping the offsets of an MPF segment up to date
 pub(crate) fn
 clear_segment
 (
@@ -108,6 +253,20 @@ clear_segment
     prefix_bytes:   Option<&[u8]>,
 )
 -> Result<(), std::io::Error>
+{
+    return modify_segments(file_buffer, |file_buffer|
+        generic_clear_segment(file_buffer, segment_marker, prefix_bytes)
+    );
+}
+
+fn
+generic_clear_segment
+(
+    file_buffer:    &mut Vec<u8>,
+    segment_marker: u8,
+    prefix_bytes:   Option<&[u8]>,
+)
+-> Result<(), std::io::Error>
 {
     check_signature(file_buffer)?;
 
@@ -283,10 +442,12 @@ pub(crate) fn
 as_u8_vec
 (
     general_encoded_metadata: &[u8],
+    metadata:                 &Metadata,
+    options:                  &WriteOptions,
 )
--> Vec<u8>
+-> Result<Vec<u8>, std::io::Error>
 {
-    encode_metadata_jpg(general_encoded_metadata)
+    encode_metadata_jpg(general_encoded_metadata, metadata, options.oversize_exif_policy)
 }
 
 
@@ -295,18 +456,38 @@ pub(crate) fn
 write_metadata
 (
     file_buffer: &mut Vec<u8>,
-    metadata:    &Metadata
+    metadata:    &Metadata,
+    options:     &WriteOptions
 )
 -> Result<(), std::io::Error>
 {
+    // Encode the data specifically for JPG before touching the file buffer,
+    // as this fails if the data is too large (depending on the policy)
+    let mut encoded_metadata = encode_metadata_jpg(&metadata.encode()?, metadata, options.oversize_exif_policy)?;
+
     // Remove old metadata
     clear_metadata(file_buffer)?;
 
-    // Encode the data specifically for JPG
-    let mut encoded_metadata = encode_metadata_jpg(&metadata.encode()?);
-
     // Insert the metadata right after the signature
-    crate::util::insert_multiple_at(file_buffer, 2, &mut encoded_metadata);
+    insert_segments(file_buffer, 2, &mut encoded_metadata)?;
+
+    // Keep the XMP data in sync with the new EXIF data, if requested
+    if options.xmp_sync_mode != XmpSyncMode::Disabled
+    {
+        sync_xmp_of_image(
+            file_buffer, 
+            metadata, 
+            options.xmp_sync_mode, 
+            &|buffer| read_xmp(buffer), 
+            &write_xmp
+        )?;
+    }
+
+    // Same for the density information of the JFIF segment
+    if options.jfif_density_sync
+    {
+        sync_jfif_density(file_buffer, metadata)?;
+    }
 
     return Ok(());
 }
@@ -319,7 +500,8 @@ pub(crate) fn
 file_write_metadata
 (
     path:     &Path,
-    metadata: &Metadata
+    metadata: &Metadata,
+    options:  &WriteOptions
 )
 -> Result<(), std::io::Error>
 {
@@ -332,11 +514,13 @@ file_write_metadata
     // Writes the metadata to the file_buffer vec
     // The called function handles the removal of old metadata and the JPG
     // specific encoding, so we pass only the generally encoded metadata here
-    write_metadata(&mut file_buffer, metadata)?;
+    write_metadata(&mut file_buffer, metadata, options)?;
 
-    // Seek back to start & write the file
+    // Seek back to start, write the file and adjust its length, possibly 
+    // truncating the file if new contents are shorter
     file.seek(SeekFrom::Start(0))?;
     file.write_all(&file_buffer)?;
+    file.set_len(file_buffer.len() as u64)?;
 
     return Ok(());
 }
@@ -468,10 +652,26 @@ generic_read_metadata
             match byte_buffer[0]
             {
                 0xe1 => {                                                       // APP1 marker
-                    // Read in & return the remaining data
+                    // Read in the remaining data
                     let mut app1_buffer = vec![0u8; remaining_length];
                     cursor.read_exact(&mut app1_buffer)?;
 
+                    // APP1 is also used for storing XMP data, so skip these
+                    if app1_buffer.starts_with(XMP_STANDARD_HEADER)
+                    || app1_buffer.starts_with(XMP_EXTENDED_HEADER)
+                    {
+                        previous_byte_was_marker_prefix = false;
+                        continue;
+                    }
+
+                    // Some cameras split EXIF data that is too large for a
+                    // single segment across multiple consecutive ones
+                    if app1_buffer.starts_with(&EXIF_HEADER)
+                    && remaining_length == MAX_SEGMENT_DATA_LENGTH
+                    {
+                        read_exif_continuation_segments(cursor, &mut app1_buffer)?;
+                    }
+
                     return Ok(app1_buffer);
                 },
 
@@ -506,4 +706,1119 @@ generic_read_metadata
             previous_byte_was_marker_prefix = byte_buffer[0] == JPG_MARKER_PREFIX;
         }
     }
-}
\ No newline at end of file
+}
+
+
+/// Appends the EXIF data of the APP1 segments that immediately follow the
+/// current position and start with the `Exif\0\0` header, without the header
+/// itself. Stops after the first segment that is not completely filled or if
+/// the next segment is not such a continuation, in which case the cursor gets
+/// positioned back at its start.
+fn
+read_exif_continuation_segments
+<T: Seek + Read>
+(
+    cursor:   &mut T,
+    exif_vec: &mut Vec<u8>,
+)
+-> Result<(), std::io::Error>
+{
+    loop
+    {
+        let mut header_buffer = [0u8; 4 + EXIF_HEADER.len()];
+        if let Err(e) = cursor.read_exact(&mut header_buffer)
+        {
+            if e.kind() == std::io::ErrorKind::UnexpectedEof
+            {
+                return Ok(());
+            }
+            return Err(e);
+        }
+
+        let marker = from_u8_vec_res_macro!(u16, &header_buffer[0..2], &Endian::Big)?;
+        let length = from_u8_vec_res_macro!(u16, &header_buffer[2..4], &Endian::Big)? as usize;
+
+        if marker != JPG_APP1_MARKER
+        || header_buffer[4..] != EXIF_HEADER
+        || length < 2 + EXIF_HEADER.len()
+        {
+            cursor.seek(SeekFrom::Current(-(header_buffer.len() as i64)))?;
+            return Ok(());
+        }
+
+        let mut data_buffer = vec![0u8; length - 2 - EXIF_HEADER.len()];
+        cursor.read_exact(&mut data_buffer)?;
+        exif_vec.extend(data_buffer);
+
+        if length - 2 < MAX_SEGMENT_DATA_LENGTH
+        {
+            return Ok(());
+        }
+    }
+}
+
+
+/// Determines the positions of all segments in the JPG data that have a 
+/// length field, i.e. everything except SOI, EOI and the entropy-coded data.
+/// Each entry consists of the marker (e.g. 0xe1 for APP1), the position of 
+/// the marker prefix and the position right after the end of the segment.
+pub(crate) fn
+get_segment_positions
+(
+    file_buffer: &[u8],
+)
+-> Result<Vec<(u8, usize, usize)>, std::io::Error>
+{
+    check_signature(file_buffer)?;
+
+    let mut segments = Vec::new();
+    let mut cursor   = Cursor::new(file_buffer);
+
+    // Setup of variables necessary for going through the file
+    let mut byte_buffer = [0u8; 1];
+    let mut previous_byte_was_marker_prefix = false;
+
+    // Skip 0xFFD8 at the start
+    cursor.set_position(2);
+
+    loop
+    {
+        if let Err(e) = cursor.read_exact(&mut byte_buffer)
+        {
+            if e.kind() == std::io::ErrorKind::UnexpectedEof
+            {
+                // Reached end of file without encountering EOI marker 0xd9
+                // See issue #93 for examples where this happens
+                return Ok(segments);
+            }
+            return Err(e);
+        }
+
+        if previous_byte_was_marker_prefix
+        {
+            match byte_buffer[0]
+            {
+                0xd9 => return Ok(segments),                                    // EOI marker
+                0xff => continue,                                               // Fill byte
+                _    => {}
+            }
+
+            let start = cursor.position() as usize - 2;
+
+            let mut length_buffer = [0u8; 2];
+            cursor.read_exact(&mut length_buffer)?;
+
+            let length = from_u8_vec_res_macro!(u16, &length_buffer, &Endian::Big)?;
+            if length < 2
+            {
+                return io_error!(InvalidData, "Mangled JPG data encountered!");
+            }
+
+            let end = start + 2 + length as usize;
+            if end > file_buffer.len()
+            {
+                return io_error!(UnexpectedEof, "JPG segment exceeds end of data!");
+            }
+
+            segments.push((byte_buffer[0], start, end));
+            cursor.set_position(end as u64);
+
+            if byte_buffer[0] == 0xda                                           // SOS marker
+            {
+                // See `generic_read_metadata`
+                if let Err(e) = skip_ecs(&mut cursor)
+                {
+                    if e.kind() == std::io::ErrorKind::UnexpectedEof
+                    {
+                        return Ok(segments);
+                    }
+                    return Err(e);
+                }
+            }
+
+            previous_byte_was_marker_prefix = false;
+        }
+        else
+        {
+            previous_byte_was_marker_prefix = byte_buffer[0] == JPG_MARKER_PREFIX;
+        }
+    }
+}
+
+/// Reads the XMP data stored in the APP1 segment(s) of the JPG data.
+/// If the standard XMP refers to extended XMP via the 
+/// `xmpNote:HasExtendedXMP` property, the chunks of the extended XMP with 
+/// the matching GUID get reassembled and merged into a single XMP packet.
+pub(crate) fn
+read_xmp
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut standard_xmp    = None;
+    let mut extended_chunks = Vec::new();
+
+    for (marker, start, end) in get_segment_positions(file_buffer)?
+    {
+        if marker != 0xe1 { continue; }
+
+        let segment_data = &file_buffer[start+4..end];
+
+        if segment_data.starts_with(XMP_STANDARD_HEADER) && standard_xmp.is_none()
+        {
+            standard_xmp = Some(segment_data[XMP_STANDARD_HEADER.len()..].to_vec());
+        }
+        else if segment_data.starts_with(XMP_EXTENDED_HEADER)
+        {
+            extended_chunks.push(&segment_data[XMP_EXTENDED_HEADER.len()..]);
+        }
+    }
+
+    let standard_xmp = match standard_xmp
+    {
+        Some(data) => data,
+        None       => return io_error!(Other, "No XMP data found!"),
+    };
+
+    // Check if there is extended XMP that needs to be reassembled
+    let guid = match crate::xmp::get_extended_xmp_guid(&standard_xmp)
+    {
+        Some(guid) => guid,
+        None       => return Ok(standard_xmp),
+    };
+
+    let mut extended_xmp   = Vec::new();
+    let mut bytes_assigned = 0usize;
+
+    for chunk in extended_chunks
+    {
+        // GUID (32 bytes), full length (4 bytes) and offset (4 bytes)
+        if chunk.len() < 40 || chunk[0..32] != *guid.as_bytes()
+        {
+            continue;
+        }
+
+        let full_length = from_u8_vec_res_macro!(u32, &chunk[32..36], &Endian::Big)? as usize;
+        let offset      = from_u8_vec_res_macro!(u32, &chunk[36..40], &Endian::Big)? as usize;
+        let chunk_data  = &chunk[40..];
+
+        if extended_xmp.is_empty()
+        {
+            extended_xmp = vec![0u8; full_length];
+        }
+
+        if full_length != extended_xmp.len() || offset + chunk_data.len() > full_length
+        {
+            return io_error!(InvalidData, "Inconsistent extended XMP chunk information!");
+        }
+
+        extended_xmp[offset..offset + chunk_data.len()].copy_from_slice(chunk_data);
+        bytes_assigned += chunk_data.len();
+    }
+
+    if extended_xmp.is_empty() || bytes_assigned != extended_xmp.len()
+    {
+        log::warn!("Extended XMP with GUID {guid} is missing or incomplete, only returning standard XMP");
+        return Ok(standard_xmp);
+    }
+
+    return crate::xmp::merge_extended_xmp(&standard_xmp, &extended_xmp)
+        .map_err(|e| io_error_plain!(InvalidData, e.to_string()));
+}
+
+pub(crate) fn
+file_read_xmp
+(
+    path: &Path
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    file_check_signature(path)?;
+    return read_xmp(&std::fs::read(path)?);
+}
+
+/// Encodes the given XMP data as APP1 segment(s). If the data does not fit 
+/// into a single segment, it gets split up into standard and extended XMP.
+fn
+encode_xmp_jpg
+(
+    xmp_data: &[u8]
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut encoded = Vec::new();
+
+    if XMP_STANDARD_HEADER.len() + xmp_data.len() <= MAX_SEGMENT_DATA_LENGTH
+    {
+        encode_segment(&mut encoded, JPG_APP1_MARKER, &[XMP_STANDARD_HEADER, xmp_data])?;
+        return Ok(encoded);
+    }
+
+    // Try to keep the simple properties in the standard XMP first, only move
+    // everything into the extended XMP if that is not sufficient
+    for move_attributes in [false, true]
+    {
+        let (standard, extended) = crate::xmp::split_xmp_for_extension(xmp_data, move_attributes)
+            .map_err(|e| io_error_plain!(InvalidData, e.to_string()))?;
+
+        // The GUID is the MD5 digest of the extended XMP as uppercase hex
+        let guid = crate::util::md5(&extended)
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<String>();
+
+        let standard = crate::xmp::add_extended_xmp_guid(&standard, &guid)
+            .map_err(|e| io_error_plain!(InvalidData, e.to_string()))?;
+
+        if XMP_STANDARD_HEADER.len() + standard.len() > MAX_SEGMENT_DATA_LENGTH
+        {
+            continue;
+        }
+
+        encode_segment(&mut encoded, JPG_APP1_MARKER, &[XMP_STANDARD_HEADER, &standard])?;
+
+        let full_length = to_u8_vec_macro!(u32, &(extended.len() as u32), &Endian::Big);
+
+        for (index, chunk) in extended.chunks(MAX_EXTENDED_XMP_CHUNK_LENGTH).enumerate()
+        {
+            let offset = (index * MAX_EXTENDED_XMP_CHUNK_LENGTH) as u32;
+
+            encode_segment(&mut encoded, JPG_APP1_MARKER, &[
+                XMP_EXTENDED_HEADER,
+                guid.as_bytes(),
+                &full_length,
+                &to_u8_vec_macro!(u32, &offset, &Endian::Big),
+                chunk
+            ])?;
+        }
+
+        return Ok(encoded);
+    }
+
+    return io_error!(InvalidData, "Could not split XMP data into standard and extended XMP!");
+}
+
+/// Appends a segment with the given marker (e.g. 0xffe1 for APP1) consisting 
+/// of the given parts to the vector. Fails if the parts don't fit into a
+/// single segment.
+fn
+encode_segment
+(
+    encoded: &mut Vec<u8>,
+    marker:  u16,
+    parts:   &[&[u8]],
+)
+-> Result<(), std::io::Error>
+{
+    let Ok(length) = u16::try_from(2 + parts.iter().map(|part| part.len()).sum::<usize>()) else {
+        return io_error!(InvalidInput, format!("Segment with marker {marker:#06x} exceeds the maximum length of a JPEG segment!"));
+    };
+
+    encoded.extend(to_u8_vec_macro!(u16, &marker, &Endian::Big));
+    encoded.extend(to_u8_vec_macro!(u16, &length, &Endian::Big));
+    for part in parts
+    {
+        encoded.extend(part.iter());
+    }
+
+    return Ok(());
+}
+
+/// Removes all standard and extended XMP segments from the JPG data
+pub(crate) fn
+clear_xmp
+(
+    file_buffer: &mut Vec<u8>,
+)
+-> Result<(), std::io::Error>
+{
+    clear_segment(file_buffer, 0xe1, Some(XMP_STANDARD_HEADER))?;
+    clear_segment(file_buffer, 0xe1, Some(XMP_EXTENDED_HEADER))?;
+    return Ok(());
+}
+
+/// Writes the given XMP data to the JPG data, replacing any previously 
+/// stored (standard and extended) XMP. The new segment(s) get placed after
+/// the leading APP0 and APP1 segments, e.g. JFIF and EXIF.
+pub(crate) fn
+write_xmp
+(
+    file_buffer: &mut Vec<u8>,
+    xmp_data:    &[u8],
+)
+-> Result<(), std::io::Error>
+{
+    clear_xmp(file_buffer)?;
+
+    let mut encoded_xmp = encode_xmp_jpg(xmp_data)?;
+
+    let insert_position = get_insert_position_after(file_buffer, 0xe0..=0xe1)?;
+
+    insert_segments(file_buffer, insert_position, &mut encoded_xmp)?;
+
+    return Ok(());
+}
+
+pub(crate) fn
+file_write_xmp
+(
+    path:     &Path,
+    xmp_data: &[u8],
+)
+-> Result<(), std::io::Error>
+{
+    let mut file_buffer: Vec<u8> = std::fs::read(path)?;
+
+    write_xmp(&mut file_buffer, xmp_data)?;
+
+    let mut file = std::fs::OpenOptions::new().write(true).truncate(true).open(path)?;
+    file.write_all(&file_buffer)?;
+
+    return Ok(());
+}
+
+/// Reads the Photoshop image resources stored in the APP13 segment(s) of the
+/// JPG data. If the resources are spread across multiple segments, the data
+/// of these segments gets concatenated before decoding.
+pub(crate) fn
+read_image_resources
+(
+    file_buffer: &[u8]
+)
+-> Result<ImageResources, std::io::Error>
+{
+    let mut resource_data = Vec::new();
+    let mut found_segment = false;
+
+    for (marker, start, end) in get_segment_positions(file_buffer)?
+    {
+        let segment_data = &file_buffer[start+4..end];
+
+        if marker == 0xed && segment_data.starts_with(PHOTOSHOP_HEADER)
+        {
+            resource_data.extend(segment_data[PHOTOSHOP_HEADER.len()..].iter());
+            found_segment = true;
+        }
+    }
+
+    if !found_segment
+    {
+        return io_error!(NotFound, "No Photoshop APP13 segment found!");
+    }
+
+    return ImageResources::decode(&resource_data);
+}
+
+pub(crate) fn
+file_read_image_resources
+(
+    path: &Path
+)
+-> Result<ImageResources, std::io::Error>
+{
+    file_check_signature(path)?;
+    return read_image_resources(&std::fs::read(path)?);
+}
+
+/// Writes the given Photoshop image resources to the JPG data, replacing the
+/// previously stored APP13 segment(s). The new segment(s) get placed after
+/// the leading APP0 to APP12 segments. If there are no resources, only the
+/// old segment(s) get removed.
+pub(crate) fn
+write_image_resources
+(
+    file_buffer: &mut Vec<u8>,
+    resources:   &ImageResources,
+)
+-> Result<(), std::io::Error>
+{
+    clear_segment(file_buffer, 0xed, Some(PHOTOSHOP_HEADER))?;
+
+    if resources.is_empty()
+    {
+        return Ok(());
+    }
+
+    let mut encoded_resources = Vec::new();
+    for chunk in resources.encode()?.chunks(MAX_SEGMENT_DATA_LENGTH - PHOTOSHOP_HEADER.len())
+    {
+        encode_segment(&mut encoded_resources, JPG_APP13_MARKER, &[PHOTOSHOP_HEADER, chunk])?;
+    }
+
+    let insert_position = get_insert_position_after(file_buffer, 0xe0..=0xec)?;
+
+    insert_segments(file_buffer, insert_position, &mut encoded_resources)?;
+
+    return Ok(());
+}
+
+pub(crate) fn
+file_write_image_resources
+(
+    path:      &Path,
+    resources: &ImageResources,
+)
+-> Result<(), std::io::Error>
+{
+    let mut file_buffer: Vec<u8> = std::fs::read(path)?;
+
+    write_image_resources(&mut file_buffer, resources)?;
+
+    let mut file = std::fs::OpenOptions::new().write(true).truncate(true).open(path)?;
+    file.write_all(&file_buffer)?;
+
+    return Ok(());
+}
+
+/// Reads the ICC profile stored in the APP2 segment(s) of the JPG data. 
+/// Larger profiles are split across multiple segments, which get 
+/// reassembled based on their sequence numbers.
+pub(crate) fn
+read_icc_profile
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut chunks = Vec::new();
+
+    for (marker, start, end) in get_segment_positions(file_buffer)?
+    {
+        let segment_data = &file_buffer[start+4..end];
+
+        if 
+            marker == 0xe2 
+            && 
+            segment_data.starts_with(ICC_PROFILE_HEADER)
+            &&
+            segment_data.len() >= ICC_PROFILE_HEADER.len() + 2
+        {
+            let sequence_number = segment_data[ICC_PROFILE_HEADER.len()    ];
+            let chunk_count     = segment_data[ICC_PROFILE_HEADER.len() + 1];
+            chunks.push((
+                sequence_number, 
+                chunk_count, 
+                &segment_data[ICC_PROFILE_HEADER.len() + 2..]
+            ));
+        }
+    }
+
+    if chunks.is_empty()
+    {
+        return io_error!(NotFound, "No ICC profile found!");
+    }
+
+    // Sequence numbers start at 1 and need to be consecutive
+    chunks.sort_by_key(|(sequence_number, _, _)| *sequence_number);
+
+    let chunk_count = chunks[0].1 as usize;
+    if 
+        chunks.len() != chunk_count 
+        || 
+        chunks.iter().enumerate().any(|(index, (sequence_number, count, _))| 
+            *sequence_number as usize != index + 1 || *count as usize != chunk_count
+        )
+    {
+        return io_error!(InvalidData, "ICC profile chunks in APP2 segments are incomplete or inconsistent!");
+    }
+
+    return Ok(chunks.iter()
+        .flat_map(|(_, _, data)| data.iter().copied())
+        .collect()
+    );
+}
+
+/// Removes all APP2 segments that store (parts of) an ICC profile
+pub(crate) fn
+remove_icc_profile
+(
+    file_buffer: &mut Vec<u8>,
+)
+-> Result<(), std::io::Error>
+{
+    return clear_segment(file_buffer, 0xe2, Some(ICC_PROFILE_HEADER));
+}
+
+/// Writes the ICC profile to the JPG data, replacing any previously stored
+/// profile. The new segment(s) get placed after the leading APP0 and APP1 
+/// segments, e.g. JFIF, EXIF and XMP.
+pub(crate) fn
+write_icc_profile
+(
+    file_buffer: &mut Vec<u8>,
+    icc_profile: &[u8],
+)
+-> Result<(), std::io::Error>
+{
+    let chunks = icc_profile.chunks(MAX_ICC_PROFILE_CHUNK_LENGTH).collect::<Vec<&[u8]>>();
+    if chunks.is_empty() || chunks.len() > 255
+    {
+        return io_error!(InvalidInput, "ICC profile is either empty or too large for JPG!");
+    }
+
+    remove_icc_profile(file_buffer)?;
+
+    let mut encoded_icc_profile = Vec::new();
+    for (index, chunk) in chunks.iter().enumerate()
+    {
+        encode_segment(&mut encoded_icc_profile, JPG_APP2_MARKER, &[
+            ICC_PROFILE_HEADER,
+            &[index as u8 + 1, chunks.len() as u8],
+            chunk
+        ])?;
+    }
+
+    let insert_position = get_insert_position_after(file_buffer, 0xe0..=0xe1)?;
+
+    insert_segments(file_buffer, insert_position, &mut encoded_icc_profile)?;
+
+    return Ok(());
+}
+
+/// Determines where new segments can be inserted after the leading segments
+/// with the given markers, e.g. APP0 and APP1 for JFIF, EXIF and XMP.
+fn
+get_insert_position_after
+(
+    file_buffer: &[u8],
+    markers:     std::ops::RangeInclusive<u8>,
+)
+-> Result<usize, std::io::Error>
+{
+    let mut insert_position = 2;
+    for (marker, start, end) in get_segment_positions(file_buffer)?
+    {
+        if start != insert_position || !markers.contains(&marker)
+        {
+            break;
+        }
+        insert_position = end;
+    }
+
+    return Ok(insert_position);
+}
+
+/// Reads the content of all COM segments of the JPG data, in order
+pub(crate) fn
+read_comments
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<Vec<u8>>, std::io::Error>
+{
+    return Ok(get_segment_positions(file_buffer)?
+        .into_iter()
+        .filter(|(marker, _, _)| *marker == 0xfe)
+        .map(|(_, start, end)| file_buffer[start+4..end].to_vec())
+        .collect()
+    );
+}
+
+/// Writes the given comments as COM segments to the JPG data, replacing all
+/// previously stored comments. The new segments get placed after the leading
+/// APP0 to APP15 segments.
+pub(crate) fn
+write_comments
+(
+    file_buffer: &mut Vec<u8>,
+    comments:    &[&[u8]],
+)
+-> Result<(), std::io::Error>
+{
+    if comments.iter().any(|comment| comment.len() > MAX_SEGMENT_DATA_LENGTH)
+    {
+        return io_error!(InvalidInput, "Comment is too long for a single COM segment!");
+    }
+
+    clear_segment(file_buffer, 0xfe, None)?;
+
+    let mut encoded_comments = Vec::new();
+    for comment in comments
+    {
+        encode_segment(&mut encoded_comments, JPG_COM_MARKER, &[comment])?;
+    }
+
+    let insert_position = get_insert_position_after(file_buffer, 0xe0..=0xef)?;
+    insert_segments(file_buffer, insert_position, &mut encoded_comments)?;
+
+    return Ok(());
+}
+
+/// Locates the first APP0 segment with the given identifier, returning its
+/// start and end position
+fn
+find_app0_segment
+(
+    file_buffer: &[u8],
+    identifier:  &[u8],
+)
+-> Result<Option<(usize, usize)>, std::io::Error>
+{
+    return Ok(get_segment_positions(file_buffer)?
+        .into_iter()
+        .find(|(marker, start, end)| *marker == 0xe0 && file_buffer[start+4..*end].starts_with(identifier))
+        .map(|(_, start, end)| (start, end))
+    );
+}
+
+/// Reads the JFIF APP0 segment of the JPG data
+pub(crate) fn
+read_jfif
+(
+    file_buffer: &[u8]
+)
+-> Result<Jfif, std::io::Error>
+{
+    match find_app0_segment(file_buffer, JFIF_IDENTIFIER)?
+    {
+        Some((start, end)) => return Jfif::decode(&file_buffer[start + 4 + JFIF_IDENTIFIER.len()..end]),
+        None               => return io_error!(NotFound, "No JFIF segment found!"),
+    }
+}
+
+/// Writes the JFIF APP0 segment, either by replacing the existing one or by
+/// inserting a new one right after the signature, as required by JFIF.
+pub(crate) fn
+write_jfif
+(
+    file_buffer: &mut Vec<u8>,
+    jfif:        &Jfif,
+)
+-> Result<(), std::io::Error>
+{
+    let mut encoded_jfif = Vec::new();
+    encode_segment(&mut encoded_jfif, JPG_APP0_MARKER, &[JFIF_IDENTIFIER, &jfif.encode()?])?;
+
+    let insert_position = match find_app0_segment(file_buffer, JFIF_IDENTIFIER)?
+    {
+        Some((start, end)) => {
+            modify_segments(file_buffer, |file_buffer| {
+                crate::util::range_remove(file_buffer, start, end);
+                return Ok(());
+            })?;
+            start
+        },
+        None => 2,
+    };
+
+    insert_segments(file_buffer, insert_position, &mut encoded_jfif)?;
+
+    return Ok(());
+}
+
+/// Reads the thumbnail stored in the JFXX APP0 extension segment
+pub(crate) fn
+read_jfxx_thumbnail
+(
+    file_buffer: &[u8]
+)
+-> Result<JfxxThumbnail, std::io::Error>
+{
+    match find_app0_segment(file_buffer, JFXX_IDENTIFIER)?
+    {
+        Some((start, end)) => return JfxxThumbnail::decode(&file_buffer[start + 4 + JFXX_IDENTIFIER.len()..end]),
+        None               => return io_error!(NotFound, "No JFXX segment found!"),
+    }
+}
+
+/// Removes all JFXX APP0 extension segments
+pub(crate) fn
+remove_jfxx_thumbnail
+(
+    file_buffer: &mut Vec<u8>,
+)
+-> Result<(), std::io::Error>
+{
+    return clear_segment(file_buffer, 0xe0, Some(JFXX_IDENTIFIER));
+}
+
+/// Writes the thumbnail as JFXX APP0 extension segment, replacing a
+/// previously stored one. As the JFXX segment has to follow the JFIF segment
+/// immediately, the JPG data must contain a JFIF segment.
+pub(crate) fn
+write_jfxx_thumbnail
+(
+    file_buffer: &mut Vec<u8>,
+    thumbnail:   &JfxxThumbnail,
+)
+-> Result<(), std::io::Error>
+{
+    let mut encoded_jfxx = Vec::new();
+    encode_segment(&mut encoded_jfxx, JPG_APP0_MARKER, &[JFXX_IDENTIFIER, &thumbnail.encode()?])?;
+
+    remove_jfxx_thumbnail(file_buffer)?;
+
+    let Some((_, jfif_end)) = find_app0_segment(file_buffer, JFIF_IDENTIFIER)? else {
+        return io_error!(NotFound, "Can't write JFXX thumbnail - No JFIF segment found!");
+    };
+
+    insert_segments(file_buffer, jfif_end, &mut encoded_jfxx)?;
+
+    return Ok(());
+}
+
+/// Inserts the encoded segment(s) at the given position of the primary image,
+/// keeping the offsets of an MPF segment up to date
+fn
+insert_segments
+(
+    file_buffer:      &mut Vec<u8>,
+    position:         usize,
+    encoded_segments: &mut Vec<u8>,
+)
+-> Result<(), std::io::Error>
+{
+    return modify_segments(file_buffer, |file_buffer| {
+        crate::util::insert_multiple_at(file_buffer, position, encoded_segments);
+        return Ok(());
+    });
+}
+
+/// Performs a modification of the segments of the primary image and updates
+/// the MPF segment (if there is one) afterwards, as the images that follow
+/// the primary image and are indexed by the MPF segment may have moved 
+/// relative to it, e.g. if a segment got inserted after the MPF segment. 
+fn
+modify_segments
+(
+    file_buffer:  &mut Vec<u8>,
+    modification: impl FnOnce(&mut Vec<u8>) -> Result<(), std::io::Error>,
+)
+-> Result<(), std::io::Error>
+{
+    let previous_index = match read_mp_index(file_buffer)
+    {
+        Ok(index) => index,
+        Err(e)    => {
+            log::warn!("Can't decode MPF segment, offsets won't be updated: {e}");
+            None
+        }
+    };
+    let previous_length = file_buffer.len();
+
+    modification(file_buffer)?;
+
+    let Some(previous_index) = previous_index else {
+        return Ok(());
+    };
+
+    // The MPF segment itself may have been removed or replaced
+    let Ok(Some(mut current_index)) = read_mp_index(file_buffer) else {
+        return Ok(());
+    };
+    if current_index.entries.len() != previous_index.entries.len()
+    {
+        return Ok(());
+    }
+
+    current_index.update_after_primary_change(
+        &previous_index, 
+        file_buffer.len() as i64 - previous_length as i64
+    );
+    current_index.write_entries(file_buffer);
+
+    return Ok(());
+}
+
+/// Decodes the MP Index IFD of the MPF APP2 segment of the primary image.
+/// Returns `None` if there is no such segment or it only contains MP
+/// attributes, as it is the case for the other images.
+pub(crate) fn
+read_mp_index
+(
+    file_buffer: &[u8]
+)
+-> Result<Option<MpIndex>, std::io::Error>
+{
+    let Some((_, start, end)) = get_segment_positions(file_buffer)?
+        .into_iter()
+        .find(|(marker, start, end)| *marker == 0xe2 && file_buffer[start+4..*end].starts_with(MPF_IDENTIFIER))
+    else
+    {
+        return Ok(None);
+    };
+
+    match MpIndex::decode(file_buffer, start, end)
+    {
+        Ok(index) => return Ok(Some(index)),
+        Err(e)    => {
+            if e.kind() == std::io::ErrorKind::NotFound
+            {
+                return Ok(None);
+            }
+            return Err(e);
+        }
+    }
+}
+
+/// Reads the entries of the MPF segment, describing the individual images
+pub(crate) fn
+read_mpf_entries
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<MpEntry>, std::io::Error>
+{
+    match read_mp_index(file_buffer)?
+    {
+        Some(index) => return Ok(index.entries),
+        None        => return io_error!(NotFound, "No MPF segment found!"),
+    }
+}
+
+/// Gets the data of the image with the given index in the MPF segment, from
+/// its SOI to its EOI marker
+pub(crate) fn
+extract_mpf_image
+(
+    file_buffer: &[u8],
+    index:       usize,
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let Some(mp_index) = read_mp_index(file_buffer)? else {
+        return io_error!(NotFound, "No MPF segment found!");
+    };
+
+    let (start, end) = mp_index.get_image_range(index, file_buffer.len())?;
+    return Ok(file_buffer[start..end].to_vec());
+}
+
+/// Writes the metadata to the image with the given index in the MPF segment.
+/// For the primary image (index 0) this is the same as `write_metadata`.
+/// Otherwise, the size of the image and the offsets of the images after it
+/// get updated in the MPF segment of the primary image.
+pub(crate) fn
+write_mpf_image_metadata
+(
+    file_buffer: &mut Vec<u8>,
+    index:       usize,
+    metadata:    &Metadata,
+    options:     &WriteOptions
+)
+-> Result<(), std::io::Error>
+{
+    let Some(mut mp_index) = read_mp_index(file_buffer)? else {
+        return io_error!(NotFound, "No MPF segment found!");
+    };
+
+    let (start, end) = mp_index.get_image_range(index, file_buffer.len())?;
+    if start == 0
+    {
+        return write_metadata(file_buffer, metadata, options);
+    }
+
+    let mut image_buffer = file_buffer[start..end].to_vec();
+    write_metadata(&mut image_buffer, metadata, options)?;
+
+    let Ok(new_size) = u32::try_from(image_buffer.len()) else {
+        return io_error!(InvalidData, "MPF image is too large!");
+    };
+
+    file_buffer.splice(start..end, image_buffer);
+
+    mp_index.update_after_image_change(index, new_size);
+    mp_index.write_entries(file_buffer);
+
+    return Ok(());
+}
+
+/// Determines the position right after the EOI marker of the primary image.
+/// Returns `None` if the data ends before an EOI marker is encountered.
+fn
+get_primary_image_end
+(
+    file_buffer: &[u8]
+)
+-> Result<Option<usize>, std::io::Error>
+{
+    let mut position = match get_segment_positions(file_buffer)?.last()
+    {
+        Some((0xda, _, end)) => {
+            let mut cursor = Cursor::new(file_buffer);
+            cursor.set_position(*end as u64);
+
+            if let Err(e) = skip_ecs(&mut cursor)
+            {
+                if e.kind() == std::io::ErrorKind::UnexpectedEof
+                {
+                    return Ok(None);
+                }
+                return Err(e);
+            }
+            cursor.position() as usize
+        },
+        Some((_, _, end)) => *end,
+        None              => 2,
+    };
+
+    // Skip fill bytes
+    while file_buffer.get(position..position+2) == Some(&[JPG_MARKER_PREFIX, 0xff])
+    {
+        position += 1;
+    }
+
+    if file_buffer.get(position..position+2) == Some(&[JPG_MARKER_PREFIX, 0xd9])
+    {
+        return Ok(Some(position + 2));
+    }
+    return Ok(None);
+}
+
+/// Reads the trailer of the JPG data, i.e. everything after the EOI marker
+/// of the primary image. This includes e.g. the additional images of an MPF
+/// segment as well as the video of a motion photo.
+pub(crate) fn
+read_trailer
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    match get_primary_image_end(file_buffer)?
+    {
+        Some(end) => return Ok(file_buffer[end..].to_vec()),
+        None      => return Ok(Vec::new()),
+    }
+}
+
+/// Locates the video of a motion photo, using the XMP data of the Google
+/// formats first and the Samsung trailer afterwards
+pub(crate) fn
+find_motion_photo
+(
+    file_buffer: &[u8]
+)
+-> Result<Option<MotionPhoto>, std::io::Error>
+{
+    let xmp_motion_photo = match read_xmp(file_buffer)
+    {
+        Ok(xmp_data) => crate::xmp::get_motion_photo_xmp(&xmp_data),
+        Err(_)       => Default::default(),
+    };
+
+    let container_index = xmp_motion_photo.container_items
+        .iter()
+        .position(|(semantic, _)| semantic == crate::xmp::MOTION_PHOTO_SEMANTIC);
+
+    // Both Google formats measure the position of the video from the end of
+    // the file, as the size of the metadata in front of it may change
+    let google_motion_photo = if let Some(index) = container_index
+    {
+        let distance = xmp_motion_photo.container_items[index..].iter().map(|(_, length)| *length).sum::<u64>();
+        Some((MotionPhotoFormat::GoogleContainer, distance, xmp_motion_photo.container_items[index].1))
+    }
+    else if xmp_motion_photo.is_micro_video
+    {
+        xmp_motion_photo.micro_video_offset
+            .map(|offset| (MotionPhotoFormat::GoogleMicroVideo, offset, offset))
+    }
+    else
+    {
+        None
+    };
+
+    if let Some((format, distance, length)) = google_motion_photo
+    {
+        let primary_end = get_primary_image_end(file_buffer)?.unwrap_or(file_buffer.len());
+
+        if distance < length || distance as usize > file_buffer.len() - primary_end
+        {
+            return io_error!(InvalidData, "Motion photo video specified by XMP exceeds the JPG trailer!");
+        }
+
+        let position = file_buffer.len() - distance as usize;
+        return Ok(Some(MotionPhoto::new(format, position, length as usize)));
+    }
+
+    if let Some(trailer) = SamsungTrailer::decode(file_buffer)?
+    {
+        return Ok(trailer.get_motion_photo(file_buffer));
+    }
+
+    return Ok(None);
+}
+
+/// Gets the video of a motion photo
+pub(crate) fn
+extract_motion_photo
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let Some(motion_photo) = find_motion_photo(file_buffer)? else {
+        return io_error!(NotFound, "No motion photo found!");
+    };
+
+    let start = motion_photo.get_position();
+    return Ok(file_buffer[start..start + motion_photo.get_length()].to_vec());
+}
+
+/// Replaces the video of a motion photo with the given one or removes it if
+/// `None` is given. Updates the XMP data or the Samsung trailer accordingly.
+pub(crate) fn
+replace_motion_photo
+(
+    file_buffer: &mut Vec<u8>,
+    video:       Option<&[u8]>,
+)
+-> Result<(), std::io::Error>
+{
+    let Some(motion_photo) = find_motion_photo(file_buffer)? else {
+        return io_error!(NotFound, "No motion photo found!");
+    };
+
+    if motion_photo.get_format() == MotionPhotoFormat::SamsungTrailer
+    {
+        if let Some(trailer) = SamsungTrailer::decode(file_buffer)?
+        {
+            return trailer.replace_motion_photo(file_buffer, video);
+        }
+    }
+
+    let start = motion_photo.get_position();
+    let end   = start + motion_photo.get_length();
+
+    // Whatever follows the video (e.g. further container items) stays in
+    // place, so the new distance of the video from the end is known upfront
+    let new_lengths = video.map(|video| (
+        video.len() as u64, 
+        (video.len() + file_buffer.len() - end) as u64
+    ));
+
+    let updated_xmp = crate::xmp::update_motion_photo_xmp(&read_xmp(file_buffer)?, new_lengths)
+        .map_err(|e| io_error_plain!(InvalidData, e.to_string()))?;
+
+    file_buffer.splice(start..end, video.unwrap_or_default().iter().copied());
+    return write_xmp(file_buffer, &updated_xmp);
+}
+
+/// Updates the density in the JFIF segment based on the resolution tags of
+/// the EXIF data. Does nothing if there is no JFIF segment or the resolution
+/// tags are missing.
+fn
+sync_jfif_density
+(
+    file_buffer: &mut Vec<u8>,
+    metadata:    &Metadata
+)
+-> Result<(), std::io::Error>
+{
+    let mut jfif = match read_jfif(file_buffer)
+    {
+        Ok(jfif) => jfif,
+        Err(e)   => {
+            log::debug!("No JFIF density to synchronize: {e}");
+            return Ok(());
+        }
+    };
+
+    if !jfif.set_density_from_metadata(metadata)
+    {
+        return Ok(());
+    }
+
+    return write_jfif(file_buffer, &jfif);
+}
@@ -1,20 +1,24 @@
 // Copyright © 2024-2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
 // See https://github.com/TechnikTobi/little_exif#license for licensing details
 
-use std::fs::File;
 use std::io::Cursor;
 use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
-use std::io::Write;
 use std::path::Path;
 
 use crate::endian::Endian;
+use crate::io_error_plain;
 use crate::metadata::Metadata;
+use crate::metadata::jxl_io::JpegReconstructionPolicy;
+use crate::metadata::jxl_io::JxlBoxCompression;
+use crate::metadata::write_options::WriteOptions;
+use crate::metadata::xmp_io::XmpSyncMode;
 use crate::u8conversion::*;
 use crate::general_file_io::*;
 use crate::util::insert_multiple_at;
 use crate::util::range_remove;
+use crate::xmp::sync_xmp_of_image;
 
 pub(crate) const JXL_SIGNATURE:          [u8; 2]  = [0xff, 0x0a];
 pub(crate) const ISO_BMFF_JXL_SIGNATURE: [u8; 12] = [
@@ -41,8 +45,11 @@ struct IsoBmffBoxType;
 impl IsoBmffBoxType {
     pub const EXIF: [u8; 4] = [0x45, 0x78, 0x69, 0x66]; // "Exif"
     pub const FTYP: [u8; 4] = [0x66, 0x74, 0x79, 0x70]; // "ftyp"
+    pub const JBRD: [u8; 4] = [0x6a, 0x62, 0x72, 0x64]; // "jbrd"
     pub const JXL:  [u8; 4] = [0x4a, 0x58, 0x4c, 0x20]; // "JXL "
     pub const JXLC: [u8; 4] = [0x6a, 0x78, 0x6c, 0x63]; // "jxlc"
+    pub const JXLL: [u8; 4] = [0x6a, 0x78, 0x6c, 0x6c]; // "jxll"
+    pub const XML:  [u8; 4] = [0x78, 0x6d, 0x6c, 0x20]; // "xml "
 }
 
 /// Checks if the given file buffer vector starts with the necessary bytes that
@@ -101,121 +108,186 @@ check_signature
     return Ok(());
 }
 
+/// The position of a box within a file buffer, see `find_box`
+struct
+BoxLocation
+{
+    start:         usize,
+    content_start: usize,
+    end:           usize,
+    compression:   JxlBoxCompression,
+}
+
+/// Reads the header of the box at the current position and returns the box
+/// type and the length of the box content. Besides regular boxes, this also
+/// handles boxes with a 64 bit length field (length 1) and boxes that extend
+/// to the end of the file (length 0), as e.g. used for the last `jxlp` box
+/// of streamed files.
 fn
-file_check_signature
+read_box_header
+<T: Seek + Read>
 (
-    path: &Path
+    cursor: &mut T
 )
--> Result<File, std::io::Error>
+-> Result<([u8; 4], u64), std::io::Error>
 {
-    let mut file = open_write_file(path)?;
+    let start = cursor.stream_position()?;
 
-    let mut signature_buffer = [0u8; 12];
-    let bytes_read = file.read(&mut signature_buffer)?;
+    let mut length_buffer = [0u8; 4];
+    let mut type_buffer   = [0u8; 4];
+    cursor.read_exact(&mut length_buffer)?;
+    cursor.read_exact(&mut type_buffer)?;
 
-    if bytes_read != 12
+    let length = match from_u8_vec_res_macro!(u32, &length_buffer, &Endian::Big)?
     {
-        return io_error!(InvalidData, "Can't open JXL file - Can't read signature!");
-    }
-
-    check_signature(&signature_buffer)?;
-
-    return Ok(file);
+        0 => {
+            let end = cursor.seek(SeekFrom::End(0))?;
+            cursor.seek(SeekFrom::Start(start + 8))?;
+            end - start
+        },
+        1 => {
+            let mut large_length_buffer = [0u8; 8];
+            cursor.read_exact(&mut large_length_buffer)?;
+            from_u8_vec_res_macro!(u64, &large_length_buffer, &Endian::Big)?
+        },
+        length => length as u64
+    };
+
+    let header_length  = cursor.stream_position()? - start;
+    let content_length = length.checked_sub(header_length).ok_or(
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Invalid box length found when reading JXL boxes!"
+        )
+    )?;
+
+    return Ok((type_buffer, content_length));
 }
 
-
-
-pub(crate) fn
-clear_metadata
+/// Finds the first box of the given type, which may also be stored in a
+/// compressed form in a `brob` box
+fn
+find_box
 (
-    file_buffer: &mut Vec<u8>
+    file_buffer: &[u8],
+    box_type:    [u8; 4],
 )
--> Result<(), std::io::Error>
+-> Result<Option<BoxLocation>, std::io::Error>
 {
-    check_signature(file_buffer)?;
-
-    let mut position = 0;
+    let mut cursor = Cursor::new(file_buffer);
 
-    loop
+    while (cursor.position() as usize) < file_buffer.len()
     {
-        if position >= file_buffer.len() { return Ok(()); }
-
-        // Get the first 4 bytes at the current cursor position to determine
-        // the length of the current box 
-        let length_buffer = file_buffer[position..position+4].to_vec();
-        let length        = from_u8_vec_res_macro!(u32, &length_buffer, &Endian::Big)? as usize;
-
-        // Next, read the box type
-        let type_buffer = file_buffer[position+4..position+8].to_vec();
-
-        if box_contains_exif(
-            &mut Cursor::new(
-                file_buffer[position+8..position+12].to_vec()
-            ), 
-            [type_buffer[0], type_buffer[1], type_buffer[2], type_buffer[3]]
-        )?
+        let start                  = cursor.position() as usize;
+        let (type_buffer, length)  = read_box_header(&mut cursor)?;
+        let content_start          = cursor.position() as usize;
+        let end                    = content_start.saturating_add(length as usize);
+
+        if end > file_buffer.len()
         {
-            range_remove(file_buffer, position, position+length);
+            return io_error!(InvalidData, "Invalid box length found when reading JXL boxes!");
         }
-        else
+
+        if type_buffer == box_type
         {
-            // Not an EXIF box so skip it
-            position += length;
+            return Ok(Some(BoxLocation {
+                start,
+                content_start,
+                end,
+                compression: JxlBoxCompression::Uncompressed
+            }));
         }
+
+        if type_buffer == BROB_BOX
+        && length >= 4
+        && file_buffer[content_start..content_start+4] == box_type
+        {
+            return Ok(Some(BoxLocation {
+                start,
+                content_start,
+                end,
+                compression: JxlBoxCompression::Brotli
+            }));
+        }
+
+        cursor.set_position(end as u64);
     }
+
+    return Ok(None);
 }
 
-pub(crate) fn
-file_clear_metadata
+/// Removes all boxes of the given type, including those that are stored in a
+/// compressed form in a `brob` box
+fn
+remove_boxes
 (
-    path: &Path
+    file_buffer: &mut Vec<u8>,
+    box_type:    [u8; 4],
 )
 -> Result<(), std::io::Error>
 {
-    let mut file = file_check_signature(path)?;
-
-    let mut length_buffer = [0u8; 4];
-    let mut type_buffer   = [0u8; 4];
-
-    loop
+    while let Some(location) = find_box(file_buffer, box_type)?
     {
-        let position        = file.stream_position()?;
-        let old_file_length = file.metadata()?.len();
-        if position >= old_file_length { return Ok(()); }
+        range_remove(file_buffer, location.start, location.end);
+    }
 
-        file.read_exact(&mut length_buffer)?;
-        file.read_exact(&mut type_buffer)?;
+    return Ok(());
+}
 
-        let length = from_u8_vec_res_macro!(u32, &length_buffer, &Endian::Big)? as usize;
+/// JPEG-recompressed JXLs contain a `jbrd` box that allows the bit-exact
+/// reconstruction of the original JPEG. As its APP segments are restored from
+/// the `Exif` and `xml ` boxes, changing these breaks the reconstruction.
+fn
+warn_about_jpeg_reconstruction_data
+(
+    file_buffer: &[u8]
+)
+-> Result<(), std::io::Error>
+{
+    if find_box(file_buffer, IsoBmffBoxType::JBRD)?.is_some()
+    {
+        log::warn!(
+            "Changing the metadata of a JXL with JPEG reconstruction data - \
+            the original JPEG can no longer be reconstructed bit-exactly!"
+        );
+    }
 
-        if box_contains_exif(&mut file, type_buffer)?
-        {
-            // Seek past the EXIF box ...
-            file.seek(SeekFrom::Current((length-8) as i64))?;
+    return Ok(());
+}
 
+pub(crate) fn
+clear_metadata
+(
+    file_buffer: &mut Vec<u8>
+)
+-> Result<(), std::io::Error>
+{
+    // A naked codestream has no metadata that could be removed
+    if starts_with_jxl_signature(file_buffer)
+    {
+        return Ok(());
+    }
 
-            // ... copy everything from here onwards into a buffer ...
-            let mut buffer = Vec::new();
-            file.read_to_end(&mut buffer)?;
+    check_signature(file_buffer)?;
 
-            // ... seek back to the start of the EXIF box ...
-            file.seek(std::io::SeekFrom::Start(position))?;
+    if find_box(file_buffer, IsoBmffBoxType::EXIF)?.is_some()
+    {
+        warn_about_jpeg_reconstruction_data(file_buffer)?;
+    }
 
-            // ... overwrite everything from here onward ...
-            file.write_all(&buffer)?;
-            file.seek(std::io::SeekFrom::Start(position))?;
+    return remove_boxes(file_buffer, IsoBmffBoxType::EXIF);
+}
 
-            // ... and finally update the file size - otherwise there will be
-            // duplicate bytes at the end!
-            file.set_len(old_file_length - length as u64)?;
-        }
-        else
-        {
-            // Not an EXIF box so skip it
-            assert_eq!(position+8, file.stream_position()?);
-            file.seek(SeekFrom::Current((length-8) as i64))?;
-        }
-    }
+pub(crate) fn
+file_clear_metadata
+(
+    path: &Path
+)
+-> Result<(), std::io::Error>
+{
+    // Load the entire file into memory instead of performing multiple read,
+    // seek and write operations
+    return modify_file(path, clear_metadata);
 }
 
 
@@ -238,27 +310,57 @@ check_brob_type_for_exif
     return Ok(brob_type == EXIF);
 }
 
+/// Compresses the given data using Brotli so that it can be stored in a 
+/// `brob` box
+fn
+compress_brotli
+(
+    data: &[u8]
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut compressed_data = Vec::new();
 
+    brotli::BrotliCompress(
+        &mut Cursor::new(data), 
+        &mut compressed_data, 
+        &brotli::enc::BrotliEncoderParams::default()
+    )?;
+
+    return Ok(compressed_data);
+}
 
+/// Encodes a box of the given type with the given content. When compressing
+/// with Brotli, a `brob` box gets created instead, which stores the original
+/// box type followed by the compressed content.
 fn
-box_contains_exif
-<T: Seek + Read>
+encode_box
 (
-    cursor:      &mut T,
-    type_buffer:  [u8; 4],
+    box_type:    [u8; 4],
+    content:     &[u8],
+    compression: JxlBoxCompression,
 )
--> Result<bool, std::io::Error>
+-> Result<Vec<u8>, std::io::Error>
 {
-    if type_buffer == EXIF
+    let (outer_box_type, box_data) = match compression
     {
-        return Ok(true);
-    }
-    if type_buffer == BROB_BOX && check_brob_type_for_exif(cursor)?
-    {
-        return Ok(true);
-    }
+        JxlBoxCompression::Uncompressed => (box_type, content.to_vec()),
+        JxlBoxCompression::Brotli       => {
+            let mut brob_data = box_type.to_vec();
+            brob_data.extend(compress_brotli(content)?);
+            (BROB_BOX, brob_data)
+        }
+    };
 
-    return Ok(false);
+    let box_length = u32::try_from(box_data.len() + 8)
+        .map_err(|_| io_error_plain!(InvalidInput, "Data too large for a JXL box!"))?;
+
+    let mut encoded_box = Vec::new();
+    encoded_box.extend(to_u8_vec_macro!(u32, &box_length, &Endian::Big));
+    encoded_box.extend(outer_box_type);
+    encoded_box.extend(box_data);
+
+    return Ok(encoded_box);
 }
 
 
@@ -310,21 +412,18 @@ generic_read_metadata
 {
     loop
     {
-        // Get the first 4 bytes at the current cursor position to determine
-        // the length of the current box (and account for the 8 bytes of length
-        // and box type)
-        let mut length_buffer = [0u8; 4];
-        cursor.read_exact(&mut length_buffer)?;
-        let length = from_u8_vec_res_macro!(u32, &length_buffer, &Endian::Big)?.checked_sub(8).ok_or(
+        // Get the type and the length of the current box, not including the
+        // box header
+        let (type_buffer, length) = read_box_header(cursor)?;
+
+        // The EXIF data is preceded by 4 bytes, so there must be at least
+        // this many bytes in the box
+        let exif_length = length.checked_sub(4).ok_or(
             std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
                 "Invalid box length found when reading JXL metadata!"
             )
-        )?;
-
-        // Next, read the box type
-        let mut type_buffer = [0u8; 4];
-        cursor.read_exact(&mut type_buffer)?;
+        );
 
         match type_buffer
         {
@@ -333,7 +432,7 @@ generic_read_metadata
                 cursor.seek(SeekFrom::Current(4))?;
 
                 // `length-4` because of the previous relative seek operation
-                let mut exif_buffer = vec![0u8; (length-4) as usize];
+                let mut exif_buffer = vec![0u8; exif_length? as usize];
                 cursor.read_exact(&mut exif_buffer)?;
 
                 return Ok(exif_buffer);
@@ -341,7 +440,7 @@ generic_read_metadata
 
             BROB_BOX => { // -> Brotli encoded data
 
-                let position = cursor.stream_position()? as usize;
+                let position = cursor.stream_position()?;
 
                 if check_brob_type_for_exif(cursor)?
                 {
@@ -350,7 +449,7 @@ generic_read_metadata
 
                     let mut compressed_exif_buffer = vec![
                         0u8; 
-                        (length-4) as usize
+                        exif_length? as usize
                     ];
                     cursor.read_exact(&mut compressed_exif_buffer)?;
                     
@@ -367,11 +466,15 @@ generic_read_metadata
 
                     // Ignore the next 4 bytes (I guess for the same reason 
                     // as above - some sort of minor version?)
+                    if decompressed_exif_buffer.len() < 4
+                    {
+                        return io_error!(InvalidData, "Invalid compressed EXIF data in JXL!");
+                    }
                     return Ok(decompressed_exif_buffer[4..].to_vec());
                 }
                 else 
                 {
-                    cursor.seek(SeekFrom::Start(position as u64 + length as u64))?;
+                    cursor.seek(SeekFrom::Start(position + length))?;
                 }
             }
 
@@ -386,132 +489,355 @@ generic_read_metadata
 fn
 encode_metadata_jxl
 (
-    exif_vec: &[u8],
+    exif_vec:    &[u8],
+    compression: JxlBoxCompression,
 )
--> Vec<u8>
+-> Result<Vec<u8>, std::io::Error>
 {
-    let exif_box_length = 0                        // Length has to include
-        + 4                                        // - the length field
-        + IsoBmffBoxType::EXIF.len()        as u32 // - the box type 
-        + ISO_BMFF_EXIF_MINOR_VERSION.len() as u32 // - the minor version
-        + EXIF_HEADER.len()                 as u32 // - the exif header
-        + exif_vec.len()                    as u32 // - the exif data
-    ;
-    
-    let mut jxl_exif = Vec::new();
-    jxl_exif.extend(to_u8_vec_macro!(u32, &exif_box_length, &Endian::Big));
-    jxl_exif.extend(IsoBmffBoxType::EXIF);
-    jxl_exif.extend(ISO_BMFF_EXIF_MINOR_VERSION);
-    jxl_exif.extend(EXIF_HEADER.iter());
-    jxl_exif.extend(exif_vec.iter());
-
-    return jxl_exif;
+    // The content of the box consists of
+    // - the minor version
+    // - the exif header
+    // - the exif data
+    let mut exif_box_content = Vec::new();
+    exif_box_content.extend(ISO_BMFF_EXIF_MINOR_VERSION);
+    exif_box_content.extend(EXIF_HEADER.iter());
+    exif_box_content.extend(exif_vec.iter());
+
+    return encode_box(IsoBmffBoxType::EXIF, &exif_box_content, compression);
 }
 
+/// Determines where to place a new metadata box: After the signature, the
+/// `ftyp` and the `jxll` box, which need to be at the start of the file, but
+/// in front of the codestream, so that the codestream boxes (`jxlc` or a
+/// sequence of `jxlp` boxes) are left intact
 fn
 find_insert_position
 (
-    file_buffer: &Vec<u8>
+    file_buffer: &[u8]
 )
 -> Result<usize, std::io::Error>
 {
     let mut cursor = Cursor::new(file_buffer);
 
-    loop
+    while (cursor.position() as usize) < file_buffer.len()
     {
-        // Get the first 4 bytes at the current cursor position to determine
-        // the length of the current box (and account for the 8 bytes of length
-        // and box type)
-        let mut length_buffer = [0u8; 4];
-        cursor.read_exact(&mut length_buffer)?;
-        let length = from_u8_vec_res_macro!(u32, &length_buffer, &Endian::Big)?.checked_sub(8).ok_or(
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Invalid box length found when reading JXL metadata!"
-            )
-        )? as usize;
-
-        // Next, read the box type
-        let mut type_buffer = [0u8; 4];
-        cursor.read_exact(&mut type_buffer)?;
+        let position              = cursor.position();
+        let (type_buffer, length) = read_box_header(&mut cursor)?;
 
         match type_buffer
         {
-            IsoBmffBoxType::JXL |
-            IsoBmffBoxType::FTYP => {
-                // Place exif box after these boxes
+            IsoBmffBoxType::JXL  |
+            IsoBmffBoxType::FTYP |
+            IsoBmffBoxType::JXLL => {
+                // Place metadata box after these boxes
                 cursor.seek(SeekFrom::Current(length as i64))?;
             }
             _ => {
-                return Ok(cursor.position() as usize - 8);
+                return Ok(position as usize);
             }
         }
     }
+
+    return Ok(file_buffer.len());
+}
+
+/// Returns the position of the first box of the given type, so that it can
+/// be replaced without moving it, e.g. if it is located between two `jxlp`
+/// boxes. If there is no such box, the default insert position is returned.
+fn
+find_replace_position
+(
+    file_buffer: &[u8],
+    box_type:    [u8; 4],
+)
+-> Result<usize, std::io::Error>
+{
+    return match find_box(file_buffer, box_type)?
+    {
+        Some(location) => Ok(location.start),
+        None           => find_insert_position(file_buffer),
+    };
+}
+
+/// Converts a naked JXL codestream into an ISO BMFF container that consists
+/// of the JXL signature box, the `ftyp` box and a `jxlc` box holding the 
+/// unmodified codestream, so that metadata boxes can be added
+fn
+wrap_codestream
+(
+    file_buffer: &mut Vec<u8>
+)
+-> Result<(), std::io::Error>
+{
+    let mut new_file_buffer = Vec::new();
+
+    // Start of the new file
+    new_file_buffer.extend(ISO_BMFF_JXL_SIGNATURE);
+    new_file_buffer.extend(FTYP_BOX);
+
+    // JXL codestream box
+    // - length of box (including 4 bytes of length & type fields each)
+    // - type field
+    // - data
+    let jxlc_box_length = u32::try_from(file_buffer.len() + 8)
+        .map_err(|_| io_error_plain!(InvalidData, "JXL codestream too large for a jxlc box!"))?;
+    new_file_buffer.extend(to_u8_vec_macro!(u32, &jxlc_box_length, &Endian::Big));
+    new_file_buffer.extend(IsoBmffBoxType::JXLC);
+    new_file_buffer.append(file_buffer);
+
+    // Replace file buffer
+    *file_buffer = new_file_buffer;
+
+    return Ok(());
 }
 
 pub(crate) fn 
 write_metadata
 (
     file_buffer: &mut Vec<u8>,
-    metadata:    &Metadata
+    metadata:    &Metadata,
+    options:     &WriteOptions
 )
 -> Result<(), std::io::Error> 
 {
     if starts_with_jxl_signature(file_buffer)
     {
-        // Need to modify the file_buffer first so that it is a ISO BMFF 
-        let mut new_file_buffer = Vec::new();
-
-        // Start of the new file
-        new_file_buffer.extend(ISO_BMFF_JXL_SIGNATURE);
-        new_file_buffer.extend(FTYP_BOX);
-
-        // JXL codestream box
-        // - length of box (including 4 bytes of length & type fields each)
-        // - type field
-        // - data
-        let jxlc_box_length = file_buffer.len() as u32 + 8;
-        new_file_buffer.extend(to_u8_vec_macro!(u32, &jxlc_box_length, &Endian::Big));
-        new_file_buffer.extend(IsoBmffBoxType::JXLC);
-        new_file_buffer.append(file_buffer);
-
-        // Replace file buffer
-        *file_buffer = new_file_buffer;
+        if !options.jxl_wrap_codestream
+        {
+            return io_error!(
+                Unsupported, 
+                "Simple JXL codestream file - No metadata! Wrapping it into a container is disabled"
+            );
+        }
+
+        wrap_codestream(file_buffer)?;
+    }
+
+    check_signature(file_buffer)?;
+
+    if find_box(file_buffer, IsoBmffBoxType::JBRD)?.is_none()
+    {
+        return write_metadata_boxes(file_buffer, metadata, options);
+    }
+
+    // The file contains JPEG reconstruction data, which stays valid as long
+    // as the (decompressed) content of the metadata boxes does not change.
+    // So the changes are made on a copy first to see if this is the case.
+    let mut new_file_buffer = file_buffer.clone();
+    write_metadata_boxes(&mut new_file_buffer, metadata, options)?;
+
+    let exif_unchanged = read_metadata(file_buffer).ok() == read_metadata(&new_file_buffer).ok();
+    let xmp_unchanged  = read_xmp(file_buffer).ok()      == read_xmp(&new_file_buffer).ok();
+
+    if !exif_unchanged || !xmp_unchanged
+    {
+        match options.jpeg_reconstruction_policy
+        {
+            JpegReconstructionPolicy::Warn => {
+                warn_about_jpeg_reconstruction_data(&new_file_buffer)?;
+            },
+
+            JpegReconstructionPolicy::Error => {
+                return io_error!(
+                    InvalidInput,
+                    "Changing the metadata of this JXL would prevent the reconstruction of the original JPEG!"
+                );
+            },
+
+            JpegReconstructionPolicy::RemoveReconstructionData => {
+                remove_boxes(&mut new_file_buffer, IsoBmffBoxType::JBRD)?;
+            },
+        }
     }
 
+    *file_buffer = new_file_buffer;
+
+    return Ok(());
+}
+
+/// Replaces the `Exif` box with the given metadata and synchronizes the XMP
+/// data if requested. The file needs to be an ISO BMFF container already.
+fn
+write_metadata_boxes
+(
+    file_buffer: &mut Vec<u8>,
+    metadata:    &Metadata,
+    options:     &WriteOptions
+)
+-> Result<(), std::io::Error>
+{
+    let insert_position = find_replace_position(file_buffer, IsoBmffBoxType::EXIF)?;
+
     // Remove old metadata
-    clear_metadata(file_buffer)?;
+    remove_boxes(file_buffer, IsoBmffBoxType::EXIF)?;
     
     // Insert new metadata
-    let mut encoded_metadata = encode_metadata_jxl(&metadata.encode()?);
-    let     insert_position  = find_insert_position(file_buffer)?;
+    let mut encoded_metadata = encode_metadata_jxl(
+        &metadata.encode()?, 
+        options.jxl_box_compression
+    )?;
     insert_multiple_at(file_buffer, insert_position, &mut encoded_metadata);
 
-    return Ok(());
+    return sync_xmp(file_buffer, metadata, options);
 }
 
 pub(crate) fn 
 file_write_metadata
 (
     path:     &Path,
-    metadata: &Metadata
+    metadata: &Metadata,
+    options:  &WriteOptions
 )
 -> Result<(), std::io::Error>
 {
     // Load the entire file into memory instead of performing multiple read, 
-    // seek and write operations
-    let mut file = open_write_file(path)?;
-    let mut file_buffer: Vec<u8> = Vec::new();
-    file.read_to_end(&mut file_buffer)?;
-
-    // Writes the metadata to the file_buffer vec
+    // seek and write operations. The file may get shorter if the old EXIF 
+    // data was larger than the new one, e.g. due to compression.
     // The called function handles the removal of old metadata and the JXL
     // specific encoding, so we pass only the generally encoded metadata here
-    write_metadata(&mut file_buffer, metadata)?;
+    return modify_file(path, |file_buffer| write_metadata(file_buffer, metadata, options));
+}
 
-    // Seek back to start & write the file
-    file.seek(SeekFrom::Start(0))?;
-    file.write_all(&file_buffer)?;
+/// Updates or removes the properties in the XMP data that mirror EXIF tags,
+/// based on the given options, and compresses the `xml ` box if requested.
+/// Does nothing if the JXL does not contain XMP data.
+fn
+sync_xmp
+(
+    file_buffer: &mut Vec<u8>,
+    metadata:    &Metadata,
+    options:     &WriteOptions
+)
+-> Result<(), std::io::Error>
+{
+    let Some(location) = find_box(file_buffer, IsoBmffBoxType::XML)? else
+    {
+        return Ok(());
+    };
+
+    // Existing compressed XMP data does not get decompressed as this is not
+    // what `JxlBoxCompression::Uncompressed` asks for when writing EXIF data
+    let new_compression = match options.jxl_box_compression
+    {
+        JxlBoxCompression::Uncompressed => location.compression,
+        JxlBoxCompression::Brotli       => JxlBoxCompression::Brotli,
+    };
+
+    if options.xmp_sync_mode == XmpSyncMode::Disabled
+    && new_compression == location.compression
+    {
+        return Ok(());
+    }
+
+    return sync_xmp_of_image(
+        file_buffer, 
+        metadata, 
+        options.xmp_sync_mode, 
+        &|buffer| read_xmp(buffer), 
+        &|buffer, xmp_data| replace_xmp_box(buffer, xmp_data, new_compression)
+    );
+}
+
+pub(crate) fn
+read_xmp
+(
+    file_buffer: &[u8]
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    check_signature(file_buffer)?;
+
+    let Some(location) = find_box(file_buffer, IsoBmffBoxType::XML)? else
+    {
+        return io_error!(NotFound, "No XMP data found in JXL file!");
+    };
+
+    match location.compression
+    {
+        JxlBoxCompression::Uncompressed => {
+            return Ok(file_buffer[location.content_start..location.end].to_vec());
+        },
+
+        JxlBoxCompression::Brotli => {
+            // Skip the original box type in front of the compressed data
+            let mut decompressed_xmp_buffer = Vec::new();
+            brotli::BrotliDecompress(
+                &mut Cursor::new(&file_buffer[location.content_start+4..location.end]),
+                &mut decompressed_xmp_buffer
+            )?;
+            return Ok(decompressed_xmp_buffer);
+        }
+    }
+}
+
+/// Writes the XMP data to the `xml ` box, replacing any previously stored XMP
+/// data. The compression of the previous `xml ` box is kept, if there is one.
+pub(crate) fn
+write_xmp
+(
+    file_buffer: &mut Vec<u8>,
+    xmp_data:    &[u8]
+)
+-> Result<(), std::io::Error>
+{
+    check_signature(file_buffer)?;
+
+    let compression = match find_box(file_buffer, IsoBmffBoxType::XML)?
+    {
+        Some(location) => location.compression,
+        None           => JxlBoxCompression::Uncompressed,
+    };
+
+    return write_xmp_with_compression(file_buffer, xmp_data, compression);
+}
+
+pub(crate) fn
+write_xmp_with_compression
+(
+    file_buffer: &mut Vec<u8>,
+    xmp_data:    &[u8],
+    compression: JxlBoxCompression
+)
+-> Result<(), std::io::Error>
+{
+    check_signature(file_buffer)?;
+    warn_about_jpeg_reconstruction_data(file_buffer)?;
+
+    return replace_xmp_box(file_buffer, xmp_data, compression);
+}
+
+fn
+replace_xmp_box
+(
+    file_buffer: &mut Vec<u8>,
+    xmp_data:    &[u8],
+    compression: JxlBoxCompression
+)
+-> Result<(), std::io::Error>
+{
+    let insert_position = find_replace_position(file_buffer, IsoBmffBoxType::XML)?;
+
+    remove_boxes(file_buffer, IsoBmffBoxType::XML)?;
+
+    let mut encoded_xmp = encode_box(IsoBmffBoxType::XML, xmp_data, compression)?;
+    insert_multiple_at(file_buffer, insert_position, &mut encoded_xmp);
 
     return Ok(());
-}
\ No newline at end of file
+}
+
+pub(crate) fn
+remove_xmp
+(
+    file_buffer: &mut Vec<u8>
+)
+-> Result<(), std::io::Error>
+{
+    check_signature(file_buffer)?;
+
+    if find_box(file_buffer, IsoBmffBoxType::XML)?.is_some()
+    {
+        warn_about_jpeg_reconstruction_data(file_buffer)?;
+    }
+
+    return remove_boxes(file_buffer, IsoBmffBoxType::XML);
+}
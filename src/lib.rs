@@ -15,7 +15,7 @@
 //! - HEIF / HEIC / HIF / AVIF
 //! - PNG
 //! - TIFF
-//! - WebP (only lossless and extended)
+//! - WebP (lossy, lossless and extended)
 //! 
 //! files and a few dozen tags in IFD0 and ExifIFD. 
 //! Interaction is done via the [`Metadata`](metadata/struct.Metadata.html) 
@@ -42,9 +42,12 @@ mod general_file_io;
 pub mod ifd;
 
 mod png;
+mod cr3;
+mod quicktime;
 mod heif;
 mod jpg;
 mod jxl;
+mod raw;
 mod tiff;
 mod webp;
 mod xmp;
@@ -56,4 +59,9 @@ pub mod u8conversion;
 pub mod exif_tag;
 pub mod exif_tag_format;
 pub mod filetype;
-pub mod metadata;
\ No newline at end of file
+pub mod metadata;
+pub mod iptc;
+pub mod jfif;
+pub mod mpf;
+pub mod motion_photo;
+pub mod photoshop;
\ No newline at end of file
@@ -59,4 +59,15 @@ Metadata
 			}
 		}
 	}
-}
\ No newline at end of file
+
+	/// Removes IFD1, which stores the thumbnail of a JPEG, as well as any
+	/// SubIFDs that are associated with it
+	pub fn
+	remove_thumbnail
+	(
+		&mut self
+	)
+	{
+		self.image_file_directories.retain(|ifd| ifd.get_generic_ifd_nr() != 1);
+	}
+}
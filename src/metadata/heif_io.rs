@@ -0,0 +1,298 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::path::Path;
+
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::io_error;
+use crate::general_file_io::modify_file;
+
+use crate::heif;
+use crate::rational::iR64;
+use crate::rational::uR64;
+
+use super::Metadata;
+
+/// An image item of a HEIF/AVIF, e.g. one of the images of a burst or a 
+/// multi-image file. The dimensions are only known if the item has an 
+/// `ispe` property. 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+HeifImageItem
+{
+    pub item_id:    u32,
+    pub item_type:  String,
+    pub width:      Option<u32>,
+    pub height:     Option<u32>,
+    pub is_primary: bool,
+}
+
+/// A property of a HEIF image item that is stored in the `ipco` box
+/// See ISO/IEC 23008-12:2017, § 6.5
+#[derive(Clone, Debug, PartialEq)]
+pub enum
+HeifItemProperty
+{
+    /// `ispe`: Width and height of the image in pixels
+    ImageSpatialExtents { width: u32, height: u32 },
+
+    /// `pixi`: Number of bits per channel
+    PixelInformation { bits_per_channel: Vec<u8> },
+
+    /// `colr` with colour type `nclx`: Colour information as defined in 
+    /// ISO/IEC 23091-2, e.g. 1/13/6 for sRGB
+    NclxColor 
+    { 
+        colour_primaries:         u16, 
+        transfer_characteristics: u16, 
+        matrix_coefficients:      u16, 
+        full_range:               bool,
+    },
+
+    /// `colr` with colour type `prof` or - if restricted - `rICC`
+    IccColor { restricted: bool, icc_profile: Vec<u8> },
+
+    /// `irot`: Anti-clockwise rotation in units of 90 degrees (0-3)
+    Rotation { angle: u8 },
+
+    /// `imir`: Mirroring along the vertical (0) or horizontal (1) axis
+    Mirror { axis: u8 },
+
+    /// `clap`: Clean aperture, with offsets relative to the image center
+    CleanAperture 
+    { 
+        width:             uR64, 
+        height:            uR64, 
+        horizontal_offset: iR64, 
+        vertical_offset:   iR64,
+    },
+
+    /// Any other property, with the content after the box header
+    Other { box_type: String, data: Vec<u8> },
+}
+
+/// A property that is associated with an image item. Essential properties
+/// have to be understood by a reader in order to process the image. 
+#[derive(Clone, Debug, PartialEq)]
+pub struct
+HeifAssociatedProperty
+{
+    pub property:  HeifItemProperty,
+    pub essential: bool,
+}
+
+/// Checks that the file at the given path is a HEIF, as the functions in 
+/// this module are specific to this format
+fn
+check_heif_path
+(
+    path: &Path
+)
+-> Result<(), std::io::Error>
+{
+    let file_type = get_file_type(path)?;
+    if file_type != FileExtension::HEIF
+    {
+        return io_error!(Unsupported, format!("Expected HEIF file but got {:?}", file_type));
+    }
+    return Ok(());
+}
+
+impl
+Metadata
+{
+    /// Lists the image items of a HEIF/AVIF that is stored as a `Vec<u8>`.
+    /// The input images of derived images, e.g. the tiles of a grid, are 
+    /// not included. 
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    ///
+    /// let file_data = std::fs::read("burst.heic").unwrap();
+    /// for item in Metadata::read_heif_image_items(&file_data).unwrap()
+    /// {
+    ///     let metadata = Metadata::new_from_heif_image(&file_data, item.item_id);
+    ///     println!("{} {:?}x{:?}: {:?}", item.item_id, item.width, item.height, metadata);
+    /// }
+    /// ```
+    pub fn
+    read_heif_image_items
+    (
+        file_buffer: &[u8]
+    )
+    -> Result<Vec<HeifImageItem>, std::io::Error>
+    {
+        return heif::read_image_items(file_buffer);
+    }
+
+    pub fn
+    file_read_heif_image_items
+    (
+        path: &Path
+    )
+    -> Result<Vec<HeifImageItem>, std::io::Error>
+    {
+        check_heif_path(path)?;
+        return heif::read_image_items(&std::fs::read(path)?);
+    }
+
+    /// Constructs a new `Metadata` object with the EXIF data that describes
+    /// the given image item of a HEIF/AVIF that is stored as a `Vec<u8>`. 
+    /// Fails with `NotFound` if there is no EXIF item linked to the image
+    /// item via a `cdsc` item reference. 
+    pub fn
+    new_from_heif_image
+    (
+        file_buffer: &[u8],
+        item_id:     u32
+    )
+    -> Result<Metadata, std::io::Error>
+    {
+        return Self::general_decoding_wrapper(heif::read_image_metadata(file_buffer, item_id));
+    }
+
+    pub fn
+    file_new_from_heif_image
+    (
+        path:    &Path,
+        item_id: u32
+    )
+    -> Result<Metadata, std::io::Error>
+    {
+        check_heif_path(path)?;
+        return Self::general_decoding_wrapper(heif::read_image_metadata(&std::fs::read(path)?, item_id));
+    }
+
+    /// Reads the properties of the given item of a HEIF/AVIF that is stored 
+    /// as a `Vec<u8>`, in the order of their association
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::metadata::heif_io::HeifItemProperty;
+    ///
+    /// let file_data = std::fs::read("image.heic").unwrap();
+    /// for associated in Metadata::read_heif_item_properties(&file_data, 1).unwrap()
+    /// {
+    ///     if let HeifItemProperty::Rotation { angle } = associated.property
+    ///     {
+    ///         println!("Rotated by {} degrees", angle as u32 * 90);
+    ///     }
+    /// }
+    /// ```
+    pub fn
+    read_heif_item_properties
+    (
+        file_buffer: &[u8],
+        item_id:     u32
+    )
+    -> Result<Vec<HeifAssociatedProperty>, std::io::Error>
+    {
+        return heif::read_item_properties(file_buffer, item_id);
+    }
+
+    pub fn
+    file_read_heif_item_properties
+    (
+        path:    &Path,
+        item_id: u32
+    )
+    -> Result<Vec<HeifAssociatedProperty>, std::io::Error>
+    {
+        check_heif_path(path)?;
+        return heif::read_item_properties(&std::fs::read(path)?, item_id);
+    }
+
+    /// Writes the property for the given item of a HEIF/AVIF that is stored
+    /// as a `Vec<u8>`, replacing the associated property of the same kind 
+    /// (e.g. `irot` or an ICC based `colr`). Properties that are shared with
+    /// other items are left untouched for these items. New descriptive 
+    /// properties get associated before the transformative ones (`clap`, 
+    /// `irot` and `imir`), as their order determines the order in which the
+    /// transformations are applied. Fails with `NotFound` if the file has no
+    /// `iprp` box. 
+    pub fn
+    write_heif_item_property
+    (
+        property:    &HeifItemProperty,
+        essential:   bool,
+        file_buffer: &mut Vec<u8>,
+        item_id:     u32
+    )
+    -> Result<(), std::io::Error>
+    {
+        return heif::write_item_property(file_buffer, item_id, property, essential);
+    }
+
+    pub fn
+    file_write_heif_item_property
+    (
+        property:  &HeifItemProperty,
+        essential: bool,
+        path:      &Path,
+        item_id:   u32
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_heif_path(path)?;
+        return modify_file(path, |file_buffer| heif::write_item_property(file_buffer, item_id, property, essential));
+    }
+
+    /// Removes the association of all properties with the given type (e.g.
+    /// `irot` for resetting the rotation) from the given item of a HEIF/AVIF
+    /// that is stored as a `Vec<u8>`. Properties that are not associated with
+    /// any item afterwards get removed. 
+    pub fn
+    remove_heif_item_property
+    (
+        file_buffer: &mut Vec<u8>,
+        item_id:     u32,
+        box_type:    &str
+    )
+    -> Result<(), std::io::Error>
+    {
+        return heif::remove_item_property(file_buffer, item_id, box_type);
+    }
+
+    pub fn
+    file_remove_heif_item_property
+    (
+        path:     &Path,
+        item_id:  u32,
+        box_type: &str
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_heif_path(path)?;
+        return modify_file(path, |file_buffer| heif::remove_item_property(file_buffer, item_id, box_type));
+    }
+
+    /// Writes the metadata to the EXIF item that describes the given image
+    /// item of a HEIF/AVIF that is stored as a `Vec<u8>`. If the image item
+    /// does not have an EXIF item yet, a new one gets created. The XMP data
+    /// is not synchronized, regardless of the `XmpSyncMode`. 
+    pub fn
+    write_to_heif_image
+    (
+        &self,
+        file_buffer: &mut Vec<u8>,
+        item_id:     u32
+    )
+    -> Result<(), std::io::Error>
+    {
+        return heif::write_image_metadata(file_buffer, self, item_id);
+    }
+
+    pub fn
+    file_write_to_heif_image
+    (
+        &self,
+        path:    &Path,
+        item_id: u32
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_heif_path(path)?;
+        return modify_file(path, |file_buffer| heif::write_image_metadata(file_buffer, self, item_id));
+    }
+}
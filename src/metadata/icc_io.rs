@@ -0,0 +1,264 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::path::Path;
+
+use crate::exif_tag::ExifTag;
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::io_error;
+use crate::general_file_io::modify_file;
+use crate::ifd::ExifTagGroup;
+
+use crate::heif;
+use crate::jpg;
+use crate::png;
+use crate::webp;
+
+use super::Metadata;
+
+/// TIFF tag that stores the ICC profile ("InterColorProfile")
+const ICC_PROFILE_TAG: u16 = 0x8773;
+
+impl
+Metadata
+{
+    /// Gets the ICC profile stored in the TIFF tag 0x8773
+    pub fn
+    get_icc_profile_tag
+    (
+        &self
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        match self.get_tag_by_hex(ICC_PROFILE_TAG, Some(ExifTagGroup::GENERIC)).next()
+        {
+            Some(tag) => return Ok(tag.value_as_u8_vec(&self.get_endian())),
+            None      => return io_error!(NotFound, "No ICC profile found!"),
+        }
+    }
+
+    /// Stores the ICC profile in the TIFF tag 0x8773 of IFD0
+    pub fn
+    set_icc_profile_tag
+    (
+        &mut self,
+        icc_profile: &[u8]
+    )
+    {
+        self.set_tag(ExifTag::UnknownUNDEF(
+            icc_profile.to_vec(),
+            ICC_PROFILE_TAG,
+            ExifTagGroup::GENERIC
+        ));
+    }
+
+    /// Removes the TIFF tag 0x8773 that stores the ICC profile
+    pub fn
+    remove_icc_profile_tag
+    (
+        &mut self
+    )
+    {
+        self.remove_tag_by_hex_group(ICC_PROFILE_TAG, ExifTagGroup::GENERIC);
+    }
+
+    /// Reads the ICC profile from an image that is stored as a `Vec<u8>`.
+    /// Depending on the file type, the profile is located in
+    /// - JPEG: the APP2 segment(s) starting with `ICC_PROFILE`
+    /// - PNG: the `iCCP` chunk
+    /// - WebP: the `ICCP` chunk
+    /// - HEIF/AVIF: a `colr` item property of type `prof` or `rICC`
+    /// - TIFF: the EXIF tag 0x8773
+    /// JXLs are not supported, as their ICC profile is part of the (possibly
+    /// compressed) color encoding in the codestream.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::filetype::FileExtension;
+    ///
+    /// let file_data   = std::fs::read("image.jpg").unwrap();
+    /// let icc_profile = Metadata::get_icc_profile(&file_data, FileExtension::JPEG).unwrap();
+    /// ```
+    #[allow(unreachable_patterns)]
+    pub fn
+    get_icc_profile
+    (
+        file_buffer: &[u8],
+        file_type:   FileExtension
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        match file_type
+        {
+            FileExtension::HEIF
+                =>  heif::read_icc_profile(file_buffer),
+            FileExtension::JPEG
+                =>  jpg::read_icc_profile(file_buffer),
+            FileExtension::PNG { as_zTXt_chunk: _ }
+                =>  png::read_icc_profile(&file_buffer.to_vec()),
+            FileExtension::WEBP
+                =>  webp::vec::read_icc_profile(&file_buffer.to_vec()),
+            FileExtension::TIFF
+                =>  Metadata::new_from_vec(&file_buffer.to_vec(), file_type)?.get_icc_profile_tag(),
+            FileExtension::JXL | FileExtension::NAKED_JXL
+                => return io_error!(
+                    Unsupported,
+                    "'get_icc_profile' is not supported for JXL, as its ICC profile is part of the codestream"
+                ),
+            _
+                => return io_error!(
+                    Other,
+                    format!(
+                        "Function 'get_icc_profile' not yet implemented for {:?}",
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Reads the ICC profile from the image at the specified path.
+    pub fn
+    file_get_icc_profile
+    (
+        path: &Path
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        let file_type   = get_file_type(path)?;
+        let file_buffer = std::fs::read(path)?;
+        return Self::get_icc_profile(&file_buffer, file_type);
+    }
+
+    /// Writes the ICC profile to an image stored as a `Vec<u8>`, replacing
+    /// any previously stored profile. For PNGs, this also removes the `sRGB`
+    /// chunk as the two are mutually exclusive.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::filetype::FileExtension;
+    ///
+    /// let icc_profile   = std::fs::read("profile.icc").unwrap();
+    /// let mut file_data = std::fs::read("image.jpg").unwrap();
+    /// Metadata::set_icc_profile(&icc_profile, &mut file_data, FileExtension::JPEG).unwrap();
+    /// ```
+    #[allow(unreachable_patterns)]
+    pub fn
+    set_icc_profile
+    (
+        icc_profile: &[u8],
+        file_buffer: &mut Vec<u8>,
+        file_type:   FileExtension
+    )
+    -> Result<(), std::io::Error>
+    {
+        if icc_profile.is_empty()
+        {
+            return io_error!(InvalidInput, "Can't write an empty ICC profile!");
+        }
+
+        match file_type
+        {
+            FileExtension::HEIF
+                =>  heif::write_icc_profile(file_buffer, icc_profile),
+            FileExtension::JPEG
+                =>  jpg::write_icc_profile(file_buffer, icc_profile),
+            FileExtension::PNG { as_zTXt_chunk: _ }
+                =>  png::write_icc_profile(file_buffer, icc_profile),
+            FileExtension::WEBP
+                =>  webp::vec::write_icc_profile(file_buffer, icc_profile),
+            FileExtension::TIFF
+                =>  {
+                    let mut metadata = Metadata::new_from_vec(file_buffer, file_type)?;
+                    metadata.set_icc_profile_tag(icc_profile);
+                    metadata.write_to_vec(file_buffer, file_type)
+                },
+            FileExtension::JXL | FileExtension::NAKED_JXL
+                => return io_error!(
+                    Unsupported,
+                    "'set_icc_profile' is not supported for JXL, as its ICC profile is part of the codestream"
+                ),
+            _
+                => return io_error!(
+                    Other,
+                    format!(
+                        "Function 'set_icc_profile' not yet implemented for {:?}",
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Writes the ICC profile to the image at the specified path, replacing
+    /// any previously stored profile.
+    pub fn
+    file_set_icc_profile
+    (
+        icc_profile: &[u8],
+        path:        &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        let file_type = get_file_type(path)?;
+        return modify_file(path, |file_buffer| Self::set_icc_profile(icc_profile, file_buffer, file_type));
+    }
+
+    /// Removes the ICC profile from an image stored as a `Vec<u8>`. Does
+    /// nothing if the image does not contain a profile.
+    #[allow(unreachable_patterns)]
+    pub fn
+    remove_icc_profile
+    (
+        file_buffer: &mut Vec<u8>,
+        file_type:   FileExtension
+    )
+    -> Result<(), std::io::Error>
+    {
+        match file_type
+        {
+            FileExtension::HEIF
+                =>  heif::remove_icc_profile(file_buffer),
+            FileExtension::JPEG
+                =>  jpg::remove_icc_profile(file_buffer),
+            FileExtension::PNG { as_zTXt_chunk: _ }
+                =>  png::remove_icc_profile(file_buffer),
+            FileExtension::WEBP
+                =>  webp::vec::remove_icc_profile(file_buffer),
+            FileExtension::TIFF
+                =>  {
+                    let mut metadata = Metadata::new_from_vec(file_buffer, file_type)?;
+                    if metadata.get_icc_profile_tag().is_err()
+                    {
+                        return Ok(());
+                    }
+                    metadata.remove_icc_profile_tag();
+                    metadata.write_to_vec(file_buffer, file_type)
+                },
+            FileExtension::JXL | FileExtension::NAKED_JXL
+                => return io_error!(
+                    Unsupported,
+                    "'remove_icc_profile' is not supported for JXL, as its ICC profile is part of the codestream"
+                ),
+            _
+                => return io_error!(
+                    Other,
+                    format!(
+                        "Function 'remove_icc_profile' not yet implemented for {:?}",
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Removes the ICC profile from the image at the specified path.
+    pub fn
+    file_remove_icc_profile
+    (
+        path: &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        let file_type = get_file_type(path)?;
+        return modify_file(path, |file_buffer| Self::remove_icc_profile(file_buffer, file_type));
+    }
+}
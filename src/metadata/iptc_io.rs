@@ -0,0 +1,354 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::path::Path;
+
+use crate::exif_tag::ExifTag;
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::io_error;
+use crate::ifd::ExifTagGroup;
+use crate::iptc::IptcData;
+use crate::photoshop::ImageResources;
+
+use crate::jpg;
+
+use super::Metadata;
+
+/// TIFF tag that stores the Photoshop image resources
+const IMAGE_RESOURCES_TAG: u16 = 0x8649;
+
+impl
+Metadata
+{
+    /// Gets the Photoshop image resources stored in the TIFF tag 0x8649
+    pub fn
+    get_image_resources
+    (
+        &self
+    )
+    -> Result<ImageResources, std::io::Error>
+    {
+        match self.get_tag_by_hex(IMAGE_RESOURCES_TAG, Some(ExifTagGroup::GENERIC)).next()
+        {
+            Some(tag) => return ImageResources::decode(&tag.value_as_u8_vec(&self.get_endian())),
+            None      => return io_error!(NotFound, "No Photoshop image resources found!"),
+        }
+    }
+
+    /// Stores the Photoshop image resources in the TIFF tag 0x8649 of IFD0,
+    /// keeping the format (BYTE or UNDEFINED) of an already existing tag.
+    /// If there are no resources, the tag gets removed.
+    pub fn
+    set_image_resources
+    (
+        &mut self,
+        resources: &ImageResources
+    )
+    -> Result<(), std::io::Error>
+    {
+        if resources.is_empty()
+        {
+            self.remove_tag_by_hex_group(IMAGE_RESOURCES_TAG, ExifTagGroup::GENERIC);
+            return Ok(());
+        }
+
+        let encoded = resources.encode()?;
+
+        let tag = match self.get_tag_by_hex(IMAGE_RESOURCES_TAG, Some(ExifTagGroup::GENERIC)).next()
+        {
+            Some(ExifTag::UnknownUNDEF(_, _, _))
+                => ExifTag::UnknownUNDEF(encoded, IMAGE_RESOURCES_TAG, ExifTagGroup::GENERIC),
+            _   => ExifTag::UnknownINT8U(encoded, IMAGE_RESOURCES_TAG, ExifTagGroup::GENERIC),
+        };
+
+        self.set_tag(tag);
+        return Ok(());
+    }
+
+    /// Reads the Photoshop image resources from an image that is stored as a
+    /// `Vec<u8>`. For JPEGs, these are stored in the APP13 segment(s), for
+    /// TIFFs in the tag 0x8649.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::filetype::FileExtension;
+    /// use little_exif::photoshop::IPTC_DIGEST_RESOURCE_ID;
+    ///
+    /// let file_data = std::fs::read("image.jpg").unwrap();
+    /// let resources = Metadata::read_image_resources(&file_data, FileExtension::JPEG).unwrap();
+    /// let digest    = resources.get_resource(IPTC_DIGEST_RESOURCE_ID);
+    /// ```
+    #[allow(unreachable_patterns)]
+    pub fn
+    read_image_resources
+    (
+        file_buffer: &[u8],
+        file_type:   FileExtension
+    )
+    -> Result<ImageResources, std::io::Error>
+    {
+        match file_type
+        {
+            FileExtension::JPEG
+                =>  jpg::read_image_resources(file_buffer),
+            FileExtension::TIFF
+                =>  Metadata::new_from_vec(&file_buffer.to_vec(), file_type)?.get_image_resources(),
+            _
+                => return io_error!(
+                    Other,
+                    format!(
+                        "Function 'read_image_resources' not yet implemented for {:?}",
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Reads the Photoshop image resources from the image at the specified
+    /// path.
+    #[allow(unreachable_patterns)]
+    pub fn
+    file_read_image_resources
+    (
+        path: &Path
+    )
+    -> Result<ImageResources, std::io::Error>
+    {
+        let file_type = get_file_type(path)?;
+
+        match file_type
+        {
+            FileExtension::JPEG
+                =>  jpg::file_read_image_resources(path),
+            FileExtension::TIFF
+                =>  Metadata::new_from_path(path)?.get_image_resources(),
+            _
+                => return io_error!(
+                    Other,
+                    format!(
+                        "Function 'file_read_image_resources' not yet implemented for {:?}",
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Writes the Photoshop image resources to an image stored as a
+    /// `Vec<u8>`, replacing all previously stored resources.
+    #[allow(unreachable_patterns)]
+    pub fn
+    write_image_resources
+    (
+        resources:   &ImageResources,
+        file_buffer: &mut Vec<u8>,
+        file_type:   FileExtension
+    )
+    -> Result<(), std::io::Error>
+    {
+        match file_type
+        {
+            FileExtension::JPEG
+                =>  jpg::write_image_resources(file_buffer, resources),
+            FileExtension::TIFF
+                =>  {
+                    let mut metadata = Metadata::new_from_vec(file_buffer, file_type)?;
+                    metadata.set_image_resources(resources)?;
+                    metadata.write_to_vec(file_buffer, file_type)
+                },
+            _
+                => return io_error!(
+                    Other,
+                    format!(
+                        "Function 'write_image_resources' not yet implemented for {:?}",
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Writes the Photoshop image resources to the image at the specified
+    /// path, replacing all previously stored resources.
+    #[allow(unreachable_patterns)]
+    pub fn
+    file_write_image_resources
+    (
+        resources: &ImageResources,
+        path:      &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        let file_type = get_file_type(path)?;
+
+        match file_type
+        {
+            FileExtension::JPEG
+                =>  jpg::file_write_image_resources(path, resources),
+            FileExtension::TIFF
+                =>  {
+                    let mut metadata = Metadata::new_from_path(path)?;
+                    metadata.set_image_resources(resources)?;
+                    metadata.write_to_file(path)
+                },
+            _
+                => return io_error!(
+                    Other,
+                    format!(
+                        "Function 'file_write_image_resources' not yet implemented for {:?}",
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Removes all Photoshop image resources with the given ID from an image
+    /// stored as a `Vec<u8>` while keeping all other resources, in contrast
+    /// to `clear_app13_segment`. Returns whether anything got removed.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::filetype::FileExtension;
+    /// use little_exif::photoshop::IPTC_DIGEST_RESOURCE_ID;
+    ///
+    /// let mut file_data = std::fs::read("image.jpg").unwrap();
+    /// Metadata::remove_image_resource(IPTC_DIGEST_RESOURCE_ID, &mut file_data, FileExtension::JPEG).unwrap();
+    /// ```
+    pub fn
+    remove_image_resource
+    (
+        resource_id: u16,
+        file_buffer: &mut Vec<u8>,
+        file_type:   FileExtension
+    )
+    -> Result<bool, std::io::Error>
+    {
+        let mut resources = Self::read_image_resources(file_buffer, file_type)?;
+
+        if !resources.remove_resource(resource_id)
+        {
+            return Ok(false);
+        }
+
+        Self::write_image_resources(&resources, file_buffer, file_type)?;
+        return Ok(true);
+    }
+
+    /// Removes all Photoshop image resources with the given ID from the
+    /// image at the specified path while keeping all other resources.
+    /// Returns whether anything got removed.
+    pub fn
+    file_remove_image_resource
+    (
+        resource_id: u16,
+        path:        &Path
+    )
+    -> Result<bool, std::io::Error>
+    {
+        let mut resources = Self::file_read_image_resources(path)?;
+
+        if !resources.remove_resource(resource_id)
+        {
+            return Ok(false);
+        }
+
+        Self::file_write_image_resources(&resources, path)?;
+        return Ok(true);
+    }
+
+    /// Reads the IPTC-IIM data from the Photoshop image resources of an
+    /// image that is stored as a `Vec<u8>`.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::filetype::FileExtension;
+    /// use little_exif::iptc::IptcTag;
+    ///
+    /// let file_data = std::fs::read("image.jpg").unwrap();
+    /// let iptc      = Metadata::read_iptc(&file_data, FileExtension::JPEG).unwrap();
+    /// let caption   = iptc.get_string(IptcTag::Caption);
+    /// let keywords  = iptc.get_strings(IptcTag::Keywords);
+    /// ```
+    pub fn
+    read_iptc
+    (
+        file_buffer: &[u8],
+        file_type:   FileExtension
+    )
+    -> Result<IptcData, std::io::Error>
+    {
+        return Self::read_image_resources(file_buffer, file_type)?.get_iptc();
+    }
+
+    /// Reads the IPTC-IIM data from the Photoshop image resources of the
+    /// image at the specified path.
+    pub fn
+    file_read_iptc
+    (
+        path: &Path
+    )
+    -> Result<IptcData, std::io::Error>
+    {
+        return Self::file_read_image_resources(path)?.get_iptc();
+    }
+
+    /// Writes the IPTC-IIM data to the Photoshop image resources of an image
+    /// stored as a `Vec<u8>`, keeping all other resources. The IPTC digest
+    /// gets updated as well.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::filetype::FileExtension;
+    /// use little_exif::iptc::IptcTag;
+    ///
+    /// let mut file_data = std::fs::read("image.jpg").unwrap();
+    /// let mut iptc      = Metadata::read_iptc(&file_data, FileExtension::JPEG).unwrap();
+    /// iptc.set_string(IptcTag::Caption, "Hello World!");
+    /// Metadata::write_iptc(&iptc, &mut file_data, FileExtension::JPEG).unwrap();
+    /// ```
+    pub fn
+    write_iptc
+    (
+        iptc:        &IptcData,
+        file_buffer: &mut Vec<u8>,
+        file_type:   FileExtension
+    )
+    -> Result<(), std::io::Error>
+    {
+        let mut resources = match Self::read_image_resources(file_buffer, file_type)
+        {
+            Ok(resources)
+                => resources,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound
+                => ImageResources::new(),
+            Err(e)
+                => return Err(e),
+        };
+
+        resources.set_iptc(iptc)?;
+        return Self::write_image_resources(&resources, file_buffer, file_type);
+    }
+
+    /// Writes the IPTC-IIM data to the Photoshop image resources of the
+    /// image at the specified path, keeping all other resources.
+    pub fn
+    file_write_iptc
+    (
+        iptc: &IptcData,
+        path: &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        let mut resources = match Self::file_read_image_resources(path)
+        {
+            Ok(resources)
+                => resources,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound
+                => ImageResources::new(),
+            Err(e)
+                => return Err(e),
+        };
+
+        resources.set_iptc(iptc)?;
+        return Self::file_write_image_resources(&resources, path);
+    }
+}
@@ -0,0 +1,576 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::path::Path;
+
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::io_error;
+use crate::general_file_io::modify_file;
+use crate::jfif::Jfif;
+use crate::jfif::JfxxThumbnail;
+use crate::mpf::MpEntry;
+use crate::motion_photo::MotionPhoto;
+
+use crate::jpg;
+
+use super::Metadata;
+use super::write_options::WriteOptions;
+
+/// Determines what happens when writing EXIF data to a JPEG that exceeds the
+/// maximum size of an APP1 segment, i.e. 65533 bytes including the `Exif\0\0`
+/// header. Large thumbnails, maker notes or user comments can cause this.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum
+OversizeExifPolicy
+{
+    /// Writing fails with an `InvalidInput` error that wraps an
+    /// `OversizeExifError` (default)
+    #[default]
+    Error,
+
+    /// The thumbnail stored in IFD1 gets removed. If the data is still too
+    /// large afterwards, writing fails as with `Error`.
+    RemoveThumbnail,
+
+    /// The thumbnail gets removed, followed by the largest tags with format
+    /// `UNDEF` (e.g. `MakerNote` or `UserComment`), one at a time, until the
+    /// data fits. The original `Metadata` struct is not affected, so the
+    /// removed tags can still be stored elsewhere.
+    RemoveLargeUndefTags,
+
+    /// The data gets split across multiple consecutive APP1 segments, each
+    /// starting with the `Exif\0\0` header. This is not part of the EXIF
+    /// standard but is used by some cameras and understood by e.g. ExifTool
+    /// and little_exif itself.
+    MultiSegment,
+}
+
+/// The error wrapped in the `std::io::Error` that gets returned if the EXIF
+/// data does not fit into a single JPEG APP1 segment
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+OversizeExifError
+{
+    /// Length of the encoded EXIF data, including the `Exif\0\0` header
+    pub length: usize,
+}
+
+impl
+std::fmt::Display
+for
+OversizeExifError
+{
+    fn
+    fmt
+    (
+        &self,
+        f: &mut std::fmt::Formatter<'_>
+    )
+    -> std::fmt::Result
+    {
+        return write!(f, "EXIF data of {} bytes exceeds the JPEG APP1 segment limit of 65533 bytes", self.length)
+    }
+}
+
+impl std::error::Error for OversizeExifError {}
+
+/// Checks that the file at the given path is a JPEG, as the functions in this
+/// module are specific to this format
+fn
+check_jpg_path
+(
+    path: &Path
+)
+-> Result<(), std::io::Error>
+{
+    let file_type = get_file_type(path)?;
+    if file_type != FileExtension::JPEG
+    {
+        return io_error!(Unsupported, format!("Expected JPEG file but got {:?}", file_type));
+    }
+    return Ok(());
+}
+
+impl
+Metadata
+{
+    /// Reads the content of all COM segments of a JPEG that is stored as a
+    /// `Vec<u8>`, in the order of their appearance. Returns an empty vector
+    /// if there are no comments.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    ///
+    /// let file_data = std::fs::read("image.jpg").unwrap();
+    /// for comment in Metadata::read_comments(&file_data).unwrap()
+    /// {
+    ///     println!("{}", String::from_utf8_lossy(&comment));
+    /// }
+    /// ```
+    pub fn
+    read_comments
+    (
+        file_buffer: &[u8]
+    )
+    -> Result<Vec<Vec<u8>>, std::io::Error>
+    {
+        return jpg::read_comments(file_buffer);
+    }
+
+    pub fn
+    file_read_comments
+    (
+        path: &Path
+    )
+    -> Result<Vec<Vec<u8>>, std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return jpg::read_comments(&std::fs::read(path)?);
+    }
+
+    /// Writes the comments as COM segments to a JPEG that is stored as a
+    /// `Vec<u8>`, replacing all previously stored comments. Each comment
+    /// gets its own segment, so it may be at most 65533 bytes long.
+    /// Passing no comments removes all COM segments.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    ///
+    /// let mut file_data = std::fs::read("image.jpg").unwrap();
+    /// Metadata::write_comments(&[b"Batch 42".as_slice()], &mut file_data).unwrap();
+    /// ```
+    pub fn
+    write_comments
+    (
+        comments:    &[&[u8]],
+        file_buffer: &mut Vec<u8>
+    )
+    -> Result<(), std::io::Error>
+    {
+        return jpg::write_comments(file_buffer, comments);
+    }
+
+    pub fn
+    file_write_comments
+    (
+        comments: &[&[u8]],
+        path:     &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return modify_file(path, |file_buffer| jpg::write_comments(file_buffer, comments));
+    }
+
+    /// Reads the JFIF APP0 segment of a JPEG that is stored as a `Vec<u8>`,
+    /// e.g. for getting the pixel density if the EXIF data does not provide
+    /// the resolution.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::filetype::FileExtension;
+    ///
+    /// let file_data    = std::fs::read("image.jpg").unwrap();
+    /// let mut metadata = Metadata::new_from_vec(&file_data, FileExtension::JPEG).unwrap();
+    /// let jfif         = Metadata::read_jfif(&file_data).unwrap();
+    /// jfif.set_resolution_of_metadata(&mut metadata);
+    /// ```
+    pub fn
+    read_jfif
+    (
+        file_buffer: &[u8]
+    )
+    -> Result<Jfif, std::io::Error>
+    {
+        return jpg::read_jfif(file_buffer);
+    }
+
+    pub fn
+    file_read_jfif
+    (
+        path: &Path
+    )
+    -> Result<Jfif, std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return jpg::read_jfif(&std::fs::read(path)?);
+    }
+
+    /// Writes the JFIF APP0 segment to a JPEG that is stored as a `Vec<u8>`,
+    /// replacing the existing one or inserting it right after the signature.
+    pub fn
+    write_jfif
+    (
+        jfif:        &Jfif,
+        file_buffer: &mut Vec<u8>
+    )
+    -> Result<(), std::io::Error>
+    {
+        return jpg::write_jfif(file_buffer, jfif);
+    }
+
+    pub fn
+    file_write_jfif
+    (
+        jfif: &Jfif,
+        path: &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return modify_file(path, |file_buffer| jpg::write_jfif(file_buffer, jfif));
+    }
+
+    /// Reads the thumbnail stored in the JFXX APP0 extension segment of a
+    /// JPEG that is stored as a `Vec<u8>`.
+    pub fn
+    read_jfxx_thumbnail
+    (
+        file_buffer: &[u8]
+    )
+    -> Result<JfxxThumbnail, std::io::Error>
+    {
+        return jpg::read_jfxx_thumbnail(file_buffer);
+    }
+
+    pub fn
+    file_read_jfxx_thumbnail
+    (
+        path: &Path
+    )
+    -> Result<JfxxThumbnail, std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return jpg::read_jfxx_thumbnail(&std::fs::read(path)?);
+    }
+
+    /// Writes the thumbnail as JFXX APP0 extension segment to a JPEG that is
+    /// stored as a `Vec<u8>`, replacing a previously stored one. The JPEG
+    /// must have a JFIF segment, which the JFXX segment has to follow.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::jfif::JfxxThumbnail;
+    ///
+    /// let thumbnail     = std::fs::read("thumbnail.jpg").unwrap();
+    /// let mut file_data = std::fs::read("image.jpg").unwrap();
+    /// Metadata::write_jfxx_thumbnail(&JfxxThumbnail::Jpeg(thumbnail), &mut file_data).unwrap();
+    /// ```
+    pub fn
+    write_jfxx_thumbnail
+    (
+        thumbnail:   &JfxxThumbnail,
+        file_buffer: &mut Vec<u8>
+    )
+    -> Result<(), std::io::Error>
+    {
+        return jpg::write_jfxx_thumbnail(file_buffer, thumbnail);
+    }
+
+    pub fn
+    file_write_jfxx_thumbnail
+    (
+        thumbnail: &JfxxThumbnail,
+        path:      &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return modify_file(path, |file_buffer| jpg::write_jfxx_thumbnail(file_buffer, thumbnail));
+    }
+
+    /// Removes the JFXX APP0 extension segment(s) of a JPEG that is stored
+    /// as a `Vec<u8>`.
+    pub fn
+    remove_jfxx_thumbnail
+    (
+        file_buffer: &mut Vec<u8>
+    )
+    -> Result<(), std::io::Error>
+    {
+        return jpg::remove_jfxx_thumbnail(file_buffer);
+    }
+
+    pub fn
+    file_remove_jfxx_thumbnail
+    (
+        path: &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return modify_file(path, jpg::remove_jfxx_thumbnail);
+    }
+
+    /// Reads the entries of the Multi-Picture Format (MPF) APP2 segment of a
+    /// JPEG that is stored as a `Vec<u8>`. Each entry describes an image,
+    /// starting with the primary image, e.g. large previews, depth maps or
+    /// the gain maps of Ultra HDR images.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    ///
+    /// let file_data = std::fs::read("image.jpg").unwrap();
+    /// for entry in Metadata::read_mpf_entries(&file_data).unwrap()
+    /// {
+    ///     println!("{:?}: {} bytes", entry.get_image_type(), entry.get_size());
+    /// }
+    /// ```
+    pub fn
+    read_mpf_entries
+    (
+        file_buffer: &[u8]
+    )
+    -> Result<Vec<MpEntry>, std::io::Error>
+    {
+        return jpg::read_mpf_entries(file_buffer);
+    }
+
+    pub fn
+    file_read_mpf_entries
+    (
+        path: &Path
+    )
+    -> Result<Vec<MpEntry>, std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return jpg::read_mpf_entries(&std::fs::read(path)?);
+    }
+
+    /// Extracts the image with the given index (as returned by
+    /// `read_mpf_entries`) from a JPEG that is stored as a `Vec<u8>`.
+    pub fn
+    extract_mpf_image
+    (
+        file_buffer: &[u8],
+        index:       usize
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        return jpg::extract_mpf_image(file_buffer, index);
+    }
+
+    pub fn
+    file_extract_mpf_image
+    (
+        path:  &Path,
+        index: usize
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return jpg::extract_mpf_image(&std::fs::read(path)?, index);
+    }
+
+    /// Constructs a new `Metadata` object from the EXIF data of the image
+    /// with the given index in the MPF segment of a JPEG.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    ///
+    /// let file_data = std::fs::read("image.jpg").unwrap();
+    /// let preview_metadata = Metadata::new_from_mpf_image(&file_data, 1).unwrap();
+    /// ```
+    pub fn
+    new_from_mpf_image
+    (
+        file_buffer: &[u8],
+        index:       usize
+    )
+    -> Result<Metadata, std::io::Error>
+    {
+        let image_buffer = jpg::extract_mpf_image(file_buffer, index)?;
+        return Metadata::new_from_vec(&image_buffer, FileExtension::JPEG);
+    }
+
+    /// Writes the metadata to the image with the given index in the MPF
+    /// segment of a JPEG that is stored as a `Vec<u8>`. The MPF segment gets
+    /// updated to account for the changed size. For the primary image
+    /// (index 0), this is the same as calling `write_to_vec`.
+    pub fn
+    write_to_mpf_image
+    (
+        &self,
+        file_buffer: &mut Vec<u8>,
+        index:       usize
+    )
+    -> Result<(), std::io::Error>
+    {
+        return self.write_to_mpf_image_with_options(file_buffer, index, &WriteOptions::default());
+    }
+
+    /// Writes the metadata to the image with the given index in the MPF
+    /// segment, using the given options. See `WriteOptions` for details.
+    pub fn
+    write_to_mpf_image_with_options
+    (
+        &self,
+        file_buffer: &mut Vec<u8>,
+        index:       usize,
+        options:     &WriteOptions
+    )
+    -> Result<(), std::io::Error>
+    {
+        return jpg::write_mpf_image_metadata(file_buffer, index, self, options);
+    }
+
+    pub fn
+    file_write_to_mpf_image
+    (
+        &self,
+        path:  &Path,
+        index: usize
+    )
+    -> Result<(), std::io::Error>
+    {
+        return self.file_write_to_mpf_image_with_options(path, index, &WriteOptions::default());
+    }
+
+    pub fn
+    file_write_to_mpf_image_with_options
+    (
+        &self,
+        path:    &Path,
+        index:   usize,
+        options: &WriteOptions
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return modify_file(path, |file_buffer| jpg::write_mpf_image_metadata(file_buffer, index, self, options));
+    }
+
+    /// Reads the trailer of a JPEG that is stored as a `Vec<u8>`, i.e. all
+    /// data after the EOI marker of the primary image. This may contain the
+    /// additional images of an MPF segment, the video of a motion photo or
+    /// other vendor specific data. Returns an empty vector if there is none.
+    pub fn
+    read_jpg_trailer
+    (
+        file_buffer: &[u8]
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        return jpg::read_trailer(file_buffer);
+    }
+
+    pub fn
+    file_read_jpg_trailer
+    (
+        path: &Path
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return jpg::read_trailer(&std::fs::read(path)?);
+    }
+
+    /// Checks if a JPEG that is stored as a `Vec<u8>` is a motion photo,
+    /// i.e. has a video appended after the image data. Supported are the
+    /// Google formats (`GCamera:MotionPhoto` with a `Container:Directory` as
+    /// well as the older `GCamera:MicroVideo`) and the Samsung trailer.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    ///
+    /// let file_data = std::fs::read("image.jpg").unwrap();
+    /// if let Some(motion_photo) = Metadata::detect_motion_photo(&file_data).unwrap()
+    /// {
+    ///     println!("{:?}: {} bytes", motion_photo.get_format(), motion_photo.get_length());
+    /// }
+    /// ```
+    pub fn
+    detect_motion_photo
+    (
+        file_buffer: &[u8]
+    )
+    -> Result<Option<MotionPhoto>, std::io::Error>
+    {
+        return jpg::find_motion_photo(file_buffer);
+    }
+
+    pub fn
+    file_detect_motion_photo
+    (
+        path: &Path
+    )
+    -> Result<Option<MotionPhoto>, std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return jpg::find_motion_photo(&std::fs::read(path)?);
+    }
+
+    /// Extracts the video of a motion photo that is stored as a `Vec<u8>`.
+    /// Fails with `NotFound` if the JPEG is not a motion photo.
+    pub fn
+    extract_motion_photo
+    (
+        file_buffer: &[u8]
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        return jpg::extract_motion_photo(file_buffer);
+    }
+
+    pub fn
+    file_extract_motion_photo
+    (
+        path: &Path
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return jpg::extract_motion_photo(&std::fs::read(path)?);
+    }
+
+    /// Replaces the video of a motion photo that is stored as a `Vec<u8>`.
+    /// The XMP data or the Samsung trailer get updated to the new length.
+    /// Fails with `NotFound` if the JPEG is not a motion photo.
+    pub fn
+    replace_motion_photo
+    (
+        video:       &[u8],
+        file_buffer: &mut Vec<u8>
+    )
+    -> Result<(), std::io::Error>
+    {
+        return jpg::replace_motion_photo(file_buffer, Some(video));
+    }
+
+    pub fn
+    file_replace_motion_photo
+    (
+        video: &[u8],
+        path:  &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return modify_file(path, |file_buffer| jpg::replace_motion_photo(file_buffer, Some(video)));
+    }
+
+    /// Removes the video of a motion photo that is stored as a `Vec<u8>`,
+    /// turning it into a regular JPEG. The motion photo properties get
+    /// removed from the XMP data, or the entry from the Samsung trailer.
+    pub fn
+    remove_motion_photo
+    (
+        file_buffer: &mut Vec<u8>
+    )
+    -> Result<(), std::io::Error>
+    {
+        return jpg::replace_motion_photo(file_buffer, None);
+    }
+
+    pub fn
+    file_remove_motion_photo
+    (
+        path: &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_jpg_path(path)?;
+        return modify_file(path, |file_buffer| jpg::replace_motion_photo(file_buffer, None));
+    }
+}
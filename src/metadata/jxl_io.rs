@@ -0,0 +1,112 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::path::Path;
+
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::io_error;
+use crate::general_file_io::modify_file;
+
+use crate::jxl;
+
+use super::Metadata;
+
+/// Determines how the `Exif` and `xml ` boxes of a JXL file get stored when
+/// writing them. Regardless of this setting, both compressed and uncompressed
+/// boxes can be read.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum
+JxlBoxCompression
+{
+    /// The data is stored in plain `Exif` and `xml ` boxes (default)
+    #[default]
+    Uncompressed,
+
+    /// The data is compressed using Brotli and stored in `brob` boxes, as
+    /// done by default by e.g. cjxl
+    Brotli,
+}
+
+/// Determines what happens when writing EXIF data to a JPEG-recompressed JXL,
+/// i.e. one with a `jbrd` box that allows the bit-exact reconstruction of the
+/// original JPEG. This data refers to the `Exif` and `xml ` boxes for the APP
+/// segments of the JPEG, so changing their content breaks the reconstruction.
+/// Nothing happens if the content of these boxes stays the same. 
+/// The policy only applies when writing the EXIF data, e.g. via
+/// `write_to_vec_with_options`. Operations that don't take any options, i.e.
+/// `clear_metadata`, `write_xmp`, `write_jxl_xmp`, `remove_xmp` and their
+/// `file_` counterparts, always behave as with `Warn`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum
+JpegReconstructionPolicy
+{
+    /// The metadata gets written and a warning is logged (default)
+    #[default]
+    Warn,
+
+    /// Writing fails with an `InvalidInput` error and the file is left as is
+    Error,
+
+    /// The `jbrd` box gets removed, so the JXL no longer claims that the
+    /// original JPEG can be reconstructed. The image itself is not affected.
+    RemoveReconstructionData,
+}
+
+/// Checks that the file at the given path is a JXL, as the functions in this
+/// module are specific to this format
+fn
+check_jxl_path
+(
+    path: &Path
+)
+-> Result<(), std::io::Error>
+{
+    let file_type = get_file_type(path)?;
+    if file_type != FileExtension::JXL
+    {
+        return io_error!(Unsupported, format!("Expected JXL file but got {:?}", file_type));
+    }
+    return Ok(());
+}
+
+impl
+Metadata
+{
+    /// Writes the raw XMP packet to a JXL that is stored as a `Vec<u8>`
+    /// using the given compression, replacing any previously stored XMP data.
+    /// In contrast, `write_xmp` keeps the compression of the existing box.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::metadata::jxl_io::JxlBoxCompression;
+    ///
+    /// let mut file_data = std::fs::read("image.jxl").unwrap();
+    /// let xmp = std::fs::read("image.xmp").unwrap();
+    /// Metadata::write_jxl_xmp(&xmp, &mut file_data, JxlBoxCompression::Brotli).unwrap();
+    /// ```
+    pub fn
+    write_jxl_xmp
+    (
+        xmp_data:    &[u8],
+        file_buffer: &mut Vec<u8>,
+        compression: JxlBoxCompression
+    )
+    -> Result<(), std::io::Error>
+    {
+        return jxl::write_xmp_with_compression(file_buffer, xmp_data, compression);
+    }
+
+    pub fn
+    file_write_jxl_xmp
+    (
+        xmp_data:    &[u8],
+        path:        &Path,
+        compression: JxlBoxCompression
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_jxl_path(path)?;
+        return modify_file(path, |file_buffer| jxl::write_xmp_with_compression(file_buffer, xmp_data, compression));
+    }
+}
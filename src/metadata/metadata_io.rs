@@ -9,14 +9,17 @@ use crate::filetype::FileExtension;
 use crate::general_file_io::io_error;
 
 use crate::general_file_io::open_read_file;
+use crate::cr3;
 use crate::heif;
 use crate::jpg;
 use crate::jxl;
 use crate::png;
+use crate::raw;
 use crate::tiff;
 use crate::webp;
 
 use super::Metadata;
+use super::write_options::WriteOptions;
 
 impl
 Metadata
@@ -67,7 +70,7 @@ Metadata
                 => heif::read_metadata(file_buffer),
             FileExtension::JPEG 
                 =>  jpg::read_metadata(file_buffer),
-            FileExtension::JXL
+            FileExtension::JXL | FileExtension::NAKED_JXL
                 =>  jxl::read_metadata(file_buffer),
             FileExtension::PNG { as_zTXt_chunk: _ }
                 =>  png::read_metadata(file_buffer),
@@ -75,6 +78,12 @@ Metadata
                 => tiff::vec::read_metadata(file_buffer),
             FileExtension::WEBP
                 => webp::vec::read_metadata(file_buffer),
+            FileExtension::DNG | FileExtension::CR2 | FileExtension::NEF | 
+            FileExtension::ARW | FileExtension::ORF | FileExtension::RW2 | 
+            FileExtension::PEF
+                => return raw::read_metadata(file_buffer),
+            FileExtension::CR3
+                => return cr3::read_metadata(file_buffer),
             _
                 => return io_error!(
                     Other, 
@@ -141,7 +150,12 @@ Metadata
 
         if let Some(content_based_file_type) = content_based_file_type
         {
-            if extension_based_file_type != content_based_file_type
+            // Some camera raw formats are ordinary TIFF files content-wise,
+            // so the file extension is more specific in this case
+            let is_tiff_based_raw = content_based_file_type == FileExtension::TIFF
+                && extension_based_file_type.is_camera_raw();
+
+            if extension_based_file_type != content_based_file_type && !is_tiff_based_raw
             {
                 log::warn!("File extension and file content yield different file type, content takes precedence");
                 extension_based_file_type = content_based_file_type;
@@ -162,7 +176,7 @@ Metadata
                 => heif::file_read_metadata(path),
             FileExtension::JPEG 
                 =>  jpg::file_read_metadata(path),
-            FileExtension::JXL
+            FileExtension::JXL | FileExtension::NAKED_JXL
                 =>  jxl::file_read_metadata(path),
             FileExtension::PNG { as_zTXt_chunk: _ } 
                 =>  png::file_read_metadata(path),
@@ -170,6 +184,12 @@ Metadata
                 => tiff::file::read_metadata(path),
             FileExtension::WEBP 
                 => webp::file::read_metadata(path),
+            FileExtension::DNG | FileExtension::CR2 | FileExtension::NEF | 
+            FileExtension::ARW | FileExtension::ORF | FileExtension::RW2 | 
+            FileExtension::PEF
+                => return raw::file_read_metadata(path),
+            FileExtension::CR3
+                => return cr3::file_read_metadata(path),
             _
                 => return io_error!(
                     Other, 
@@ -198,7 +218,7 @@ Metadata
                 => heif::clear_metadata(file_buffer),
             FileExtension::JPEG 
                 =>  jpg::clear_metadata(file_buffer),
-            FileExtension::JXL
+            FileExtension::JXL | FileExtension::NAKED_JXL
                 =>  jxl::clear_metadata(file_buffer),
             FileExtension::PNG { as_zTXt_chunk: _ }
                 =>  png::clear_metadata(file_buffer),
@@ -347,7 +367,7 @@ Metadata
                 => heif::file_clear_metadata(path),
             FileExtension::JPEG 
                 =>  jpg::file_clear_metadata(path),
-            FileExtension::JXL
+            FileExtension::JXL | FileExtension::NAKED_JXL
                 =>  jxl::file_clear_metadata(path),
             FileExtension::PNG { as_zTXt_chunk: _ }
                 =>  png::file_clear_metadata(path),
@@ -373,7 +393,6 @@ Metadata
     /// have to determine where to write this, update the file size information
     /// and so on - check file type specific implementations or documentation
     /// for further details
-    #[allow(unreachable_patterns)]
     pub fn
     as_u8_vec
     (
@@ -381,6 +400,21 @@ Metadata
         for_file_type: FileExtension
     )
     -> Result<Vec<u8>, std::io::Error>
+    {
+        return self.as_u8_vec_with_options(for_file_type, &WriteOptions::default());
+    }
+
+    /// Converts the metadata into a file specific vector of bytes, using the
+    /// given options (e.g. `oversize_exif_policy` for JPEGs)
+    #[allow(unreachable_patterns)]
+    pub fn
+    as_u8_vec_with_options
+    (
+        &self,
+        for_file_type: FileExtension,
+        options:       &WriteOptions
+    )
+    -> Result<Vec<u8>, std::io::Error>
     {
         let general_encoded_metadata = self.encode()?;
 
@@ -389,7 +423,7 @@ Metadata
             FileExtension::PNG { as_zTXt_chunk } 
                 =>  png::as_u8_vec(&general_encoded_metadata, as_zTXt_chunk),
             FileExtension::JPEG 
-                =>  jpg::as_u8_vec(&general_encoded_metadata),
+                =>  jpg::as_u8_vec(&general_encoded_metadata, self, options)?,
             FileExtension::WEBP 
                  => webp::as_u8_vec(&general_encoded_metadata),
             FileExtension::HEIF 
@@ -406,9 +440,11 @@ Metadata
         })
     }
 
-    /// Writes the metadata to an image stored as a Vec<u8>
-    /// For now, this only works for JPGs
-    #[allow(unreachable_patterns)]
+    /// Writes the metadata to an image stored as a Vec<u8>, using the default
+    /// write options
+    /// A naked JXL codestream gets wrapped into an ISO BMFF container first
+    /// Camera raw files are edited in place: Existing data is never moved,
+    /// values that don't fit into their old location get appended instead
     pub fn
     write_to_vec
     (
@@ -417,21 +453,58 @@ Metadata
         file_type:   FileExtension
     )
     -> Result<(), std::io::Error>
+    {
+        return self.write_to_vec_with_options(file_buffer, file_type, &WriteOptions::default());
+    }
+
+    /// Writes the metadata to an image stored as a Vec<u8>, using the given
+    /// options. See `WriteOptions` for details.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::metadata::jpg_io::OversizeExifPolicy;
+    /// use little_exif::metadata::write_options::WriteOptions;
+    /// use little_exif::filetype::FileExtension;
+    ///
+    /// let mut file_data = std::fs::read("image.jpg").unwrap();
+    /// let metadata = Metadata::new_from_vec(&file_data, FileExtension::JPEG).unwrap();
+    /// let options  = WriteOptions {
+    ///     oversize_exif_policy: OversizeExifPolicy::RemoveThumbnail,
+    ///     ..WriteOptions::default()
+    /// };
+    /// metadata.write_to_vec_with_options(&mut file_data, FileExtension::JPEG, &options).unwrap();
+    /// ```
+    #[allow(unreachable_patterns)]
+    pub fn
+    write_to_vec_with_options
+    (
+        &self,
+        file_buffer: &mut Vec<u8>,
+        file_type:   FileExtension,
+        options:     &WriteOptions
+    )
+    -> Result<(), std::io::Error>
     {
         match file_type
         {
             FileExtension::HEIF
-                => heif::write_metadata(file_buffer, self),
+                => heif::write_metadata(file_buffer, self, options),
             FileExtension::JPEG 
-                =>  jpg::write_metadata(file_buffer, self),
-            FileExtension::JXL 
-                =>  jxl::write_metadata(file_buffer, self),
+                =>  jpg::write_metadata(file_buffer, self, options),
+            FileExtension::JXL | FileExtension::NAKED_JXL
+                =>  jxl::write_metadata(file_buffer, self, options),
             FileExtension::PNG { as_zTXt_chunk: _ }
-                =>  png::write_metadata(file_buffer, self),
+                =>  png::write_metadata(file_buffer, self, options),
             FileExtension::TIFF
                 => tiff::vec::write_metadata(file_buffer, self),
             FileExtension::WEBP
-                => webp::vec::write_metadata(file_buffer, self),
+                => webp::vec::write_metadata(file_buffer, self, options),
+            FileExtension::DNG | FileExtension::CR2 | FileExtension::NEF | 
+            FileExtension::ARW | FileExtension::ORF | FileExtension::RW2 | 
+            FileExtension::PEF
+                => raw::write_metadata(file_buffer, self),
+            FileExtension::CR3
+                => cr3::write_metadata(file_buffer, self),
             _
                 => return io_error!(
                     Other, 
@@ -448,7 +521,6 @@ Metadata
     /// - The file does not exist at the given path
     /// - Interpreting the given path fails
     /// - The file type is not supported
-    #[allow(unreachable_patterns)]
     pub fn
     write_to_file
     (
@@ -456,23 +528,44 @@ Metadata
         path: &Path
     )
     -> Result<(), std::io::Error>
+    {
+        return self.write_to_file_with_options(path, &WriteOptions::default());
+    }
+
+    /// Writes the metadata to the specified file, using the given options.
+    /// See `WriteOptions` for details.
+    #[allow(unreachable_patterns)]
+    pub fn
+    write_to_file_with_options
+    (
+        &self,
+        path:    &Path,
+        options: &WriteOptions
+    )
+    -> Result<(), std::io::Error>
     {
         let file_type = get_file_type(path)?;
 
         match file_type
         {
             FileExtension::HEIF
-                => heif::file_write_metadata(path, self),
+                => heif::file_write_metadata(path, self, options),
             FileExtension::JPEG 
-                =>  jpg::file_write_metadata(path, self),
-            FileExtension::JXL 
-                =>  jxl::file_write_metadata(path, self),
+                =>  jpg::file_write_metadata(path, self, options),
+            FileExtension::JXL | FileExtension::NAKED_JXL
+                =>  jxl::file_write_metadata(path, self, options),
             FileExtension::PNG { as_zTXt_chunk: _ }
-                =>  png::file_write_metadata(path, self),
+                =>  png::file_write_metadata(path, self, options),
             FileExtension::TIFF
                 => tiff::file::write_metadata(path, self),
             FileExtension::WEBP 
-                => webp::file::write_metadata(path, self),
+                => webp::file::write_metadata(path, self, options),
+            FileExtension::DNG | FileExtension::CR2 | FileExtension::NEF | 
+            FileExtension::ARW | FileExtension::ORF | FileExtension::RW2 | 
+            FileExtension::PEF
+                => raw::file_write_metadata(path, self),
+            FileExtension::CR3
+                => cr3::file_write_metadata(path, self),
             _
                 => return io_error!(
                     Other, 
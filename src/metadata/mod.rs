@@ -6,6 +6,15 @@ pub mod iterator;
 pub mod edit;
 pub mod get;
 pub mod set;
+pub mod xmp_io;
+pub mod iptc_io;
+pub mod icc_io;
+pub mod jpg_io;
+pub mod png_io;
+pub mod heif_io;
+pub mod jxl_io;
+pub mod quicktime_io;
+pub mod write_options;
 
 use std::io::Cursor;
 use std::io::Read;
@@ -25,7 +34,7 @@ pub struct
 Metadata
 {
     endian:                 Endian,
-    image_file_directories: Vec<ImageFileDirectory>
+    image_file_directories: Vec<ImageFileDirectory>,
 }
 
 impl
@@ -47,7 +56,11 @@ Metadata
     ()
     -> Metadata
     {
-        Metadata { endian: Endian::Little, image_file_directories: Vec::new() }
+        Metadata 
+        { 
+            endian:                 Endian::Little, 
+            image_file_directories: Vec::new(),
+        }
     }
 
     /// Creates an IFD in this struct if it does not exist yet.
@@ -78,6 +91,24 @@ Metadata
     }
 
 
+    /// Constructs a new `Metadata` object from the decoded IFDs
+    pub(crate) fn
+    new_from_decoded_ifds
+    (
+        endian:                 Endian,
+        image_file_directories: Vec<ImageFileDirectory>,
+    )
+    -> Metadata
+    {
+        let mut data = Metadata
+        {
+            endian,
+            image_file_directories,
+        };
+        data.sort_data();
+        return data;
+    }
+
     pub(crate) fn
     general_decoding_wrapper
     (
@@ -91,9 +122,7 @@ Metadata
             let     decoding_result   = Self::decode(&mut pre_decode_cursor);
             if let Ok((endian, image_file_directories)) = decoding_result
             {
-                let mut data = Metadata { endian, image_file_directories };
-                data.sort_data();
-                return Ok(data);
+                return Ok(Self::new_from_decoded_ifds(endian, image_file_directories));
             }
             else if let Err(decode_error) = decoding_result
             {
@@ -299,23 +328,61 @@ Metadata
         // Get offset to IFD0
         let mut ifd0_offset_buffer = vec![0u8; 4];
         data_cursor.read_exact(&mut ifd0_offset_buffer)?;
-        let mut ifd_offset_option = Some(from_u8_vec_res_macro!(u32, &ifd0_offset_buffer, &endian)?);
+        let ifd0_offset = from_u8_vec_res_macro!(u32, &ifd0_offset_buffer, &endian)?;
 
         // Decode all the IFDs
+        let ifds = Self::decode_ifds(
+            data_cursor, 
+            data_start_position, 
+            &endian, 
+            ifd0_offset, 
+            true
+        )?;
+
+        return Ok((endian, ifds));
+    }
+
+    /// Decodes the chain of generic IFDs starting with IFD0 at the given 
+    /// offset, together with their SubIFDs. Offsets are relative to the
+    /// given start position of the TIFF data. 
+    pub(crate) fn
+    decode_ifds
+    <T: Read + Seek>
+    (
+        data_cursor:         &mut T,
+        data_start_position: u64,
+        endian:              &Endian,
+        ifd0_offset:         u32,
+        read_strip_data:     bool,
+    )
+    -> Result<Vec<ImageFileDirectory>, std::io::Error>
+    {
+        let mut ifd_offset_option = Some(ifd0_offset);
+        let mut visited_offsets   = Vec::new();
+
         let mut ifds = Vec::new();
         let mut generic_ifd_nr = 0;
         while let Some(ifd_offset) = ifd_offset_option
         {
-            data_cursor.set_position(data_start_position);
+            // Guard against IFDs that link back to an earlier one
+            if visited_offsets.contains(&ifd_offset)
+            {
+                log::warn!("IFD at offset {ifd_offset} was already decoded, stopping here");
+                break;
+            }
+            visited_offsets.push(ifd_offset);
+
+            data_cursor.seek(std::io::SeekFrom::Start(data_start_position))?;
             data_cursor.seek(std::io::SeekFrom::Current(ifd_offset as i64))?;
 
             let decode_result = ImageFileDirectory::decode_ifd(
                 data_cursor,
                 data_start_position,
-                &endian,
+                endian,
                 &ExifTagGroup::GENERIC,
                 generic_ifd_nr,
-                &mut ifds
+                &mut ifds,
+                read_strip_data
             );
 
             ifd_offset_option = decode_result?;
@@ -323,9 +390,7 @@ Metadata
             generic_ifd_nr += 1;
         }
 
-
-
-        return Ok((endian, ifds));
+        return Ok(ifds);
     }
 }
 
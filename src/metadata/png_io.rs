@@ -0,0 +1,249 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::path::Path;
+
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::io_error;
+use crate::general_file_io::modify_file;
+
+use crate::png;
+
+use super::Metadata;
+
+/// The chunk type that is used for storing a textual entry of a PNG
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum
+PngTextFormat
+{
+    /// Uncompressed Latin-1 text (`tEXt`)
+    Text,
+
+    /// Compressed Latin-1 text (`zTXt`)
+    CompressedText,
+
+    /// Uncompressed UTF-8 text with optional language tag (`iTXt`)
+    InternationalText,
+
+    /// Compressed UTF-8 text with optional language tag (`iTXt`)
+    CompressedInternationalText,
+}
+
+/// A textual entry of a PNG, consisting of a keyword and the text. The PNG
+/// specification predefines keywords like `Title`, `Author`, `Description`,
+/// `Copyright`, `Creation Time` or `Software`, but any other keyword of 1-79
+/// Latin-1 characters (without leading, trailing or consecutive spaces) can
+/// be used as well. The language tag and translated keyword are only
+/// supported by the international formats.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+PngTextEntry
+{
+    pub keyword:            String,
+    pub text:               String,
+    pub format:             PngTextFormat,
+    pub language_tag:       String,
+    pub translated_keyword: String,
+}
+
+impl
+PngTextEntry
+{
+    /// Creates a new, uncompressed entry. Text that can be represented in
+    /// Latin-1 gets stored in a `tEXt` chunk, otherwise `iTXt` is used.
+    pub fn
+    new
+    (
+        keyword: &str,
+        text:    &str
+    )
+    -> PngTextEntry
+    {
+        let format = if text.chars().all(|character| (character as u32) <= 0xff)
+        {
+            PngTextFormat::Text
+        }
+        else
+        {
+            PngTextFormat::InternationalText
+        };
+
+        return PngTextEntry {
+            keyword:            keyword.to_string(),
+            text:               text.to_string(),
+            format,
+            language_tag:       String::new(),
+            translated_keyword: String::new(),
+        };
+    }
+
+    /// Creates a new, uncompressed `iTXt` entry with the given language tag
+    /// (e.g. `en` or `de-AT`) and the keyword translated into that language
+    pub fn
+    new_international
+    (
+        keyword:            &str,
+        text:               &str,
+        language_tag:       &str,
+        translated_keyword: &str,
+    )
+    -> PngTextEntry
+    {
+        return PngTextEntry {
+            keyword:            keyword.to_string(),
+            text:               text.to_string(),
+            format:             PngTextFormat::InternationalText,
+            language_tag:       language_tag.to_string(),
+            translated_keyword: translated_keyword.to_string(),
+        };
+    }
+}
+
+/// Checks that the file at the given path is a PNG, as the functions in this
+/// module are specific to this format
+fn
+check_png_path
+(
+    path: &Path
+)
+-> Result<(), std::io::Error>
+{
+    let file_type = get_file_type(path)?;
+    if !matches!(file_type, FileExtension::PNG { as_zTXt_chunk: _ })
+    {
+        return io_error!(Unsupported, format!("Expected PNG file but got {:?}", file_type));
+    }
+    return Ok(());
+}
+
+impl
+Metadata
+{
+    /// Reads all textual entries (`tEXt`, `zTXt` and `iTXt` chunks) of a PNG
+    /// that is stored as a `Vec<u8>`, in the order of the chunks. The chunks
+    /// that store the EXIF and XMP data are not included.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    ///
+    /// let file_data = std::fs::read("image.png").unwrap();
+    /// for entry in Metadata::read_png_text(&file_data).unwrap()
+    /// {
+    ///     println!("{}: {}", entry.keyword, entry.text);
+    /// }
+    /// ```
+    pub fn
+    read_png_text
+    (
+        file_buffer: &[u8]
+    )
+    -> Result<Vec<PngTextEntry>, std::io::Error>
+    {
+        return png::read_text_entries(&file_buffer.to_vec());
+    }
+
+    pub fn
+    file_read_png_text
+    (
+        path: &Path
+    )
+    -> Result<Vec<PngTextEntry>, std::io::Error>
+    {
+        check_png_path(path)?;
+        return png::read_text_entries(&std::fs::read(path)?);
+    }
+
+    /// Gets the first textual entry of a PNG with the given keyword
+    pub fn
+    get_png_text
+    (
+        keyword:     &str,
+        file_buffer: &[u8]
+    )
+    -> Result<Option<PngTextEntry>, std::io::Error>
+    {
+        return Ok(Metadata::read_png_text(file_buffer)?
+            .into_iter()
+            .find(|entry| entry.keyword == keyword));
+    }
+
+    pub fn
+    file_get_png_text
+    (
+        keyword: &str,
+        path:    &Path
+    )
+    -> Result<Option<PngTextEntry>, std::io::Error>
+    {
+        return Ok(Metadata::file_read_png_text(path)?
+            .into_iter()
+            .find(|entry| entry.keyword == keyword));
+    }
+
+    /// Writes the textual entry to a PNG that is stored as a `Vec<u8>`,
+    /// replacing all entries with the same keyword and language tag. Fails
+    /// with `InvalidInput` if the keyword is invalid, the text can't be
+    /// encoded in the requested format or the keyword is used for storing
+    /// the EXIF or XMP data.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::metadata::png_io::PngTextEntry;
+    /// use little_exif::metadata::png_io::PngTextFormat;
+    ///
+    /// let mut file_data = std::fs::read("image.png").unwrap();
+    /// Metadata::write_png_text(&PngTextEntry::new("Title", "Sunset"), &mut file_data).unwrap();
+    ///
+    /// let mut description = PngTextEntry::new("Description", "A long description");
+    /// description.format  = PngTextFormat::CompressedText;
+    /// Metadata::write_png_text(&description, &mut file_data).unwrap();
+    /// ```
+    pub fn
+    write_png_text
+    (
+        entry:       &PngTextEntry,
+        file_buffer: &mut Vec<u8>
+    )
+    -> Result<(), std::io::Error>
+    {
+        return png::write_text_entry(file_buffer, entry);
+    }
+
+    pub fn
+    file_write_png_text
+    (
+        entry: &PngTextEntry,
+        path:  &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_png_path(path)?;
+        return modify_file(path, |file_buffer| png::write_text_entry(file_buffer, entry));
+    }
+
+    /// Removes all textual entries with the given keyword (regardless of
+    /// their language tag) from a PNG that is stored as a `Vec<u8>`
+    pub fn
+    remove_png_text
+    (
+        keyword:     &str,
+        file_buffer: &mut Vec<u8>
+    )
+    -> Result<(), std::io::Error>
+    {
+        return png::remove_text_entries(file_buffer, keyword);
+    }
+
+    pub fn
+    file_remove_png_text
+    (
+        keyword: &str,
+        path:    &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_png_path(path)?;
+        return modify_file(path, |file_buffer| png::remove_text_entries(file_buffer, keyword));
+    }
+}
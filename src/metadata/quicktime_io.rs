@@ -0,0 +1,135 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::path::Path;
+
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::io_error;
+
+use crate::quicktime;
+
+use super::Metadata;
+
+/// The metadata of a QuickTime (MOV) or MP4 file that describes when and
+/// where it was captured. When reading, the fields are `None` if the file
+/// does not provide them. When writing, `None` removes the location, the
+/// creation date and the content identifier, while the creation time is
+/// left unchanged, as `mvhd` always has one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct
+QuickTimeMetadata
+{
+    /// The creation time of the movie in `moov/mvhd`, given in seconds since
+    /// midnight, January 1, 1904 (UTC)
+    pub creation_time:      Option<u64>,
+
+    /// The location in ISO 6709 notation, e.g. `+48.2082+016.3738+171.000/`,
+    /// stored as `com.apple.quicktime.location.ISO6709` item and/or in the
+    /// `©xyz` entry of `moov/udta`
+    pub location:           Option<String>,
+
+    /// The creation date in ISO 8601 notation including the time zone, e.g.
+    /// `2024-08-28T18:42:55+0200`, stored as `com.apple.quicktime.creationdate`
+    /// item and/or in the `©day` entry of `moov/udta`
+    pub creation_date:      Option<String>,
+
+    /// The `com.apple.quicktime.content.identifier` item, which pairs a Live
+    /// Photo video with its still image
+    pub content_identifier: Option<String>,
+}
+
+/// Checks that the file at the given path is a QuickTime or MP4 file, as the
+/// functions in this module are specific to these formats
+fn
+check_quicktime_path
+(
+    path: &Path
+)
+-> Result<(), std::io::Error>
+{
+    let file_type = get_file_type(path)?;
+    if !matches!(file_type, FileExtension::MP4 | FileExtension::MOV)
+    {
+        return io_error!(Unsupported, format!("Expected MP4 or MOV file but got {:?}", file_type));
+    }
+    return Ok(());
+}
+
+impl
+Metadata
+{
+    /// Reads the QuickTime metadata of a MOV or MP4 file that is stored as a
+    /// `Vec<u8>`. If the location or creation date is stored both as item in
+    /// `moov/meta` and in `moov/udta`, the item takes precedence.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    ///
+    /// let file_data = std::fs::read("video.mov").unwrap();
+    /// let metadata  = Metadata::read_quicktime_metadata(&file_data).unwrap();
+    /// println!("{:?}", metadata.location);
+    /// ```
+    pub fn
+    read_quicktime_metadata
+    (
+        file_buffer: &[u8]
+    )
+    -> Result<QuickTimeMetadata, std::io::Error>
+    {
+        return quicktime::read_metadata(file_buffer);
+    }
+
+    pub fn
+    file_read_quicktime_metadata
+    (
+        path: &Path
+    )
+    -> Result<QuickTimeMetadata, std::io::Error>
+    {
+        check_quicktime_path(path)?;
+        return quicktime::file_read_metadata(path);
+    }
+
+    /// Writes the QuickTime metadata to a MOV or MP4 file that is stored as a
+    /// `Vec<u8>`. Existing entries of the location and creation date get
+    /// updated wherever they are stored, new ones are added to `moov/udta`.
+    /// The content identifier is always stored as item in `moov/meta`.
+    /// If `moov` changes its size, the chunk offsets of all tracks get
+    /// adjusted to the moved media data. Fails with `Unsupported` for
+    /// fragmented files in that case, as their `moof` boxes are not updated.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    ///
+    /// let mut file_data = std::fs::read("video.mov").unwrap();
+    /// let mut metadata  = Metadata::read_quicktime_metadata(&file_data).unwrap();
+    /// metadata.location = Some("+48.2082+016.3738/".to_string());
+    /// Metadata::write_quicktime_metadata(&metadata, &mut file_data).unwrap();
+    /// ```
+    pub fn
+    write_quicktime_metadata
+    (
+        metadata:    &QuickTimeMetadata,
+        file_buffer: &mut Vec<u8>
+    )
+    -> Result<(), std::io::Error>
+    {
+        return quicktime::write_metadata(file_buffer, metadata);
+    }
+
+    /// Writes the QuickTime metadata to a MOV or MP4 file. The file only gets
+    /// loaded into memory entirely if media data located after `moov` has to
+    /// be moved.
+    pub fn
+    file_write_quicktime_metadata
+    (
+        metadata: &QuickTimeMetadata,
+        path:     &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        check_quicktime_path(path)?;
+        return quicktime::file_write_metadata(path, metadata);
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use super::jpg_io::OversizeExifPolicy;
+use super::jxl_io::JpegReconstructionPolicy;
+use super::jxl_io::JxlBoxCompression;
+use super::xmp_io::XmpSyncMode;
+
+/// Options that determine how the metadata gets written to a file, e.g. via
+/// `write_to_vec_with_options` or `write_to_file_with_options`. These are
+/// not part of the metadata itself, so they are not affected by reading or
+/// editing a `Metadata` object. Options that don't apply to the format of
+/// the file are ignored.
+/// # Examples
+/// ```no_run
+/// use little_exif::metadata::Metadata;
+/// use little_exif::metadata::write_options::WriteOptions;
+/// use little_exif::metadata::xmp_io::XmpSyncMode;
+///
+/// let path     = std::path::Path::new("image.jpg");
+/// let metadata = Metadata::new_from_path(path).unwrap();
+/// let options  = WriteOptions {
+///     xmp_sync_mode: XmpSyncMode::Update,
+///     ..WriteOptions::default()
+/// };
+/// metadata.write_to_file_with_options(path, &options).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct
+WriteOptions
+{
+    /// How existing XMP data gets synchronized with the EXIF data. Applies
+    /// to JPEG, PNG, HEIF, JXL and WebP. See `XmpSyncMode` for details.
+    pub xmp_sync_mode:              XmpSyncMode,
+
+    /// Whether the density in the JFIF segment of a JPEG gets updated with
+    /// the EXIF tags `XResolution`, `YResolution` and `ResolutionUnit`. If the
+    /// JPEG does not have a JFIF segment, none gets created.
+    pub jfif_density_sync:          bool,
+
+    /// What happens when the EXIF data is too large for a single JPEG APP1
+    /// segment. See `OversizeExifPolicy` for details.
+    pub oversize_exif_policy:       OversizeExifPolicy,
+
+    /// Whether the `Exif` box of a JXL gets compressed using Brotli. If the
+    /// JXL contains XMP data, its `xml ` box gets stored the same way. See
+    /// `JxlBoxCompression` for details.
+    pub jxl_box_compression:        JxlBoxCompression,
+
+    /// Whether a naked JXL codestream, which can't hold any metadata, gets
+    /// wrapped into an ISO BMFF container. This is lossless. If disabled,
+    /// writing to a naked codestream returns an error instead.
+    pub jxl_wrap_codestream:        bool,
+
+    /// What happens when the metadata of a JXL with JPEG reconstruction data
+    /// gets changed. Only applies to writing the EXIF data, see
+    /// `JpegReconstructionPolicy` for details.
+    pub jpeg_reconstruction_policy: JpegReconstructionPolicy,
+}
+
+impl
+Default
+for
+WriteOptions
+{
+    /// The XMP data is not synchronized, the JFIF density is not updated,
+    /// oversize EXIF data results in an error, JXL boxes are uncompressed,
+    /// naked JXL codestreams get wrapped and changing the metadata of a JXL
+    /// with JPEG reconstruction data logs a warning.
+    fn
+    default
+    ()
+    -> Self
+    {
+        return WriteOptions {
+            xmp_sync_mode:              XmpSyncMode::default(),
+            jfif_density_sync:          false,
+            oversize_exif_policy:       OversizeExifPolicy::default(),
+            jxl_box_compression:        JxlBoxCompression::default(),
+            jxl_wrap_codestream:        true,
+            jpeg_reconstruction_policy: JpegReconstructionPolicy::default(),
+        };
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::path::Path;
+
+use crate::filetype::get_file_type;
+use crate::filetype::FileExtension;
+use crate::general_file_io::io_error;
+use crate::general_file_io::modify_file;
+
+use crate::heif;
+use crate::jpg;
+use crate::jxl;
+use crate::png;
+use crate::webp;
+
+use super::Metadata;
+
+/// Determines what happens to the XMP data of an image when writing the EXIF
+/// data via e.g. `write_to_vec_with_options`. Many applications prefer
+/// properties in the XMP data over their EXIF counterparts, so values that 
+/// are only updated in the EXIF data may appear to be unchanged.
+/// Currently supported for JPEG, PNG, HEIF, JXL and WebP. 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum
+XmpSyncMode
+{
+    /// The XMP data is left untouched (default)
+    #[default]
+    Disabled,
+
+    /// Properties in the XMP data that mirror EXIF tags (e.g. `tiff:Make`,
+    /// `exif:DateTimeOriginal` or `dc:description`) get updated with the 
+    /// values of the EXIF tags. If a tag is not present in the EXIF data, 
+    /// its mirrored property is kept, as it may hold data that only exists
+    /// in the XMP data. If the value of a tag can't be represented in XMP,
+    /// its mirrored property gets removed. No new properties get added. 
+    Update,
+
+    /// All properties in the XMP data that mirror EXIF tags get removed,
+    /// including everything in the `tiff:`, `exif:` and `exifEX:` namespaces
+    Remove,
+}
+
+impl
+Metadata
+{
+    /// Reads the raw XMP packet from an image that is stored as a `Vec<u8>`.
+    /// Currently supported for JPEG, PNG, HEIF, JXL and WebP. For JPEGs, extended
+    /// XMP that is spread across multiple APP1 segments gets reassembled and 
+    /// merged into a single packet. For JXLs, `xml ` boxes that are compressed
+    /// in a `brob` box get decompressed.
+    /// # Examples
+    /// ```no_run
+    /// use little_exif::metadata::Metadata;
+    /// use little_exif::filetype::FileExtension;
+    /// 
+    /// let file_data = std::fs::read("image.jpg").unwrap();
+    /// let xmp = Metadata::read_xmp(&file_data, FileExtension::JPEG).unwrap();
+    /// ```
+    #[allow(unreachable_patterns)]
+    pub fn
+    read_xmp
+    (
+        file_buffer: &[u8],
+        file_type:   FileExtension
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        match file_type
+        {
+            FileExtension::HEIF
+                => heif::read_xmp(file_buffer),
+            FileExtension::JPEG 
+                =>  jpg::read_xmp(file_buffer),
+            FileExtension::JXL
+                =>  jxl::read_xmp(file_buffer),
+            FileExtension::PNG { as_zTXt_chunk: _ }
+                =>  png::read_xmp(&file_buffer.to_vec()),
+            FileExtension::WEBP
+                =>  webp::vec::read_xmp(&file_buffer.to_vec()),
+            _
+                => return io_error!(
+                    Other, 
+                    format!(
+                        "Function 'read_xmp' not yet implemented for {:?}", 
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Reads the raw XMP packet from the image at the specified path.
+    #[allow(unreachable_patterns)]
+    pub fn
+    file_read_xmp
+    (
+        path: &Path
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        let file_type = get_file_type(path)?;
+
+        match file_type
+        {
+            FileExtension::HEIF
+                => heif::file_read_xmp(path),
+            FileExtension::JPEG 
+                =>  jpg::file_read_xmp(path),
+            FileExtension::JXL
+                =>  jxl::read_xmp(&std::fs::read(path)?),
+            FileExtension::PNG { as_zTXt_chunk: _ }
+                =>  png::file_read_xmp(path),
+            FileExtension::WEBP
+                =>  webp::vec::read_xmp(&std::fs::read(path)?),
+            _
+                => return io_error!(
+                    Other, 
+                    format!(
+                        "Function 'file_read_xmp' not yet implemented for {:?}", 
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Writes the raw XMP packet to an image stored as a `Vec<u8>`, replacing
+    /// any previously stored XMP data. 
+    /// For JPEGs, packets that are too large for a single APP1 segment get 
+    /// split into standard and extended XMP. For HEIFs, this requires an 
+    /// already existing XMP item. For WebPs, the `XMP ` chunk gets placed 
+    /// after the image data and the EXIF chunk and the flag in the VP8X
+    /// chunk is set, converting the file to the Extended File Format if 
+    /// required. For JXLs, the compression of an existing `xml ` box is kept,
+    /// see `write_jxl_xmp` for choosing it explicitly.
+    #[allow(unreachable_patterns)]
+    pub fn
+    write_xmp
+    (
+        xmp_data:    &[u8],
+        file_buffer: &mut Vec<u8>,
+        file_type:   FileExtension
+    )
+    -> Result<(), std::io::Error>
+    {
+        match file_type
+        {
+            FileExtension::HEIF
+                => heif::write_xmp(file_buffer, xmp_data),
+            FileExtension::JPEG 
+                =>  jpg::write_xmp(file_buffer, xmp_data),
+            FileExtension::JXL
+                =>  jxl::write_xmp(file_buffer, xmp_data),
+            FileExtension::PNG { as_zTXt_chunk: _ }
+                =>  png::write_xmp(file_buffer, xmp_data),
+            FileExtension::WEBP
+                =>  webp::vec::write_xmp(file_buffer, xmp_data),
+            _
+                => return io_error!(
+                    Other, 
+                    format!(
+                        "Function 'write_xmp' not yet implemented for {:?}", 
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Writes the raw XMP packet to the image at the specified path, 
+    /// replacing any previously stored XMP data. 
+    #[allow(unreachable_patterns)]
+    pub fn
+    file_write_xmp
+    (
+        xmp_data: &[u8],
+        path:     &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        let file_type = get_file_type(path)?;
+
+        match file_type
+        {
+            FileExtension::HEIF
+                => heif::file_write_xmp(path, xmp_data),
+            FileExtension::JPEG 
+                =>  jpg::file_write_xmp(path, xmp_data),
+            FileExtension::JXL
+                =>  modify_file(path, |file_buffer| jxl::write_xmp(file_buffer, xmp_data)),
+            FileExtension::PNG { as_zTXt_chunk: _ }
+                =>  png::file_write_xmp(path, xmp_data),
+            FileExtension::WEBP
+                =>  modify_file(path, |file_buffer| webp::vec::write_xmp(file_buffer, xmp_data)),
+            _
+                => return io_error!(
+                    Other, 
+                    format!(
+                        "Function 'file_write_xmp' not yet implemented for {:?}", 
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Removes the XMP data from an image stored as a `Vec<u8>`. Does 
+    /// nothing if the image does not contain any XMP data. 
+    /// Currently supported for JPEG, PNG, JXL and WebP. 
+    #[allow(unreachable_patterns)]
+    pub fn
+    remove_xmp
+    (
+        file_buffer: &mut Vec<u8>,
+        file_type:   FileExtension
+    )
+    -> Result<(), std::io::Error>
+    {
+        match file_type
+        {
+            FileExtension::JPEG 
+                =>  jpg::clear_xmp(file_buffer),
+            FileExtension::JXL
+                =>  jxl::remove_xmp(file_buffer),
+            FileExtension::PNG { as_zTXt_chunk: _ }
+                =>  png::remove_xmp(file_buffer),
+            FileExtension::WEBP
+                =>  webp::vec::remove_xmp(file_buffer),
+            _
+                => return io_error!(
+                    Other, 
+                    format!(
+                        "Function 'remove_xmp' not yet implemented for {:?}", 
+                        file_type
+                    )
+                ),
+        }
+    }
+
+    /// Removes the XMP data from the image at the specified path. 
+    pub fn
+    file_remove_xmp
+    (
+        path: &Path
+    )
+    -> Result<(), std::io::Error>
+    {
+        let file_type = get_file_type(path)?;
+        return modify_file(path, |file_buffer| Self::remove_xmp(file_buffer, file_type));
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! Motion photos, i.e. JPEG images with a short (MP4) video appended after
+//! the end of the image data. Google stores the location of the video in the
+//! XMP data, either via `GCamera:MicroVideoOffset` (older format) or via the
+//! items of the `Container:Directory`. Samsung uses an index at the very end
+//! of the file, the SEFH directory followed by its length and `SEFT`.
+
+use crate::general_file_io::io_error;
+
+const SEFH_IDENTIFIER: &[u8] = b"SEFH";
+const SEFT_IDENTIFIER: &[u8] = b"SEFT";
+
+// Name of the data block in the Samsung trailer that contains the video
+const SAMSUNG_MOTION_PHOTO_NAME: &[u8] = b"MotionPhoto_Data";
+
+// Each entry of the SEFH directory consists of two bytes of padding, the
+// type, the offset of the data block relative to the start of the directory
+// (counting backwards) and the length of the block
+const SEF_ENTRY_LENGTH: usize = 12;
+
+/// The way the location of the video is stored
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum
+MotionPhotoFormat
+{
+    /// `GCamera:MotionPhoto` and an item with the semantic `MotionPhoto` in
+    /// the `Container:Directory` of the XMP data
+    GoogleContainer,
+
+    /// `GCamera:MicroVideo` and `GCamera:MicroVideoOffset` in the XMP data
+    GoogleMicroVideo,
+
+    /// The `MotionPhoto_Data` block of the SEFH/SEFT trailer
+    SamsungTrailer,
+}
+
+/// The location of the video that is embedded in a motion photo
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+MotionPhoto
+{
+    format:   MotionPhotoFormat,
+    position: usize,
+    length:   usize,
+}
+
+impl
+MotionPhoto
+{
+    pub(crate) fn
+    new
+    (
+        format:   MotionPhotoFormat,
+        position: usize,
+        length:   usize,
+    )
+    -> MotionPhoto
+    {
+        return MotionPhoto { format, position, length };
+    }
+
+    pub fn
+    get_format
+    (
+        &self
+    )
+    -> MotionPhotoFormat
+    {
+        return self.format;
+    }
+
+    /// Gets the absolute position of the video in the file
+    pub fn
+    get_position
+    (
+        &self
+    )
+    -> usize
+    {
+        return self.position;
+    }
+
+    /// Gets the length of the video in bytes
+    pub fn
+    get_length
+    (
+        &self
+    )
+    -> usize
+    {
+        return self.length;
+    }
+}
+
+#[derive(Clone, Debug)]
+struct
+SefEntry
+{
+    padding:    u16,
+    entry_type: u16,
+    offset:     u32,
+    length:     u32,
+}
+
+/// The SEFH directory at the end of Samsung JPEGs and the data blocks it
+/// refers to
+#[derive(Clone, Debug)]
+pub(crate) struct
+SamsungTrailer
+{
+    /// Absolute position of the SEFH directory
+    position: usize,
+    version:  u32,
+    entries:  Vec<SefEntry>,
+}
+
+impl
+SamsungTrailer
+{
+    /// Decodes the trailer at the end of the file. Returns `None` if the file
+    /// does not end with `SEFT`.
+    pub(crate) fn
+    decode
+    (
+        file_buffer: &[u8]
+    )
+    -> Result<Option<SamsungTrailer>, std::io::Error>
+    {
+        if !file_buffer.ends_with(SEFT_IDENTIFIER) || file_buffer.len() < 8
+        {
+            return Ok(None);
+        }
+
+        let length_position = file_buffer.len() - 8;
+        let directory_length = u32::from_le_bytes([
+            file_buffer[length_position    ],
+            file_buffer[length_position + 1],
+            file_buffer[length_position + 2],
+            file_buffer[length_position + 3],
+        ]) as usize;
+
+        if directory_length < 12 || directory_length > length_position
+        {
+            return io_error!(InvalidData, "Invalid length of Samsung SEFH directory!");
+        }
+
+        let position  = length_position - directory_length;
+        let directory = &file_buffer[position..length_position];
+
+        if !directory.starts_with(SEFH_IDENTIFIER)
+        {
+            return io_error!(InvalidData, "Samsung trailer does not start with SEFH!");
+        }
+
+        let read_u32 = |data: &[u8], at: usize| u32::from_le_bytes([data[at], data[at+1], data[at+2], data[at+3]]);
+        let read_u16 = |data: &[u8], at: usize| u16::from_le_bytes([data[at], data[at+1]]);
+
+        let version     = read_u32(directory, 4);
+        let entry_count = read_u32(directory, 8) as usize;
+
+        if 12 + entry_count * SEF_ENTRY_LENGTH > directory.len()
+        {
+            return io_error!(InvalidData, "Samsung SEFH directory is too short for its entries!");
+        }
+
+        let mut entries = Vec::new();
+        for index in 0..entry_count
+        {
+            let entry_start = 12 + index * SEF_ENTRY_LENGTH;
+            let entry = SefEntry {
+                padding:    read_u16(directory, entry_start    ),
+                entry_type: read_u16(directory, entry_start + 2),
+                offset:     read_u32(directory, entry_start + 4),
+                length:     read_u32(directory, entry_start + 8),
+            };
+
+            if entry.offset as usize > position || entry.length > entry.offset
+            {
+                return io_error!(InvalidData, "Samsung trailer entry points outside of the file!");
+            }
+
+            entries.push(entry);
+        }
+
+        return Ok(Some(SamsungTrailer { position, version, entries }));
+    }
+
+    fn
+    encode
+    (
+        &self
+    )
+    -> Vec<u8>
+    {
+        let mut encoded = SEFH_IDENTIFIER.to_vec();
+        encoded.extend(self.version.to_le_bytes());
+        encoded.extend((self.entries.len() as u32).to_le_bytes());
+
+        for entry in &self.entries
+        {
+            encoded.extend(entry.padding.to_le_bytes());
+            encoded.extend(entry.entry_type.to_le_bytes());
+            encoded.extend(entry.offset.to_le_bytes());
+            encoded.extend(entry.length.to_le_bytes());
+        }
+
+        let directory_length = encoded.len() as u32;
+        encoded.extend(directory_length.to_le_bytes());
+        encoded.extend(SEFT_IDENTIFIER);
+        return encoded;
+    }
+
+    /// Gets the absolute start and end position of the block of the entry
+    fn
+    get_block_range
+    (
+        &self,
+        index: usize,
+    )
+    -> (usize, usize)
+    {
+        let start = self.position - self.entries[index].offset as usize;
+        return (start, start + self.entries[index].length as usize);
+    }
+
+    /// Each data block starts with the padding, the type, the length of the
+    /// name and the name itself. Returns the length of this header.
+    fn
+    get_block_header_length
+    (
+        &self,
+        file_buffer: &[u8],
+        index:       usize,
+    )
+    -> Option<usize>
+    {
+        let (start, end) = self.get_block_range(index);
+        let name_length  = u32::from_le_bytes(file_buffer.get(start+4..start+8)?.try_into().ok()?) as usize;
+        let header_end   = start + 8 + name_length;
+
+        if header_end > end
+        {
+            return None;
+        }
+        return Some(8 + name_length);
+    }
+
+    fn
+    find_motion_photo_entry
+    (
+        &self,
+        file_buffer: &[u8],
+    )
+    -> Option<(usize, usize)>
+    {
+        return (0..self.entries.len()).find_map(|index| {
+            let header_length = self.get_block_header_length(file_buffer, index)?;
+            let (start, _)    = self.get_block_range(index);
+
+            if &file_buffer[start+8..start+header_length] == SAMSUNG_MOTION_PHOTO_NAME
+            {
+                return Some((index, header_length));
+            }
+            return None;
+        });
+    }
+
+    pub(crate) fn
+    get_motion_photo
+    (
+        &self,
+        file_buffer: &[u8],
+    )
+    -> Option<MotionPhoto>
+    {
+        let (index, header_length) = self.find_motion_photo_entry(file_buffer)?;
+        let (start, end)           = self.get_block_range(index);
+
+        return Some(MotionPhoto::new(
+            MotionPhotoFormat::SamsungTrailer,
+            start + header_length,
+            end - start - header_length
+        ));
+    }
+
+    /// Replaces the video in the `MotionPhoto_Data` block or removes the
+    /// entire block if no video is given. As the offsets are relative to
+    /// the SEFH directory, the offsets of all blocks in front of the changed
+    /// block need to be updated.
+    pub(crate) fn
+    replace_motion_photo
+    (
+        mut self,
+        file_buffer: &mut Vec<u8>,
+        video:       Option<&[u8]>,
+    )
+    -> Result<(), std::io::Error>
+    {
+        let Some((index, header_length)) = self.find_motion_photo_entry(file_buffer) else {
+            return io_error!(NotFound, "No motion photo data in Samsung trailer!");
+        };
+
+        let (start, end) = self.get_block_range(index);
+
+        let new_block = match video
+        {
+            Some(video) => {
+                let mut new_block = file_buffer[start..start+header_length].to_vec();
+                new_block.extend(video);
+                new_block
+            },
+            None => Vec::new(),
+        };
+
+        let delta = new_block.len() as i64 - (end - start) as i64;
+        if u32::try_from(new_block.len()).is_err()
+        {
+            return io_error!(InvalidInput, "Video is too large for Samsung trailer!");
+        }
+
+        file_buffer.splice(start..end, new_block.iter().copied());
+
+        for entry in self.entries.iter_mut()
+        {
+            if self.position - entry.offset as usize <= start
+            {
+                entry.offset = (entry.offset as i64 + delta) as u32;
+            }
+        }
+
+        if video.is_some()
+        {
+            self.entries[index].length = new_block.len() as u32;
+        }
+        else
+        {
+            self.entries.remove(index);
+        }
+
+        let new_position = (self.position as i64 + delta) as usize;
+        file_buffer.truncate(new_position);
+
+        // Without any entries left, the trailer itself gets removed as well
+        if !self.entries.is_empty()
+        {
+            self.position = new_position;
+            file_buffer.extend(self.encode());
+        }
+
+        return Ok(());
+    }
+}
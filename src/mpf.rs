@@ -0,0 +1,409 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! The Multi-Picture Format (MPF) APP2 segment of JPEG files, which indexes
+//! additional images (e.g. large previews, depth maps or gain maps) that are
+//! stored after the end of the primary image. The segment contains a TIFF
+//! structure whose first IFD is the MP Index IFD, with the `MPEntry` tag
+//! describing the individual images. See CIPA DC-007.
+
+use crate::endian::Endian;
+use crate::general_file_io::io_error;
+use crate::u8conversion::*;
+
+/// The identifier at the start of the MPF APP2 segment
+pub(crate) const MPF_IDENTIFIER: &[u8] = b"MPF\0";
+
+// Tag of the MP Index IFD that stores the entries of all images
+const MP_ENTRY_TAG:    u16   = 0xb002;
+
+// Each entry consists of the attribute, the size and the offset (4 bytes
+// each) as well as the two dependent image entry numbers (2 bytes each)
+const MP_ENTRY_LENGTH: usize = 16;
+
+/// The type of an individual image, as stored in the lower 24 bits of the
+/// image attribute
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum
+MpImageType
+{
+    /// E.g. used for depth maps or the gain maps of Ultra HDR images
+    Undefined,
+    BaselinePrimary,
+    LargeThumbnailVga,
+    LargeThumbnailFullHd,
+    MultiFramePanorama,
+    MultiFrameDisparity,
+    MultiFrameMultiAngle,
+    Other(u32),
+}
+
+impl
+MpImageType
+{
+    fn
+    from_u32
+    (
+        type_code: u32
+    )
+    -> MpImageType
+    {
+        match type_code
+        {
+            0x000000 => MpImageType::Undefined,
+            0x030000 => MpImageType::BaselinePrimary,
+            0x010001 => MpImageType::LargeThumbnailVga,
+            0x010002 => MpImageType::LargeThumbnailFullHd,
+            0x020001 => MpImageType::MultiFramePanorama,
+            0x020002 => MpImageType::MultiFrameDisparity,
+            0x020003 => MpImageType::MultiFrameMultiAngle,
+            _        => MpImageType::Other(type_code),
+        }
+    }
+}
+
+/// An entry of the MP Index IFD, describing an individual image
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+MpEntry
+{
+    attribute:        u32,
+    size:             u32,
+    offset:           u32,
+    dependent_images: [u16; 2],
+}
+
+impl
+MpEntry
+{
+    fn
+    decode
+    (
+        data:   &[u8],
+        endian: &Endian
+    )
+    -> Result<MpEntry, std::io::Error>
+    {
+        return Ok(MpEntry {
+            attribute:        from_u8_vec_res_macro!(u32, &data[0..4],   endian)?,
+            size:             from_u8_vec_res_macro!(u32, &data[4..8],   endian)?,
+            offset:           from_u8_vec_res_macro!(u32, &data[8..12],  endian)?,
+            dependent_images: [
+                from_u8_vec_res_macro!(u16, &data[12..14], endian)?,
+                from_u8_vec_res_macro!(u16, &data[14..16], endian)?,
+            ],
+        });
+    }
+
+    fn
+    encode
+    (
+        &self,
+        endian: &Endian
+    )
+    -> Vec<u8>
+    {
+        let mut encoded = Vec::with_capacity(MP_ENTRY_LENGTH);
+        encoded.extend(to_u8_vec_macro!(u32, &self.attribute,           endian));
+        encoded.extend(to_u8_vec_macro!(u32, &self.size,                endian));
+        encoded.extend(to_u8_vec_macro!(u32, &self.offset,              endian));
+        encoded.extend(to_u8_vec_macro!(u16, &self.dependent_images[0], endian));
+        encoded.extend(to_u8_vec_macro!(u16, &self.dependent_images[1], endian));
+        return encoded;
+    }
+
+    pub fn
+    get_image_type
+    (
+        &self
+    )
+    -> MpImageType
+    {
+        return MpImageType::from_u32(self.attribute & 0x00ffffff);
+    }
+
+    /// Gets the raw image attribute, consisting of the flags, the image data
+    /// format and the type code
+    pub fn
+    get_attribute
+    (
+        &self
+    )
+    -> u32
+    {
+        return self.attribute;
+    }
+
+    /// Gets the size of the image in bytes, from its SOI to its EOI marker
+    pub fn
+    get_size
+    (
+        &self
+    )
+    -> u32
+    {
+        return self.size;
+    }
+
+    /// Gets the offset of the image relative to the start of the TIFF header
+    /// in the MPF segment. Zero for the first (primary) image.
+    pub fn
+    get_offset
+    (
+        &self
+    )
+    -> u32
+    {
+        return self.offset;
+    }
+
+    /// Gets the entry numbers (starting at 1) of the dependent images, with
+    /// zero indicating that there is none
+    pub fn
+    get_dependent_images
+    (
+        &self
+    )
+    -> [u16; 2]
+    {
+        return self.dependent_images;
+    }
+
+    pub fn
+    is_dependent_parent
+    (
+        &self
+    )
+    -> bool
+    {
+        return self.attribute & 0x80000000 != 0;
+    }
+
+    pub fn
+    is_dependent_child
+    (
+        &self
+    )
+    -> bool
+    {
+        return self.attribute & 0x40000000 != 0;
+    }
+
+    pub fn
+    is_representative
+    (
+        &self
+    )
+    -> bool
+    {
+        return self.attribute & 0x20000000 != 0;
+    }
+}
+
+/// The decoded MP Index IFD, together with the positions in the JPEG data
+/// that are needed for locating the images and updating the entries
+#[derive(Clone, Debug)]
+pub(crate) struct
+MpIndex
+{
+    /// Absolute position of the TIFF header, which is the origin of offsets
+    pub(crate) tiff_start: usize,
+
+    /// Absolute position of the encoded MP entries
+    entries_position:      usize,
+
+    endian:                Endian,
+    pub(crate) entries:    Vec<MpEntry>,
+}
+
+impl
+MpIndex
+{
+    /// Decodes the MP Index IFD of the MPF segment that spans from `start` to
+    /// `end` (both absolute positions in the JPEG data, `start` pointing to
+    /// the marker prefix)
+    pub(crate) fn
+    decode
+    (
+        file_buffer: &[u8],
+        start:       usize,
+        end:         usize,
+    )
+    -> Result<MpIndex, std::io::Error>
+    {
+        let tiff_start = start + 4 + MPF_IDENTIFIER.len();
+        let tiff_data  = &file_buffer[tiff_start..end];
+
+        if tiff_data.len() < 8
+        {
+            return io_error!(InvalidData, "MPF segment is too short!");
+        }
+
+        let endian = match tiff_data.get(0..4)
+        {
+            Some([0x49, 0x49, 0x2a, 0x00]) => Endian::Little,
+            Some([0x4d, 0x4d, 0x00, 0x2a]) => Endian::Big,
+            _ => return io_error!(InvalidData, "Invalid TIFF header in MPF segment!"),
+        };
+
+        let ifd_offset = from_u8_vec_res_macro!(u32, &tiff_data[4..8], &endian)? as usize;
+        let Some(entry_count_data) = tiff_data.get(ifd_offset..ifd_offset + 2) else {
+            return io_error!(InvalidData, "MP Index IFD exceeds MPF segment!");
+        };
+        let entry_count = from_u8_vec_res_macro!(u16, entry_count_data, &endian)? as usize;
+
+        for index in 0..entry_count
+        {
+            let entry_start = ifd_offset + 2 + 12 * index;
+            let Some(ifd_entry) = tiff_data.get(entry_start..entry_start + 12) else {
+                return io_error!(InvalidData, "MP Index IFD exceeds MPF segment!");
+            };
+
+            if from_u8_vec_res_macro!(u16, &ifd_entry[0..2], &endian)? != MP_ENTRY_TAG
+            {
+                continue;
+            }
+
+            let byte_count     = from_u8_vec_res_macro!(u32, &ifd_entry[4..8],  &endian)? as usize;
+            let entries_offset = from_u8_vec_res_macro!(u32, &ifd_entry[8..12], &endian)? as usize;
+
+            // A single entry already needs more than 4 bytes, so the data is
+            // never stored in the value field itself
+            if byte_count % MP_ENTRY_LENGTH != 0 || byte_count == 0
+            {
+                return io_error!(InvalidData, "Invalid length of MPEntry data!");
+            }
+
+            let Some(entries_data) = tiff_data.get(entries_offset..entries_offset + byte_count) else {
+                return io_error!(InvalidData, "MPEntry data exceeds MPF segment!");
+            };
+
+            let entries = entries_data
+                .chunks(MP_ENTRY_LENGTH)
+                .map(|entry_data| MpEntry::decode(entry_data, &endian))
+                .collect::<Result<Vec<MpEntry>, std::io::Error>>()?;
+
+            return Ok(MpIndex {
+                tiff_start,
+                entries_position: tiff_start + entries_offset,
+                endian,
+                entries,
+            });
+        }
+
+        return io_error!(NotFound, "MPF segment does not contain an MP Index IFD!");
+    }
+
+    /// Overwrites the encoded entries in the JPEG data with the current
+    /// values. As the number of entries can't change, the size stays the same.
+    pub(crate) fn
+    write_entries
+    (
+        &self,
+        file_buffer: &mut [u8],
+    )
+    {
+        for (index, entry) in self.entries.iter().enumerate()
+        {
+            let position = self.entries_position + index * MP_ENTRY_LENGTH;
+            file_buffer[position..position + MP_ENTRY_LENGTH].copy_from_slice(&entry.encode(&self.endian));
+        }
+    }
+
+    /// Determines the absolute start and end position of the image with the
+    /// given index
+    pub(crate) fn
+    get_image_range
+    (
+        &self,
+        index:       usize,
+        file_length: usize,
+    )
+    -> Result<(usize, usize), std::io::Error>
+    {
+        let Some(entry) = self.entries.get(index) else {
+            return io_error!(NotFound, format!("MPF contains no image with index {}!", index));
+        };
+
+        let start = if index == 0 && entry.offset == 0 { 0 } else { self.tiff_start + entry.offset as usize };
+        let end   = start + entry.size as usize;
+
+        if end > file_length
+        {
+            return io_error!(InvalidData, format!("MPF image with index {} exceeds end of data!", index));
+        }
+
+        return Ok((start, end));
+    }
+
+    /// Updates the entries after the segments of the primary image got
+    /// modified, which changed the length of the data by `delta` bytes. All
+    /// other images got moved by `delta` as well, whereas the TIFF header may
+    /// or may not have been moved, depending on where the change took place.
+    /// The given index describes the state prior to the modification.
+    pub(crate) fn
+    update_after_primary_change
+    (
+        &mut self,
+        previous_index: &MpIndex,
+        delta:          i64,
+    )
+    {
+        for (index, (entry, previous_entry)) in self.entries.iter_mut().zip(previous_index.entries.iter()).enumerate()
+        {
+            if index == 0 && previous_entry.offset == 0
+            {
+                entry.size = (previous_entry.size as i64 + delta) as u32;
+                continue;
+            }
+
+            let new_image_start = previous_index.tiff_start as i64 + previous_entry.offset as i64 + delta;
+            entry.offset = (new_image_start - self.tiff_start as i64) as u32;
+        }
+    }
+
+    /// Updates the entries after the image with the given index (which must
+    /// not be the primary image) got replaced by one with the new size, so
+    /// all images stored after it got moved
+    pub(crate) fn
+    update_after_image_change
+    (
+        &mut self,
+        index:    usize,
+        new_size: u32,
+    )
+    {
+        let old_offset = self.entries[index].offset;
+        let delta      = new_size as i64 - self.entries[index].size as i64;
+
+        for (other_index, entry) in self.entries.iter_mut().enumerate()
+        {
+            if other_index != index && entry.offset > old_offset
+            {
+                entry.offset = (entry.offset as i64 + delta) as u32;
+            }
+        }
+
+        self.entries[index].size = new_size;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn
+    image_type_and_flags()
+    {
+        let entry = MpEntry { attribute: 0xa0030000, size: 10, offset: 0, dependent_images: [2, 3] };
+        assert_eq!(entry.get_image_type(), MpImageType::BaselinePrimary);
+        assert!(entry.is_dependent_parent());
+        assert!(!entry.is_dependent_child());
+        assert!(entry.is_representative());
+
+        let encoded = entry.encode(&Endian::Big);
+        assert_eq!(MpEntry::decode(&encoded, &Endian::Big).unwrap(), entry);
+    }
+}
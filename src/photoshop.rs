@@ -0,0 +1,398 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! Photoshop Image Resource Blocks (IRB), as stored in the APP13 segment of
+//! JPEG files (following the `Photoshop 3.0` header) or in the TIFF tag
+//! 0x8649. Each resource consists of a signature (usually `8BIM`), a 16 bit
+//! ID, a padded Pascal string as name and the (padded) resource data.
+
+use std::io::Cursor;
+use std::io::Read;
+
+use crate::endian::Endian;
+use crate::general_file_io::io_error;
+use crate::iptc::IptcData;
+use crate::u8conversion::*;
+use crate::util::read_be_u16;
+use crate::util::read_be_u32;
+
+/// The header in front of the resources in a JPEG APP13 segment
+pub(crate) const PHOTOSHOP_HEADER: &[u8] = b"Photoshop 3.0\0";
+
+/// The default signature of an image resource
+pub const IRB_SIGNATURE: [u8; 4] = *b"8BIM";
+
+/// Resource ID of the IPTC-IIM data
+pub const IPTC_NAA_RESOURCE_ID:    u16 = 0x0404;
+
+/// Resource ID of the MD5 digest of the IPTC-IIM data. Photoshop uses this
+/// to detect whether the IPTC data has been modified by another application
+pub const IPTC_DIGEST_RESOURCE_ID: u16 = 0x0425;
+
+/// Other signatures that are known to be used for image resources
+const KNOWN_SIGNATURES: [[u8; 4]; 5] = [
+    IRB_SIGNATURE,
+    *b"PHUT",
+    *b"AgHg",
+    *b"DCSR",
+    *b"MeSa",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct
+ImageResource
+{
+    signature: [u8; 4],
+    id:        u16,
+    name:      Vec<u8>,
+    data:      Vec<u8>,
+}
+
+impl
+ImageResource
+{
+    /// Creates a new `8BIM` resource with an empty name
+    pub fn
+    new
+    (
+        id:   u16,
+        data: Vec<u8>
+    )
+    -> ImageResource
+    {
+        return ImageResource {
+            signature: IRB_SIGNATURE,
+            id,
+            name:      Vec::new(),
+            data
+        };
+    }
+
+    pub fn
+    get_signature
+    (
+        &self
+    )
+    -> [u8; 4]
+    {
+        return self.signature;
+    }
+
+    pub fn
+    get_id
+    (
+        &self
+    )
+    -> u16
+    {
+        return self.id;
+    }
+
+    /// Gets the raw bytes of the name of the resource, which is usually empty
+    pub fn
+    get_name
+    (
+        &self
+    )
+    -> &[u8]
+    {
+        return &self.name;
+    }
+
+    pub fn
+    get_data
+    (
+        &self
+    )
+    -> &[u8]
+    {
+        return &self.data;
+    }
+
+    pub fn
+    set_data
+    (
+        &mut self,
+        data: Vec<u8>
+    )
+    {
+        self.data = data;
+    }
+
+    fn
+    encode
+    (
+        &self,
+        encoded: &mut Vec<u8>
+    )
+    -> Result<(), std::io::Error>
+    {
+        if self.name.len() > 255
+        {
+            return io_error!(InvalidInput, "Name of image resource is too long!");
+        }
+
+        if self.data.len() > u32::MAX as usize
+        {
+            return io_error!(InvalidInput, "Data of image resource is too long!");
+        }
+
+        encoded.extend(self.signature.iter());
+        encoded.extend(to_u8_vec_macro!(u16, &self.id, &Endian::Big));
+
+        // Pascal string, padded to an even length (including the length byte)
+        encoded.push(self.name.len() as u8);
+        encoded.extend(self.name.iter());
+        if self.name.len() % 2 == 0
+        {
+            encoded.push(0x00);
+        }
+
+        // Resource data, padded to an even length
+        encoded.extend(to_u8_vec_macro!(u32, &(self.data.len() as u32), &Endian::Big));
+        encoded.extend(self.data.iter());
+        if self.data.len() % 2 == 1
+        {
+            encoded.push(0x00);
+        }
+
+        return Ok(());
+    }
+}
+
+/// A list of Photoshop image resources, kept in their original order
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct
+ImageResources
+{
+    resources: Vec<ImageResource>,
+}
+
+impl
+ImageResources
+{
+    pub fn
+    new
+    ()
+    -> ImageResources
+    {
+        return ImageResources { resources: Vec::new() };
+    }
+
+    /// Decodes the image resources from the given data, which must *not*
+    /// start with the `Photoshop 3.0` header of the JPEG APP13 segment
+    pub fn
+    decode
+    (
+        data: &[u8]
+    )
+    -> Result<ImageResources, std::io::Error>
+    {
+        let mut resources = Vec::new();
+        let mut cursor    = Cursor::new(data);
+
+        // Some writers pad the end of the data with zeros, so stop as soon as
+        // there aren't enough bytes left for another resource header
+        while (cursor.position() as usize) + 12 <= data.len()
+        {
+            let mut signature = [0u8; 4];
+            cursor.read_exact(&mut signature)?;
+
+            if !KNOWN_SIGNATURES.contains(&signature)
+            {
+                if signature == [0u8; 4]
+                {
+                    break;
+                }
+
+                return io_error!(
+                    InvalidData,
+                    format!("Unknown image resource signature: {:02x?}", signature)
+                );
+            }
+
+            let id = read_be_u16(&mut cursor)?;
+
+            let mut name_length = [0u8; 1];
+            cursor.read_exact(&mut name_length)?;
+
+            let mut name = vec![0u8; name_length[0] as usize];
+            cursor.read_exact(&mut name)?;
+            if name.len() % 2 == 0
+            {
+                cursor.set_position(cursor.position() + 1);
+            }
+
+            let data_length = read_be_u32(&mut cursor)? as usize;
+            let data_start  = cursor.position() as usize;
+
+            if data_start + data_length > data.len()
+            {
+                return io_error!(
+                    UnexpectedEof,
+                    format!("Image resource 0x{:04x} exceeds end of data!", id)
+                );
+            }
+
+            resources.push(ImageResource {
+                signature,
+                id,
+                name,
+                data: data[data_start..data_start + data_length].to_vec()
+            });
+
+            cursor.set_position((data_start + data_length + data_length % 2) as u64);
+        }
+
+        return Ok(ImageResources { resources });
+    }
+
+    /// Encodes the image resources, without the `Photoshop 3.0` header
+    pub fn
+    encode
+    (
+        &self
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        let mut encoded = Vec::new();
+        for resource in &self.resources
+        {
+            resource.encode(&mut encoded)?;
+        }
+        return Ok(encoded);
+    }
+
+    pub fn
+    is_empty
+    (
+        &self
+    )
+    -> bool
+    {
+        return self.resources.is_empty();
+    }
+
+    pub fn
+    iter
+    (
+        &self
+    )
+    -> std::slice::Iter<'_, ImageResource>
+    {
+        return self.resources.iter();
+    }
+
+    /// Gets the first resource with the given ID
+    pub fn
+    get_resource
+    (
+        &self,
+        id: u16
+    )
+    -> Option<&ImageResource>
+    {
+        return self.resources.iter().find(|resource| resource.id == id);
+    }
+
+    /// Replaces the data of the first resource with the given ID or appends
+    /// a new `8BIM` resource if no such resource exists yet
+    pub fn
+    set_resource
+    (
+        &mut self,
+        id:   u16,
+        data: Vec<u8>
+    )
+    {
+        if let Some(resource) = self.resources.iter_mut().find(|resource| resource.id == id)
+        {
+            resource.data = data;
+            return;
+        }
+
+        self.resources.push(ImageResource::new(id, data));
+    }
+
+    /// Removes all resources with the given ID while keeping all others.
+    /// Returns whether anything got removed.
+    pub fn
+    remove_resource
+    (
+        &mut self,
+        id: u16
+    )
+    -> bool
+    {
+        let old_length = self.resources.len();
+        self.resources.retain(|resource| resource.id != id);
+        return old_length != self.resources.len();
+    }
+
+    /// Decodes the IPTC-IIM data stored in resource 0x0404
+    pub fn
+    get_iptc
+    (
+        &self
+    )
+    -> Result<IptcData, std::io::Error>
+    {
+        match self.get_resource(IPTC_NAA_RESOURCE_ID)
+        {
+            Some(resource) => return IptcData::decode(&resource.data),
+            None           => return io_error!(NotFound, "No IPTC data found!"),
+        }
+    }
+
+    /// Stores the given IPTC-IIM data in resource 0x0404. The IPTC digest in
+    /// resource 0x0425 gets updated accordingly, as otherwise Photoshop
+    /// would consider the IPTC data to be outdated.
+    pub fn
+    set_iptc
+    (
+        &mut self,
+        iptc: &IptcData
+    )
+    -> Result<(), std::io::Error>
+    {
+        let encoded = iptc.encode()?;
+        let digest  = crate::util::md5(&encoded).to_vec();
+
+        self.set_resource(IPTC_NAA_RESOURCE_ID,    encoded);
+        self.set_resource(IPTC_DIGEST_RESOURCE_ID, digest);
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn
+    encode_decode_round_trip()
+    -> Result<(), std::io::Error>
+    {
+        let data = vec![
+            0x38, 0x42, 0x49, 0x4d, 0x04, 0x04, 0x00, 0x00,                     // 8BIM, 0x0404, empty name + padding
+            0x00, 0x00, 0x00, 0x03, 0x1c, 0x02, 0x00, 0x00,                     // 3 bytes of data + padding
+            0x38, 0x42, 0x49, 0x4d, 0x04, 0x25, 0x01, 0x41,                     // 8BIM, 0x0425, name "A"
+            0x00, 0x00, 0x00, 0x02, 0xab, 0xcd,                                 // 2 bytes of data
+        ];
+
+        let resources = ImageResources::decode(&data)?;
+        assert_eq!(resources.iter().count(), 2);
+        assert_eq!(resources.get_resource(0x0404).unwrap().get_data(), &[0x1c, 0x02, 0x00]);
+        assert_eq!(resources.get_resource(0x0425).unwrap().get_name(), b"A");
+        assert_eq!(resources.encode()?, data);
+
+        let mut resources = resources;
+        assert!(resources.remove_resource(IPTC_DIGEST_RESOURCE_ID));
+        assert!(!resources.remove_resource(IPTC_DIGEST_RESOURCE_ID));
+        assert_eq!(resources.encode()?, data[..16]);
+
+        Ok(())
+    }
+}
@@ -112,6 +112,12 @@ build_png_chunk_type_enum![
     (pHYs,  false,      false,      BEFORE_IDAT),
     (sPLT,  false,      true,       BEFORE_IDAT),
 
+    // APNG chunks, the first fcTL may precede the IDAT chunk(s) if the 
+    // default image is part of the animation
+    (acTL,  false,      false,      BEFORE_IDAT),
+    (fcTL,  false,      true,       NONE),
+    (fdAT,  false,      true,       NONE),
+
     (eXIf,  false,      false,      NONE), // not sure if ordering is correct
     (tIME,  false,      false,      NONE),
     (iTXt,  false,      true,       NONE),
@@ -18,8 +18,13 @@ use std::path::Path;
 use crc::Crc;
 use crc::CRC_32_ISO_HDLC;
 use miniz_oxide::deflate::compress_to_vec_zlib;
+use miniz_oxide::inflate::decompress_to_vec_zlib;
 use text::construct_similar_with_new_data;
+use text::decode_text_chunk;
+use text::encode_keyword;
+use text::encode_text_chunk;
 use text::get_data_from_text_chunk;
+use text::get_raw_keyword;
 
 use crate::general_file_io::io_error;
 use crate::general_file_io::open_read_file;
@@ -29,15 +34,18 @@ use crate::general_file_io::BIG_ENDIAN_INFO;
 use crate::general_file_io::NEWLINE;
 use crate::general_file_io::SPACE;
 use crate::metadata::Metadata;
+use crate::metadata::png_io::PngTextEntry;
+use crate::metadata::write_options::WriteOptions;
+use crate::metadata::xmp_io::XmpSyncMode;
 
 use crate::png::chunk::PngChunk;
 use crate::png::read::read_chunk_length;
 use crate::png::read::read_chunk_name;
 use crate::png::read::read_chunk_data;
 use crate::png::read::read_chunk_crc;
-use crate::png::text::get_keyword_from_text_chunk;
 
 use crate::xmp::remove_exif_from_xmp;
+use crate::xmp::sync_xmp_of_image;
 use crate::util::range_remove;
 
 pub(crate) const PNG_SIGNATURE: [u8; 8] = [0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a];
@@ -309,17 +317,7 @@ generic_read_metadata
                 )?;
 
                 // Check that this chunk contains raw profile EXIF data
-                let keyword = get_keyword_from_text_chunk(&chunk_data);
-                let mut has_raw_profile_type_exif = false;
-                if keyword.len() == RAW_PROFILE_TYPE_EXIF.len()
-                {
-                    has_raw_profile_type_exif = keyword
-                        .bytes()
-                        .zip(RAW_PROFILE_TYPE_EXIF.iter())
-                        .all(|(a,b)| a == *b);
-                }
-
-                if !has_raw_profile_type_exif
+                if get_raw_keyword(&chunk_data) != RAW_PROFILE_TYPE_EXIF
                 {
                     // Skip CRC from current (wrong) chunk and continue
                     cursor.seek(std::io::SeekFrom::Current(4))?;
@@ -378,13 +376,27 @@ file_clear_metadata
 
 // Clears existing metadata chunk from a png file
 // Gets called before writing any new metadata
-#[allow(non_snake_case)]
 pub(crate) fn
 clear_metadata
 (
     file_buffer: &mut Vec<u8>
 )
 -> Result<(), std::io::Error>
+{
+    return clear_metadata_chunks(file_buffer, true);
+}
+
+// Clears existing metadata chunks from a png file. The `exif:` properties
+// of the XMP data only get removed if requested, as updating the XMP data
+// in sync with the new EXIF data needs them to be kept
+#[allow(non_snake_case)]
+fn
+clear_metadata_chunks
+(
+    file_buffer:         &mut Vec<u8>,
+    clear_exif_from_xmp: bool
+)
+-> Result<(), std::io::Error>
 {
     // Parse the PNG - if this fails, the clear operation fails as well
     let parse_png_result = vec_parse_png(file_buffer)?;
@@ -419,29 +431,13 @@ clear_metadata
                     chunk.length() as usize
                 )?;
 
-                let keyword = get_keyword_from_text_chunk(&chunk_data);
-
-                // Compare to the "Raw profile type exif" string constant
-                let mut has_raw_profile_type_exif = false;
-                if keyword.len() == RAW_PROFILE_TYPE_EXIF.len()
-                {
-                    has_raw_profile_type_exif = keyword
-                        .bytes()
-                        .zip(RAW_PROFILE_TYPE_EXIF.iter())
-                        .all(|(a,b)| a == *b);
-                }
-
-                // Compare to the "XML:com.adobe.xmp" string constant
-                let mut has_xml_com_adobe_xmp = false;
-                if keyword.len() == XML_COM_ADOBE_XMP.len()
-                {
-                    has_xml_com_adobe_xmp = keyword
-                        .bytes()
-                        .zip(XML_COM_ADOBE_XMP.iter())
-                        .all(|(a,b)| a == *b);
-                }
+                // Compare to the "Raw profile type exif" and 
+                // "XML:com.adobe.xmp" string constants
+                let keyword                   = get_raw_keyword(&chunk_data);
+                let has_raw_profile_type_exif = keyword == RAW_PROFILE_TYPE_EXIF;
+                let has_xml_com_adobe_xmp     = keyword == XML_COM_ADOBE_XMP;
 
-                if has_xml_com_adobe_xmp
+                if has_xml_com_adobe_xmp && clear_exif_from_xmp
                 {
                     // Don't fully remove the chunk, only remove EXIF from XMP
                     // To do that, reposition the cursor to the start of the 
@@ -557,27 +553,47 @@ pub(crate) fn
 write_metadata
 (
     file_buffer: &mut Vec<u8>,
-    metadata:    &Metadata
+    metadata:    &Metadata,
+    options:     &WriteOptions
 )
 -> Result<(), std::io::Error>
 {
     // First clear the existing metadata
     // This also parses the PNG and checks its validity, so it is safe to
     // assume that is, in fact, a usable PNG file
-    clear_metadata(file_buffer)?;
+    // The `exif:` properties of the XMP data are kept when updating them
+    clear_metadata_chunks(
+        file_buffer, 
+        options.xmp_sync_mode != XmpSyncMode::Update
+    )?;
 
     // Parsed PNG is Ok to use - Create a cursor for writing
-    let mut cursor = Cursor::new(file_buffer);
+    let mut cursor = Cursor::new(&mut *file_buffer);
 
     // Call the generic write function
-    return generic_write_metadata(&mut cursor, metadata);
+    generic_write_metadata(&mut cursor, metadata)?;
+
+    // Keep the XMP data in sync with the new EXIF data, if requested
+    if options.xmp_sync_mode != XmpSyncMode::Disabled
+    {
+        sync_xmp_of_image(
+            file_buffer, 
+            metadata, 
+            options.xmp_sync_mode, 
+            &read_xmp, 
+            &write_xmp
+        )?;
+    }
+
+    return Ok(());
 }
 
 pub(crate) fn
 file_write_metadata
 (
     path:     &Path,
-    metadata: &Metadata
+    metadata: &Metadata,
+    options:  &WriteOptions
 )
 -> Result<(), std::io::Error>
 {
@@ -588,7 +604,7 @@ file_write_metadata
     let mut file_buffer: Vec<u8> = std::fs::read(path)?;
 
     // Clear old metadata and write new to buffer
-    write_metadata(&mut file_buffer, metadata)?;
+    write_metadata(&mut file_buffer, metadata, options)?;
 
     // Write the file
     // Possible to optimize further by returning the purged bytestream itself?
@@ -601,6 +617,477 @@ file_write_metadata
     return Ok(());
 }
 
+/// Determines the position where new metadata chunks (EXIF, XMP or other
+/// text chunks) get inserted: Right before the first chunk of the image 
+/// data, which is either IDAT or - for APNGs where the default image is part
+/// of the animation - the first fcTL chunk. This way, the chunks that must
+/// precede PLTE or IDAT stay in front and the animation chunks following the
+/// new chunk are moved as a whole without being modified.
+fn
+get_metadata_insert_position
+(
+    chunks: &[PngChunk]
+)
+-> u64
+{
+    let mut position = PNG_SIGNATURE.len() as u64;
+
+    for chunk in chunks
+    {
+        if let PngChunk::IDAT(_) | PngChunk::fcTL(_) | PngChunk::fdAT(_) | PngChunk::IEND(_) = chunk
+        {
+            break;
+        }
+
+        // Length, type, data and CRC
+        position += 12 + chunk.length() as u64;
+    }
+
+    return position;
+}
+
+/// Locates all text chunks, i.e. tEXt, zTXt and iTXt. Returns the position
+/// of each chunk, its name and its data field.
+fn
+find_text_chunks
+(
+    file_buffer: &Vec<u8>
+)
+-> Result<Vec<(u64, String, Vec<u8>)>, std::io::Error>
+{
+    let parse_png_result = vec_parse_png(file_buffer)?;
+    let mut cursor       = check_signature(file_buffer)?;
+    let mut text_chunks  = Vec::new();
+
+    for chunk in &parse_png_result
+    {
+        let chunk_start = cursor.position();
+
+        if let "iTXt" | "zTXt" | "tEXt" = chunk.as_string().as_str()
+        {
+            // Skip chunk length and type (4+4 Bytes)
+            cursor.seek(SeekFrom::Current(4+4))?;
+
+            let chunk_data = read_chunk_data(
+                &mut cursor, 
+                chunk.length() as usize
+            )?;
+
+            text_chunks.push((chunk_start, chunk.as_string(), chunk_data));
+
+            // Skip the CRC
+            cursor.seek(SeekFrom::Current(4))?;
+            continue;
+        }
+
+        cursor.seek(SeekFrom::Current(12 + chunk.length() as i64))?;
+    }
+
+    return Ok(text_chunks);
+}
+
+/// Locates the text chunk that stores the XMP data, i.e. the one with the 
+/// "XML:com.adobe.xmp" keyword. Returns the position of the chunk, its name
+/// and its data field.
+fn
+find_xmp_chunk
+(
+    file_buffer: &Vec<u8>
+)
+-> Result<Option<(u64, String, Vec<u8>)>, std::io::Error>
+{
+    return Ok(find_text_chunks(file_buffer)?
+        .into_iter()
+        .find(|(_, _, chunk_data)| get_raw_keyword(chunk_data) == XML_COM_ADOBE_XMP));
+}
+
+pub(crate) fn
+read_xmp
+(
+    file_buffer: &Vec<u8>
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    match find_xmp_chunk(file_buffer)?
+    {
+        Some((_, chunk_name, chunk_data)) 
+            => get_data_from_text_chunk(&chunk_name, &chunk_data),
+        None 
+            => io_error!(Other, "No XMP data found!"),
+    }
+}
+
+pub(crate) fn
+file_read_xmp
+(
+    path: &Path
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    file_check_signature(path)?;
+    return read_xmp(&std::fs::read(path)?);
+}
+
+/// Writes the XMP data to the PNG, replacing the data of an already existing
+/// XMP text chunk. If there is none, a new, uncompressed iTXt chunk gets 
+/// inserted right before the image data, see `get_metadata_insert_position`.
+#[allow(non_snake_case)]
+pub(crate) fn
+write_xmp
+(
+    file_buffer: &mut Vec<u8>,
+    xmp_data:    &[u8],
+)
+-> Result<(), std::io::Error>
+{
+    let (insert_position, chunk_name, new_chunk_data) = match find_xmp_chunk(file_buffer)?
+    {
+        Some((chunk_start, chunk_name, chunk_data)) => {
+            let new_chunk_data = construct_similar_with_new_data(
+                &chunk_name, 
+                &chunk_data, 
+                xmp_data
+            )?;
+
+            // Remove the old chunk, the new one gets written at its position
+            let mut cursor = Cursor::new(&mut *file_buffer);
+            cursor.set_position(chunk_start);
+            remove_chunk_at(&mut cursor)?;
+
+            (chunk_start, chunk_name, new_chunk_data)
+        },
+
+        None => {
+            // Keyword, NUL, compression flag & method, empty language tag and
+            // translated keyword, each followed by a NUL, and the XMP data
+            let mut iTXt_chunk_data = XML_COM_ADOBE_XMP.to_vec();
+            iTXt_chunk_data.extend([0x00, 0x00, 0x00, 0x00, 0x00]);
+            iTXt_chunk_data.extend(xmp_data);
+
+            let insert_position = get_metadata_insert_position(&vec_parse_png(file_buffer)?);
+
+            (insert_position, "iTXt".to_string(), iTXt_chunk_data)
+        }
+    };
+
+    let mut cursor = Cursor::new(file_buffer);
+    cursor.set_position(insert_position);
+    return write_chunk(&mut cursor, &chunk_name, &new_chunk_data);
+}
+
+pub(crate) fn
+file_write_xmp
+(
+    path:     &Path,
+    xmp_data: &[u8],
+)
+-> Result<(), std::io::Error>
+{
+    let mut file_buffer: Vec<u8> = std::fs::read(path)?;
+
+    write_xmp(&mut file_buffer, xmp_data)?;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(path)?;
+    file.write_all(&file_buffer)?;
+
+    return Ok(());
+}
+
+/// Removes all text chunks that store XMP data
+pub(crate) fn
+remove_xmp
+(
+    file_buffer: &mut Vec<u8>,
+)
+-> Result<(), std::io::Error>
+{
+    for (chunk_start, _, chunk_data) in find_text_chunks(file_buffer)?.into_iter().rev()
+    {
+        if get_raw_keyword(&chunk_data) == XML_COM_ADOBE_XMP
+        {
+            let mut cursor = Cursor::new(&mut *file_buffer);
+            cursor.set_position(chunk_start);
+            remove_chunk_at(&mut cursor)?;
+        }
+    }
+
+    return Ok(());
+}
+
+/// Locates the first chunk with the given name. Returns the position of the
+/// chunk and its data field.
+fn
+find_chunk
+(
+    file_buffer: &Vec<u8>,
+    chunk_name:  &str,
+)
+-> Result<Option<(u64, Vec<u8>)>, std::io::Error>
+{
+    let parse_png_result = vec_parse_png(file_buffer)?;
+    let mut cursor       = check_signature(file_buffer)?;
+
+    for chunk in &parse_png_result
+    {
+        if chunk.as_string() == chunk_name
+        {
+            let chunk_start = cursor.position();
+
+            // Skip chunk length and type (4+4 Bytes)
+            cursor.seek(SeekFrom::Current(4+4))?;
+
+            let chunk_data = read_chunk_data(
+                &mut cursor, 
+                chunk.length() as usize
+            )?;
+
+            return Ok(Some((chunk_start, chunk_data)));
+        }
+
+        cursor.seek(SeekFrom::Current(12 + chunk.length() as i64))?;
+    }
+
+    return Ok(None);
+}
+
+/// Removes all chunks with the given name
+fn
+remove_chunks
+(
+    file_buffer: &mut Vec<u8>,
+    chunk_name:  &str,
+)
+-> Result<(), std::io::Error>
+{
+    while let Some((chunk_start, _)) = find_chunk(file_buffer, chunk_name)?
+    {
+        let mut cursor = Cursor::new(&mut *file_buffer);
+        cursor.set_position(chunk_start);
+        remove_chunk_at(&mut cursor)?;
+    }
+
+    return Ok(());
+}
+
+/// Reads the ICC profile from the iCCP chunk, which consists of the profile
+/// name, a NUL separator, the compression method and the zlib compressed
+/// profile.
+#[allow(non_snake_case)]
+pub(crate) fn
+read_icc_profile
+(
+    file_buffer: &Vec<u8>
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let Some((_, iCCP_chunk_data)) = find_chunk(file_buffer, "iCCP")? else {
+        return io_error!(NotFound, "No ICC profile found!");
+    };
+
+    let Some(name_end) = iCCP_chunk_data.iter().position(|byte| *byte == 0x00) else {
+        return io_error!(InvalidData, "iCCP chunk is missing NUL separator after profile name!");
+    };
+
+    // Skip the NUL separator and the compression method (0 = deflate)
+    let Some(compressed_profile) = iCCP_chunk_data.get(name_end + 2..) else {
+        return io_error!(InvalidData, "iCCP chunk is too short!");
+    };
+
+    match decompress_to_vec_zlib(compressed_profile)
+    {
+        Ok(profile) => return Ok(profile),
+        Err(_)      => return io_error!(InvalidData, "Could not inflate ICC profile of iCCP chunk!"),
+    }
+}
+
+/// Writes the ICC profile as iCCP chunk right after the IHDR chunk, 
+/// replacing a previously stored profile (but keeping its name). As the 
+/// iCCP and sRGB chunks are mutually exclusive, an sRGB chunk gets removed.
+#[allow(non_snake_case)]
+pub(crate) fn
+write_icc_profile
+(
+    file_buffer: &mut Vec<u8>,
+    icc_profile: &[u8],
+)
+-> Result<(), std::io::Error>
+{
+    let profile_name = match find_chunk(file_buffer, "iCCP")?
+    {
+        Some((_, iCCP_chunk_data)) => iCCP_chunk_data
+            .iter()
+            .take_while(|byte| **byte != 0x00)
+            .copied()
+            .collect::<Vec<u8>>(),
+        None => b"ICC profile".to_vec(),
+    };
+
+    remove_chunks(file_buffer, "iCCP")?;
+    remove_chunks(file_buffer, "sRGB")?;
+
+    // Profile name, NUL separator, compression method & compressed profile
+    let mut iCCP_chunk_data = profile_name;
+    iCCP_chunk_data.extend([0x00, 0x00]);
+    iCCP_chunk_data.extend(compress_to_vec_zlib(icc_profile, 8));
+
+    // Insert after the IHDR chunk (length, type, data and CRC)
+    let IHDR_length = vec_parse_png(file_buffer)?[0].length();
+    let insert_position = PNG_SIGNATURE.len() as u64 
+        + IHDR_length as u64 
+        + 12;
+
+    let mut cursor = Cursor::new(file_buffer);
+    cursor.set_position(insert_position);
+    return write_chunk(&mut cursor, "iCCP", &iCCP_chunk_data);
+}
+
+pub(crate) fn
+remove_icc_profile
+(
+    file_buffer: &mut Vec<u8>,
+)
+-> Result<(), std::io::Error>
+{
+    return remove_chunks(file_buffer, "iCCP");
+}
+
+/// Checks if the keyword is one of those that are used for storing the EXIF
+/// and XMP data, as these chunks are handled separately
+fn
+is_metadata_keyword
+(
+    keyword: &[u8]
+)
+-> bool
+{
+    return keyword == RAW_PROFILE_TYPE_EXIF || keyword == XML_COM_ADOBE_XMP;
+}
+
+/// Reads all text chunks except the ones storing the EXIF and XMP data.
+/// Chunks that can't be decoded are skipped with a warning.
+pub(crate) fn
+read_text_entries
+(
+    file_buffer: &Vec<u8>
+)
+-> Result<Vec<PngTextEntry>, std::io::Error>
+{
+    let mut entries = Vec::new();
+
+    for (_, chunk_name, chunk_data) in find_text_chunks(file_buffer)?
+    {
+        if is_metadata_keyword(get_raw_keyword(&chunk_data))
+        {
+            continue;
+        }
+
+        match decode_text_chunk(&chunk_name, &chunk_data)
+        {
+            Ok(entry) => entries.push(entry),
+            Err(e)    => log::warn!("Skipping undecodable {chunk_name} chunk: {e}"),
+        }
+    }
+
+    return Ok(entries);
+}
+
+/// Writes the entry as text chunk, replacing all text chunks with the same
+/// keyword and language tag. The new chunk takes the position of the first
+/// replaced chunk or gets inserted right before the image data, see 
+/// `get_metadata_insert_position`.
+#[allow(non_snake_case)]
+pub(crate) fn
+write_text_entry
+(
+    file_buffer: &mut Vec<u8>,
+    entry:       &PngTextEntry,
+)
+-> Result<(), std::io::Error>
+{
+    let (chunk_name, chunk_data) = encode_text_chunk(entry)?;
+    let keyword = get_raw_keyword(&chunk_data);
+
+    if is_metadata_keyword(keyword)
+    {
+        return io_error!(InvalidInput, "Keyword is reserved for EXIF or XMP data!");
+    }
+
+    let mut insert_position = None;
+
+    // Going backwards keeps the positions of the remaining chunks valid
+    for (chunk_start, old_chunk_name, old_chunk_data) in find_text_chunks(file_buffer)?.into_iter().rev()
+    {
+        if get_raw_keyword(&old_chunk_data) != keyword
+        {
+            continue;
+        }
+
+        // Entries in other languages are kept, as well as chunks that can't
+        // be decoded and therefore can't be compared
+        match decode_text_chunk(&old_chunk_name, &old_chunk_data)
+        {
+            Ok(old_entry) => {
+                if !old_entry.language_tag.eq_ignore_ascii_case(&entry.language_tag)
+                {
+                    continue;
+                }
+            },
+            Err(e) => {
+                log::warn!("Keeping undecodable {old_chunk_name} chunk: {e}");
+                continue;
+            }
+        }
+
+        let mut cursor = Cursor::new(&mut *file_buffer);
+        cursor.set_position(chunk_start);
+        remove_chunk_at(&mut cursor)?;
+
+        insert_position = Some(chunk_start);
+    }
+
+    let insert_position = match insert_position
+    {
+        Some(position) => position,
+        None           => get_metadata_insert_position(&vec_parse_png(file_buffer)?),
+    };
+
+    let mut cursor = Cursor::new(file_buffer);
+    cursor.set_position(insert_position);
+    return write_chunk(&mut cursor, chunk_name, &chunk_data);
+}
+
+/// Removes all text chunks with the given keyword
+pub(crate) fn
+remove_text_entries
+(
+    file_buffer: &mut Vec<u8>,
+    keyword:     &str,
+)
+-> Result<(), std::io::Error>
+{
+    let keyword = encode_keyword(keyword)?;
+
+    if is_metadata_keyword(&keyword)
+    {
+        return io_error!(InvalidInput, "Keyword is reserved for EXIF or XMP data!");
+    }
+
+    for (chunk_start, _, chunk_data) in find_text_chunks(file_buffer)?.into_iter().rev()
+    {
+        if get_raw_keyword(&chunk_data) == keyword
+        {
+            let mut cursor = Cursor::new(&mut *file_buffer);
+            cursor.set_position(chunk_start);
+            remove_chunk_at(&mut cursor)?;
+        }
+    }
+
+    return Ok(());
+}
+
 /// Assumes the cursor to be positioned at the insert position
 #[allow(non_snake_case)]
 fn
@@ -665,19 +1152,10 @@ generic_write_metadata
 {
     cursor.seek(SeekFrom::Start(8))?;
 
-    let mut IHDR_length = 0u32;
-
-    if let Ok(chunks) = generic_parse_png(cursor)
-    {
-        IHDR_length = chunks[0].length();
-    }
+    let seek_start = get_metadata_insert_position(&generic_parse_png(cursor)?);
 
     // Encode the data specifically for PNG and open the image file
     let encoded_metadata = encode_metadata_png(&metadata.encode()?);
-    let seek_start = 0u64         // Skip ...
-    + PNG_SIGNATURE.len() as u64  // PNG Signature
-    + IHDR_length         as u64  // IHDR data section
-    + 12                        ; // rest of IHDR chunk (length, type, CRC)
 
     // Build data of new chunk using zlib compression (level=8 -> default)
     let zTXt_chunk_data: Vec<u8> = construct_zTXt_chunk_data(
@@ -5,26 +5,141 @@ use miniz_oxide::inflate::decompress_to_vec_zlib;
 use miniz_oxide::deflate::compress_to_vec_zlib;
 
 use crate::general_file_io::io_error;
+use crate::metadata::png_io::PngTextEntry;
+use crate::metadata::png_io::PngTextFormat;
+
+/// The fields of the data section of a text chunk. The keyword, language
+/// tag and translated keyword are stored as raw bytes, the text as it is
+/// stored in the chunk, i.e. possibly compressed.
+/// For iTXt chunks, the data section consists of (from the specifications,
+/// see paragraph 11.3.3.4 of https://www.w3.org/TR/png ):
+/// - Keyword              1-79 bytes (character string)
+/// - Null separator       1 byte (null character)
+/// - Compression flag     1 byte
+/// - Compression method   1 byte
+/// - Language tag         0 or more bytes (character string)
+/// - Null separator       1 byte (null character)
+/// - Translated keyword   0 or more bytes
+/// - Null separator       1 byte (null character)
+/// - Text                 0 or more bytes
+/// tEXt and zTXt chunks only have the keyword and the text, the latter with
+/// a preceding compression method in case of zTXt.
+struct
+TextChunkFields<'a>
+{
+    keyword:            &'a [u8],
+    format:             PngTextFormat,
+    compression_method: u8,
+    language_tag:       &'a [u8],
+    translated_keyword: &'a [u8],
+    text_data:          &'a [u8],
+}
 
-/// This gets the keyword of a $TEXT chunk.
-/// Fortunately, this is the same for tEXt, zTXt and iTXt, as they all
-/// start with a keyword that is followed by a NUL separator.
-pub(crate) fn
-get_keyword_from_text_chunk
-(
-    chunk_data: &[u8]
-)
--> String
+impl<'a>
+TextChunkFields<'a>
 {
-    let mut keyword_buffer = Vec::new();
-    for character in chunk_data
+    /// Splits the data field of a text chunk into its fields
+    fn
+    new_from_chunk_data
+    (
+        chunk_name: &str,
+        chunk_data: &'a [u8],
+    )
+    -> Result<Self, std::io::Error>
+    {
+        let Some(keyword_end) = chunk_data.iter().position(|byte| *byte == 0x00) else {
+            return io_error!(InvalidData, "Missing NUL separator after keyword of text chunk!");
+        };
+
+        let mut fields = TextChunkFields {
+            keyword:            &chunk_data[..keyword_end],
+            format:             PngTextFormat::Text,
+            compression_method: 0x00,
+            language_tag:       &[],
+            translated_keyword: &[],
+            text_data:          &[],
+        };
+
+        let remainder = &chunk_data[keyword_end + 1..];
+
+        match chunk_name
+        {
+            "tEXt" => {
+                fields.text_data = remainder;
+            },
+
+            "zTXt" => {
+                let Some((compression_method, compressed_data)) = remainder.split_first() else {
+                    return io_error!(InvalidData, "zTXt chunk is missing the compression method!");
+                };
+
+                fields.format             = PngTextFormat::CompressedText;
+                fields.compression_method = *compression_method;
+                fields.text_data          = compressed_data;
+            },
+
+            "iTXt" => {
+                if remainder.len() < 2
+                {
+                    return io_error!(InvalidData, "iTXt chunk is missing the compression information!");
+                }
+
+                // Language tag, translated keyword and the text, where the
+                // latter may contain NUL bytes if it is compressed
+                let mut split = remainder[2..].splitn(3, |byte| *byte == 0x00);
+                let (Some(language_tag), Some(translated_keyword), Some(text_data)) = (split.next(), split.next(), split.next()) else {
+                    return io_error!(InvalidData, "iTXt chunk is missing NUL separators!");
+                };
+
+                fields.format = match remainder[0]
+                {
+                    0x00 => PngTextFormat::InternationalText,
+                    _    => PngTextFormat::CompressedInternationalText,
+                };
+                fields.compression_method = remainder[1];
+                fields.language_tag       = language_tag;
+                fields.translated_keyword = translated_keyword;
+                fields.text_data          = text_data;
+            },
+
+            _ => {
+                return io_error!(Other, "Unknown text chunk!");
+            }
+        }
+
+        return Ok(fields);
+    }
+
+    fn
+    is_compressed
+    (
+        &self
+    )
+    -> bool
     {
-        if *character == 0x00 { break; }
-        keyword_buffer.push(*character);
+        return matches!(
+            self.format, 
+            PngTextFormat::CompressedText | PngTextFormat::CompressedInternationalText
+        );
+    }
+
+    /// Gets the text as raw bytes, decompressing it if necessary
+    fn
+    get_text
+    (
+        &self
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        if self.is_compressed()
+        {
+            return inflate_text(self.compression_method, self.text_data);
+        }
+        return Ok(self.text_data.to_vec());
     }
-    return String::from_utf8_lossy(&keyword_buffer).into_owned()
 }
 
+/// Gets the (decompressed) text of a tEXt, zTXt or iTXt chunk as raw bytes
 pub(crate) fn
 get_data_from_text_chunk
 (
@@ -33,104 +148,11 @@ get_data_from_text_chunk
 )
 -> Result<Vec<u8>, std::io::Error>
 {
-    // The keyword length is required in all cases for determining the start
-    // of the actual data
-    let keyword_length = get_keyword_from_text_chunk(chunk_data).len();
-
-    match chunk_name
-    {
-        "tEXt" => {
-            // Find start of data
-            // For this we take the keyword length and add 1 for the NUL byte
-            let data_start = keyword_length + 1;
-            
-            return Ok(chunk_data[data_start..].to_vec());
-        },
-
-        "zTXt" => {
-            // Find start of data
-            // For this we take the keyword length and add 2 for the NUL byte
-            // and the byte representing the compression method
-            let data_start = keyword_length + 2;
-
-            // Check compression method
-            if chunk_data[keyword_length + 1] != 0x00
-            {
-                return io_error!(
-                    Other,
-                    "Unknown compression method for zTXt!"
-                );
-            }
-
-            // Decode zlib data
-            if let Ok(decompressed_data) = decompress_to_vec_zlib(
-                &chunk_data[data_start..]
-            )
-            {
-                return Ok(decompressed_data);
-            }
-            else
-            {
-                return io_error!(
-                    Other, 
-                    "Could not inflate compressed chunk data!"
-                );
-            }
-        },
-
-        "iTXt" => {
-            // We need more than just the keyword length
-            let (
-                compression_flag,
-                compression_method,
-                _keyword,
-                language_tag,
-                translated_keyword,
-            ) = get_info_about_iTXt_chunk(chunk_data);
-
-            let data_start = keyword_length // keyword
-                + 3                         // NUL, compression flag & method
-                + language_tag.len()        // language tag
-                + 1                         // NUL
-                + translated_keyword.len()  // translated keyword
-                + 1;                        // NUL
-
-            if compression_flag == 0x00
-            {
-                // No compression, simply return the data
-                return Ok(chunk_data[data_start..].to_vec());
-            }
-            
-            if compression_method != 0x00
-            {
-                return io_error!(
-                    Other,
-                    "Unknown compression method for iTXt!"
-                );
-            }
-
-            // Decode zlib data
-            if let Ok(decompressed_data) = decompress_to_vec_zlib(
-                &chunk_data[data_start..]
-            )
-            {
-                return Ok(decompressed_data);
-            }
-            else
-            {
-                return io_error!(
-                    Other, 
-                    "Could not inflate compressed chunk data!"
-                );
-            }
-        },
-
-        _ => {
-            return io_error!(Other, "Unknown text chunk!");
-        }
-    }
+    return TextChunkFields::new_from_chunk_data(chunk_name, chunk_data)?.get_text();
 }
 
+/// Constructs the data field of a text chunk with the same keyword, type
+/// and compression as the old one, but with the new data as text
 pub(crate) fn
 construct_similar_with_new_data
 (
@@ -143,168 +165,241 @@ construct_similar_with_new_data
     // Note: data is just the text after the keyword an so on, while *chunk*
     // data describes the entire data field that includes the keyword, the
     // compression information and so on
+    let fields = TextChunkFields::new_from_chunk_data(chunk_name, old_chunk_data)?;
+
+    if fields.is_compressed() && fields.compression_method != 0x00
+    {
+        return io_error!(Other, "Unknown compression method for text chunk!");
+    }
 
     // The keyword and NUL will be needed in every cases:
-    let     keyword        = get_keyword_from_text_chunk(old_chunk_data);
-    let mut new_chunk_data = keyword
-        .bytes()
-        .collect::<Vec<u8>>();
+    let mut new_chunk_data = fields.keyword.to_vec();
     new_chunk_data.push(0x00);
 
-    match chunk_name
+    match fields.format
     {
-        "tEXt" => {
-            new_chunk_data.extend(new_data);
-        },
-
-        "zTXt" => {
-            // Check compression method
-            if old_chunk_data[keyword.len() + 1] != 0x00
-            {
-                return io_error!(
-                    Other,
-                    "Unknown compression method for zTXt!"
-                );
-            }
+        PngTextFormat::Text => {},
 
-            new_chunk_data.extend(compress_to_vec_zlib(new_data, 8).iter());
+        PngTextFormat::CompressedText => {
+            new_chunk_data.push(fields.compression_method);
         },
 
-        "iTXt" => {
-            // We need more than just the keyword
-            let (
-                compression_flag,
-                compression_method,
-                _keyword,
-                language_tag,
-                translated_keyword,
-            ) = get_info_about_iTXt_chunk(old_chunk_data);
-
+        PngTextFormat::InternationalText | PngTextFormat::CompressedInternationalText => {
             // Push compression information
-            new_chunk_data.push(compression_flag);
-            new_chunk_data.push(compression_method);
+            new_chunk_data.push(fields.is_compressed() as u8);
+            new_chunk_data.push(fields.compression_method);
 
             // Add the language tag and translated keyword
-            new_chunk_data.extend(language_tag.bytes());
+            new_chunk_data.extend(fields.language_tag);
             new_chunk_data.push(0x00);
-            new_chunk_data.extend(translated_keyword.bytes());
+            new_chunk_data.extend(fields.translated_keyword);
             new_chunk_data.push(0x00);
-
-            // Check compression
-            if compression_flag == 0x00
-            {
-                // No compression, simply add the new data
-                new_chunk_data.extend(new_data);
-            }
-            else
-            {
-                if compression_method != 0x00
-                {
-                    return io_error!(
-                        Other,
-                        "Unknown compression method for iTXt!"
-                    );
-                }
-                new_chunk_data.extend(
-                    compress_to_vec_zlib(new_data, 8).iter()
-                );
-            }
         },
+    }
 
-        _ => {
-            return io_error!(Other, "Unknown text chunk!");
-        }
+    if fields.is_compressed()
+    {
+        new_chunk_data.extend(compress_to_vec_zlib(new_data, 8));
+    }
+    else
+    {
+        new_chunk_data.extend(new_data);
     }
 
     return Ok(new_chunk_data);
 }
 
-/// The iTXt chunk is in its structure more complex than e.g. tEXt. The data
-/// section consists of (from the specifications, see paragraph 11.3.3.4 of
-/// https://www.w3.org/TR/png ):
-/// - Keyword              1-79 bytes (character string)
-/// - Null separator       1 byte (null character)
-/// - Compression flag     1 byte
-/// - Compression method   1 byte
-/// - Language tag         0 or more bytes (character string)
-/// - Null separator       1 byte (null character)
-/// - Translated keyword   0 or more bytes
-/// - Null separator       1 byte (null character)
-/// - Text                 0 or more bytes
-#[allow(non_snake_case)]
+/// Decodes text as ISO/IEC 8859-1 (Latin-1), which is used for keywords as
+/// well as the text of tEXt and zTXt chunks
 fn
-get_info_about_iTXt_chunk
+decode_latin1
 (
-    chunk_data:   &[u8]
-)
--> (
-    u8,     // compression flag
-    u8,     // compression method
-    String, // keyword
-    String, // language tag
-    String, // translated keyword
+    data: &[u8]
 )
+-> String
 {
-    // Tells us where we currently are in the chunk data
-    let mut chunk_counter = 0;
-
-    // Buffers for the different attributes
-    let mut keyword_buffer = Vec::new();
+    return data.iter().map(|byte| *byte as char).collect();
+}
 
-    loop
+fn
+encode_latin1
+(
+    text: &str
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let mut encoded = Vec::with_capacity(text.len());
+    for character in text.chars()
     {
-        if chunk_data[chunk_counter] != 0x00
-        {
-            keyword_buffer.push(chunk_data[chunk_counter]);
-            chunk_counter += 1;
-        }
-        else
+        if character as u32 > 0xff
         {
-            break;
+            return io_error!(
+                InvalidInput,
+                format!("Character '{}' can't be represented in Latin-1!", character)
+            );
         }
+        encoded.push(character as u8);
     }
+    return Ok(encoded);
+}
 
-    let _null_separator_1  = chunk_data[chunk_counter + 0];
-    let compression_flag   = chunk_data[chunk_counter + 1];
-    let compression_method = chunk_data[chunk_counter + 2];
+/// Encodes the keyword and checks that it complies with the specification:
+/// 1-79 printable Latin-1 characters without leading, trailing or 
+/// consecutive spaces
+pub(crate) fn
+encode_keyword
+(
+    keyword: &str
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    let encoded = encode_latin1(keyword)?;
 
-    chunk_counter += 3;
-    let mut language_tag_buffer = Vec::new();
+    if encoded.is_empty() || encoded.len() > 79
+    {
+        return io_error!(InvalidInput, "Keyword of text chunk must be 1-79 characters long!");
+    }
 
-    loop 
+    if !encoded.iter().all(|byte| matches!(byte, 0x20..=0x7e | 0xa1..=0xff))
     {
-        if chunk_data[chunk_counter] != 0x00
-        {
-            language_tag_buffer.push(chunk_data[chunk_counter]);
-            chunk_counter += 1;
-        }
-        else
-        {
-            break;
-        }
+        return io_error!(InvalidInput, "Keyword of text chunk contains non-printable characters!");
+    }
+
+    if keyword.starts_with(' ') || keyword.ends_with(' ') || keyword.contains("  ")
+    {
+        return io_error!(InvalidInput, "Keyword of text chunk must not have leading, trailing or consecutive spaces!");
     }
 
-    chunk_counter += 1;
-    let mut translated_keyword_buffer = Vec::new();
+    return Ok(encoded);
+}
+
+/// Gets the raw bytes of the keyword, i.e. everything up to the first NUL
+pub(crate) fn
+get_raw_keyword
+(
+    chunk_data: &[u8]
+)
+-> &[u8]
+{
+    let keyword_end = chunk_data.iter().position(|byte| *byte == 0x00).unwrap_or(chunk_data.len());
+    return &chunk_data[..keyword_end];
+}
 
-    loop 
+fn
+inflate_text
+(
+    compression_method: u8,
+    compressed_data:    &[u8],
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    if compression_method != 0x00
     {
-        if chunk_data[chunk_counter] != 0x00
-        {
-            translated_keyword_buffer.push(chunk_data[chunk_counter]);
-            chunk_counter += 1;
-        }
-        else
-        {
-            break;
-        }
+        return io_error!(Other, "Unknown compression method for text chunk!");
     }
 
-    return (
-        compression_flag,
-        compression_method,
-        String::from_utf8(keyword_buffer           ).unwrap_or_default(),
-        String::from_utf8(language_tag_buffer      ).unwrap_or_default(),
-        String::from_utf8(translated_keyword_buffer).unwrap_or_default()
-    );
-}
\ No newline at end of file
+    match decompress_to_vec_zlib(compressed_data)
+    {
+        Ok(data) => return Ok(data),
+        Err(_)   => return io_error!(Other, "Could not inflate compressed chunk data!"),
+    }
+}
+
+/// Decodes the entire data field of a text chunk, taking care of the
+/// different text encodings (Latin-1 for tEXt and zTXt, UTF-8 for iTXt)
+pub(crate) fn
+decode_text_chunk
+(
+    chunk_name: &str,
+    chunk_data: &[u8],
+)
+-> Result<PngTextEntry, std::io::Error>
+{
+    let fields = TextChunkFields::new_from_chunk_data(chunk_name, chunk_data)?;
+    let text   = fields.get_text()?;
+
+    let text = match fields.format
+    {
+        PngTextFormat::Text | PngTextFormat::CompressedText
+            => decode_latin1(&text),
+
+        PngTextFormat::InternationalText | PngTextFormat::CompressedInternationalText
+            => match String::from_utf8(text)
+            {
+                Ok(text) => text,
+                Err(_)   => return io_error!(InvalidData, "Text of iTXt chunk is not valid UTF-8!"),
+            },
+    };
+
+    return Ok(PngTextEntry {
+        keyword:            decode_latin1(fields.keyword),
+        text,
+        format:             fields.format,
+        language_tag:       String::from_utf8_lossy(fields.language_tag).into_owned(),
+        translated_keyword: String::from_utf8_lossy(fields.translated_keyword).into_owned(),
+    });
+}
+
+/// Encodes the entry as data field of a text chunk and returns the name of
+/// the chunk type alongside
+pub(crate) fn
+encode_text_chunk
+(
+    entry: &PngTextEntry
+)
+-> Result<(&'static str, Vec<u8>), std::io::Error>
+{
+    let mut chunk_data = encode_keyword(&entry.keyword)?;
+    chunk_data.push(0x00);
+
+    let compressed = match entry.format
+    {
+        PngTextFormat::Text | PngTextFormat::CompressedText => {
+            if !entry.language_tag.is_empty() || !entry.translated_keyword.is_empty()
+            {
+                return io_error!(InvalidInput, "Language tag and translated keyword require an iTXt chunk!");
+            }
+
+            let text = encode_latin1(&entry.text)?;
+
+            if entry.format == PngTextFormat::Text
+            {
+                chunk_data.extend(text);
+                return Ok(("tEXt", chunk_data));
+            }
+
+            // Compression method 0 (deflate)
+            chunk_data.push(0x00);
+            chunk_data.extend(compress_to_vec_zlib(&text, 8));
+            return Ok(("zTXt", chunk_data));
+        },
+
+        PngTextFormat::InternationalText           => false,
+        PngTextFormat::CompressedInternationalText => true,
+    };
+
+    if !entry.language_tag.is_ascii() || entry.language_tag.contains('\0') || entry.translated_keyword.contains('\0')
+    {
+        return io_error!(InvalidInput, "Invalid language tag or translated keyword for iTXt chunk!");
+    }
+
+    // Compression flag & method
+    chunk_data.push(compressed as u8);
+    chunk_data.push(0x00);
+
+    chunk_data.extend(entry.language_tag.bytes());
+    chunk_data.push(0x00);
+    chunk_data.extend(entry.translated_keyword.bytes());
+    chunk_data.push(0x00);
+
+    if compressed
+    {
+        chunk_data.extend(compress_to_vec_zlib(entry.text.as_bytes(), 8));
+    }
+    else
+    {
+        chunk_data.extend(entry.text.as_bytes());
+    }
+
+    return Ok(("iTXt", chunk_data));
+}
@@ -0,0 +1,809 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+// QuickTime (MOV) and MP4 files are based on ISO BMFF and keep their metadata
+// in the `moov` box, which gets loaded into memory as a tree of boxes. Only
+// the boxes on the way to the metadata and the chunk offsets get parsed, all
+// other boxes are kept as they are:
+// - moov/mvhd:           Creation time of the movie
+// - moov/udta/©xyz:      Location in ISO 6709 notation
+// - moov/udta/©day:      Creation date
+// - moov/meta/keys+ilst: Apple's metadata items, e.g. for the location
+// - moov/trak/mdia/minf/stbl/stco+co64: Chunk offsets into `mdat`
+// See: https://developer.apple.com/documentation/quicktime-file-format
+
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+
+use crate::general_file_io::modify_file;
+use crate::general_file_io::open_read_file;
+use crate::general_file_io::open_write_file;
+use crate::heif::box_header::BoxHeader;
+use crate::heif::box_type::BoxType;
+use crate::io_error;
+use crate::metadata::quicktime_io::QuickTimeMetadata;
+
+/// Boxes that contain other boxes and need to be parsed to get to the
+/// metadata or the chunk offsets
+const CONTAINER_BOXES: [&[u8; 4]; 8] = [
+    b"moov", b"trak", b"mdia", b"minf", b"stbl", b"udta", b"meta", b"ilst"
+];
+
+const LOCATION_BOX:      &[u8; 4] = b"\xa9xyz";
+const CREATION_DATE_BOX: &[u8; 4] = b"\xa9day";
+
+const LOCATION_KEY:           &str = "com.apple.quicktime.location.ISO6709";
+const CREATION_DATE_KEY:      &str = "com.apple.quicktime.creationdate";
+const CONTENT_IDENTIFIER_KEY: &str = "com.apple.quicktime.content.identifier";
+
+/// The packed ISO 639-2/T language code "und" (undetermined), used for new
+/// text entries in `udta`
+const UNDETERMINED_LANGUAGE: u16 = 0x55c4;
+
+/// The type indicator of a `data` box that holds UTF-8 text
+const DATA_TYPE_UTF8: u32 = 1;
+
+/// A box of the `moov` tree. For containers, `content` holds the data before
+/// the child boxes (e.g. version and flags of an ISO `meta` box), `trailer`
+/// holds the data after them (e.g. the 4 zero bytes that may end `udta`).
+/// For all other boxes, `content` holds their entire content.
+#[derive(Clone, Debug)]
+struct
+QuickTimeBox
+{
+    header:   BoxHeader,
+    content:  Vec<u8>,
+    children: Option<Vec<QuickTimeBox>>,
+    trailer:  Vec<u8>,
+}
+
+impl
+QuickTimeBox
+{
+    fn
+    new
+    (
+        box_type: &[u8; 4],
+        content:  Vec<u8>,
+        children: Option<Vec<QuickTimeBox>>,
+    )
+    -> QuickTimeBox
+    {
+        let mut header = BoxHeader::new_simple_box_header();
+        header.set_box_type(BoxType::from_4_bytes(*box_type));
+        return QuickTimeBox { header, content, children, trailer: Vec::new() };
+    }
+
+    /// Parses the boxes in the data. Data at the end that is too short for
+    /// a box gets returned separately.
+    fn
+    parse_boxes
+    (
+        data: &[u8]
+    )
+    -> Result<(Vec<QuickTimeBox>, Vec<u8>), std::io::Error>
+    {
+        let mut boxes    = Vec::new();
+        let mut position = 0;
+        let mut cursor   = Cursor::new(data);
+
+        while data.len() - position >= 8
+        {
+            cursor.set_position(position as u64);
+            let header = BoxHeader::read_simple_box_header(&mut cursor)?;
+
+            // A size of 0 indicates that the box extends to the end
+            // See also: ISO/IEC 14496-12:2015, § 4.2
+            let box_end = match header.get_box_size()
+            {
+                0    => data.len(),
+                size => position.saturating_add(size as usize),
+            };
+
+            let content_start = position + header.get_header_size() as usize;
+            if box_end > data.len() || box_end < content_start
+            {
+                return io_error!(InvalidData, format!("Invalid size of box {:?} in moov!", header.get_box_type()));
+            }
+
+            boxes.push(QuickTimeBox::parse(header, &data[content_start..box_end])?);
+            position = box_end;
+        }
+
+        return Ok((boxes, data[position..].to_vec()));
+    }
+
+    fn
+    parse
+    (
+        header:  BoxHeader,
+        content: &[u8],
+    )
+    -> Result<QuickTimeBox, std::io::Error>
+    {
+        let box_type = header.get_box_type().to_4_bytes();
+        if !CONTAINER_BOXES.iter().any(|container| container[..] == box_type[..])
+        {
+            return Ok(QuickTimeBox { header, content: content.to_vec(), children: None, trailer: Vec::new() });
+        }
+
+        // In QuickTime files, `meta` is an ordinary container that starts
+        // with its `hdlr` box, while ISO BMFF defines it as full box
+        let content_length = if &box_type[..] == b"meta" && content.get(4..8) != Some(b"hdlr") { 4 } else { 0 };
+        if content.len() < content_length
+        {
+            return io_error!(InvalidData, "Invalid meta box in moov!");
+        }
+
+        let (children, trailer) = Self::parse_boxes(&content[content_length..])?;
+        return Ok(QuickTimeBox {
+            header,
+            content:  content[..content_length].to_vec(),
+            children: Some(children),
+            trailer
+        });
+    }
+
+    fn
+    serialize
+    (
+        &self
+    )
+    -> Result<Vec<u8>, std::io::Error>
+    {
+        let mut content = self.content.clone();
+        for child in self.children.iter().flatten()
+        {
+            content.extend(child.serialize()?);
+        }
+        content.extend(&self.trailer);
+
+        let mut header = self.header.clone();
+        header.set_box_size(header.get_header_size() + content.len() as u64);
+        if header.get_box_size() > u32::MAX as u64
+        {
+            return io_error!(Unsupported, format!("Box {:?} is too large!", header.get_box_type()));
+        }
+
+        let mut serialized = header.serialize();
+        serialized.extend(content);
+        return Ok(serialized);
+    }
+
+    fn
+    is
+    (
+        &self,
+        box_type: &[u8; 4]
+    )
+    -> bool
+    {
+        return self.header.get_box_type().to_4_bytes()[..] == box_type[..];
+    }
+
+    fn
+    child
+    (
+        &self,
+        box_type: &[u8; 4]
+    )
+    -> Option<&QuickTimeBox>
+    {
+        return self.children.iter().flatten().find(|child| child.is(box_type));
+    }
+
+    fn
+    child_mut
+    (
+        &mut self,
+        box_type: &[u8; 4]
+    )
+    -> Option<&mut QuickTimeBox>
+    {
+        return self.children.iter_mut().flatten().find(|child| child.is(box_type));
+    }
+
+    /// Gets the child with the given type, adding a new one at the end if it
+    /// does not exist yet
+    fn
+    child_or_insert
+    (
+        &mut self,
+        new_box: QuickTimeBox
+    )
+    -> &mut QuickTimeBox
+    {
+        let box_type = new_box.header.get_box_type();
+        let children = self.children.get_or_insert_with(Vec::new);
+        let index = match children.iter().position(|child| child.header.get_box_type() == box_type)
+        {
+            Some(index) => index,
+            None        => { children.push(new_box); children.len() - 1 }
+        };
+        return &mut children[index];
+    }
+
+    fn
+    remove_children
+    (
+        &mut self,
+        box_type: &[u8; 4]
+    )
+    {
+        if let Some(children) = self.children.as_mut()
+        {
+            children.retain(|child| !child.is(box_type));
+        }
+    }
+}
+
+/// The location of a top level box in the file
+struct
+TopLevelBox
+{
+    box_type: Vec<u8>,
+    start:    u64,
+    end:      u64,
+}
+
+fn
+read_top_level_boxes
+<T: Read + Seek>
+(
+    cursor: &mut T
+)
+-> Result<Vec<TopLevelBox>, std::io::Error>
+{
+    let file_length  = cursor.seek(SeekFrom::End(0))?;
+    let mut boxes    = Vec::new();
+    let mut position = 0;
+
+    while position + 8 <= file_length
+    {
+        cursor.seek(SeekFrom::Start(position))?;
+        let header = BoxHeader::read_simple_box_header(cursor)?;
+
+        let box_end = match header.get_box_size()
+        {
+            0    => file_length,
+            size => position + size,
+        };
+
+        if box_end > file_length || box_end < position + header.get_header_size()
+        {
+            return io_error!(InvalidData, format!("Invalid size of top level box {:?}!", header.get_box_type()));
+        }
+
+        boxes.push(TopLevelBox { box_type: header.get_box_type().to_4_bytes(), start: position, end: box_end });
+        position = box_end;
+    }
+
+    return Ok(boxes);
+}
+
+/// Reads the `moov` box and parses it into a tree of boxes
+fn
+read_moov
+<'a, T: Read + Seek>
+(
+    cursor:    &mut T,
+    top_level: &'a [TopLevelBox],
+)
+-> Result<(QuickTimeBox, &'a TopLevelBox), std::io::Error>
+{
+    let Some(moov_location) = top_level.iter().find(|location| location.box_type == b"moov") else {
+        return io_error!(InvalidData, "Could not find moov box!");
+    };
+
+    let mut moov_data = vec![0u8; (moov_location.end - moov_location.start) as usize];
+    cursor.seek(SeekFrom::Start(moov_location.start))?;
+    cursor.read_exact(&mut moov_data)?;
+
+    let (mut boxes, _) = QuickTimeBox::parse_boxes(&moov_data)?;
+    return Ok((boxes.remove(0), moov_location));
+}
+
+/// Decodes a text entry of `udta`, consisting of the length of the text, the
+/// language code and the text itself
+fn
+read_udta_text
+(
+    moov:     &QuickTimeBox,
+    box_type: &[u8; 4],
+)
+-> Option<String>
+{
+    let content = &moov.child(b"udta")?.child(box_type)?.content;
+    let length  = u16::from_be_bytes(content.get(0..2)?.try_into().ok()?) as usize;
+    let text    = content.get(4..4+length)?;
+    return Some(String::from_utf8_lossy(text).trim_end_matches('\0').to_string());
+}
+
+/// Updates a text entry of `udta`, keeping its language code. The entry is
+/// only created if `create` is set.
+fn
+write_udta_text
+(
+    moov:     &mut QuickTimeBox,
+    box_type: &[u8; 4],
+    text:     Option<&str>,
+    create:   bool,
+)
+-> Result<(), std::io::Error>
+{
+    let Some(text) = text else {
+        if let Some(udta) = moov.child_mut(b"udta")
+        {
+            udta.remove_children(box_type);
+        }
+        return Ok(());
+    };
+
+    if moov.child(b"udta").and_then(|udta| udta.child(box_type)).is_none() && !create
+    {
+        return Ok(());
+    }
+
+    if text.len() > u16::MAX as usize
+    {
+        return io_error!(InvalidInput, "Text is too long for a udta entry!");
+    }
+
+    let udta  = moov.child_or_insert(QuickTimeBox::new(b"udta", Vec::new(), Some(Vec::new())));
+    let entry = udta.child_or_insert(QuickTimeBox::new(box_type, Vec::new(), None));
+
+    let language = match entry.content.get(2..4)
+    {
+        Some(language) => language.to_vec(),
+        None           => UNDETERMINED_LANGUAGE.to_be_bytes().to_vec(),
+    };
+
+    entry.content = (text.len() as u16).to_be_bytes().to_vec();
+    entry.content.extend(language);
+    entry.content.extend(text.as_bytes());
+
+    return Ok(());
+}
+
+/// Decodes the keys of `meta/keys`, which consist of version and flags, the
+/// number of entries and the entries themselves, each with its size, the key
+/// namespace and the key
+fn
+read_keys
+(
+    keys: &QuickTimeBox
+)
+-> Result<Vec<(Vec<u8>, String)>, std::io::Error>
+{
+    let content = &keys.content;
+    if content.len() < 8
+    {
+        return io_error!(InvalidData, "Invalid keys box!");
+    }
+
+    let count        = u32::from_be_bytes([content[4], content[5], content[6], content[7]]);
+    let mut entries  = Vec::new();
+    let mut position = 8;
+
+    for _ in 0..count
+    {
+        let Some(size_bytes) = content.get(position..position+4) else {
+            return io_error!(InvalidData, "Invalid keys box!");
+        };
+        let size = u32::from_be_bytes([size_bytes[0], size_bytes[1], size_bytes[2], size_bytes[3]]) as usize;
+        let Some(entry) = content.get(position..position.saturating_add(size)).filter(|entry| entry.len() >= 8) else {
+            return io_error!(InvalidData, "Invalid entry in keys box!");
+        };
+
+        entries.push((entry[4..8].to_vec(), String::from_utf8_lossy(&entry[8..]).to_string()));
+        position += size;
+    }
+
+    return Ok(entries);
+}
+
+fn
+encode_keys
+(
+    version_and_flags: &[u8],
+    entries:           &[(Vec<u8>, String)],
+)
+-> Vec<u8>
+{
+    let mut content = version_and_flags.to_vec();
+    content.extend((entries.len() as u32).to_be_bytes());
+    for (namespace, key) in entries
+    {
+        content.extend((8 + key.len() as u32).to_be_bytes());
+        content.extend(namespace);
+        content.extend(key.as_bytes());
+    }
+    return content;
+}
+
+/// Reads the value of a metadata item with the given key. The items in
+/// `meta/ilst` use the 1-based index of their key as box type and store the
+/// value in a `data` box, consisting of the type indicator, the locale and
+/// the value itself.
+fn
+read_meta_item
+(
+    moov: &QuickTimeBox,
+    key:  &str,
+)
+-> Result<Option<String>, std::io::Error>
+{
+    let Some(meta) = moov.child(b"meta") else { return Ok(None); };
+    let (Some(keys), Some(ilst)) = (meta.child(b"keys"), meta.child(b"ilst")) else {
+        return Ok(None);
+    };
+
+    let Some(index) = read_keys(keys)?.iter().position(|(_, candidate)| candidate == key) else {
+        return Ok(None);
+    };
+
+    let Some(item) = ilst.child(&(index as u32 + 1).to_be_bytes()) else {
+        return Ok(None);
+    };
+
+    let (data_boxes, _) = QuickTimeBox::parse_boxes(&item.content)?;
+    return Ok(data_boxes.iter()
+        .find(|data_box| data_box.is(b"data") && data_box.content.len() >= 8)
+        .map(|data_box| String::from_utf8_lossy(&data_box.content[8..]).to_string())
+    );
+}
+
+/// Updates a metadata item, adding a new key if `create` is set. Removing an
+/// item also removes its key, so the items of the following keys are moved
+/// down by one.
+fn
+write_meta_item
+(
+    moov:   &mut QuickTimeBox,
+    key:    &str,
+    value:  Option<&str>,
+    create: bool,
+)
+-> Result<(), std::io::Error>
+{
+    let has_meta = moov.child(b"meta").is_some();
+    let has_keys = moov.child(b"meta").and_then(|meta| meta.child(b"keys")).is_some();
+    if !has_keys && (value.is_none() || !create)
+    {
+        return Ok(());
+    }
+
+    // A meta box without keys uses another handler, e.g. the `mdir` handler
+    // of iTunes with its own types of items
+    if has_meta && !has_keys
+    {
+        return io_error!(Unsupported, format!("Can't add {key} to meta box without keys!"));
+    }
+
+    // A new meta box is created QuickTime style, starting with a handler of
+    // type mdta that is followed by the keys and items
+    let mut hdlr_content = vec![0u8; 8];
+    hdlr_content.extend(b"mdta");
+    hdlr_content.extend([0u8; 13]);
+    let new_meta = QuickTimeBox::new(b"meta", Vec::new(), Some(vec![QuickTimeBox::new(b"hdlr", hdlr_content, None)]));
+
+    let meta = moov.child_or_insert(new_meta);
+
+    let keys_box    = meta.child_or_insert(QuickTimeBox::new(b"keys", vec![0u8; 8], None));
+    let mut entries = read_keys(keys_box)?;
+    let version     = keys_box.content[0..4].to_vec();
+    let position    = entries.iter().position(|(_, candidate)| candidate == key);
+
+    let Some(value) = value else {
+        let Some(position) = position else { return Ok(()); };
+
+        entries.remove(position);
+        keys_box.content = encode_keys(&version, &entries);
+
+        if let Some(items) = meta.child_mut(b"ilst").and_then(|ilst| ilst.children.as_mut())
+        {
+            let removed_index = position as u32 + 1;
+            items.retain(|item| item.header.get_box_type().to_4_bytes() != removed_index.to_be_bytes());
+            for item in items.iter_mut()
+            {
+                let index_bytes = item.header.get_box_type().to_4_bytes();
+                let index       = u32::from_be_bytes([index_bytes[0], index_bytes[1], index_bytes[2], index_bytes[3]]);
+                if index > removed_index
+                {
+                    item.header.set_box_type(BoxType::from_4_bytes((index - 1).to_be_bytes()));
+                }
+            }
+        }
+        return Ok(());
+    };
+
+    let index = match position
+    {
+        Some(position) => position as u32 + 1,
+        None if create => {
+            entries.push((b"mdta".to_vec(), key.to_string()));
+            keys_box.content = encode_keys(&version, &entries);
+            entries.len() as u32
+        },
+        None => return Ok(()),
+    };
+
+    let mut data_content = DATA_TYPE_UTF8.to_be_bytes().to_vec();
+    data_content.extend([0u8; 4]);
+    data_content.extend(value.as_bytes());
+
+    let ilst = meta.child_or_insert(QuickTimeBox::new(b"ilst", Vec::new(), Some(Vec::new())));
+    let item = ilst.child_or_insert(QuickTimeBox::new(&index.to_be_bytes(), Vec::new(), None));
+    item.content = QuickTimeBox::new(b"data", data_content, None).serialize()?;
+
+    return Ok(());
+}
+
+fn
+read_creation_time
+(
+    moov: &QuickTimeBox
+)
+-> Option<u64>
+{
+    let content = &moov.child(b"mvhd")?.content;
+    return match content.first()?
+    {
+        0 => Some(u32::from_be_bytes(content.get(4..8)?.try_into().ok()?) as u64),
+        1 => Some(u64::from_be_bytes(content.get(4..12)?.try_into().ok()?)),
+        _ => None,
+    };
+}
+
+fn
+write_creation_time
+(
+    moov:          &mut QuickTimeBox,
+    creation_time: u64,
+)
+-> Result<(), std::io::Error>
+{
+    let Some(mvhd) = moov.child_mut(b"mvhd") else {
+        return io_error!(InvalidData, "Could not find mvhd box!");
+    };
+
+    match (mvhd.content.first(), mvhd.content.len())
+    {
+        (Some(0), 8..) => {
+            let Ok(creation_time) = u32::try_from(creation_time) else {
+                return io_error!(InvalidInput, "Creation time does not fit into version 0 mvhd box!");
+            };
+            mvhd.content[4..8].copy_from_slice(&creation_time.to_be_bytes());
+        },
+        (Some(1), 12..) => {
+            mvhd.content[4..12].copy_from_slice(&creation_time.to_be_bytes());
+        },
+        _ => return io_error!(InvalidData, "Invalid mvhd box!"),
+    }
+
+    return Ok(());
+}
+
+/// Shifts the chunk offsets of all tracks that point to data after the
+/// `moov` box, as this data moves when the size of `moov` changes
+fn
+shift_chunk_offsets
+(
+    moov:     &mut QuickTimeBox,
+    moov_end: u64,
+    shift:    i64,
+)
+-> Result<(), std::io::Error>
+{
+    for trak in moov.children.iter_mut().flatten().filter(|child| child.is(b"trak"))
+    {
+        let Some(stbl) = trak.child_mut(b"mdia")
+            .and_then(|mdia| mdia.child_mut(b"minf"))
+            .and_then(|minf| minf.child_mut(b"stbl"))
+        else {
+            continue;
+        };
+
+        for table in stbl.children.iter_mut().flatten()
+        {
+            let offset_size = if table.is(b"stco") { 4 } else if table.is(b"co64") { 8 } else { continue; };
+
+            // Version and flags, followed by the number of entries
+            let content = &mut table.content;
+            if content.len() < 8
+            {
+                return io_error!(InvalidData, "Invalid chunk offset box!");
+            }
+            let count = u32::from_be_bytes([content[4], content[5], content[6], content[7]]) as usize;
+            if content.len() < 8 + count * offset_size
+            {
+                return io_error!(InvalidData, "Invalid chunk offset box!");
+            }
+
+            for entry in content[8..8 + count * offset_size].chunks_exact_mut(offset_size)
+            {
+                let offset = if offset_size == 4
+                {
+                    u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]) as u64
+                }
+                else
+                {
+                    u64::from_be_bytes([entry[0], entry[1], entry[2], entry[3], entry[4], entry[5], entry[6], entry[7]])
+                };
+
+                if offset < moov_end
+                {
+                    continue;
+                }
+
+                let Ok(new_offset) = u64::try_from(offset as i128 + shift as i128) else {
+                    return io_error!(InvalidData, "Invalid chunk offset!");
+                };
+
+                if offset_size == 4
+                {
+                    let Ok(new_offset) = u32::try_from(new_offset) else {
+                        return io_error!(Unsupported, "Chunk offset does not fit into stco box after moving the data!");
+                    };
+                    entry.copy_from_slice(&new_offset.to_be_bytes());
+                }
+                else
+                {
+                    entry.copy_from_slice(&new_offset.to_be_bytes());
+                }
+            }
+        }
+    }
+
+    return Ok(());
+}
+
+fn
+generic_read_metadata
+<T: Read + Seek>
+(
+    cursor: &mut T
+)
+-> Result<QuickTimeMetadata, std::io::Error>
+{
+    let top_level = read_top_level_boxes(cursor)?;
+    let (moov, _) = read_moov(cursor, &top_level)?;
+
+    return Ok(QuickTimeMetadata {
+        creation_time:      read_creation_time(&moov),
+        location:           read_meta_item(&moov, LOCATION_KEY)?.or_else(|| read_udta_text(&moov, LOCATION_BOX)),
+        creation_date:      read_meta_item(&moov, CREATION_DATE_KEY)?.or_else(|| read_udta_text(&moov, CREATION_DATE_BOX)),
+        content_identifier: read_meta_item(&moov, CONTENT_IDENTIFIER_KEY)?,
+    });
+}
+
+/// Prepares writing the metadata by updating the `moov` box. Returns the
+/// start and end of the old box and the new box, or `None` if nothing
+/// changes. If the size of `moov` changes, the chunk offsets get updated
+/// so that they point to the moved data.
+fn
+generic_prepare_write
+<T: Read + Seek>
+(
+    cursor:   &mut T,
+    metadata: &QuickTimeMetadata,
+)
+-> Result<Option<(u64, u64, Vec<u8>)>, std::io::Error>
+{
+    let top_level = read_top_level_boxes(cursor)?;
+    let (mut moov, moov_location) = read_moov(cursor, &top_level)?;
+    let old_moov = moov.serialize()?;
+
+    if let Some(creation_time) = metadata.creation_time
+    {
+        write_creation_time(&mut moov, creation_time)?;
+    }
+
+    // Existing entries get updated, but new ones are only added to `udta`
+    // (except for the content identifier, which is only stored as item)
+    let location_item      = read_meta_item(&moov, LOCATION_KEY)?;
+    let creation_date_item = read_meta_item(&moov, CREATION_DATE_KEY)?;
+
+    write_udta_text(&mut moov, LOCATION_BOX,      metadata.location.as_deref(),      location_item.is_none())?;
+    write_udta_text(&mut moov, CREATION_DATE_BOX, metadata.creation_date.as_deref(), creation_date_item.is_none())?;
+    write_meta_item(&mut moov, LOCATION_KEY,           metadata.location.as_deref(),           false)?;
+    write_meta_item(&mut moov, CREATION_DATE_KEY,      metadata.creation_date.as_deref(),      false)?;
+    write_meta_item(&mut moov, CONTENT_IDENTIFIER_KEY, metadata.content_identifier.as_deref(), true)?;
+
+    let mut new_moov = moov.serialize()?;
+    if new_moov == old_moov
+    {
+        return Ok(None);
+    }
+
+    let shift = new_moov.len() as i64 - (moov_location.end - moov_location.start) as i64;
+    if shift != 0
+    {
+        // Fragmented files store offsets in their `moof` boxes as well
+        if top_level.iter().any(|location| location.box_type == b"moof")
+        {
+            return io_error!(Unsupported, "Changing the size of moov is not supported for fragmented files!");
+        }
+
+        shift_chunk_offsets(&mut moov, moov_location.end, shift)?;
+        new_moov = moov.serialize()?;
+    }
+
+    return Ok(Some((moov_location.start, moov_location.end, new_moov)));
+}
+
+pub(crate) fn
+read_metadata
+(
+    file_buffer: &[u8]
+)
+-> Result<QuickTimeMetadata, std::io::Error>
+{
+    return generic_read_metadata(&mut Cursor::new(file_buffer));
+}
+
+pub(crate) fn
+file_read_metadata
+(
+    path: &Path
+)
+-> Result<QuickTimeMetadata, std::io::Error>
+{
+    return generic_read_metadata(&mut open_read_file(path)?);
+}
+
+pub(crate) fn
+write_metadata
+(
+    file_buffer: &mut Vec<u8>,
+    metadata:    &QuickTimeMetadata
+)
+-> Result<(), std::io::Error>
+{
+    if let Some((start, end, new_moov)) = generic_prepare_write(&mut Cursor::new(&*file_buffer), metadata)?
+    {
+        file_buffer.splice(start as usize..end as usize, new_moov);
+    }
+    return Ok(());
+}
+
+/// Writes the metadata to the file. The file only gets loaded into memory if
+/// the size of `moov` changes and there is data after it that has to move.
+pub(crate) fn
+file_write_metadata
+(
+    path:     &Path,
+    metadata: &QuickTimeMetadata
+)
+-> Result<(), std::io::Error>
+{
+    let mut file = open_write_file(path)?;
+    let Some((start, end, new_moov)) = generic_prepare_write(&mut file, metadata)? else {
+        return Ok(());
+    };
+
+    let file_length = file.seek(SeekFrom::End(0))?;
+    if new_moov.len() as u64 == end - start || end == file_length
+    {
+        file.seek(SeekFrom::Start(start))?;
+        file.write_all(&new_moov)?;
+
+        // Only a trailing moov may have shrunk, otherwise the data after it
+        // (e.g. mdat) must be kept
+        if end == file_length
+        {
+            file.set_len(start + new_moov.len() as u64)?;
+        }
+        return Ok(());
+    }
+
+    drop(file);
+    return modify_file(path, |file_buffer| {
+        file_buffer.splice(start as usize..end as usize, new_moov);
+        return Ok(());
+    });
+}
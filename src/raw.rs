@@ -0,0 +1,721 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+use std::io::BufReader;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+
+use crate::endian::Endian;
+use crate::exif_tag::decode::decode_tag_with_format_exceptions;
+use crate::exif_tag::ExifTag;
+use crate::exif_tag::TagType;
+use crate::exif_tag_format::ExifTagFormat;
+use crate::general_file_io::open_write_file;
+use crate::general_file_io::open_read_file;
+use crate::io_error;
+use crate::ifd::ExifTagGroup;
+use crate::ifd::ImageFileDirectory;
+use crate::metadata::Metadata;
+use crate::u8conversion::*;
+
+/// Magic numbers that camera raw files use instead of the 42 of a TIFF file,
+/// as read with the endianness given by the first two bytes
+const TIFF_MAGIC:    u16 = 0x002a;
+const ORF_MAGIC:     u16 = 0x4f52; // "IIRO" or "MMOR"
+const ORF_SR_MAGIC:  u16 = 0x5253; // "IISR"
+const RW2_MAGIC:     u16 = 0x0055; // "IIU\0"
+
+/// Tags of generic IFDs that describe the layout and encoding of the image
+/// data, i.e. its size, samples, strips, tiles and sub-IFDs (e.g. with the
+/// full size raw image). These never get removed. 
+const IMAGE_STRUCTURE_TAGS: [u16; 21] = [
+    0x00fe, 0x00ff,                 // NewSubfileType, SubfileType
+    0x0100, 0x0101,                 // ImageWidth, ImageHeight
+    0x0102, 0x0103, 0x0106,         // BitsPerSample, Compression, PhotometricInterpretation
+    0x0111, 0x0115, 0x0116, 0x0117, // StripOffsets, SamplesPerPixel, RowsPerStrip, StripByteCounts
+    0x011c,                         // PlanarConfiguration
+    0x0142, 0x0143, 0x0144, 0x0145, // TileWidth, TileLength, TileOffsets, TileByteCounts
+    0x014a,                         // SubIFDs
+    0x0153,                         // SampleFormat
+    0x0201, 0x0202,                 // JPEGInterchangeFormat, JPEGInterchangeFormatLength
+    0x0212,                         // YCbCrSubSampling
+];
+
+/// Where the offset to an IFD is stored: In the file header (IFD0), in the
+/// link section of the previous generic IFD or in an offset tag entry (e.g.
+/// 0x8769 for the ExifIFD) of the parent IFD. IFDs are referenced via their
+/// index in the vector of `RawIfd`s. 
+#[derive(Clone, Copy, Debug)]
+enum
+IfdPointer
+{
+    Header,
+    NextLink(usize),
+    Entry(usize, u16),
+}
+
+/// Offset and length of a value that is stored outside of its IFD entry
+type DataLocation = (u32, u32);
+
+/// A single IFD entry as it is stored in the file, where `value` is either
+/// the value itself or the offset to it
+#[derive(Clone, Debug)]
+struct
+RawIfdEntry
+{
+    tag:    u16,
+    format: u16,
+    count:  u32,
+    value:  [u8; 4],
+}
+
+/// An IFD as it is stored in the file. IFDs that get added by the edit have
+/// no `position` yet. 
+#[derive(Debug)]
+struct
+RawIfd
+{
+    group:            ExifTagGroup,
+    generic_ifd_nr:   u32,
+    position:         Option<u64>,
+    original_entries: usize,
+    entries:          Vec<RawIfdEntry>,
+    next_link:        u32,
+    pointer:          IfdPointer,
+    modified:         bool,
+}
+
+/// Collects the changes to a raw file before anything gets written: Patches
+/// of existing bytes and data that gets appended to the end of the file.
+struct
+RawFileEdit
+{
+    file_length: u64,
+    patches:     Vec<(u64, Vec<u8>)>,
+    appended:    Vec<u8>,
+}
+
+impl
+RawFileEdit
+{
+    /// Appends the data at a word boundary, as required by the TIFF 
+    /// specification for offsets, and returns the offset of the data
+    fn
+    append
+    (
+        &mut self,
+        data: &[u8]
+    )
+    -> Result<u32, std::io::Error>
+    {
+        if (self.file_length + self.appended.len() as u64) % 2 == 1
+        {
+            self.appended.push(0x00);
+        }
+
+        let offset = self.file_length + self.appended.len() as u64;
+        if offset + data.len() as u64 > u32::MAX as u64
+        {
+            return io_error!(Other, "Raw file is too large to append metadata!");
+        }
+
+        self.appended.extend(data);
+        return Ok(offset as u32);
+    }
+}
+
+/// Reads the TIFF-like header of a camera raw file, checks its magic number
+/// and returns the endianness and the offset of IFD0.
+/// CR2 files store additional information after the usual 8 bytes of the 
+/// TIFF header, which are not relevant here as IFD0 is found via the offset
+/// as usual. 
+fn
+read_header
+<T: Read + Seek>
+(
+    cursor: &mut T
+)
+-> Result<(Endian, u32), std::io::Error>
+{
+    let mut header = [0u8; 8];
+    cursor.seek(SeekFrom::Start(0))?;
+    cursor.read_exact(&mut header)?;
+
+    let endian = match header[0..2]
+    {
+        [0x49, 0x49] => Endian::Little,
+        [0x4d, 0x4d] => Endian::Big,
+        _            => return io_error!(InvalidData, format!("Illegal endian information in raw file: {:?}", &header[0..2]))
+    };
+
+    let magic_number = from_u8_vec_res_macro!(u16, &header[2..4], &endian)?;
+    if ![TIFF_MAGIC, ORF_MAGIC, ORF_SR_MAGIC, RW2_MAGIC].contains(&magic_number)
+    {
+        return io_error!(InvalidData, format!("Unknown magic number in raw file: 0x{magic_number:04x}"));
+    }
+
+    let ifd0_offset = from_u8_vec_res_macro!(u32, &header[4..8], &endian)?;
+    return Ok((endian, ifd0_offset));
+}
+
+/// Reads the header of a camera raw file and decodes the IFDs that it points
+/// to. The image data referenced by the strip tags is not read, so only the
+/// metadata has to be loaded into memory.
+fn
+generic_read_metadata
+<T: Read + Seek>
+(
+    cursor: &mut T
+)
+-> Result<Metadata, std::io::Error>
+{
+    let (endian, ifd0_offset) = read_header(cursor)?;
+    let ifds = Metadata::decode_ifds(cursor, 0, &endian, ifd0_offset, false)?;
+
+    return Ok(Metadata::new_from_decoded_ifds(endian, ifds));
+}
+
+/// Encodes an offset for the value section of an IFD entry
+fn
+offset_as_entry_value
+(
+    offset: u32,
+    endian: &Endian,
+)
+-> [u8; 4]
+{
+    let mut value = [0u8; 4];
+    value.copy_from_slice(&to_u8_vec_macro!(u32, &offset, endian));
+    return value;
+}
+
+/// Guards against IFDs that link back to an IFD that was already read
+fn
+is_already_read
+(
+    ifds:   &[RawIfd],
+    offset: u32,
+)
+-> bool
+{
+    return ifds.iter().any(|ifd| ifd.position == Some(offset as u64));
+}
+
+/// Reads the entry table of the IFD at the given offset and, recursively, 
+/// the SubIFDs it links to
+fn
+read_raw_ifd
+<T: Read + Seek>
+(
+    cursor:          &mut T,
+    endian:          &Endian,
+    offset:          u32,
+    group:           ExifTagGroup,
+    generic_ifd_nr:  u32,
+    pointer:         IfdPointer,
+    ifds:            &mut Vec<RawIfd>,
+)
+-> Result<(), std::io::Error>
+{
+    cursor.seek(SeekFrom::Start(offset as u64))?;
+
+    let mut count_buffer = [0u8; 2];
+    cursor.read_exact(&mut count_buffer)?;
+    let count = from_u8_vec_res_macro!(u16, &count_buffer, endian)? as usize;
+
+    let mut entry_buffer = vec![0u8; count * 12];
+    cursor.read_exact(&mut entry_buffer)?;
+
+    let mut entries = Vec::new();
+    for entry in entry_buffer.chunks_exact(12)
+    {
+        entries.push(RawIfdEntry {
+            tag:    from_u8_vec_res_macro!(u16, &entry[0..2], endian)?,
+            format: from_u8_vec_res_macro!(u16, &entry[2..4], endian)?,
+            count:  from_u8_vec_res_macro!(u32, &entry[4..8], endian)?,
+            value:  [entry[8], entry[9], entry[10], entry[11]],
+        });
+    }
+
+    // A SubIFD at the very end of the file may not have a link section
+    let mut link_buffer = [0u8; 4];
+    let next_link = match cursor.read_exact(&mut link_buffer)
+    {
+        Ok(_)  => from_u8_vec_res_macro!(u32, &link_buffer, endian)?,
+        Err(_) => 0,
+    };
+
+    let index = ifds.len();
+    ifds.push(RawIfd {
+        group,
+        generic_ifd_nr,
+        position:         Some(offset as u64),
+        original_entries: entries.len(),
+        entries:          entries.clone(),
+        next_link,
+        pointer,
+        modified:         false,
+    });
+
+    for entry in entries
+    {
+        let Ok(tag) = ExifTag::from_u16(entry.tag, &group) else { continue; };
+        if let TagType::IFD_OFFSET(subifd_group) = tag.get_tag_type()
+        {
+            let subifd_offset = from_u8_vec_res_macro!(u32, &entry.value, endian)?;
+            if is_already_read(ifds, subifd_offset)
+            {
+                log::warn!("IFD at offset {subifd_offset} was already read, skipping it");
+                continue;
+            }
+
+            read_raw_ifd(
+                cursor, 
+                endian, 
+                subifd_offset, 
+                subifd_group, 
+                generic_ifd_nr, 
+                IfdPointer::Entry(index, entry.tag), 
+                ifds
+            )?;
+        }
+    }
+
+    return Ok(());
+}
+
+/// Reads the chain of generic IFDs starting with IFD0, together with their
+/// SubIFDs. Only the entry tables are read, not the values. 
+fn
+read_raw_ifds
+<T: Read + Seek>
+(
+    cursor:      &mut T,
+    endian:      &Endian,
+    ifd0_offset: u32,
+)
+-> Result<Vec<RawIfd>, std::io::Error>
+{
+    let mut ifds = Vec::new();
+
+    let mut ifd_offset     = ifd0_offset;
+    let mut pointer        = IfdPointer::Header;
+    let mut generic_ifd_nr = 0;
+
+    while ifd_offset != 0
+    {
+        if is_already_read(&ifds, ifd_offset)
+        {
+            log::warn!("IFD at offset {ifd_offset} was already read, stopping here");
+            break;
+        }
+
+        let index = ifds.len();
+        read_raw_ifd(
+            cursor, 
+            endian, 
+            ifd_offset, 
+            ExifTagGroup::GENERIC, 
+            generic_ifd_nr, 
+            pointer, 
+            &mut ifds
+        )?;
+
+        ifd_offset      = ifds[index].next_link;
+        pointer         = IfdPointer::NextLink(index);
+        generic_ifd_nr += 1;
+    }
+
+    return Ok(ifds);
+}
+
+/// Reads the data of an entry and decodes it into a tag, the same way as
+/// `decode_ifd` does. Returns `None` if the entry can't be decoded, e.g. due
+/// to an unknown format, in which case it is considered to be different from
+/// any tag. Also returns the offset and length of the data if it is stored 
+/// outside of the entry. 
+fn
+decode_raw_entry
+<T: Read + Seek>
+(
+    cursor: &mut T,
+    endian: &Endian,
+    group:  &ExifTagGroup,
+    entry:  &RawIfdEntry,
+)
+-> Result<(Option<ExifTag>, Option<DataLocation>), std::io::Error>
+{
+    let Some(format) = ExifTagFormat::from_u16(entry.format) else {
+        return Ok((None, None));
+    };
+    let Some(byte_count) = format.bytes_per_component().checked_mul(entry.count) else {
+        return Ok((None, None));
+    };
+
+    let raw_data;
+    let mut data_location = None;
+    if byte_count > 4
+    {
+        let offset = from_u8_vec_res_macro!(u32, &entry.value, endian)?;
+        let mut data_buffer = vec![0u8; byte_count as usize];
+        cursor.seek(SeekFrom::Start(offset as u64))?;
+        if cursor.read_exact(&mut data_buffer).is_err()
+        {
+            return Ok((None, None));
+        }
+        raw_data      = data_buffer;
+        data_location = Some((offset, byte_count));
+    }
+    else
+    {
+        raw_data = entry.value[0..byte_count as usize].to_vec();
+    }
+
+    let tag = match ExifTag::from_u16(entry.tag, group)
+    {
+        Ok(tag) => decode_tag_with_format_exceptions(&tag, format, &raw_data, endian, entry.tag, group).ok(),
+        Err(_)  => ExifTag::from_u16_with_data(entry.tag, &format, &raw_data, endian, group).ok(),
+    };
+
+    return Ok((tag, data_location));
+}
+
+/// Checks if the tag of an entry may be removed from an IFD because it is 
+/// not in the metadata. This is not the case for offset tags, as these are
+/// not part of the metadata in the first place. In generic IFDs, the tags
+/// that describe the structure of the image data as well as unknown tags 
+/// (which may be needed for decoding the raw data) are kept as well. 
+fn
+is_removable
+(
+    tag:   u16,
+    group: &ExifTagGroup,
+)
+-> bool
+{
+    if *group == ExifTagGroup::GENERIC && IMAGE_STRUCTURE_TAGS.contains(&tag)
+    {
+        return false;
+    }
+
+    return match ExifTag::from_u16(tag, group)
+    {
+        Ok(known_tag) => known_tag.get_tag_type() == TagType::VALUE && known_tag.is_writable(),
+        Err(_)        => *group != ExifTagGroup::GENERIC,
+    };
+}
+
+/// Returns the index of the given IFD, adding it and its parent IFDs if they
+/// don't exist in the file yet. A new SubIFD gets linked via an offset tag
+/// in its parent, a new generic IFD via the link of the previous one. 
+fn
+find_or_create_raw_ifd
+(
+    ifds:           &mut Vec<RawIfd>,
+    group:          ExifTagGroup,
+    generic_ifd_nr: u32,
+)
+-> Result<usize, std::io::Error>
+{
+    if let Some(index) = ifds.iter().position(|ifd| 
+        ifd.group          == group && 
+        ifd.generic_ifd_nr == generic_ifd_nr
+    )
+    {
+        return Ok(index);
+    }
+
+    let new_ifd = ImageFileDirectory::new_with_tags(Vec::new(), group, generic_ifd_nr);
+    let pointer = if let Some((parent_group, offset_tag)) = new_ifd.get_offset_tag_for_parent_ifd()
+    {
+        let parent = find_or_create_raw_ifd(ifds, parent_group, generic_ifd_nr)?;
+        ifds[parent].entries.push(RawIfdEntry {
+            tag:    offset_tag.as_u16(),
+            format: ExifTagFormat::INT32U.as_u16(),
+            count:  1,
+            value:  [0x00; 4],
+        });
+        ifds[parent].modified = true;
+        IfdPointer::Entry(parent, offset_tag.as_u16())
+    }
+    else
+    {
+        let Some(previous) = ifds.iter().rposition(|ifd| 
+            ifd.group          == ExifTagGroup::GENERIC && 
+            ifd.generic_ifd_nr <  generic_ifd_nr
+        ) else {
+            return io_error!(InvalidData, format!("Can't link new generic IFD {generic_ifd_nr} in raw file!"));
+        };
+        IfdPointer::NextLink(previous)
+    };
+
+    ifds.push(RawIfd {
+        group,
+        generic_ifd_nr,
+        position:         None,
+        original_entries: 0,
+        entries:          Vec::new(),
+        next_link:        0,
+        pointer,
+        modified:         true,
+    });
+
+    return Ok(ifds.len() - 1);
+}
+
+/// Updates the entries of an IFD so that they match the given tags. Values
+/// that are unchanged are not touched at all. Changed values that fit into
+/// the space of the old value are patched in place, otherwise they get 
+/// appended to the end of the file. 
+/// Entries of the generic IFDs are never removed, as these also describe 
+/// the image data (e.g. `ImageWidth` or `Compression`).
+fn
+update_raw_ifd
+<T: Read + Seek>
+(
+    cursor: &mut T,
+    endian: &Endian,
+    ifd:    &mut RawIfd,
+    tags:   &[ExifTag],
+    edit:   &mut RawFileEdit,
+)
+-> Result<(), std::io::Error>
+{
+    let mut synced_tags = Vec::new();
+
+    for tag in tags
+    {
+        if tag.get_tag_type() != TagType::VALUE || !tag.is_writable() || synced_tags.contains(&tag.as_u16())
+        {
+            continue;
+        }
+        synced_tags.push(tag.as_u16());
+
+        let existing_entry = ifd.entries.iter().position(|entry| entry.tag == tag.as_u16());
+        let mut old_data_location = None;
+        if let Some(index) = existing_entry
+        {
+            let (old_tag, data_location) = decode_raw_entry(cursor, endian, &ifd.group, &ifd.entries[index])?;
+            if old_tag.as_ref() == Some(tag)
+            {
+                continue;
+            }
+            old_data_location = data_location;
+        }
+
+        let count      = tag.number_of_components();
+        let byte_count = count * tag.format().bytes_per_component();
+        let mut data   = tag.value_as_u8_vec(endian);
+        data.resize(byte_count as usize, 0x00);
+
+        let value = if byte_count <= 4
+        {
+            data.resize(4, 0x00);
+            [data[0], data[1], data[2], data[3]]
+        }
+        else if let Some((offset, old_byte_count)) = old_data_location.filter(|(_, old_byte_count)| byte_count <= *old_byte_count)
+        {
+            data.resize(old_byte_count as usize, 0x00);
+            edit.patches.push((offset as u64, data));
+            offset_as_entry_value(offset, endian)
+        }
+        else
+        {
+            let offset = edit.append(&data)?;
+            offset_as_entry_value(offset, endian)
+        };
+
+        let new_entry = RawIfdEntry {
+            tag:    tag.as_u16(),
+            format: tag.format().as_u16(),
+            count,
+            value,
+        };
+
+        match existing_entry
+        {
+            Some(index) => ifd.entries[index] = new_entry,
+            None        => ifd.entries.push(new_entry),
+        }
+        ifd.modified = true;
+    }
+
+    let group          = ifd.group;
+    let previous_count = ifd.entries.len();
+    ifd.entries.retain(|entry| synced_tags.contains(&entry.tag) || !is_removable(entry.tag, &group));
+    ifd.modified |= ifd.entries.len() != previous_count;
+
+    return Ok(());
+}
+
+/// Writes the entry tables of the modified IFDs. A table that is not longer
+/// than before is written in place, otherwise it gets appended to the end of
+/// the file and the pointer to it is updated. As pointers are stored in IFDs
+/// that were read (or created) before the IFD they point to, the IFDs are
+/// processed in reverse order. 
+fn
+write_raw_ifd_tables
+(
+    endian: &Endian,
+    ifds:   &mut [RawIfd],
+    edit:   &mut RawFileEdit,
+)
+-> Result<(), std::io::Error>
+{
+    for index in (0..ifds.len()).rev()
+    {
+        let ifd = &mut ifds[index];
+        if !ifd.modified
+        {
+            continue;
+        }
+
+        ifd.entries.sort_by_key(|entry| entry.tag);
+
+        let mut table = to_u8_vec_macro!(u16, &(ifd.entries.len() as u16), endian);
+        for entry in &ifd.entries
+        {
+            table.extend(to_u8_vec_macro!(u16, &entry.tag,    endian));
+            table.extend(to_u8_vec_macro!(u16, &entry.format, endian));
+            table.extend(to_u8_vec_macro!(u32, &entry.count,  endian));
+            table.extend(entry.value);
+        }
+        table.extend(to_u8_vec_macro!(u32, &ifd.next_link, endian));
+
+        if let Some(position) = ifd.position
+        {
+            if ifd.entries.len() <= ifd.original_entries
+            {
+                edit.patches.push((position, table));
+                continue;
+            }
+        }
+
+        let offset  = edit.append(&table)?;
+        let pointer = ifd.pointer;
+        ifd.position = Some(offset as u64);
+
+        match pointer
+        {
+            IfdPointer::Header => {
+                edit.patches.push((4, to_u8_vec_macro!(u32, &offset, endian)));
+            },
+            IfdPointer::NextLink(previous) => {
+                ifds[previous].next_link = offset;
+                ifds[previous].modified  = true;
+            },
+            IfdPointer::Entry(parent, tag) => {
+                if let Some(entry) = ifds[parent].entries.iter_mut().find(|entry| entry.tag == tag)
+                {
+                    entry.value = offset_as_entry_value(offset, endian);
+                }
+                ifds[parent].modified = true;
+            },
+        }
+    }
+
+    return Ok(());
+}
+
+/// Writes the metadata to a camera raw file without moving any of the 
+/// existing data, as maker notes and vendor tools rely on absolute offsets.
+/// Everything gets prepared first, so the file is only modified if the edit
+/// can be carried out completely. 
+fn
+generic_write_metadata
+<T: Read + Write + Seek>
+(
+    cursor:   &mut T,
+    metadata: &Metadata
+)
+-> Result<(), std::io::Error>
+{
+    let (endian, ifd0_offset) = read_header(cursor)?;
+    let mut ifds = read_raw_ifds(cursor, &endian, ifd0_offset)?;
+
+    let mut edit = RawFileEdit {
+        file_length: cursor.seek(SeekFrom::End(0))?,
+        patches:     Vec::new(),
+        appended:    Vec::new(),
+    };
+
+    // Add the IFDs that don't exist in the file yet
+    for ifd in metadata.get_ifds()
+    {
+        if ifd.get_tags().iter().any(|tag| tag.get_tag_type() == TagType::VALUE && tag.is_writable())
+        {
+            find_or_create_raw_ifd(&mut ifds, ifd.get_ifd_type(), ifd.get_generic_ifd_nr())?;
+        }
+    }
+
+    // IFDs in the file that are not part of the metadata are treated as if
+    // they were empty
+    for raw_ifd in ifds.iter_mut()
+    {
+        let tags = metadata.get_ifd(raw_ifd.group, raw_ifd.generic_ifd_nr)
+            .map(|ifd| ifd.get_tags().as_slice())
+            .unwrap_or_default();
+        update_raw_ifd(cursor, &endian, raw_ifd, tags, &mut edit)?;
+    }
+
+    write_raw_ifd_tables(&endian, &mut ifds, &mut edit)?;
+
+    // Write the appended data first, so that nothing in the file refers to
+    // data that does not exist yet
+    cursor.seek(SeekFrom::End(0))?;
+    cursor.write_all(&edit.appended)?;
+
+    for (position, data) in edit.patches
+    {
+        cursor.seek(SeekFrom::Start(position))?;
+        cursor.write_all(&data)?;
+    }
+
+    return Ok(());
+}
+
+pub(crate) fn
+read_metadata
+(
+    file_buffer: &[u8]
+)
+-> Result<Metadata, std::io::Error>
+{
+    return generic_read_metadata(&mut Cursor::new(file_buffer));
+}
+
+pub(crate) fn
+file_read_metadata
+(
+    path: &Path
+)
+-> Result<Metadata, std::io::Error>
+{
+    return generic_read_metadata(&mut BufReader::new(open_read_file(path)?));
+}
+
+pub(crate) fn
+write_metadata
+(
+    file_buffer: &mut Vec<u8>,
+    metadata:    &Metadata
+)
+-> Result<(), std::io::Error>
+{
+    return generic_write_metadata(&mut Cursor::new(file_buffer), metadata);
+}
+
+pub(crate) fn
+file_write_metadata
+(
+    path:     &Path,
+    metadata: &Metadata
+)
+-> Result<(), std::io::Error>
+{
+    return generic_write_metadata(&mut open_write_file(path)?, metadata);
+}
@@ -297,6 +297,85 @@ where T: Copy
     vec.truncate(new_vec_len);
 }
 
+/// Computes the MD5 digest of the given data as specified in RFC 1321.
+/// Only used for identification purposes (e.g. the GUID of extended XMP
+/// data in JPEG files), not for anything security related.
+pub(crate) fn
+md5
+(
+    data: &[u8]
+)
+-> [u8; 16]
+{
+    const SHIFTS: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22,
+        5,  9, 14, 20, 5,  9, 14, 20, 5,  9, 14, 20, 5,  9, 14, 20,
+        4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23,
+        6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+
+    // Constants derived from the sine function, see RFC 1321
+    let constants: Vec<u32> = (0..64)
+        .map(|i| ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32)
+        .collect();
+
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+    // Pad the message to a multiple of 64 bytes, ending with the bit length
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56
+    {
+        message.push(0x00);
+    }
+    message.extend(((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    for block in message.chunks_exact(64)
+    {
+        let words: Vec<u32> = block
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect();
+
+        let [mut a, mut b, mut c, mut d] = state;
+
+        for i in 0..64
+        {
+            let (f, g) = match i / 16
+            {
+                0 => ((b & c) | (!b & d),  i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d,          (3 * i + 5) % 16),
+                _ => (c ^ (b | !d),       (7 * i)     % 16),
+            };
+
+            let rotated = a
+                .wrapping_add(f)
+                .wrapping_add(constants[i])
+                .wrapping_add(words[g])
+                .rotate_left(SHIFTS[i]);
+
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(rotated);
+        }
+
+        state[0] = state[0].wrapping_add(a);
+        state[1] = state[1].wrapping_add(b);
+        state[2] = state[2].wrapping_add(c);
+        state[3] = state[3].wrapping_add(d);
+    }
+
+    let mut digest = [0u8; 16];
+    for (index, value) in state.iter().enumerate()
+    {
+        digest[4*index..4*index+4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    return digest;
+}
+
 /*
 /// Inserts a slice into a vector at a given offset, shifting elements 
 /// starting at the offset towards the end.
@@ -370,4 +449,34 @@ where T: Copy
         },
     }
 }
-*/
\ No newline at end of file
+*/
+
+#[cfg(test)]
+mod tests 
+{
+    use super::md5;
+
+    fn
+    to_hex_string
+    (
+        digest: &[u8]
+    )
+    -> String
+    {
+        return digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    }
+
+    #[test]
+    fn
+    md5_test_suite()
+    {
+        // Test suite from RFC 1321, Appendix A.5
+        assert_eq!(to_hex_string(&md5(b"")),    "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(to_hex_string(&md5(b"a")),   "0cc175b9c0f1b6a831c399e269772661");
+        assert_eq!(to_hex_string(&md5(b"abc")), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(
+            to_hex_string(&md5(b"12345678901234567890123456789012345678901234567890123456789012345678901234567890")), 
+            "57edf4a22be3c955ac49da2e2107b67a"
+        );
+    }
+}
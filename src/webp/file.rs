@@ -10,11 +10,14 @@ use std::path::Path;
 
 use crate::endian::*;
 use crate::metadata::Metadata;
+use crate::metadata::write_options::WriteOptions;
+use crate::metadata::xmp_io::XmpSyncMode;
 use crate::u8conversion::*;
 use crate::general_file_io::*;
 use super::riff_chunk::RiffChunk;
 use super::riff_chunk::RiffChunkDescriptor;
 use super::*;
+use crate::xmp::sync_xmp_of_image;
 
 /// A WebP file starts as follows
 /// - The RIFF signature: ASCII characters "R", "I", "F", "F"  -> 4 bytes
@@ -406,18 +409,21 @@ convert_to_extended_format
 {
     // Start by getting the first chunk of the WebP file
     file.seek(SeekFrom::Start(12))?;
-    let first_chunk = get_next_chunk(file)?;
+    let mut first_chunk = get_next_chunk(file)?;
 
-    // Find out what simple type of WebP file we are dealing with
-    let (width, height) = match first_chunk.descriptor().header().as_str()
+    // An ALPH chunk is only allowed in the extended format, but just in case
+    // there is one without a VP8X chunk, the 'VP8 ' chunk follows it
+    let has_alph_chunk = first_chunk.descriptor().header() == "ALPH";
+    if has_alph_chunk
     {
-        "VP8 " 
-            => get_dimension_info_from_vp8_chunk(first_chunk.payload()),
-        "VP8L"
-            => get_dimension_info_from_vp8l_chunk(first_chunk.payload()),
-        _ 
-            => io_error!(Other, "Expected either 'VP8 ' or 'VP8L' chunk for conversion!")
-    }?;
+        first_chunk = get_next_chunk(file)?;
+    }
+
+    // Find out what simple type of WebP file we are dealing with
+    let (width, height, uses_alpha) = get_canvas_info_from_simple_format(
+        first_chunk.descriptor().header().as_str(),
+        first_chunk.payload()
+    )?;
 
     let width_vec  = to_u8_vec_macro!(u32, &width,  &Endian::Little);
     let height_vec = to_u8_vec_macro!(u32, &height, &Endian::Little);
@@ -428,6 +434,11 @@ convert_to_extended_format
         0x00, 0x00, 0x00, 0x00, // Flags and reserved area                         -> 4 byte
     ];
 
+    if has_alph_chunk || uses_alpha
+    {
+        vp8x_chunk[8] |= ALPHA_FLAG;
+    }
+
     // Add the two 24 bits for width and height information
     for byte in  width_vec.iter().take(3) { vp8x_chunk.push(*byte); }
     for byte in height_vec.iter().take(3) { vp8x_chunk.push(*byte); }
@@ -459,42 +470,6 @@ convert_to_extended_format
 
 
 
-fn
-get_dimension_info_from_vp8l_chunk
-(
-    payload: &[u8],
-)
--> Result<(u32, u32), std::io::Error>
-{
-    // Get the 4 bytes containing the dimension information
-    // (although we only need 28 bits)
-    // Starting at byte 1 instead of 0 due to the 0x2F byte
-    // See: https://developers.google.com/speed/webp/docs/webp_lossless_bitstream_specification#3_riff_header
-    let width_height_info_buffer = payload[1..5].to_vec();
-    
-    // Convert to a single u32 number for bit-mask operations
-    let width_height_info = from_u8_vec_res_macro!(u32, &width_height_info_buffer, &Endian::Little)?;
-    
-    let mut width  = 0;
-    let mut height = 0;
-
-    // Get the first 14 bit to construct the width
-    for bit_index in 0..14
-    {
-        width  |= ((width_height_info >> (27 - bit_index)) & 0x01) << (13 - (bit_index % 14));
-    }
-
-    // Get the next 14 bit to construct the height
-    for bit_index in 14..28
-    {
-        height |= ((width_height_info >> (27 - bit_index)) & 0x01) << (13 - (bit_index % 14));
-    }
-
-    return Ok((width, height));
-}
-
-
-
 fn
 set_exif_flag
 (
@@ -585,8 +560,9 @@ clear_metadata
     // Compute a delta of how much the file size information has to change
     let mut delta = 0i32;
 
-    // Skip the WEBP signature
-    file.seek(std::io::SeekFrom::Current(4i64))?;
+    // Skip the RIFF header and WEBP signature, as the cursor is still located
+    // after the VP8X flags
+    file.seek(SeekFrom::Start(12u64))?;
 
     for parsed_chunk in parse_webp_result
     {
@@ -654,7 +630,8 @@ pub(crate) fn
 write_metadata
 (
     path:     &Path,
-    metadata: &Metadata
+    metadata: &Metadata,
+    options:  &WriteOptions
 )
 -> Result<(), std::io::Error>
 {
@@ -664,54 +641,13 @@ write_metadata
     // Encode the general metadata format to WebP specifications
     let encoded_metadata = encode_metadata_webp(&metadata.encode()?);
 
-    // Open the file...
-    let mut file = check_signature(path)?;
-
-    // ...and find a location where to put the EXIF chunk
-    // This is done by requesting a chunk descriptor as long as we find a chunk
-    // that is both known and should be located *before* the EXIF chunk
-    let pre_exif_chunks = [
-        "VP8X",
-        "VP8",
-        "VP8L",
-        "ICCP",
-        "ANIM"
-    ];
-
-    loop
-    {
-        // Request a chunk descriptor. If this fails, check the error 
-        // Depending on its type, either continue normally or return it
-        let chunk_descriptor_result = get_next_chunk_descriptor(&mut file);
-
-        match chunk_descriptor_result
-        {
-            Ok(chunk_descriptor) => {
-                let mut chunk_type_found_in_pre_exif_chunks = false;
-
-                // Check header of chunk descriptor against any of the known chunks
-                // that should come before the EXIF chunk
-                for pre_exif_chunk in &pre_exif_chunks
-                {
-                    chunk_type_found_in_pre_exif_chunks |= pre_exif_chunk.to_lowercase() == chunk_descriptor.header().to_lowercase();
-                }
+    // Find a location where to put the EXIF chunk, which is after the image
+    // data or the frames of an animation...
+    let insert_position = get_exif_insert_position(&parse_webp(path)?);
 
-                if !chunk_type_found_in_pre_exif_chunks
-                {
-                    break;
-                }
-            },
-            Err(e) => {
-                match e.kind()
-                {
-                    std::io::ErrorKind::UnexpectedEof
-                        => break, // No further chunks, place EXIF chunk here
-                    _
-                        => return Err(e)
-                }
-            }
-        }
-    }
+    // ...and open the file
+    let mut file = check_signature(path)?;
+    file.seek(SeekFrom::Start(insert_position as u64))?;
 
     // Next, read remaining file into a buffer...
     let current_file_cursor = SeekFrom::Start(file.stream_position()?);
@@ -736,6 +672,18 @@ write_metadata
     // Finally, set the EXIF flag
     set_exif_flag(path, true)?;
 
+    // Keep the XMP data in sync with the new EXIF data, if requested
+    if options.xmp_sync_mode != XmpSyncMode::Disabled
+    {
+        modify_file(path, |file_buffer| sync_xmp_of_image(
+            file_buffer, 
+            metadata, 
+            options.xmp_sync_mode, 
+            &super::vec::read_xmp, 
+            &super::vec::write_xmp
+        ))?;
+    }
+
     return Ok(());
 }
 
@@ -10,6 +10,20 @@ pub(crate) const RIFF_SIGNATURE:       [u8; 4] = [0x52, 0x49, 0x46, 0x46];
 pub(crate) const WEBP_SIGNATURE:       [u8; 4] = [0x57, 0x45, 0x42, 0x50];
 pub(crate) const VP8X_HEADER:          &str    = "VP8X";
 pub(crate) const EXIF_CHUNK_HEADER:    &str    = "EXIF";
+pub(crate) const ICCP_CHUNK_HEADER:    &str    = "ICCP";
+pub(crate) const XMP_CHUNK_HEADER:     &str    = "XMP ";
+
+// Flags in the VP8X chunk, see:
+// https://developers.google.com/speed/webp/docs/riff_container#extended_file_format
+pub(crate) const ICC_FLAG:             u8      = 0x20;
+pub(crate) const ALPHA_FLAG:           u8      = 0x10;
+pub(crate) const EXIF_FLAG:            u8      = 0x08;
+pub(crate) const XMP_FLAG:             u8      = 0x04;
+
+// Chunks that have to precede the EXIF (and XMP) chunk, i.e. everything up
+// to and including the image data or - for animations - the frames, see:
+// https://developers.google.com/speed/webp/docs/riff_container#extended_file_format
+const PRE_EXIF_CHUNKS: [&str; 7] = ["VP8X", "ICCP", "ANIM", "ANMF", "ALPH", "VP8 ", "VP8L"];
 
 use std::fs::File;
 
@@ -20,6 +34,8 @@ use crate::u8conversion::from_u8_vec_res_macro;
 use crate::u8conversion::to_u8_vec_macro;
 use crate::u8conversion::U8conversion;
 
+use riff_chunk::RiffChunkDescriptor;
+
 fn
 check_riff_signature
 (
@@ -130,6 +146,63 @@ encode_metadata_webp
 
 
 
+/// Determines the position right after the last chunk of the given types.
+/// If there is no such chunk, this is the position of the first chunk.
+fn
+get_insert_position_after
+(
+    chunks:           &[RiffChunkDescriptor],
+    preceding_chunks: &[&str],
+)
+-> usize
+{
+    // Skip the RIFF header and WEBP signature
+    let mut position        = 12;
+    let mut insert_position = 12;
+
+    for chunk in chunks
+    {
+        // fourCC, size information and payload (including padding)
+        position += 8 + chunk.len();
+
+        if preceding_chunks.contains(&chunk.header().as_str())
+        {
+            insert_position = position;
+        }
+    }
+
+    return insert_position;
+}
+
+/// Determines the position right after the last chunk that has to precede
+/// the EXIF chunk. Unknown chunks following the image data or the last frame
+/// of an animation, as well as an XMP chunk, stay behind the new EXIF chunk.
+fn
+get_exif_insert_position
+(
+    chunks: &[RiffChunkDescriptor]
+)
+-> usize
+{
+    return get_insert_position_after(chunks, &PRE_EXIF_CHUNKS);
+}
+
+/// Determines the position right after the image data (or the frames of an
+/// animation) and the EXIF chunk, where the XMP chunk has to be placed
+fn
+get_xmp_insert_position
+(
+    chunks: &[RiffChunkDescriptor]
+)
+-> usize
+{
+    let mut preceding_chunks = PRE_EXIF_CHUNKS.to_vec();
+    preceding_chunks.push(EXIF_CHUNK_HEADER);
+    return get_insert_position_after(chunks, &preceding_chunks);
+}
+
+
+
 /// Provides the WebP specific encoding result as vector of bytes to be used
 /// by the user (e.g. in combination with another library)
 pub(crate) fn
@@ -142,6 +215,28 @@ as_u8_vec
     encode_metadata_webp(general_encoded_metadata)
 }
 
+/// Determines the canvas width and height (each minus one, as stored in the
+/// VP8X chunk) as well as whether the image uses an alpha channel, based on
+/// the image data chunk of a WebP file in the simple file format
+pub(crate) fn
+get_canvas_info_from_simple_format
+(
+    header:  &str,
+    payload: &[u8],
+)
+-> Result<(u32, u32, bool), std::io::Error>
+{
+    match header
+    {
+        "VP8 " => {
+            let (width, height) = get_dimension_info_from_vp8_chunk(payload)?;
+            return Ok((width, height, false));
+        },
+        "VP8L" => return get_dimension_info_from_vp8l_chunk(payload),
+        _      => return io_error!(Other, format!("Expected either 'VP8 ' or 'VP8L' chunk for conversion but got {:?}!", header)),
+    }
+}
+
 fn
 get_dimension_info_from_vp8_chunk
 (
@@ -155,10 +250,21 @@ get_dimension_info_from_vp8_chunk
     // VP8 Data Format https://datatracker.ietf.org/doc/html/rfc6386#section-9.1
     // Parsing function function vp8_parse_frame_header: https://datatracker.ietf.org/doc/html/rfc6386#section-20.4
 
+    if payload.len() < 10
+    {
+        return io_error!(InvalidData, "VP8 chunk is too short to contain a frame header!");
+    }
+
+    // The first bit of the 3 byte frame tag is zero for key frames, which
+    // are the only ones that contain the dimensions (and the only ones 
+    // allowed in a WebP file)
+    if payload[0] & 0x01 != 0
+    {
+        return io_error!(InvalidData, "Expected VP8 key frame!");
+    }
+
     let header_magic = payload[3..=5].to_vec();
-    if 
-        header_magic.len() != 3 || 
-        !matches!(header_magic.as_slice(), &[0x9d, 0x01, 0x2a]) 
+    if !matches!(header_magic.as_slice(), &[0x9d, 0x01, 0x2a]) 
     {
         return io_error!(Other, "Invalid VP8 Frame Header Magic");
     }
@@ -174,5 +280,37 @@ get_dimension_info_from_vp8_chunk
     let width  = width_info  & bitmask_14;
     let height = height_info & bitmask_14;
 
+    if width == 0 || height == 0
+    {
+        return io_error!(InvalidData, "VP8 frame header specifies zero width or height!");
+    }
+
     return Ok((width as u32 -1, height as u32 -1));
-}
\ No newline at end of file
+}
+
+fn
+get_dimension_info_from_vp8l_chunk
+(
+    payload: &[u8],
+)
+-> Result<(u32, u32, bool), std::io::Error>
+{
+    // Get the 4 bytes following the 0x2F signature byte, which contain (in 
+    // this order, starting with the least significant bit) the width minus
+    // one (14 bit), the height minus one (14 bit), whether alpha is used 
+    // (1 bit) and the version (3 bit)
+    // See: https://developers.google.com/speed/webp/docs/webp_lossless_bitstream_specification#3_riff_header
+    let Some(width_height_info_buffer) = payload.get(1..5) else {
+        return io_error!(InvalidData, "VP8L chunk is too short to contain a header!");
+    };
+
+    // Convert to a single u32 number for bit-mask operations
+    let width_height_info = from_u8_vec_res_macro!(u32, width_height_info_buffer, &Endian::Little)?;
+
+    let bitmask_14 = (1 << 14) - 1;
+    let width      =  width_height_info        & bitmask_14;
+    let height     = (width_height_info >> 14) & bitmask_14;
+    let uses_alpha = (width_height_info >> 28) & 0x01 == 1;
+
+    return Ok((width, height, uses_alpha));
+}
@@ -8,8 +8,11 @@ use std::io::Write;
 
 use crate::general_file_io::EXIF_HEADER;
 use crate::metadata::Metadata;
+use crate::metadata::write_options::WriteOptions;
+use crate::metadata::xmp_io::XmpSyncMode;
 use crate::util::insert_multiple_at;
 use crate::util::range_remove;
+use crate::xmp::sync_xmp_of_image;
 
 use super::riff_chunk::RiffChunk;
 use super::riff_chunk::RiffChunkDescriptor;
@@ -391,18 +394,21 @@ convert_to_extended_format
     #[allow(clippy::useless_asref)]
     let mut read_cursor = Cursor::new(cursor.get_ref().as_ref());
     read_cursor.set_position(12);
-    let first_chunk = get_next_chunk(&mut read_cursor)?;
+    let mut first_chunk = get_next_chunk(&mut read_cursor)?;
 
-    // Find out what simple type of WebP file we are dealing with
-    let (width, height) = match first_chunk.descriptor().header().as_str()
+    // An ALPH chunk is only allowed in the extended format, but just in case
+    // there is one without a VP8X chunk, the 'VP8 ' chunk follows it
+    let has_alph_chunk = first_chunk.descriptor().header() == "ALPH";
+    if has_alph_chunk
     {
-        "VP8 "
-            => {log::debug!("VP8 !"); io_error!(Other, "Conversion from Simple File Format with 'VP8' chunk to Extended File Format not yet implemented!") },
-        "VP8L"
-            => get_dimension_info_from_vp8l_chunk(first_chunk.payload()),
-        _ 
-            => io_error!(Other, format!("Expected either 'VP8 ' or 'VP8L' chunk for conversion but got {:?}!", first_chunk.descriptor().header().as_str()))
-    }?;
+        first_chunk = get_next_chunk(&mut read_cursor)?;
+    }
+
+    // Find out what simple type of WebP file we are dealing with
+    let (width, height, uses_alpha) = get_canvas_info_from_simple_format(
+        first_chunk.descriptor().header().as_str(),
+        first_chunk.payload()
+    )?;
 
     let width_vec  = to_u8_vec_macro!(u32, &width,  &Endian::Little);
     let height_vec = to_u8_vec_macro!(u32, &height, &Endian::Little);
@@ -413,6 +419,11 @@ convert_to_extended_format
         0x00, 0x00, 0x00, 0x00, // Flags and reserved area                         -> 4 byte
     ];
 
+    if has_alph_chunk || uses_alpha
+    {
+        vp8x_chunk[8] |= ALPHA_FLAG;
+    }
+
     // Add the two 24 bits for width and height information
     for byte in  width_vec.iter().take(3) { vp8x_chunk.push(*byte); }
     for byte in height_vec.iter().take(3) { vp8x_chunk.push(*byte); }
@@ -429,46 +440,27 @@ convert_to_extended_format
 
 
 fn
-get_dimension_info_from_vp8l_chunk
+set_exif_flag
 (
-    payload: &[u8],
+    cursor: &mut Cursor<&mut Vec<u8>>,
+    exif_flag_value: bool
 )
--> Result<(u32, u32), std::io::Error>
+-> Result<(), std::io::Error>
 {
-    // Get the 4 bytes containing the dimension information
-    // (although we only need 28 bits)
-    // Starting at byte 1 instead of 0 due to the 0x2F byte
-    // See: https://developers.google.com/speed/webp/docs/webp_lossless_bitstream_specification#3_riff_header
-    let width_height_info_buffer = payload[1..5].to_vec();
-    
-    // Convert to a single u32 number for bit-mask operations
-    let width_height_info = from_u8_vec_res_macro!(u32, &width_height_info_buffer, &Endian::Little)?;
-    
-    let mut width  = 0;
-    let mut height = 0;
-
-    // Get the first 14 bit to construct the width
-    for bit_index in 0..14
-    {
-        width  |= ((width_height_info >> (27 - bit_index)) & 0x01) << (13 - (bit_index % 14));
-    }
-
-    // Get the next 14 bit to construct the height
-    for bit_index in 14..28
-    {
-        height |= ((width_height_info >> (27 - bit_index)) & 0x01) << (13 - (bit_index % 14));
-    }
-
-    return Ok((width, height));
+    return set_vp8x_flag(cursor, EXIF_FLAG, exif_flag_value);
 }
 
 
 
+/// Sets or unsets the given flag (e.g. `EXIF_FLAG`) in the VP8X chunk. If 
+/// the file does not have a VP8X chunk yet, it gets converted to the 
+/// Extended File Format first.
 fn
-set_exif_flag
+set_vp8x_flag
 (
-    cursor: &mut Cursor<&mut Vec<u8>>,
-    exif_flag_value: bool
+    cursor:     &mut Cursor<&mut Vec<u8>>,
+    flag:       u8,
+    flag_value: bool
 )
 -> Result<(), std::io::Error>
 {
@@ -492,16 +484,16 @@ set_exif_flag
     }	
 
     // At this point we know that we have a VP8X chunk at the expected location
-    // Mask the old flag by either or-ing with 1 at the EXIF flag position for
-    // setting it to true, or and-ing with 1 everywhere but the EXIF flag pos
+    // Mask the old flag by either or-ing with 1 at the flag position for
+    // setting it to true, or and-ing with 1 everywhere but the flag position
     // to set it to false
-    cursor.get_mut()[20] = if exif_flag_value
+    cursor.get_mut()[20] = if flag_value
     {
-        cursor.get_ref()[20] | 0x08
+        cursor.get_ref()[20] | flag
     }
     else
     {
-        cursor.get_ref()[20] & 0b11110111
+        cursor.get_ref()[20] & !flag
     };
 
     Ok(())
@@ -542,8 +534,8 @@ clear_metadata
     // Compute a delta of how much the file size information has to change
     let mut delta = 0i32;
 
-    // Skip the WEBP signature
-    cursor.set_position(4);
+    // Skip the RIFF header and WEBP signature
+    cursor.set_position(12);
 
     for parsed_chunk in parse_webp_result
     {
@@ -594,7 +586,8 @@ pub(crate) fn
 write_metadata
 (
     file_buffer: &mut Vec<u8>,
-    metadata:    &Metadata
+    metadata:    &Metadata,
+    options:     &WriteOptions
 )
 -> Result<(), std::io::Error>
 {
@@ -605,63 +598,12 @@ write_metadata
     let mut encoded_metadata = encode_metadata_webp(&metadata.encode()?);
     let encoded_metadata_len = encoded_metadata.len() as i32;
 
-    // Find a location where to put the EXIF chunk
-    // This is done by requesting a chunk descriptor as long as we find a chunk
-    // that is both known and should be located *before* the EXIF chunk
-    let pre_exif_chunks = [
-        "VP8X",
-        "VP8",
-        "VP8L",
-        "ICCP",
-        "ANIM"
-    ];
-
-    // Clippy wrongly needs this allowance, otherwise it states that this call
-    // to as_ref does nothing. However, it *does* something, mainly 
-    // creating an immutable reference that gets passed as a copy to the
-    // constructor of the cursor, so file_buffer is later on still available
-    // when calling insert_multiple_at.
-    // I guess there is a less messy solution to this?
-    #[allow(clippy::useless_asref)]
-    let mut read_cursor = Cursor::new(file_buffer.as_ref());
-
-    loop
-    {
-        // Request a chunk descriptor. If this fails, check the error 
-        // Depending on its type, either continue normally or return it
-        let chunk_descriptor_result = get_next_chunk_descriptor(&mut read_cursor);
-
-        match chunk_descriptor_result
-        {
-            Ok(chunk_descriptor) => {
-                let mut chunk_type_found_in_pre_exif_chunks = false;
-
-                // Check header of chunk descriptor against any of the known chunks
-                // that should come before the EXIF chunk
-                for pre_exif_chunk in &pre_exif_chunks
-                {
-                    chunk_type_found_in_pre_exif_chunks |= pre_exif_chunk.to_lowercase() == chunk_descriptor.header().to_lowercase();
-                }
-
-                if !chunk_type_found_in_pre_exif_chunks
-                {
-                    break;
-                }
-            },
-            Err(e) => {
-                match e.kind()
-                {
-                    std::io::ErrorKind::UnexpectedEof
-                        => break, // No further chunks, place EXIF chunk here
-                    _
-                        => return Err(e)
-                }
-            }
-        }
-    }
+    // Find a location where to put the EXIF chunk, which is after the image
+    // data or the frames of an animation
+    let insert_position = get_exif_insert_position(&parse_webp(file_buffer)?);
 
     // Write the EXIF chunk at the found location
-    insert_multiple_at(file_buffer, read_cursor.position() as usize, &mut encoded_metadata);
+    insert_multiple_at(file_buffer, insert_position, &mut encoded_metadata);
 
     // Update the file size information by adding the byte count of the EXIF chunk
     // (Note: Due to  the WebP specific encoding function, this vector already
@@ -669,11 +611,224 @@ write_metadata
     // possible padding byte. Therefore, simply taking the length of this
     // vector takes their byte count also into account and no further values
     // need to be added)
-    let mut write_cursor = Cursor::new(file_buffer);
+    let mut write_cursor = Cursor::new(&mut *file_buffer);
     update_file_size_information(&mut write_cursor, encoded_metadata_len)?;
 
     // Finally, set the EXIF flag
     set_exif_flag(&mut write_cursor, true)?;
 
+    // Keep the XMP data in sync with the new EXIF data, if requested
+    if options.xmp_sync_mode != XmpSyncMode::Disabled
+    {
+        sync_xmp_of_image(
+            file_buffer, 
+            metadata, 
+            options.xmp_sync_mode, 
+            &read_xmp, 
+            &write_xmp
+        )?;
+    }
+
+    return Ok(());
+}
+
+
+
+/// Locates the first chunk with the given fourCC. Returns the position of the
+/// chunk and the size of its payload (without the possible padding byte).
+fn
+find_chunk
+(
+    file_buffer: &Vec<u8>,
+    chunk_type:  &str
+)
+-> Result<Option<(usize, usize)>, std::io::Error>
+{
+    check_signature(file_buffer)?;
+
+    let mut position = 12usize;
+    while position + 8 <= file_buffer.len()
+    {
+        let chunk_size = from_u8_vec_res_macro!(
+            u32, 
+            &file_buffer[position+4..position+8], 
+            &Endian::Little
+        )? as usize;
+
+        if &file_buffer[position..position+4] == chunk_type.as_bytes()
+        {
+            if position + 8 + chunk_size > file_buffer.len()
+            {
+                return io_error!(UnexpectedEof, format!("{} chunk exceeds end of WebP data!", chunk_type));
+            }
+            return Ok(Some((position, chunk_size)));
+        }
+
+        position += 8 + chunk_size + chunk_size % 2;
+    }
+
+    return Ok(None);
+}
+
+/// Reads the ICC profile stored in the ICCP chunk
+pub(crate) fn
+read_icc_profile
+(
+    file_buffer: &Vec<u8>
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    match find_chunk(file_buffer, ICCP_CHUNK_HEADER)?
+    {
+        Some((position, size))
+            => return Ok(file_buffer[position+8..position+8+size].to_vec()),
+        None
+            => return io_error!(NotFound, "No ICC profile found!"),
+    }
+}
+
+/// Removes all chunks with the given fourCC and unsets the corresponding flag
+/// in the VP8X chunk
+fn
+remove_chunks
+(
+    file_buffer: &mut Vec<u8>,
+    chunk_type:  &str,
+    flag:        u8
+)
+-> Result<(), std::io::Error>
+{
+    // The file size needs to be updated after each removal as locating the
+    // next chunk checks that it is consistent with the buffer length
+    while let Some((position, size)) = find_chunk(file_buffer, chunk_type)?
+    {
+        let chunk_byte_count = 8 + size + size % 2;
+        range_remove(file_buffer, position, position + chunk_byte_count);
+        update_file_size_information(
+            &mut Cursor::new(&mut *file_buffer), 
+            -(chunk_byte_count as i32)
+        )?;
+    }
+
+    // Simple File Format WebPs can't contain such chunks, so there is no
+    // flag that would need to be unset
+    if file_buffer.get(12..16) != Some(VP8X_HEADER.as_bytes())
+    {
+        return Ok(());
+    }
+
+    return set_vp8x_flag(&mut Cursor::new(file_buffer), flag, false);
+}
+
+/// Encodes the payload as chunk with the given fourCC, including the size
+/// information and the possible padding byte
+fn
+encode_chunk
+(
+    chunk_type: &str,
+    payload:    &[u8]
+)
+-> Vec<u8>
+{
+    let mut chunk = chunk_type.as_bytes().to_vec();
+    chunk.extend(to_u8_vec_macro!(u32, &(payload.len() as u32), &Endian::Little));
+    chunk.extend(payload.iter());
+    if payload.len() % 2 != 0
+    {
+        chunk.push(0x00);
+    }
+    return chunk;
+}
+
+/// Removes the ICCP chunk(s) and unsets the ICC flag in the VP8X chunk
+pub(crate) fn
+remove_icc_profile
+(
+    file_buffer: &mut Vec<u8>
+)
+-> Result<(), std::io::Error>
+{
+    return remove_chunks(file_buffer, ICCP_CHUNK_HEADER, ICC_FLAG);
+}
+
+/// Writes the ICC profile as ICCP chunk, replacing any previously stored 
+/// profile. According to the specification, this chunk has to come right 
+/// after the VP8X chunk. 
+pub(crate) fn
+write_icc_profile
+(
+    file_buffer: &mut Vec<u8>,
+    icc_profile: &[u8]
+)
+-> Result<(), std::io::Error>
+{
+    remove_icc_profile(file_buffer)?;
+
+    // Sets the flag and converts the file to the Extended File Format, if 
+    // required, so the VP8X chunk with its 10 bytes of payload exists
+    let mut cursor = Cursor::new(file_buffer);
+    set_vp8x_flag(&mut cursor, ICC_FLAG, true)?;
+
+    let mut iccp_chunk = encode_chunk(ICCP_CHUNK_HEADER, icc_profile);
+    let iccp_chunk_len = iccp_chunk.len() as i32;
+
+    insert_multiple_at(cursor.get_mut(), 12 + 8 + 10, &mut iccp_chunk);
+    update_file_size_information(&mut cursor, iccp_chunk_len)?;
+
+    return Ok(());
+}
+
+/// Reads the XMP data stored in the `XMP ` chunk
+pub(crate) fn
+read_xmp
+(
+    file_buffer: &Vec<u8>
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+    match find_chunk(file_buffer, XMP_CHUNK_HEADER)?
+    {
+        Some((position, size))
+            => return Ok(file_buffer[position+8..position+8+size].to_vec()),
+        None
+            => return io_error!(NotFound, "No XMP data found!"),
+    }
+}
+
+/// Removes the `XMP ` chunk(s) and unsets the XMP flag in the VP8X chunk
+pub(crate) fn
+remove_xmp
+(
+    file_buffer: &mut Vec<u8>
+)
+-> Result<(), std::io::Error>
+{
+    return remove_chunks(file_buffer, XMP_CHUNK_HEADER, XMP_FLAG);
+}
+
+/// Writes the XMP data as `XMP ` chunk, replacing any previously stored XMP
+/// data. The chunk gets placed after the image data (or the frames of an 
+/// animation) and the EXIF chunk, as required by the specification. 
+pub(crate) fn
+write_xmp
+(
+    file_buffer: &mut Vec<u8>,
+    xmp_data:    &[u8]
+)
+-> Result<(), std::io::Error>
+{
+    remove_xmp(file_buffer)?;
+
+    // Sets the flag and converts the file to the Extended File Format, if 
+    // required, before determining the position of the new chunk
+    set_vp8x_flag(&mut Cursor::new(&mut *file_buffer), XMP_FLAG, true)?;
+
+    let mut xmp_chunk   = encode_chunk(XMP_CHUNK_HEADER, xmp_data);
+    let xmp_chunk_len   = xmp_chunk.len() as i32;
+    let insert_position = get_xmp_insert_position(&parse_webp(file_buffer)?);
+
+    insert_multiple_at(file_buffer, insert_position, &mut xmp_chunk);
+    update_file_size_information(&mut Cursor::new(file_buffer), xmp_chunk_len)?;
+
     return Ok(());
 }
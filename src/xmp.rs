@@ -1,12 +1,20 @@
 // Copyright © 2025 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
 // See https://github.com/TechnikTobi/little_exif#license for licensing details
 
+use quick_xml::events::BytesEnd;
 use quick_xml::events::BytesStart;
+use quick_xml::events::BytesText;
 use quick_xml::events::Event;
 use quick_xml::Reader;
 use quick_xml::Writer;
 use std::io::Cursor;
 
+use crate::exif_tag::ExifTag;
+use crate::io_error_plain;
+use crate::ifd::ExifTagGroup;
+use crate::metadata::Metadata;
+use crate::metadata::xmp_io::XmpSyncMode;
+
 /// Some images also contain XMP metadata, which in turn may include EXIF data
 /// that is simply a duplicate from e.g. the eXIf chunk in a PNG.
 /// This function takes in the raw XMP information and removes EXIF attributes,
@@ -18,6 +26,20 @@ remove_exif_from_xmp
     data: &[u8]
 )
 -> Result<Vec<u8>, Box<dyn std::error::Error>>
+{
+    return remove_properties_from_xmp(data, &|name| name.starts_with("exif:"));
+}
+
+/// Removes all elements and attributes from the raw XMP information whose 
+/// (prefixed) name is matched by the given function, while maintaining all
+/// other XMP information.
+pub(crate) fn
+remove_properties_from_xmp
+(
+    data:          &[u8],
+    is_to_remove:  &dyn Fn(&str) -> bool,
+)
+-> Result<Vec<u8>, Box<dyn std::error::Error>>
 {
     let mut reader = Reader::from_reader(data);
     let mut writer = Writer::new(Cursor::new(Vec::new()));
@@ -40,26 +62,26 @@ remove_exif_from_xmp
             Ok(Event::Start(ref event)) => {
                 let event_name = String::from_utf8(event.name().0.to_vec())?;
 
-                if event_name.starts_with("exif:")
+                if skip_depth > 0 || is_to_remove(&event_name)
                 {
                     skip_depth += 1;
                 }
-                else if skip_depth == 0
+                else
                 {
-                    writer.write_event(Event::Start(get_exif_filtered_event(event)?))?;
+                    writer.write_event(Event::Start(get_filtered_event(event, is_to_remove)?))?;
                 }
             }
 
             Ok(Event::Empty(ref event)) => {
                 let event_name = String::from_utf8(event.name().0.to_vec())?;
 
-                if event_name.starts_with("exif:")
+                if is_to_remove(&event_name)
                 {
                     // do nothing
                 }
                 else if skip_depth == 0
                 {
-                    writer.write_event(Event::Empty(get_exif_filtered_event(event)?))?;
+                    writer.write_event(Event::Empty(get_filtered_event(event, is_to_remove)?))?;
                 }
             }
 
@@ -118,9 +140,10 @@ remove_exif_from_xmp
 }
 
 fn
-get_exif_filtered_event<'a>
+get_filtered_event<'a>
 (
-    event: &'a BytesStart<'a>
+    event:         &'a BytesStart<'a>,
+    is_to_remove:  &dyn Fn(&str) -> bool,
 )
 -> Result<BytesStart<'a>, Box<dyn std::error::Error>>
 {
@@ -137,7 +160,7 @@ get_exif_filtered_event<'a>
                         attribute.key.as_ref()
                     ) 
                     {
-                        !key.starts_with("exif:")
+                        !is_to_remove(key)
                     } else {
                         true
                     }
@@ -146,4 +169,1141 @@ get_exif_filtered_event<'a>
     );
 
     return Ok(new_event);
-}
\ No newline at end of file
+}
+
+
+/// Namespace used by Adobe for storing e.g. the `HasExtendedXMP` property
+const XMP_NOTE_NAMESPACE: &str = "http://ns.adobe.com/xmp/note/";
+const HAS_EXTENDED_XMP:   &str = "xmpNote:HasExtendedXMP";
+
+/// Checks if the attribute of a top level rdf:Description element needs to 
+/// stay in place when splitting or merging XMP data, i.e. namespace 
+/// declarations and the `rdf:about` attribute.
+fn
+is_structural_attribute
+(
+    key: &str
+)
+-> bool
+{
+    return key == "rdf:about" || key == "xmlns" || key.starts_with("xmlns:");
+}
+
+/// Constructs a copy of the given element, keeping only those attributes 
+/// that are (or are not, depending on `keep_structural`) structural
+fn
+get_attribute_filtered_event<'a>
+(
+    event:           &'a BytesStart<'a>,
+    keep_structural: bool,
+)
+-> Result<BytesStart<'a>, Box<dyn std::error::Error>>
+{
+    let mut new_event = BytesStart::new(
+        std::str::from_utf8(event.name().0)?
+    );
+
+    new_event.extend_attributes(
+        event.attributes()
+            .filter_map(Result::ok)
+            .filter(|attribute| 
+                {
+                    let key = std::str::from_utf8(attribute.key.as_ref())
+                        .unwrap_or_default();
+                    is_structural_attribute(key) || !keep_structural
+                }
+            ),
+    );
+
+    return Ok(new_event);
+}
+
+/// Reads the GUID stored in the `xmpNote:HasExtendedXMP` property of the 
+/// given (standard) XMP data, either as attribute or as element.
+/// Returns `None` if there is no such property. 
+pub(crate) fn
+get_extended_xmp_guid
+(
+    data: &[u8]
+)
+-> Option<String>
+{
+    let mut reader      = Reader::from_reader(data);
+    let mut read_buffer = Vec::new();
+    let mut in_property = false;
+
+    loop
+    {
+        match reader.read_event_into(&mut read_buffer)
+        {
+            Ok(Event::Start(ref event)) | Ok(Event::Empty(ref event)) => {
+                if event.name().0 == HAS_EXTENDED_XMP.as_bytes()
+                {
+                    in_property = true;
+                }
+
+                for attribute in event.attributes().filter_map(Result::ok)
+                {
+                    if attribute.key.as_ref() == HAS_EXTENDED_XMP.as_bytes()
+                    {
+                        return String::from_utf8(attribute.value.to_vec()).ok();
+                    }
+                }
+            }
+
+            Ok(Event::Text(ref event)) => {
+                if in_property
+                {
+                    return String::from_utf8(event.to_vec())
+                        .ok()
+                        .map(|guid| guid.trim().to_string());
+                }
+            }
+
+            Ok(Event::End(_)) => {
+                in_property = false;
+            }
+
+            Ok(Event::Eof) | Err(_) => {
+                return None;
+            }
+
+            Ok(_) => {}
+        }
+
+        read_buffer.clear();
+    }
+}
+
+/// Merges the extended XMP data into the standard XMP data by moving all
+/// top level rdf:Description elements of the extended XMP into the rdf:RDF
+/// element of the standard XMP. The `xmpNote:HasExtendedXMP` property gets
+/// removed in the process as the result no longer depends on extended data.
+pub(crate) fn
+merge_extended_xmp
+(
+    standard: &[u8],
+    extended: &[u8],
+)
+-> Result<Vec<u8>, Box<dyn std::error::Error>>
+{
+    let mut merged = remove_properties_from_xmp(
+        standard, 
+        &|name| name == HAS_EXTENDED_XMP
+    )?;
+
+    // Locate the content of the rdf:RDF element in the extended XMP
+    let extended_rdf_start = find_subsequence(extended, b"<rdf:RDF")
+        .and_then(|start| find_subsequence(&extended[start..], b">")
+            .map(|end| start + end + 1)
+        );
+    let extended_rdf_end = rfind_subsequence(extended, b"</rdf:RDF>");
+
+    // Locate the end of the rdf:RDF element in the standard XMP
+    let standard_rdf_end = rfind_subsequence(&merged, b"</rdf:RDF>");
+
+    match (extended_rdf_start, extended_rdf_end, standard_rdf_end)
+    {
+        (Some(start), Some(end), Some(insert_position)) if start <= end => {
+            let mut extended_content = extended[start..end].to_vec();
+            crate::util::insert_multiple_at(
+                &mut merged, 
+                insert_position, 
+                &mut extended_content
+            );
+        }
+
+        _ => {
+            return Err("Could not locate rdf:RDF element for merging extended XMP".into());
+        }
+    }
+
+    return Ok(merged);
+}
+
+/// Splits the given XMP data into a standard and an extended part. 
+/// The standard part keeps the top level rdf:Description elements but 
+/// without their child elements, which are moved into the extended part. 
+/// If `move_attributes` is set, the attributes (e.g. simple properties) of 
+/// the rdf:Description elements are moved as well, making the standard part
+/// as small as possible. 
+/// Note that the standard part does not yet contain the 
+/// `xmpNote:HasExtendedXMP` property, see `add_extended_xmp_guid`.
+pub(crate) fn
+split_xmp_for_extension
+(
+    data:            &[u8],
+    move_attributes: bool,
+)
+-> Result<(Vec<u8>, Vec<u8>), Box<dyn std::error::Error>>
+{
+    let mut reader          = Reader::from_reader(data);
+    let mut standard_writer = Writer::new(Cursor::new(Vec::new()));
+    let mut extended_writer = Writer::new(Cursor::new(Vec::new()));
+    let mut read_buffer     = Vec::new();
+
+    // Number of currently open elements & level of the rdf:RDF element
+    let mut depth     = 0u32;
+    let mut rdf_level = None::<u32>;
+
+    loop
+    {
+        let read_event = reader.read_event_into(&mut read_buffer)?;
+
+        // Top level rdf:Description elements are direct children of rdf:RDF
+        // Everything below them is considered content that gets moved
+        let is_top_level = rdf_level.map(|level| depth == level).unwrap_or(false);
+        let is_content   = rdf_level.map(|level| depth >  level).unwrap_or(false);
+
+        match read_event
+        {
+            Event::Start(ref event) if is_top_level => {
+                standard_writer.write_event(Event::Start(get_attribute_filtered_event(event, move_attributes)?))?;
+                extended_writer.write_event(Event::Start(get_attribute_filtered_event(event, !move_attributes)?))?;
+                depth += 1;
+            }
+
+            Event::Empty(ref event) if is_top_level => {
+                standard_writer.write_event(Event::Empty(get_attribute_filtered_event(event, move_attributes)?))?;
+                extended_writer.write_event(Event::Empty(get_attribute_filtered_event(event, !move_attributes)?))?;
+            }
+
+            Event::Start(ref event) => {
+                if event.name().0 == b"rdf:RDF"
+                {
+                    rdf_level = Some(depth + 1);
+                }
+                depth += 1;
+
+                if !is_content { standard_writer.write_event(Event::Start(event.clone()))?; }
+                extended_writer.write_event(Event::Start(event.clone()))?;
+            }
+
+            Event::End(ref event) => {
+                depth = depth.saturating_sub(1);
+
+                // Re-evaluate as the depth has changed
+                let closes_content = rdf_level.map(|level| depth > level).unwrap_or(false);
+                if event.name().0 == b"rdf:RDF"
+                {
+                    rdf_level = None;
+                }
+
+                if !closes_content { standard_writer.write_event(Event::End(event.clone()))?; }
+                extended_writer.write_event(Event::End(event.clone()))?;
+            }
+
+            // The extended XMP is serialized without packet wrapper
+            Event::PI(_) | Event::Decl(_) => {
+                standard_writer.write_event(read_event.borrow())?;
+            }
+
+            Event::Eof => {
+                break;
+            }
+
+            other_event => {
+                if !is_content { standard_writer.write_event(other_event.borrow())?; }
+                extended_writer.write_event(other_event)?;
+            }
+        }
+
+        read_buffer.clear();
+    }
+
+    return Ok((
+        standard_writer.into_inner().into_inner(),
+        extended_writer.into_inner().into_inner()
+    ));
+}
+
+/// Adds the `xmpNote:HasExtendedXMP` property with the given GUID as 
+/// attribute to the first top level rdf:Description element 
+pub(crate) fn
+add_extended_xmp_guid
+(
+    data: &[u8],
+    guid: &str,
+)
+-> Result<Vec<u8>, Box<dyn std::error::Error>>
+{
+    let mut reader      = Reader::from_reader(data);
+    let mut writer      = Writer::new(Cursor::new(Vec::new()));
+    let mut read_buffer = Vec::new();
+    let mut guid_added  = false;
+    let mut in_rdf      = false;
+
+    loop
+    {
+        let read_event = reader.read_event_into(&mut read_buffer)?;
+
+        match read_event
+        {
+            Event::Start(ref event) | Event::Empty(ref event)
+            if in_rdf && !guid_added && event.name().0 == b"rdf:Description" 
+            => {
+                let mut new_event = event.clone();
+
+                let has_namespace = event.attributes()
+                    .filter_map(Result::ok)
+                    .any(|attribute| attribute.key.as_ref() == b"xmlns:xmpNote");
+
+                if !has_namespace
+                {
+                    new_event.push_attribute(("xmlns:xmpNote", XMP_NOTE_NAMESPACE));
+                }
+                new_event.push_attribute((HAS_EXTENDED_XMP, guid));
+                guid_added = true;
+
+                if let Event::Start(_) = read_event
+                {
+                    writer.write_event(Event::Start(new_event))?;
+                }
+                else
+                {
+                    writer.write_event(Event::Empty(new_event))?;
+                }
+            }
+
+            Event::Start(ref event) => {
+                in_rdf |= event.name().0 == b"rdf:RDF";
+                writer.write_event(read_event.borrow())?;
+            }
+
+            Event::Eof => {
+                break;
+            }
+
+            other_event => {
+                writer.write_event(other_event)?;
+            }
+        }
+
+        read_buffer.clear();
+    }
+
+    if !guid_added
+    {
+        return Err("Could not find rdf:Description element for storing GUID of extended XMP".into());
+    }
+
+    return Ok(writer.into_inner().into_inner());
+}
+
+fn
+find_subsequence
+(
+    haystack: &[u8],
+    needle:   &[u8],
+)
+-> Option<usize>
+{
+    return haystack.windows(needle.len()).position(|window| window == needle);
+}
+
+fn
+rfind_subsequence
+(
+    haystack: &[u8],
+    needle:   &[u8],
+)
+-> Option<usize>
+{
+    return haystack.windows(needle.len()).rposition(|window| window == needle);
+}
+
+
+
+/// How the value of an XMP property is structured
+#[derive(Clone, Copy, PartialEq)]
+enum
+XmpValueKind
+{
+    Simple,
+    LangAlt,
+    Seq,
+}
+
+/// An XMP property name, how its value is structured and the new value(s)
+type XmpPropertyUpdate<'a> = (&'a str, XmpValueKind, Vec<String>);
+
+/// EXIF tags (hex value & group) and their mirrored XMP properties, based on
+/// the mappings of the XMP specification (Part 2) and the guidelines of the
+/// Metadata Working Group
+const MIRRORED_XMP_PROPERTIES: [(u16, ExifTagGroup, &str, XmpValueKind); 30] = [
+    (0x010e, ExifTagGroup::GENERIC, "dc:description",              XmpValueKind::LangAlt),
+    (0x010f, ExifTagGroup::GENERIC, "tiff:Make",                   XmpValueKind::Simple ),
+    (0x0110, ExifTagGroup::GENERIC, "tiff:Model",                  XmpValueKind::Simple ),
+    (0x0112, ExifTagGroup::GENERIC, "tiff:Orientation",            XmpValueKind::Simple ),
+    (0x011a, ExifTagGroup::GENERIC, "tiff:XResolution",            XmpValueKind::Simple ),
+    (0x011b, ExifTagGroup::GENERIC, "tiff:YResolution",            XmpValueKind::Simple ),
+    (0x0128, ExifTagGroup::GENERIC, "tiff:ResolutionUnit",         XmpValueKind::Simple ),
+    (0x0131, ExifTagGroup::GENERIC, "xmp:CreatorTool",             XmpValueKind::Simple ),
+    (0x0132, ExifTagGroup::GENERIC, "xmp:ModifyDate",              XmpValueKind::Simple ),
+    (0x0132, ExifTagGroup::GENERIC, "tiff:DateTime",               XmpValueKind::Simple ),
+    (0x013b, ExifTagGroup::GENERIC, "dc:creator",                  XmpValueKind::Seq    ),
+    (0x8298, ExifTagGroup::GENERIC, "dc:rights",                   XmpValueKind::LangAlt),
+    (0x829a, ExifTagGroup::EXIF,    "exif:ExposureTime",           XmpValueKind::Simple ),
+    (0x829d, ExifTagGroup::EXIF,    "exif:FNumber",                XmpValueKind::Simple ),
+    (0x8822, ExifTagGroup::EXIF,    "exif:ExposureProgram",        XmpValueKind::Simple ),
+    (0x8827, ExifTagGroup::EXIF,    "exif:ISOSpeedRatings",        XmpValueKind::Seq    ),
+    (0x9003, ExifTagGroup::EXIF,    "exif:DateTimeOriginal",       XmpValueKind::Simple ),
+    (0x9004, ExifTagGroup::EXIF,    "exif:DateTimeDigitized",      XmpValueKind::Simple ),
+    (0x9004, ExifTagGroup::EXIF,    "xmp:CreateDate",              XmpValueKind::Simple ),
+    (0x9204, ExifTagGroup::EXIF,    "exif:ExposureBiasValue",      XmpValueKind::Simple ),
+    (0x9207, ExifTagGroup::EXIF,    "exif:MeteringMode",           XmpValueKind::Simple ),
+    (0x920a, ExifTagGroup::EXIF,    "exif:FocalLength",            XmpValueKind::Simple ),
+    (0xa002, ExifTagGroup::EXIF,    "exif:PixelXDimension",        XmpValueKind::Simple ),
+    (0xa003, ExifTagGroup::EXIF,    "exif:PixelYDimension",        XmpValueKind::Simple ),
+    (0xa405, ExifTagGroup::EXIF,    "exif:FocalLengthIn35mmFilm",  XmpValueKind::Simple ),
+    (0xa420, ExifTagGroup::EXIF,    "exif:ImageUniqueID",          XmpValueKind::Simple ),
+    (0xa430, ExifTagGroup::EXIF,    "exifEX:CameraOwnerName",      XmpValueKind::Simple ),
+    (0xa431, ExifTagGroup::EXIF,    "exifEX:BodySerialNumber",     XmpValueKind::Simple ),
+    (0xa433, ExifTagGroup::EXIF,    "exifEX:LensMake",             XmpValueKind::Simple ),
+    (0xa434, ExifTagGroup::EXIF,    "exifEX:LensModel",            XmpValueKind::Simple ),
+];
+
+/// Converts an EXIF date (e.g. "2024:01:31 12:34:56") into the XMP date 
+/// format (e.g. "2024-01-31T12:34:56"), optionally with a time zone offset.
+/// Returns `None` if the value is not an ASCII string with at least a date.
+fn
+exif_date_to_xmp_date
+(
+    exif_date: &str,
+    offset:    Option<&str>,
+)
+-> Option<String>
+{
+    let mut xmp_date = exif_date.trim().to_string();
+
+    if !xmp_date.is_ascii() || xmp_date.len() < 10
+    {
+        return None;
+    }
+
+    xmp_date.replace_range(4..5, "-");
+    xmp_date.replace_range(7..8, "-");
+    if xmp_date.len() >= 11
+    {
+        xmp_date.replace_range(10..11, "T");
+    }
+
+    if let Some(offset) = offset
+    {
+        xmp_date.push_str(offset.trim());
+    }
+
+    return Some(xmp_date);
+}
+
+/// Gets the value(s) of the tag, formatted as required by the XMP spec
+fn
+get_xmp_values_of_tag
+(
+    metadata: &Metadata,
+    tag:      &ExifTag,
+)
+-> Option<Vec<String>>
+{
+    // Helper for getting the time zone offset that belongs to a date tag
+    let get_offset = |hex: u16| -> Option<String>
+    {
+        match metadata.get_tag_by_hex(hex, Some(ExifTagGroup::EXIF)).next()
+        {
+            Some(ExifTag::OffsetTime(offset))          |
+            Some(ExifTag::OffsetTimeOriginal(offset))  |
+            Some(ExifTag::OffsetTimeDigitized(offset)) 
+                => Some(offset.trim_end_matches('\0').to_string()),
+            _   => None,
+        }
+    };
+
+    let values = match tag
+    {
+        ExifTag::ImageDescription(value) |
+        ExifTag::Make(value)             |
+        ExifTag::Model(value)            |
+        ExifTag::Software(value)         |
+        ExifTag::Artist(value)           |
+        ExifTag::Copyright(value)        |
+        ExifTag::ImageUniqueID(value)    |
+        ExifTag::OwnerName(value)        |
+        ExifTag::SerialNumber(value)     |
+        ExifTag::LensMake(value)         |
+        ExifTag::LensModel(value)
+            => vec![value.trim_end_matches('\0').to_string()],
+
+        ExifTag::ModifyDate(value)
+            => vec![exif_date_to_xmp_date(value.trim_end_matches('\0'), get_offset(0x9010).as_deref())?],
+        ExifTag::DateTimeOriginal(value)
+            => vec![exif_date_to_xmp_date(value.trim_end_matches('\0'), get_offset(0x9011).as_deref())?],
+        ExifTag::CreateDate(value)
+            => vec![exif_date_to_xmp_date(value.trim_end_matches('\0'), get_offset(0x9012).as_deref())?],
+
+        ExifTag::Orientation(values)             |
+        ExifTag::ResolutionUnit(values)          |
+        ExifTag::ExposureProgram(values)         |
+        ExifTag::ISO(values)                     |
+        ExifTag::MeteringMode(values)            |
+        ExifTag::FocalLengthIn35mmFormat(values)
+            => values.iter().map(|value| value.to_string()).collect(),
+
+        ExifTag::ExifImageWidth(values)  |
+        ExifTag::ExifImageHeight(values)
+            => values.iter().map(|value| value.to_string()).collect(),
+
+        ExifTag::XResolution(values)  |
+        ExifTag::YResolution(values)  |
+        ExifTag::ExposureTime(values) |
+        ExifTag::FNumber(values)      |
+        ExifTag::FocalLength(values)
+            => values.iter().map(|value| format!("{}/{}", value.nominator, value.denominator)).collect(),
+
+        ExifTag::ExposureCompensation(values)
+            => values.iter().map(|value| format!("{}/{}", value.nominator, value.denominator)).collect(),
+
+        _ => return None,
+    };
+
+    if values.is_empty()
+    {
+        return None;
+    }
+
+    return Some(values);
+}
+
+/// Synchronizes the given XMP data with the EXIF data of the metadata 
+/// object, based on the given sync mode. 
+pub(crate) fn
+sync_xmp_with_exif
+(
+    data:     &[u8],
+    metadata: &Metadata,
+    mode:     XmpSyncMode,
+)
+-> Result<Vec<u8>, Box<dyn std::error::Error>>
+{
+    match mode
+    {
+        XmpSyncMode::Disabled => {
+            return Ok(data.to_vec());
+        },
+
+        XmpSyncMode::Remove => {
+            return remove_properties_from_xmp(data, &|name| 
+                name.starts_with("tiff:")   ||
+                name.starts_with("exif:")   ||
+                name.starts_with("exifEX:") ||
+                MIRRORED_XMP_PROPERTIES.iter().any(|entry| entry.2 == name)
+            );
+        },
+
+        XmpSyncMode::Update => {
+            let mut updates = Vec::new();
+            let mut removes = Vec::new();
+
+            // Properties without a tag in the EXIF data may hold data that
+            // is only stored in the XMP data and are therefore kept
+            for (hex, group, name, kind) in MIRRORED_XMP_PROPERTIES.iter()
+            {
+                let Some(tag) = metadata.get_tag_by_hex(*hex, Some(*group)).next() else
+                {
+                    continue;
+                };
+
+                match get_xmp_values_of_tag(metadata, tag)
+                {
+                    Some(values) => updates.push((*name, *kind, values)),
+                    None         => removes.push(*name),
+                }
+            }
+
+            let reduced = remove_properties_from_xmp(
+                data, 
+                &|name| removes.contains(&name)
+            )?;
+
+            return update_properties_in_xmp(&reduced, &updates);
+        },
+    }
+}
+
+/// Format specific functions for reading and writing the raw XMP packet
+type ReadXmpFn<'a>  = dyn Fn(&Vec<u8>) -> Result<Vec<u8>, std::io::Error> + 'a;
+type WriteXmpFn<'a> = dyn Fn(&mut Vec<u8>, &[u8]) -> Result<(), std::io::Error> + 'a;
+
+/// Synchronizes the XMP data of an image stored as a `Vec<u8>` with the EXIF 
+/// data of the metadata object, based on the given sync mode. The XMP data
+/// gets read and written back using the given format specific functions.
+/// Does nothing if the image does not contain any XMP data. 
+pub(crate) fn
+sync_xmp_of_image
+(
+    file_buffer: &mut Vec<u8>,
+    metadata:    &Metadata,
+    mode:        XmpSyncMode,
+    read_xmp:    &ReadXmpFn<'_>,
+    write_xmp:   &WriteXmpFn<'_>,
+)
+-> Result<(), std::io::Error>
+{
+    let xmp_data = match read_xmp(file_buffer)
+    {
+        Ok(data) => data,
+        Err(e)   => {
+            log::debug!("No XMP data to synchronize: {e}");
+            return Ok(());
+        }
+    };
+
+    let synced_xmp_data = sync_xmp_with_exif(&xmp_data, metadata, mode)
+        .map_err(|e| io_error_plain!(InvalidData, e.to_string()))?;
+
+    return write_xmp(file_buffer, &synced_xmp_data);
+}
+
+/// Replaces the values of existing properties in the XMP data, regardless 
+/// of whether they are stored as attribute or as element
+fn
+update_properties_in_xmp
+(
+    data:    &[u8],
+    updates: &[XmpPropertyUpdate],
+)
+-> Result<Vec<u8>, Box<dyn std::error::Error>>
+{
+    let mut reader      = Reader::from_reader(data);
+    let mut writer      = Writer::new(Cursor::new(Vec::new()));
+    let mut read_buffer = Vec::new();
+
+    // Needed for skipping the old content of an updated property element,
+    // which gets kept for LangAlt properties to preserve other languages
+    let mut skip_depth  = 0u32;
+    let mut old_content: Option<(String, Vec<Event<'static>>)> = None;
+
+    let find_update = |name: &[u8]| 
+    {
+        updates.iter().find(|(property, _, _)| property.as_bytes() == name)
+    };
+
+    loop
+    {
+        let read_event = reader.read_event_into(&mut read_buffer)?;
+
+        if skip_depth > 0
+        {
+            match read_event
+            {
+                Event::Start(_) => skip_depth += 1,
+                Event::End(_)   => skip_depth -= 1,
+                Event::Eof      => break,
+                _               => {},
+            }
+
+            // Write the end of the updated property element
+            if skip_depth == 0
+            {
+                if let Some((value, content)) = old_content.take()
+                {
+                    write_lang_alt_value(&mut writer, value, &content)?;
+                }
+                writer.write_event(read_event)?;
+            }
+            else if let Some((_, content)) = old_content.as_mut()
+            {
+                content.push(read_event.into_owned());
+            }
+
+            read_buffer.clear();
+            continue;
+        }
+
+        match read_event
+        {
+            Event::Start(ref event) => {
+                let new_event = get_attribute_updated_event(event, &find_update)?;
+
+                if let Some((_, kind, values)) = find_update(event.name().0)
+                {
+                    writer.write_event(Event::Start(new_event))?;
+                    match kind
+                    {
+                        XmpValueKind::LangAlt => old_content = Some((values.join(" "), Vec::new())),
+                        _                     => write_xmp_values(&mut writer, *kind, values)?,
+                    }
+                    skip_depth = 1;
+                }
+                else
+                {
+                    writer.write_event(Event::Start(new_event))?;
+                }
+            }
+
+            Event::Empty(ref event) => {
+                let new_event = get_attribute_updated_event(event, &find_update)?;
+
+                if let Some((_, kind, values)) = find_update(event.name().0)
+                {
+                    // Turn the empty element into one with content
+                    let end_event = new_event.to_end().into_owned();
+                    writer.write_event(Event::Start(new_event))?;
+                    write_xmp_values(&mut writer, *kind, values)?;
+                    writer.write_event(Event::End(end_event))?;
+                }
+                else
+                {
+                    writer.write_event(Event::Empty(new_event))?;
+                }
+            }
+
+            Event::Eof => {
+                break;
+            }
+
+            other_event => {
+                writer.write_event(other_event)?;
+            }
+        }
+
+        read_buffer.clear();
+    }
+
+    return Ok(writer.into_inner().into_inner());
+}
+
+/// Constructs a copy of the given element where the values of attributes 
+/// that need to be updated are replaced
+fn
+get_attribute_updated_event<'a, 'b>
+(
+    event:       &'a BytesStart<'a>,
+    find_update: &dyn Fn(&[u8]) -> Option<&'b XmpPropertyUpdate<'b>>,
+)
+-> Result<BytesStart<'a>, Box<dyn std::error::Error>>
+{
+    let mut new_event = BytesStart::new(
+        std::str::from_utf8(event.name().0)?
+    );
+
+    for attribute in event.attributes().filter_map(Result::ok)
+    {
+        if let Some((name, _, values)) = find_update(attribute.key.as_ref())
+        {
+            new_event.push_attribute((*name, values.join(" ").as_str()));
+        }
+        else
+        {
+            new_event.push_attribute(attribute);
+        }
+    }
+
+    return Ok(new_event);
+}
+
+/// Writes the content of a property element, e.g. an rdf:Alt for LangAlt
+fn
+write_xmp_values
+(
+    writer: &mut Writer<Cursor<Vec<u8>>>,
+    kind:   XmpValueKind,
+    values: &[String],
+)
+-> Result<(), Box<dyn std::error::Error>>
+{
+    match kind
+    {
+        XmpValueKind::Simple => {
+            writer.write_event(Event::Text(BytesText::new(&values.join(" "))))?;
+        },
+
+        XmpValueKind::LangAlt => {
+            let mut item = BytesStart::new("rdf:li");
+            item.push_attribute(("xml:lang", "x-default"));
+
+            writer.write_event(Event::Start(BytesStart::new("rdf:Alt")))?;
+            writer.write_event(Event::Start(item))?;
+            writer.write_event(Event::Text(BytesText::new(&values.join(" "))))?;
+            writer.write_event(Event::End(BytesEnd::new("rdf:li")))?;
+            writer.write_event(Event::End(BytesEnd::new("rdf:Alt")))?;
+        },
+
+        XmpValueKind::Seq => {
+            writer.write_event(Event::Start(BytesStart::new("rdf:Seq")))?;
+            for value in values
+            {
+                writer.write_event(Event::Start(BytesStart::new("rdf:li")))?;
+                writer.write_event(Event::Text(BytesText::new(value)))?;
+                writer.write_event(Event::End(BytesEnd::new("rdf:li")))?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("rdf:Seq")))?;
+        },
+    }
+
+    return Ok(());
+}
+
+/// Checks if the element is the default alternative of a LangAlt property
+fn
+is_x_default
+(
+    event: &BytesStart
+)
+-> bool
+{
+    return event.name().0 == b"rdf:li" && event.attributes()
+        .filter_map(Result::ok)
+        .any(|attribute| attribute.key.as_ref() == b"xml:lang" && attribute.value.as_ref() == b"x-default");
+}
+
+/// Writes the content of a LangAlt property element, based on its old
+/// content. Only the text of the x-default alternative gets replaced (or
+/// inserted as first alternative if there is none), all other languages
+/// are kept as they are.
+fn
+write_lang_alt_value
+(
+    writer:      &mut Writer<Cursor<Vec<u8>>>,
+    value:       String,
+    old_content: &[Event<'static>],
+)
+-> Result<(), Box<dyn std::error::Error>>
+{
+    let has_alt = old_content.iter().any(|event| matches!(event, Event::Start(alt) if alt.name().0 == b"rdf:Alt"));
+    if !has_alt
+    {
+        return write_xmp_values(writer, XmpValueKind::LangAlt, &[value]);
+    }
+
+    let has_x_default = old_content.iter().any(|event| 
+        matches!(event, Event::Start(item) | Event::Empty(item) if is_x_default(item))
+    );
+
+    let mut skip_depth = 0u32;
+    for event in old_content
+    {
+        if skip_depth > 0
+        {
+            match event
+            {
+                Event::Start(_) => skip_depth += 1,
+                Event::End(_)   => skip_depth -= 1,
+                _               => {},
+            }
+
+            if skip_depth == 0
+            {
+                writer.write_event(event.borrow())?;
+            }
+            continue;
+        }
+
+        match event
+        {
+            Event::Start(item) if is_x_default(item) => {
+                writer.write_event(event.borrow())?;
+                writer.write_event(Event::Text(BytesText::new(&value)))?;
+                skip_depth = 1;
+            },
+
+            Event::Empty(item) if is_x_default(item) => {
+                writer.write_event(Event::Start(item.borrow()))?;
+                writer.write_event(Event::Text(BytesText::new(&value)))?;
+                writer.write_event(Event::End(item.to_end()))?;
+            },
+
+            Event::Start(alt) if alt.name().0 == b"rdf:Alt" && !has_x_default => {
+                let mut item = BytesStart::new("rdf:li");
+                item.push_attribute(("xml:lang", "x-default"));
+
+                writer.write_event(event.borrow())?;
+                writer.write_event(Event::Start(item))?;
+                writer.write_event(Event::Text(BytesText::new(&value)))?;
+                writer.write_event(Event::End(BytesEnd::new("rdf:li")))?;
+            },
+
+            _ => writer.write_event(event.borrow())?,
+        }
+    }
+
+    return Ok(());
+}
+
+// Properties of the (Google) motion photo formats, which store the video
+// after the end of the JPEG image
+const MICRO_VIDEO:           &str = "GCamera:MicroVideo";
+const MICRO_VIDEO_OFFSET:    &str = "GCamera:MicroVideoOffset";
+const ITEM_SEMANTIC:         &str = "Item:Semantic";
+const ITEM_LENGTH:           &str = "Item:Length";
+pub(crate) const MOTION_PHOTO_SEMANTIC: &str = "MotionPhoto";
+
+/// Information about the embedded video of a motion photo that is stored in
+/// the XMP data
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct
+MotionPhotoXmp
+{
+    /// `GCamera:MicroVideo` is set to 1 (older format)
+    pub(crate) is_micro_video:     bool,
+
+    /// Value of `GCamera:MicroVideoOffset`, i.e. the distance of the start
+    /// of the video from the end of the file
+    pub(crate) micro_video_offset: Option<u64>,
+
+    /// Semantic and length of all `Container:Item` elements in order, where
+    /// the length is measured in bytes
+    pub(crate) container_items:    Vec<(String, u64)>,
+}
+
+/// Reads the properties of the `GCamera` and `Container` namespaces that are
+/// needed for locating the video of a motion photo. Properties of the items
+/// in the container directory are expected to be stored as attributes.
+pub(crate) fn
+get_motion_photo_xmp
+(
+    data: &[u8]
+)
+-> MotionPhotoXmp
+{
+    let mut reader           = Reader::from_reader(data);
+    let mut read_buffer      = Vec::new();
+    let mut current_property = None;
+    let mut result           = MotionPhotoXmp::default();
+
+    let set_property = |result: &mut MotionPhotoXmp, name: &[u8], value: &[u8]| 
+    {
+        let value = String::from_utf8_lossy(value);
+        if name == MICRO_VIDEO.as_bytes()
+        {
+            result.is_micro_video = value.trim() == "1";
+        }
+        else if name == MICRO_VIDEO_OFFSET.as_bytes()
+        {
+            result.micro_video_offset = value.trim().parse::<u64>().ok();
+        }
+    };
+
+    loop
+    {
+        match reader.read_event_into(&mut read_buffer)
+        {
+            Ok(Event::Start(ref event)) | Ok(Event::Empty(ref event)) => {
+                current_property = Some(event.name().0.to_vec());
+
+                let mut semantic = None;
+                let mut length   = 0;
+
+                for attribute in event.attributes().filter_map(Result::ok)
+                {
+                    let key = attribute.key.as_ref();
+                    if key == ITEM_SEMANTIC.as_bytes()
+                    {
+                        semantic = Some(String::from_utf8_lossy(&attribute.value).to_string());
+                    }
+                    else if key == ITEM_LENGTH.as_bytes()
+                    {
+                        length = String::from_utf8_lossy(&attribute.value).trim().parse::<u64>().unwrap_or(0);
+                    }
+                    else
+                    {
+                        set_property(&mut result, key, &attribute.value);
+                    }
+                }
+
+                if let Some(semantic) = semantic
+                {
+                    result.container_items.push((semantic, length));
+                }
+            }
+
+            Ok(Event::Text(ref event)) => {
+                if let Some(name) = &current_property
+                {
+                    set_property(&mut result, name, event);
+                }
+            }
+
+            Ok(Event::End(_)) => {
+                current_property = None;
+            }
+
+            Ok(Event::Eof) | Err(_) => {
+                return result;
+            }
+
+            Ok(_) => {}
+        }
+
+        read_buffer.clear();
+    }
+}
+
+/// Updates the XMP data of a motion photo after its video got replaced by
+/// another one, given the new length of the video and its new distance from
+/// the end of the file. This affects the `Item:Length` of the motion photo
+/// item in the container directory and `GCamera:MicroVideoOffset` (if set).
+/// If nothing is given, the video got removed, so all motion photo related
+/// properties as well as the container item get removed.
+pub(crate) fn
+update_motion_photo_xmp
+(
+    data:        &[u8],
+    new_lengths: Option<(u64, u64)>,
+)
+-> Result<Vec<u8>, Box<dyn std::error::Error>>
+{
+    let Some((new_length, new_offset)) = new_lengths else {
+        let reduced = remove_properties_from_xmp(
+            data, 
+            &|name| name.starts_with("GCamera:MotionPhoto") || name.starts_with(MICRO_VIDEO)
+        )?;
+        return update_motion_photo_item(&reduced, None);
+    };
+
+    let mut updated = data.to_vec();
+    if get_motion_photo_xmp(data).micro_video_offset.is_some()
+    {
+        updated = update_properties_in_xmp(
+            data, 
+            &[(MICRO_VIDEO_OFFSET, XmpValueKind::Simple, vec![new_offset.to_string()])]
+        )?;
+    }
+    return update_motion_photo_item(&updated, Some(&new_length.to_string()));
+}
+
+/// Checks if the element is the item of the container directory that 
+/// describes the video of a motion photo
+fn
+is_motion_photo_item
+(
+    event: &BytesStart
+)
+-> bool
+{
+    return event.attributes()
+        .filter_map(Result::ok)
+        .any(|attribute| 
+            attribute.key.as_ref() == ITEM_SEMANTIC.as_bytes() 
+            && 
+            attribute.value.as_ref() == MOTION_PHOTO_SEMANTIC.as_bytes()
+        );
+}
+
+/// Sets the `Item:Length` of the motion photo item in the container 
+/// directory or removes the item (including the enclosing `rdf:li`)
+fn
+update_motion_photo_item
+(
+    data:       &[u8],
+    new_length: Option<&str>,
+)
+-> Result<Vec<u8>, Box<dyn std::error::Error>>
+{
+    let mut reader      = Reader::from_reader(data);
+    let mut writer      = Writer::new(Cursor::new(Vec::new()));
+    let mut read_buffer = Vec::new();
+
+    // The events of the current top level rdf:li element are held back, as
+    // they have to be dropped if it contains the motion photo item
+    let mut pending_events: Option<Vec<Event<'static>>> = None;
+    let mut drop_pending    = false;
+    let mut list_item_depth = 0u32;
+
+    // Needed for skipping a removed item that is not part of an rdf:li
+    let mut skip_depth = 0u32;
+
+    loop
+    {
+        let read_event = reader.read_event_into(&mut read_buffer)?.into_owned();
+        read_buffer.clear();
+
+        if skip_depth > 0
+        {
+            match read_event
+            {
+                Event::Start(_) => skip_depth += 1,
+                Event::End(_)   => skip_depth -= 1,
+                Event::Eof      => break,
+                _               => {},
+            }
+            continue;
+        }
+
+        let event = match read_event
+        {
+            Event::Eof => break,
+
+            Event::Start(ref item) | Event::Empty(ref item) if is_motion_photo_item(item) => {
+                let is_start = matches!(read_event, Event::Start(_));
+
+                let Some(new_length) = new_length else {
+                    if pending_events.is_some()
+                    {
+                        drop_pending = true;
+                    }
+                    else if is_start
+                    {
+                        skip_depth = 1;
+                    }
+                    continue;
+                };
+
+                let mut updated_item = BytesStart::new(std::str::from_utf8(item.name().0)?.to_string());
+                for attribute in item.attributes().filter_map(Result::ok)
+                {
+                    if attribute.key.as_ref() == ITEM_LENGTH.as_bytes()
+                    {
+                        updated_item.push_attribute((ITEM_LENGTH, new_length));
+                    }
+                    else
+                    {
+                        updated_item.push_attribute(attribute);
+                    }
+                }
+
+                if is_start { Event::Start(updated_item) } else { Event::Empty(updated_item) }
+            }
+
+            other_event => other_event,
+        };
+
+        let is_list_item_end = matches!(&event, Event::End(end) if end.name().0 == b"rdf:li");
+
+        if let Event::Start(ref start) = event
+        {
+            if start.name().0 == b"rdf:li"
+            {
+                list_item_depth += 1;
+                if list_item_depth == 1
+                {
+                    pending_events = Some(Vec::new());
+                    drop_pending   = false;
+                }
+            }
+        }
+
+        match pending_events.as_mut()
+        {
+            Some(events) => events.push(event),
+            None         => writer.write_event(event)?,
+        }
+
+        if is_list_item_end && list_item_depth > 0
+        {
+            list_item_depth -= 1;
+            if list_item_depth == 0
+            {
+                let events = pending_events.take().unwrap_or_default();
+                if !drop_pending
+                {
+                    for event in events
+                    {
+                        writer.write_event(event)?;
+                    }
+                }
+            }
+        }
+    }
+
+    return Ok(writer.into_inner().into_inner());
+}
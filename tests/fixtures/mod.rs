@@ -0,0 +1,715 @@
+// Copyright © 2026 Tobias J. Prisching <tobias.prisching@icloud.com> and CONTRIBUTORS
+// See https://github.com/TechnikTobi/little_exif#license for licensing details
+
+//! Builders for synthetic test files and helpers for walking their boxes
+//! and chunks, shared by the tests of the different file formats
+
+use std::fs::read;
+
+/// Builds a JPEG with an MPF segment right after the SOI marker of the first
+/// image, followed by the other images
+pub fn
+build_mpf_jpg
+(
+	images: &[Vec<u8>]
+)
+-> Vec<u8>
+{
+	let entries_offset = 8 + 2 + 3 * 12 + 4;
+	let mut tiff = b"MM\0\x2a\0\0\0\x08\0\x03".to_vec();
+	tiff.extend([0xb0, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x04]);
+	tiff.extend(b"0100");
+	tiff.extend([0xb0, 0x01, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01]);
+	tiff.extend((images.len() as u32).to_be_bytes());
+	tiff.extend([0xb0, 0x02, 0x00, 0x07]);
+	tiff.extend((16 * images.len() as u32).to_be_bytes());
+	tiff.extend((entries_offset as u32).to_be_bytes());
+	tiff.extend([0, 0, 0, 0]);
+
+	let segment_length = 2 + 4 + tiff.len() + 16 * images.len();
+	let primary_size   = images[0].len() + 2 + segment_length;
+	let tiff_start     = 2 + 4 + 4;
+
+	let mut offset = primary_size - tiff_start;
+	for (index, image) in images.iter().enumerate()
+	{
+		let (attribute, size, image_offset) = match index
+		{
+			0 => (0x20030000u32, primary_size, 0),
+			_ => (0x00010001u32, image.len(),  offset),
+		};
+		tiff.extend(attribute.to_be_bytes());
+		tiff.extend((size as u32).to_be_bytes());
+		tiff.extend((image_offset as u32).to_be_bytes());
+		tiff.extend([0, 0, 0, 0]);
+		if index > 0 { offset += image.len(); }
+	}
+
+	let mut file_data = vec![0xff, 0xd8, 0xff, 0xe2];
+	file_data.extend((segment_length as u16).to_be_bytes());
+	file_data.extend(b"MPF\0");
+	file_data.extend(tiff);
+	file_data.extend(&images[0][2..]);
+	for image in &images[1..]
+	{
+		file_data.extend(image);
+	}
+	file_data
+}
+
+/// Builds a Samsung trailer consisting of the given blocks (type, name and
+/// data), followed by the SEFH directory
+pub fn
+build_samsung_trailer
+(
+	blocks: &[(u16, &[u8], &[u8])]
+)
+-> Vec<u8>
+{
+	let mut trailer = Vec::new();
+	let mut entries = Vec::new();
+
+	for (block_type, name, data) in blocks
+	{
+		let start = trailer.len();
+		trailer.extend([0, 0]);
+		trailer.extend(block_type.to_le_bytes());
+		trailer.extend((name.len() as u32).to_le_bytes());
+		trailer.extend(*name);
+		trailer.extend(*data);
+		entries.push((*block_type, start, trailer.len() - start));
+	}
+
+	let directory_start = trailer.len();
+	trailer.extend(b"SEFH");
+	trailer.extend(106u32.to_le_bytes());
+	trailer.extend((entries.len() as u32).to_le_bytes());
+	for (block_type, start, length) in entries
+	{
+		trailer.extend([0, 0]);
+		trailer.extend(block_type.to_le_bytes());
+		trailer.extend(((directory_start - start) as u32).to_le_bytes());
+		trailer.extend((length as u32).to_le_bytes());
+	}
+	trailer.extend(((trailer.len() - directory_start) as u32).to_le_bytes());
+	trailer.extend(b"SEFT");
+	trailer
+}
+
+/// Splits PNG data into its chunks (including length, type and CRC)
+pub fn
+split_png_chunks
+(
+	file_data: &[u8]
+)
+-> Vec<(String, Vec<u8>)>
+{
+	let mut chunks   = Vec::new();
+	let mut position = 8;
+	while position < file_data.len()
+	{
+		let length = u32::from_be_bytes([file_data[position], file_data[position+1], file_data[position+2], file_data[position+3]]) as usize;
+		let name   = String::from_utf8_lossy(&file_data[position+4..position+8]).to_string();
+		chunks.push((name, file_data[position..position+12+length].to_vec()));
+		position += 12 + length;
+	}
+	chunks
+}
+
+pub fn
+encode_png_chunk
+(
+	name: &str,
+	data: &[u8]
+)
+-> Vec<u8>
+{
+	let mut crc_input = name.as_bytes().to_vec();
+	crc_input.extend(data);
+	let checksum = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC).checksum(&crc_input);
+
+	let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+	chunk.extend(crc_input);
+	chunk.extend(checksum.to_be_bytes());
+	chunk
+}
+
+/// Turns a PNG into an APNG with two frames, the first one being the
+/// default image
+pub fn
+build_apng
+(
+	file_data: &[u8]
+)
+-> Vec<u8>
+{
+	let frame_control = |sequence_number: u32| {
+		let mut data = sequence_number.to_be_bytes().to_vec();
+		data.extend(&file_data[16..24]);                                        // Width and height from IHDR
+		data.extend([0; 8]);                                                    // x and y offset
+		data.extend([0, 1, 0, 10, 0, 0]);                                       // Delay, dispose and blend op
+		encode_png_chunk("fcTL", &data)
+	};
+
+	let chunks   = split_png_chunks(file_data);
+	let mut apng = file_data[..8].to_vec();
+	let mut idat = Vec::new();
+
+	for (index, (name, chunk)) in chunks.iter().enumerate()
+	{
+		match name.as_str()
+		{
+			"IHDR" => {
+				apng.extend(chunk);
+				apng.extend(encode_png_chunk("acTL", &[0, 0, 0, 2, 0, 0, 0, 0]));
+			},
+			"IDAT" => {
+				if chunks[index - 1].0 != "IDAT"
+				{
+					apng.extend(frame_control(0));
+				}
+				apng.extend(chunk);
+				idat.extend(&chunk[8..chunk.len()-4]);
+			},
+			"IEND" => {
+				apng.extend(frame_control(1));
+				let mut frame_data = 2u32.to_be_bytes().to_vec();
+				frame_data.extend(&idat);
+				apng.extend(encode_png_chunk("fdAT", &frame_data));
+				apng.extend(chunk);
+			},
+			_ => apng.extend(chunk),
+		}
+	}
+	apng
+}
+
+/// Splits WebP data into its chunks (including fourCC, size and padding)
+pub fn
+split_webp_chunks
+(
+	file_data: &[u8]
+)
+-> Vec<(String, Vec<u8>)>
+{
+	let mut chunks   = Vec::new();
+	let mut position = 12;
+	while position < file_data.len()
+	{
+		let size = u32::from_le_bytes([file_data[position+4], file_data[position+5], file_data[position+6], file_data[position+7]]) as usize;
+		let end  = position + 8 + size + size % 2;
+		chunks.push((String::from_utf8_lossy(&file_data[position..position+4]).to_string(), file_data[position..end].to_vec()));
+		position = end;
+	}
+	chunks
+}
+
+pub fn
+encode_webp_chunk
+(
+	name: &str,
+	data: &[u8]
+)
+-> Vec<u8>
+{
+	let mut chunk = name.as_bytes().to_vec();
+	chunk.extend((data.len() as u32).to_le_bytes());
+	chunk.extend(data);
+	if data.len() % 2 != 0
+	{
+		chunk.push(0);
+	}
+	chunk
+}
+
+/// Builds an animated WebP with two frames, both using the image data of the
+/// simple lossy sample, followed by an XMP chunk
+pub fn
+build_animated_webp()
+-> Result<Vec<u8>, std::io::Error>
+{
+	let simple     = read("tests/sample2_simple_lossy.webp")?;
+	let image_data = &simple[12..];
+
+	// Animation and XMP flags, canvas of 1000x1000
+	let mut vp8x = vec![0x02 | 0x04, 0, 0, 0];
+	vp8x.extend(&999u32.to_le_bytes()[..3]);
+	vp8x.extend(&999u32.to_le_bytes()[..3]);
+
+	let mut chunks = encode_webp_chunk("VP8X", &vp8x);
+	chunks.extend(encode_webp_chunk("ANIM", &[0xff, 0xff, 0xff, 0xff, 0, 0]));
+	for duration in [100u32, 200]
+	{
+		// Offset, frame size, duration and flags
+		let mut frame = vec![0; 6];
+		frame.extend(&999u32.to_le_bytes()[..3]);
+		frame.extend(&999u32.to_le_bytes()[..3]);
+		frame.extend(&duration.to_le_bytes()[..3]);
+		frame.push(0);
+		frame.extend(image_data);
+		chunks.extend(encode_webp_chunk("ANMF", &frame));
+	}
+	chunks.extend(encode_webp_chunk("XMP ", b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"/>"));
+
+	let mut file_data = b"RIFF".to_vec();
+	file_data.extend((chunks.len() as u32 + 4).to_le_bytes());
+	file_data.extend(b"WEBP");
+	file_data.extend(chunks);
+	Ok(file_data)
+}
+
+/// Encodes an ISO BMFF box, with version and flags for full boxes
+pub fn
+encode_iso_box
+(
+	box_type:  &str,
+	full_box:  Option<(u8, u32)>,
+	payload:   &[u8],
+)
+-> Vec<u8>
+{
+	let mut content = Vec::new();
+	if let Some((version, flags)) = full_box
+	{
+		content.push(version);
+		content.extend(&flags.to_be_bytes()[1..]);
+	}
+	content.extend(payload);
+
+	let mut encoded = (content.len() as u32 + 8).to_be_bytes().to_vec();
+	encoded.extend(box_type.as_bytes());
+	encoded.extend(content);
+	encoded
+}
+
+/// An item of a synthetic HEIF: ID, type, construction method and extents
+/// (extent index, offset, length)
+pub struct
+TestHeifItem
+{
+	pub id:                  u16,
+	pub item_type:           &'static str,
+	pub construction_method: u16,
+	pub extents:             Vec<(u32, u32, u32)>,
+}
+
+/// Builds a HEIF consisting of ftyp, meta (with iloc version 1 using 4 byte 
+/// fields) and mdat, which is omitted if there is no data for it. The first
+/// item is the primary item. The offsets of items stored in the file are 
+/// relative to the start of the mdat data. Image sizes (item ID, width and
+/// height) are stored as ispe properties, which are shared by items with 
+/// the same size. 
+pub fn
+build_heif
+(
+	items:       &[TestHeifItem],
+	references:  &[(&str, u16, Vec<u16>)],
+	idat_data:   &[u8],
+	mdat_data:   &[u8],
+	image_sizes: &[(u16, u32, u32)],
+)
+-> Vec<u8>
+{
+	let ftyp = encode_iso_box("ftyp", None, b"heic\0\0\0\0mif1heic");
+
+	let mut hdlr = vec![0; 4];
+	hdlr.extend(b"pict");
+	hdlr.extend([0; 13]);
+
+	let build_meta = |mdat_start: u32| {
+		let mut iloc = vec![0x44, 0x04];
+		iloc.extend((items.len() as u16).to_be_bytes());
+		for item in items
+		{
+			iloc.extend(item.id.to_be_bytes());
+			iloc.extend(item.construction_method.to_be_bytes());
+			iloc.extend([0, 0]);
+			iloc.extend((item.extents.len() as u16).to_be_bytes());
+			for (index, offset, length) in &item.extents
+			{
+				let offset = if item.construction_method == 0 { offset + mdat_start } else { *offset };
+				iloc.extend(index.to_be_bytes());
+				iloc.extend(offset.to_be_bytes());
+				iloc.extend(length.to_be_bytes());
+			}
+		}
+
+		let mut iinf = (items.len() as u16).to_be_bytes().to_vec();
+		for item in items
+		{
+			let mut infe = item.id.to_be_bytes().to_vec();
+			infe.extend([0, 0]);
+			infe.extend(item.item_type.as_bytes());
+			infe.push(0);
+			iinf.extend(encode_iso_box("infe", Some((2, 0)), &infe));
+		}
+
+		let mut iref = Vec::new();
+		for (reference_type, from_id, to_ids) in references
+		{
+			let mut reference = from_id.to_be_bytes().to_vec();
+			reference.extend((to_ids.len() as u16).to_be_bytes());
+			to_ids.iter().for_each(|to_id| reference.extend(to_id.to_be_bytes()));
+			iref.extend(encode_iso_box(reference_type, None, &reference));
+		}
+
+		let mut meta = encode_iso_box("hdlr", Some((0, 0)), &hdlr);
+		meta.extend(encode_iso_box("pitm", Some((0, 0)), &items[0].id.to_be_bytes()));
+		meta.extend(encode_iso_box("iloc", Some((1, 0)), &iloc));
+		meta.extend(encode_iso_box("iinf", Some((0, 0)), &iinf));
+		if !references.is_empty()
+		{
+			meta.extend(encode_iso_box("iref", Some((0, 0)), &iref));
+		}
+		if !image_sizes.is_empty()
+		{
+			let mut ipco  = Vec::new();
+			let mut sizes = Vec::new();
+			let mut ipma  = (image_sizes.len() as u32).to_be_bytes().to_vec();
+			for (item_id, width, height) in image_sizes
+			{
+				let index = match sizes.iter().position(|size| *size == (width, height))
+				{
+					Some(index) => index,
+					None        => {
+						let mut ispe = width.to_be_bytes().to_vec();
+						ispe.extend(height.to_be_bytes());
+						ipco.extend(encode_iso_box("ispe", Some((0, 0)), &ispe));
+						sizes.push((width, height));
+						sizes.len() - 1
+					}
+				};
+
+				ipma.extend(item_id.to_be_bytes());
+				ipma.extend([1, index as u8 + 1]);
+			}
+
+			let mut iprp = encode_iso_box("ipco", None, &ipco);
+			iprp.extend(encode_iso_box("ipma", Some((0, 0)), &ipma));
+			meta.extend(encode_iso_box("iprp", None, &iprp));
+		}
+		if !idat_data.is_empty()
+		{
+			meta.extend(encode_iso_box("idat", None, idat_data));
+		}
+		encode_iso_box("meta", Some((0, 0)), &meta)
+	};
+
+	let mdat_start = (ftyp.len() + build_meta(0).len() + 8) as u32;
+
+	let mut file_data = ftyp;
+	file_data.extend(build_meta(mdat_start));
+	if !mdat_data.is_empty()
+	{
+		file_data.extend(encode_iso_box("mdat", None, mdat_data));
+	}
+	file_data
+}
+
+/// Finds the payload of the first box with the given type in the data
+pub fn
+find_iso_box
+<'a>
+(
+	file_data: &'a [u8],
+	box_type:  &str,
+)
+-> Option<&'a [u8]>
+{
+	let position = file_data.windows(4).position(|window| window == box_type.as_bytes())?;
+	let size     = u32::from_be_bytes([file_data[position-4], file_data[position-3], file_data[position-2], file_data[position-1]]) as usize;
+	Some(&file_data[position+4..position-4+size])
+}
+
+/// Reads the data of an item stored in the file or in the idat box, based on
+/// the iloc box with 4 byte fields as written by `build_heif`
+pub fn
+read_heif_item
+(
+	file_data: &[u8],
+	item_id:   u16,
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+	let missing_box = |box_type: &str| std::io::Error::new(std::io::ErrorKind::NotFound, format!("No {box_type} box"));
+
+	let iloc       = find_iso_box(file_data, "iloc").ok_or_else(|| missing_box("iloc"))?;
+	let field_size = |shift: u8| ((u16::from_be_bytes([iloc[4], iloc[5]]) >> shift) & 0x0f) as usize;
+	let (offset_size, length_size, base_offset_size, index_size) = (field_size(12), field_size(8), field_size(4), field_size(0));
+	assert_eq!((offset_size, length_size, base_offset_size, index_size), (4, 4, 0, 4));
+
+	let mut position = 8;
+	let mut data     = Vec::new();
+	for _ in 0..u16::from_be_bytes([iloc[6], iloc[7]])
+	{
+		let id           = u16::from_be_bytes([iloc[position], iloc[position+1]]);
+		let method       = u16::from_be_bytes([iloc[position+2], iloc[position+3]]) & 0x0f;
+		let extent_count = u16::from_be_bytes([iloc[position+6], iloc[position+7]]);
+		position += 8;
+
+		for _ in 0..extent_count
+		{
+			let offset = u32::from_be_bytes([iloc[position+4], iloc[position+5], iloc[position+6], iloc[position+7]]) as usize;
+			let length = u32::from_be_bytes([iloc[position+8], iloc[position+9], iloc[position+10], iloc[position+11]]) as usize;
+			position += 12;
+
+			if id == item_id
+			{
+				let source = if method == 1 { find_iso_box(file_data, "idat").ok_or_else(|| missing_box("idat"))? } else { file_data };
+				data.extend(&source[offset..offset+length]);
+			}
+		}
+	}
+	Ok(data)
+}
+
+/// Splits ISO BMFF data into its top level boxes (including the header),
+/// where a size of zero means that the box extends to the end of the data
+pub fn
+split_iso_boxes
+(
+	file_data: &[u8]
+)
+-> Vec<(String, Vec<u8>)>
+{
+	let mut boxes    = Vec::new();
+	let mut position = 0;
+	while position < file_data.len()
+	{
+		let size = match u32::from_be_bytes([file_data[position], file_data[position+1], file_data[position+2], file_data[position+3]]) as usize
+		{
+			0    => file_data.len() - position,
+			size => size,
+		};
+		boxes.push((String::from_utf8_lossy(&file_data[position+4..position+8]).to_string(), file_data[position..position+size].to_vec()));
+		position += size;
+	}
+	boxes
+}
+
+/// Lists the types of the top level boxes of an ISO BMFF file, with the
+/// original type of `brob` boxes appended, e.g. `brob:Exif`
+pub fn
+get_top_level_box_types
+(
+	file_data: &[u8]
+)
+-> Vec<String>
+{
+	split_iso_boxes(file_data)
+		.into_iter()
+		.map(|(box_type, iso_box)| match box_type.as_str()
+		{
+			"brob" => format!("brob:{}", String::from_utf8_lossy(&iso_box[8..12])),
+			_      => box_type,
+		})
+		.collect()
+}
+
+/// Appends an IFD with the given entries (tag, format, component count and
+/// value) to the TIFF data, followed by the values that don't fit into the
+/// entries themselves. Returns the offset of the IFD.
+pub fn
+append_test_ifd
+(
+	file_data:  &mut Vec<u8>,
+	big_endian: bool,
+	entries:    &[(u16, u16, u32, Vec<u8>)],
+)
+-> u32
+{
+	let u16_bytes = |value: u16| if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+	let u32_bytes = |value: u32| if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+
+	let ifd_offset       = file_data.len();
+	let mut value_offset = ifd_offset + 2 + entries.len() * 12 + 4;
+	let mut values       = Vec::<u8>::new();
+
+	file_data.extend(u16_bytes(entries.len() as u16));
+	for (tag, format, count, value) in entries
+	{
+		file_data.extend(u16_bytes(*tag));
+		file_data.extend(u16_bytes(*format));
+		file_data.extend(u32_bytes(*count));
+		if value.len() <= 4
+		{
+			let mut inline_value = value.clone();
+			inline_value.resize(4, 0);
+			file_data.extend(inline_value);
+		}
+		else
+		{
+			file_data.extend(u32_bytes(value_offset as u32));
+			values.extend(value);
+			value_offset += value.len();
+		}
+	}
+	file_data.extend([0, 0, 0, 0]);
+	file_data.extend(values);
+
+	ifd_offset as u32
+}
+
+/// Builds a minimal camera raw file consisting of the given header, followed 
+/// by some image data, the GPS IFD, the ExifIFD and finally IFD0, which 
+/// references the image data via a strip and has a SubIFDs tag
+pub fn
+build_test_raw
+(
+	header: &[u8],
+)
+-> Vec<u8>
+{
+	let big_endian = header.starts_with(b"MM");
+	let u32_bytes  = |value: u32| if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+
+	let mut file_data = header.to_vec();
+
+	let image_data_offset = file_data.len() as u32;
+	file_data.extend([0xab; 64]);
+
+	let gps_offset  = append_test_ifd(&mut file_data, big_endian, &[
+		(0x0001, 2, 2, b"N\0".to_vec()),
+	]);
+	let exif_offset = append_test_ifd(&mut file_data, big_endian, &[
+		(0x9003, 2, 20, b"2024:05:06 07:08:09\0".to_vec()),
+		(0xa434, 2, 14, b"EF50mm f/1.8\0\0".to_vec()),
+	]);
+	let ifd0_offset = append_test_ifd(&mut file_data, big_endian, &[
+		(0x010f, 2,  6, b"Maker\0".to_vec()),
+		(0x0110, 2, 10, b"Raw Model\0".to_vec()),
+		(0x0100, 4,  1, u32_bytes(8).to_vec()),
+		(0x0111, 4,  1, u32_bytes(image_data_offset).to_vec()),
+		(0x0117, 4,  1, u32_bytes(64).to_vec()),
+		(0x014a, 13, 1, u32_bytes(image_data_offset).to_vec()),
+		(0x8769, 4,  1, u32_bytes(exif_offset).to_vec()),
+		(0x8825, 4,  1, u32_bytes(gps_offset).to_vec()),
+	]);
+
+	file_data[4..8].copy_from_slice(&u32_bytes(ifd0_offset));
+	file_data
+}
+
+/// Builds a TIFF structure with a single IFD for a CMT box of a CR3 file, 
+/// followed by the given number of padding bytes
+pub fn
+build_test_cmt
+(
+	entries: &[(u16, u16, u32, Vec<u8>)],
+	padding: usize,
+)
+-> Vec<u8>
+{
+	let mut cmt_data = b"II*\0\x08\0\0\0".to_vec();
+	append_test_ifd(&mut cmt_data, false, entries);
+	cmt_data.extend(vec![0u8; padding]);
+	cmt_data
+}
+
+/// Builds a CR3 file consisting of ftyp, moov (with Canon's uuid box that
+/// holds the CMT boxes, followed by mvhd) and mdat
+pub fn
+build_test_cr3()
+-> Vec<u8>
+{
+	let mut canon_payload = vec![
+		0x85, 0xc0, 0xb6, 0x87, 0x82, 0x0f, 0x11, 0xe0, 
+		0x81, 0x11, 0xf4, 0xce, 0x46, 0x2b, 0x6a, 0x48
+	];
+	canon_payload.extend(encode_iso_box("CMT1", None, &build_test_cmt(&[
+		(0x010f, 2, 6,  b"Canon\0".to_vec()),
+		(0x0110, 2, 10, b"EOS R5 II\0".to_vec()),
+	], 32)));
+	canon_payload.extend(encode_iso_box("CMT2", None, &build_test_cmt(&[
+		(0x9003, 2, 20, b"2024:05:06 07:08:09\0".to_vec()),
+	], 0)));
+	canon_payload.extend(encode_iso_box("CMT3", None, b"II*\0\x08\0\0\0\0\0\0\0\0\0"));
+	canon_payload.extend(encode_iso_box("CMT4", None, &build_test_cmt(&[
+		(0x0001, 2, 2, b"N\0".to_vec()),
+	], 0)));
+
+	let mut moov_payload = encode_iso_box("uuid", None, &canon_payload);
+	moov_payload.extend(encode_iso_box("mvhd", Some((0, 0)), &[0u8; 96]));
+
+	let mut file_data = encode_iso_box("ftyp", None, b"crx \0\0\0\x01crx isom");
+	file_data.extend(encode_iso_box("moov", None, &moov_payload));
+	file_data.extend(encode_iso_box("mdat", None, &[0xab; 64]));
+	file_data
+}
+
+/// Builds a QuickTime file consisting of ftyp, moov and mdat with two chunks
+/// of a single track. With `with_metadata`, moov contains the location in 
+/// udta as well as the location and content identifier as items in meta.
+pub fn
+build_test_quicktime
+(
+	brand:         &[u8; 4],
+	with_metadata: bool,
+)
+-> Vec<u8>
+{
+	let ftyp = encode_iso_box("ftyp", None, &[&brand[..], b"\0\0\0\0", &brand[..]].concat());
+
+	// Creation time, modification time, timescale and duration, followed by
+	// the remaining fields of a version 0 mvhd
+	let mut mvhd_payload = 3_000_000_000u32.to_be_bytes().to_vec();
+	mvhd_payload.extend(3_000_000_001u32.to_be_bytes());
+	mvhd_payload.extend(600u32.to_be_bytes());
+	mvhd_payload.extend(1200u32.to_be_bytes());
+	mvhd_payload.resize(96, 0);
+
+	let location = b"+48.2082+016.3738+171.000/";
+	let mut udta_payload = (location.len() as u16).to_be_bytes().to_vec();
+	udta_payload.extend(0x15c7u16.to_be_bytes());
+	udta_payload.extend(location);
+
+	// The type of the location entry starts with the non-ASCII byte 0xa9 (©)
+	let mut xyz = encode_iso_box("?xyz", None, &udta_payload);
+	xyz[4] = 0xa9;
+
+	let mut keys_payload = 2u32.to_be_bytes().to_vec();
+	for key in ["com.apple.quicktime.content.identifier", "com.apple.quicktime.location.ISO6709"]
+	{
+		keys_payload.extend((key.len() as u32 + 8).to_be_bytes());
+		keys_payload.extend(b"mdta");
+		keys_payload.extend(key.as_bytes());
+	}
+
+	let mut ilst_payload = Vec::new();
+	for (index, value) in [("\0\0\0\x01", &b"8A7B2C1D-TEST"[..]), ("\0\0\0\x02", &location[..])]
+	{
+		let data = encode_iso_box("data", None, &[&1u32.to_be_bytes()[..], &[0u8; 4], value].concat());
+		ilst_payload.extend(encode_iso_box(index, None, &data));
+	}
+
+	let mut meta_payload = encode_iso_box("hdlr", Some((0, 0)), b"\0\0\0\0mdta\0\0\0\0\0\0\0\0\0\0\0\0\0");
+	meta_payload.extend(encode_iso_box("keys", Some((0, 0)), &keys_payload));
+	meta_payload.extend(encode_iso_box("ilst", None, &ilst_payload));
+
+	let build_moov = |chunk_offset: u32| {
+		let mut stco_payload = 2u32.to_be_bytes().to_vec();
+		stco_payload.extend(chunk_offset.to_be_bytes());
+		stco_payload.extend((chunk_offset + 16).to_be_bytes());
+
+		let stbl = encode_iso_box("stbl", None, &encode_iso_box("stco", Some((0, 0)), &stco_payload));
+		let minf = encode_iso_box("minf", None, &stbl);
+		let mdia = encode_iso_box("mdia", None, &minf);
+
+		let mut moov_payload = encode_iso_box("mvhd", Some((0, 0)), &mvhd_payload);
+		moov_payload.extend(encode_iso_box("trak", None, &mdia));
+		if with_metadata
+		{
+			moov_payload.extend(encode_iso_box("udta", None, &xyz));
+			moov_payload.extend(encode_iso_box("meta", None, &meta_payload));
+		}
+		encode_iso_box("moov", None, &moov_payload)
+	};
+
+	// The chunks start right after the header of mdat
+	let chunk_offset = (ftyp.len() + build_moov(0).len() + 8) as u32;
+
+	let mut file_data = ftyp;
+	file_data.extend(build_moov(chunk_offset));
+	file_data.extend(encode_iso_box("mdat", None, &[[0x11; 16], [0x22; 16]].concat()));
+	file_data
+}
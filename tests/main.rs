@@ -9,6 +9,38 @@ use std::path::Path;
 extern crate little_exif;
 use little_exif::metadata::Metadata;
 use little_exif::exif_tag::ExifTag;
+use little_exif::metadata::write_options::WriteOptions;
+use little_exif::metadata::xmp_io::XmpSyncMode;
+use little_exif::metadata::jpg_io::OversizeExifError;
+use little_exif::metadata::jpg_io::OversizeExifPolicy;
+use little_exif::metadata::jxl_io::JpegReconstructionPolicy;
+use little_exif::metadata::jxl_io::JxlBoxCompression;
+use little_exif::metadata::png_io::PngTextEntry;
+use little_exif::metadata::png_io::PngTextFormat;
+use little_exif::iptc::IptcData;
+use little_exif::iptc::IptcTag;
+use little_exif::photoshop::IPTC_DIGEST_RESOURCE_ID;
+use little_exif::jfif::JfifUnits;
+use little_exif::jfif::JfxxThumbnail;
+use little_exif::rational::uR64;
+
+mod fixtures;
+use fixtures::build_animated_webp;
+use fixtures::build_apng;
+use fixtures::build_heif;
+use fixtures::build_mpf_jpg;
+use fixtures::build_samsung_trailer;
+use fixtures::build_test_cr3;
+use fixtures::build_test_quicktime;
+use fixtures::build_test_raw;
+use fixtures::encode_iso_box;
+use fixtures::encode_png_chunk;
+use fixtures::find_iso_box;
+use fixtures::get_top_level_box_types;
+use fixtures::read_heif_item;
+use fixtures::split_png_chunks;
+use fixtures::split_webp_chunks;
+use fixtures::TestHeifItem;
 
 #[test]
 fn
@@ -509,8 +541,6 @@ fn
 write_to_file_webp_simple_lossy() 
 -> Result<(), std::io::Error>
 {
-	// Currently not active as the "VP8 " converter does not exist yet!
-
 	// Remove file from previous run and replace it with fresh copy
 	if let Err(error) = remove_file("tests/sample2_simple_lossy_copy.webp")
 	{
@@ -765,4 +795,2205 @@ compare_write_to_webp_extended()
 		"tests/sample2_extended_copy2.webp",
 		little_exif::filetype::FileExtension::WEBP
 	)
-}
\ No newline at end of file
+}
+#[test]
+fn
+write_and_read_extended_xmp_jpg()
+-> Result<(), std::io::Error>
+{
+	let file_extension = little_exif::filetype::FileExtension::JPEG;
+	let mut image_data = read("tests/sample2.jpg")?;
+
+	// Construct XMP data that is too large for a single APP1 segment
+	let history = "<rdf:li>Some rather long history entry</rdf:li>".repeat(4000);
+	let xmp = format!(
+		"<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\
+		<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\
+		<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+		<rdf:Description rdf:about=\"\" xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\" xmp:CreatorTool=\"little_exif\">\
+		<xmp:History><rdf:Seq>{}</rdf:Seq></xmp:History>\
+		</rdf:Description>\
+		</rdf:RDF>\
+		</x:xmpmeta>\
+		<?xpacket end=\"w\"?>",
+		history
+	);
+	assert!(xmp.len() > 0xffff);
+
+	Metadata::write_xmp(xmp.as_bytes(), &mut image_data, file_extension)?;
+
+	// Writing again should replace and not duplicate the XMP segments
+	Metadata::write_xmp(xmp.as_bytes(), &mut image_data, file_extension)?;
+
+	// The EXIF data must not be affected by the XMP segments
+	assert!(Metadata::new_from_vec(&image_data, file_extension).is_ok());
+
+	let read_xmp = String::from_utf8(Metadata::read_xmp(&image_data, file_extension)?).unwrap();
+	assert!(read_xmp.contains("xmp:CreatorTool=\"little_exif\""));
+	assert!(read_xmp.contains(&history));
+	assert!(!read_xmp.contains("HasExtendedXMP"));
+	assert_eq!(read_xmp.matches("Some rather long history entry").count(), 4000);
+
+	Ok(())
+}
+
+fn
+sync_xmp_generic
+(
+	path:           &str,
+	file_extension: little_exif::filetype::FileExtension
+)
+-> Result<(), std::io::Error>
+{
+	let mut image_data = read(path)?;
+
+	let xmp = "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\
+		<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+		<rdf:Description rdf:about=\"\" \
+		xmlns:tiff=\"http://ns.adobe.com/tiff/1.0/\" \
+		xmlns:exif=\"http://ns.adobe.com/exif/1.0/\" \
+		xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+		tiff:Make=\"OldMake\" tiff:Model=\"OldModel\">\
+		<dc:description><rdf:Alt><rdf:li xml:lang=\"x-default\">Old</rdf:li><rdf:li xml:lang=\"de\">Alt</rdf:li></rdf:Alt></dc:description>\
+		<dc:rights><rdf:Alt><rdf:li xml:lang=\"de\">Alle Rechte</rdf:li></rdf:Alt></dc:rights>\
+		<exif:DateTimeOriginal>2000-01-01T00:00:00</exif:DateTimeOriginal>\
+		<dc:subject><rdf:Bag><rdf:li>unrelated</rdf:li></rdf:Bag></dc:subject>\
+		</rdf:Description>\
+		</rdf:RDF>\
+		</x:xmpmeta>";
+
+	Metadata::write_xmp(xmp.as_bytes(), &mut image_data, file_extension)?;
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("New & improved".to_string()));
+	metadata.set_tag(ExifTag::Make("NewMake".to_string()));
+	metadata.set_tag(ExifTag::DateTimeOriginal("2024:05:06 07:08:09".to_string()));
+	metadata.set_tag(ExifTag::Copyright("All rights".to_string()));
+
+	// Update mode: mirrored properties get the new values, properties of 
+	// tags that are not in the EXIF data are kept
+	let mut updated_data = image_data.clone();
+	let mut options = WriteOptions { xmp_sync_mode: XmpSyncMode::Update, ..WriteOptions::default() };
+	metadata.write_to_vec_with_options(&mut updated_data, file_extension, &options)?;
+
+	let updated_xmp = String::from_utf8_lossy(&Metadata::read_xmp(&updated_data, file_extension)?).to_string();
+	assert!(updated_xmp.contains("tiff:Make=\"NewMake\""));
+	assert!(updated_xmp.contains("tiff:Model=\"OldModel\""));
+	assert!(updated_xmp.contains("<exif:DateTimeOriginal>2024-05-06T07:08:09</exif:DateTimeOriginal>"));
+	assert!(updated_xmp.contains("<rdf:li xml:lang=\"x-default\">New &amp; improved</rdf:li>"));
+	assert!(!updated_xmp.contains(">Old<"));
+	assert!(updated_xmp.contains("<rdf:li>unrelated</rdf:li>"));
+	assert!(Metadata::new_from_vec(&updated_data, file_extension).is_ok());
+
+	// Only the x-default alternative of LangAlt properties is updated, or
+	// inserted as first alternative, other languages are kept
+	assert!(updated_xmp.contains("<rdf:li xml:lang=\"de\">Alt</rdf:li>"));
+	assert!(updated_xmp.contains(
+		"<rdf:Alt><rdf:li xml:lang=\"x-default\">All rights</rdf:li><rdf:li xml:lang=\"de\">Alle Rechte</rdf:li></rdf:Alt>"
+	));
+
+	// Dates that are not ASCII can't be converted and are removed instead
+	let mut invalid_date_data = image_data.clone();
+	let mut invalid_date_metadata = metadata.clone();
+	invalid_date_metadata.set_tag(ExifTag::DateTimeOriginal("２０２４:05:06 07:08:09".to_string()));
+	invalid_date_metadata.write_to_vec_with_options(&mut invalid_date_data, file_extension, &options)?;
+
+	let invalid_date_xmp = String::from_utf8_lossy(&Metadata::read_xmp(&invalid_date_data, file_extension)?).to_string();
+	assert!(!invalid_date_xmp.contains("exif:DateTimeOriginal"));
+	assert!(invalid_date_xmp.contains("tiff:Make=\"NewMake\""));
+
+	// Remove mode: all mirrored properties are gone
+	let mut removed_data = image_data.clone();
+	options.xmp_sync_mode = XmpSyncMode::Remove;
+	metadata.write_to_vec_with_options(&mut removed_data, file_extension, &options)?;
+
+	let removed_xmp = String::from_utf8_lossy(&Metadata::read_xmp(&removed_data, file_extension)?).to_string();
+	assert!(!removed_xmp.contains("tiff:Make"));
+	assert!(!removed_xmp.contains("exif:DateTimeOriginal"));
+	assert!(!removed_xmp.contains("dc:description"));
+	assert!(removed_xmp.contains("<rdf:li>unrelated</rdf:li>"));
+
+	// Disabled mode (default): XMP is left untouched
+	let mut untouched_data = image_data.clone();
+	options.xmp_sync_mode = XmpSyncMode::Disabled;
+	metadata.write_to_vec_with_options(&mut untouched_data, file_extension, &options)?;
+
+	let untouched_xmp = String::from_utf8_lossy(&Metadata::read_xmp(&untouched_data, file_extension)?).to_string();
+	assert!(untouched_xmp.contains("tiff:Model=\"OldModel\""));
+
+	Ok(())
+}
+
+#[test]
+fn
+sync_xmp_jpg()
+-> Result<(), std::io::Error>
+{
+	sync_xmp_generic("tests/sample2.jpg", little_exif::filetype::FileExtension::JPEG)
+}
+
+#[test]
+fn
+sync_xmp_png()
+-> Result<(), std::io::Error>
+{
+	sync_xmp_generic(
+		"tests/sample2.png", 
+		little_exif::filetype::FileExtension::PNG { as_zTXt_chunk: false }
+	)
+}
+
+#[test]
+fn
+sync_xmp_webp()
+-> Result<(), std::io::Error>
+{
+	sync_xmp_generic("tests/sample2_simple_lossless.webp", little_exif::filetype::FileExtension::WEBP)
+}
+
+#[test]
+fn
+read_iptc_jpg()
+-> Result<(), std::io::Error>
+{
+	let image_data = read("tests/read_sample.jpg")?;
+	let iptc = Metadata::read_iptc(&image_data, little_exif::filetype::FileExtension::JPEG)?;
+
+	assert!(iptc.is_utf8());
+	assert_eq!(iptc.get_string(IptcTag::Caption), Some("Hello World!".to_string()));
+
+	Ok(())
+}
+
+fn
+write_iptc_generic
+(
+	path:           &str,
+	file_extension: little_exif::filetype::FileExtension
+)
+-> Result<(), std::io::Error>
+{
+	let mut image_data = read(path)?;
+
+	let mut iptc = IptcData::new();
+	iptc.set_string(IptcTag::Caption, "Grüße aus Graz");
+	iptc.set_string(IptcTag::Byline, "Tobias");
+	iptc.set_strings(IptcTag::Keywords, &["Graz", "Schloßberg"]);
+
+	Metadata::write_iptc(&iptc, &mut image_data, file_extension)?;
+
+	// The EXIF data must not be affected
+	assert!(Metadata::new_from_vec(&image_data, file_extension).is_ok());
+
+	let read_iptc = Metadata::read_iptc(&image_data, file_extension)?;
+	assert_eq!(read_iptc, iptc);
+	assert!(read_iptc.is_utf8());
+	assert_eq!(read_iptc.get_string(IptcTag::Caption), Some("Grüße aus Graz".to_string()));
+	assert_eq!(read_iptc.get_strings(IptcTag::Keywords), vec!["Graz", "Schloßberg"]);
+
+	// Removing the IPTC digest must keep the IPTC data itself
+	assert!(Metadata::remove_image_resource(IPTC_DIGEST_RESOURCE_ID, &mut image_data, file_extension)?);
+	assert!(!Metadata::remove_image_resource(IPTC_DIGEST_RESOURCE_ID, &mut image_data, file_extension)?);
+
+	let resources = Metadata::read_image_resources(&image_data, file_extension)?;
+	assert!(resources.get_resource(IPTC_DIGEST_RESOURCE_ID).is_none());
+	assert_eq!(resources.get_iptc()?, iptc);
+
+	Ok(())
+}
+
+#[test]
+fn
+write_iptc_jpg()
+-> Result<(), std::io::Error>
+{
+	write_iptc_generic("tests/sample2.jpg", little_exif::filetype::FileExtension::JPEG)
+}
+
+#[test]
+fn
+write_iptc_tiff()
+-> Result<(), std::io::Error>
+{
+	write_iptc_generic("tests/read_sample.tif", little_exif::filetype::FileExtension::TIFF)
+}
+
+fn
+icc_profile_generic
+(
+	path:           &str,
+	file_extension: little_exif::filetype::FileExtension
+)
+-> Result<(), std::io::Error>
+{
+	let mut image_data   = read(path)?;
+	let     exif_present = Metadata::new_from_vec(&image_data, file_extension).is_ok();
+
+	// Large enough to require multiple APP2 segments in JPEGs
+	let icc_profile: Vec<u8> = (0..150_000u32).map(|i| (i % 251) as u8).collect();
+
+	Metadata::set_icc_profile(&icc_profile, &mut image_data, file_extension)?;
+	assert_eq!(Metadata::get_icc_profile(&image_data, file_extension)?, icc_profile);
+
+	// Replacing the profile must not leave the old one behind
+	let smaller_icc_profile = vec![0x42u8; 333];
+	Metadata::set_icc_profile(&smaller_icc_profile, &mut image_data, file_extension)?;
+	assert_eq!(Metadata::get_icc_profile(&image_data, file_extension)?, smaller_icc_profile);
+
+	// The EXIF data must not be affected
+	assert_eq!(Metadata::new_from_vec(&image_data, file_extension).is_ok(), exif_present);
+
+	Metadata::remove_icc_profile(&mut image_data, file_extension)?;
+	assert!(matches!(
+		Metadata::get_icc_profile(&image_data, file_extension),
+		Err(error) if error.kind() == std::io::ErrorKind::NotFound
+	));
+	assert_eq!(Metadata::new_from_vec(&image_data, file_extension).is_ok(), exif_present);
+
+	Ok(())
+}
+
+#[test]
+fn
+icc_profile_jpg()
+-> Result<(), std::io::Error>
+{
+	icc_profile_generic("tests/sample2.jpg", little_exif::filetype::FileExtension::JPEG)
+}
+
+#[test]
+fn
+icc_profile_png()
+-> Result<(), std::io::Error>
+{
+	icc_profile_generic("tests/sample2.png", little_exif::filetype::FileExtension::PNG { as_zTXt_chunk: false })
+}
+
+#[test]
+fn
+icc_profile_webp()
+-> Result<(), std::io::Error>
+{
+	icc_profile_generic("tests/sample2_extended.webp", little_exif::filetype::FileExtension::WEBP)?;
+	icc_profile_generic("tests/sample2_simple_lossless.webp", little_exif::filetype::FileExtension::WEBP)
+}
+
+#[test]
+fn
+icc_profile_avif()
+-> Result<(), std::io::Error>
+{
+	icc_profile_generic("tests/read_sample.avif", little_exif::filetype::FileExtension::HEIF)
+}
+
+#[test]
+fn
+icc_profile_tiff()
+-> Result<(), std::io::Error>
+{
+	icc_profile_generic("tests/read_sample.tif", little_exif::filetype::FileExtension::TIFF)
+}
+
+#[test]
+fn
+icc_profile_jxl()
+-> Result<(), std::io::Error>
+{
+	// The ICC profile of a JXL is part of the codestream, which is left alone
+	let file_extension = little_exif::filetype::FileExtension::JXL;
+	let mut image_data = read("tests/with_exif.jxl")?;
+	let original_data  = image_data.clone();
+
+	let is_unsupported = |result: Result<_, std::io::Error>| matches!(
+		result, 
+		Err(error) if error.kind() == std::io::ErrorKind::Unsupported
+	);
+
+	assert!(is_unsupported(Metadata::get_icc_profile(&image_data, file_extension).map(|_| ())));
+	assert!(is_unsupported(Metadata::set_icc_profile(&[0x42u8; 333], &mut image_data, file_extension)));
+	assert!(is_unsupported(Metadata::remove_icc_profile(&mut image_data, file_extension)));
+	assert_eq!(image_data, original_data);
+
+	Ok(())
+}
+
+#[test]
+fn
+jpg_comments()
+-> Result<(), std::io::Error>
+{
+	let existing = Metadata::read_comments(&read("issue_tests/resources/issue_000025/A0579322.jpg")?)?;
+	assert_eq!(existing.len(), 1);
+	assert!(existing[0].starts_with(b"Thumb"));
+
+	let mut file_data = read("tests/sample2.jpg")?;
+	let original_len  = file_data.len();
+	assert!(Metadata::read_comments(&file_data)?.is_empty());
+
+	Metadata::write_comments(&[b"first".as_slice(), b"second".as_slice()], &mut file_data)?;
+	assert_eq!(Metadata::read_comments(&file_data)?, vec![b"first".to_vec(), b"second".to_vec()]);
+
+	// Replaces the previous comments
+	Metadata::write_comments(&[b"third".as_slice()], &mut file_data)?;
+	assert_eq!(Metadata::read_comments(&file_data)?, vec![b"third".to_vec()]);
+
+	// EXIF data is still intact
+	let metadata = Metadata::new_from_vec(&file_data, little_exif::filetype::FileExtension::JPEG)?;
+	assert!(metadata.into_iter().next().is_some());
+
+	Metadata::write_comments(&[], &mut file_data)?;
+	assert!(Metadata::read_comments(&file_data)?.is_empty());
+	assert_eq!(file_data.len(), original_len);
+
+	Ok(())
+}
+
+#[test]
+fn
+jpg_jfif()
+-> Result<(), std::io::Error>
+{
+	let mut file_data = read("tests/sample2.jpg")?;
+	let original_len  = file_data.len();
+
+	let mut jfif = Metadata::read_jfif(&file_data)?;
+	assert_eq!(jfif.get_version().0, 1);
+
+	jfif.set_version(1, 2);
+	jfif.set_density(JfifUnits::DotsPerCentimeter, 118, 59)?;
+	assert!(jfif.set_density(JfifUnits::DotsPerInch, 0, 1).is_err());
+	Metadata::write_jfif(&jfif, &mut file_data)?;
+
+	let read_back = Metadata::read_jfif(&file_data)?;
+	assert_eq!(read_back.get_version(), (1, 2));
+	assert_eq!(read_back.get_units(), JfifUnits::DotsPerCentimeter);
+	assert_eq!(read_back.get_density(), (118, 59));
+	assert!(read_back.get_thumbnail().is_none());
+	assert_eq!(file_data.len(), original_len);
+
+	// Uncompressed thumbnail within the JFIF segment itself
+	jfif.set_thumbnail(2, 1, vec![255, 0, 0, 0, 0, 255])?;
+	assert!(jfif.set_thumbnail(2, 2, vec![0; 3]).is_err());
+	Metadata::write_jfif(&jfif, &mut file_data)?;
+	assert_eq!(Metadata::read_jfif(&file_data)?.get_thumbnail(), Some((2, 1, [255, 0, 0, 0, 0, 255].as_slice())));
+	assert_eq!(file_data.len(), original_len + 6);
+
+	// Insert a JFIF segment into a JPEG that has none
+	let mut no_jfif = file_data.clone();
+	let app0_length = u16::from_be_bytes([no_jfif[4], no_jfif[5]]) as usize;
+	no_jfif.drain(2..4+app0_length);
+	assert!(Metadata::read_jfif(&no_jfif).is_err());
+	Metadata::write_jfif(&jfif, &mut no_jfif)?;
+	assert_eq!(no_jfif, file_data);
+
+	Ok(())
+}
+
+#[test]
+fn
+jpg_jfxx_thumbnail()
+-> Result<(), std::io::Error>
+{
+	let mut file_data = read("tests/sample2.jpg")?;
+	let original      = file_data.clone();
+	assert!(Metadata::read_jfxx_thumbnail(&file_data).is_err());
+
+	let thumbnails = [
+		JfxxThumbnail::Jpeg(read("tests/no_exif.jpeg")?),
+		JfxxThumbnail::Palette { width: 2, height: 2, palette: vec![7; 768], pixels: vec![0, 1, 2, 3] },
+		JfxxThumbnail::Rgb { width: 1, height: 2, pixels: vec![1, 2, 3, 4, 5, 6] },
+	];
+
+	for thumbnail in thumbnails.iter()
+	{
+		Metadata::write_jfxx_thumbnail(thumbnail, &mut file_data)?;
+		assert_eq!(&Metadata::read_jfxx_thumbnail(&file_data)?, thumbnail);
+		assert!(Metadata::read_jfif(&file_data).is_ok());
+	}
+
+	Metadata::remove_jfxx_thumbnail(&mut file_data)?;
+	assert!(Metadata::read_jfxx_thumbnail(&file_data).is_err());
+	assert_eq!(file_data, original);
+
+	Ok(())
+}
+
+#[test]
+fn
+jpg_jfif_density_sync()
+-> Result<(), std::io::Error>
+{
+	let mut file_data = read("tests/sample2.jpg")?;
+	let mut metadata  = Metadata::new_from_vec(&file_data, little_exif::filetype::FileExtension::JPEG)?;
+	metadata.set_tag(ExifTag::XResolution(vec![uR64 { nominator: 600, denominator: 2 }]));
+	metadata.set_tag(ExifTag::YResolution(vec![uR64 { nominator: 150, denominator: 1 }]));
+	metadata.set_tag(ExifTag::ResolutionUnit(vec![2]));
+
+	// Disabled by default
+	let before = Metadata::read_jfif(&file_data)?;
+	metadata.write_to_vec(&mut file_data, little_exif::filetype::FileExtension::JPEG)?;
+	assert_eq!(Metadata::read_jfif(&file_data)?.get_density(), before.get_density());
+
+	let options = WriteOptions { jfif_density_sync: true, ..WriteOptions::default() };
+	metadata.write_to_vec_with_options(&mut file_data, little_exif::filetype::FileExtension::JPEG, &options)?;
+	let jfif = Metadata::read_jfif(&file_data)?;
+	assert_eq!(jfif.get_units(), JfifUnits::DotsPerInch);
+	assert_eq!(jfif.get_density(), (300, 150));
+
+	// And the other way round
+	let mut from_jfif = Metadata::new();
+	jfif.set_resolution_of_metadata(&mut from_jfif);
+	assert_eq!(
+		from_jfif.get_tag(&ExifTag::XResolution(Vec::new())).next(),
+		Some(&ExifTag::XResolution(vec![uR64 { nominator: 300, denominator: 1 }]))
+	);
+
+	Ok(())
+}
+
+fn
+oversize_exif_metadata
+(
+	policy: OversizeExifPolicy
+)
+-> Result<(Vec<u8>, Metadata, WriteOptions), std::io::Error>
+{
+	let file_data    = read("tests/sample2.jpg")?;
+	let mut metadata = Metadata::new_from_vec(&file_data, little_exif::filetype::FileExtension::JPEG)?;
+	metadata.set_tag(ExifTag::ImageDescription("Oversize".to_string()));
+	metadata.set_tag(ExifTag::MakerNote((0..150000).map(|i| (i % 251) as u8).collect()));
+	let options = WriteOptions { oversize_exif_policy: policy, ..WriteOptions::default() };
+	Ok((file_data, metadata, options))
+}
+
+#[test]
+fn
+jpg_oversize_exif_error()
+-> Result<(), std::io::Error>
+{
+	let (mut file_data, metadata, options) = oversize_exif_metadata(OversizeExifPolicy::Error)?;
+	let original = file_data.clone();
+
+	let error = metadata.write_to_vec_with_options(&mut file_data, little_exif::filetype::FileExtension::JPEG, &options).unwrap_err();
+	assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+	let oversize_error = error.get_ref().and_then(|e| e.downcast_ref::<OversizeExifError>()).unwrap();
+	assert!(oversize_error.length > 150000);
+	assert!(metadata.as_u8_vec(little_exif::filetype::FileExtension::JPEG).is_err());
+
+	// The file must not be touched
+	assert_eq!(file_data, original);
+
+	// The thumbnail is not the problem here
+	let (mut file_data, metadata, options) = oversize_exif_metadata(OversizeExifPolicy::RemoveThumbnail)?;
+	assert!(metadata.write_to_vec_with_options(&mut file_data, little_exif::filetype::FileExtension::JPEG, &options).is_err());
+
+	Ok(())
+}
+
+#[test]
+fn
+jpg_oversize_exif_remove_thumbnail()
+-> Result<(), std::io::Error>
+{
+	let mut file_data = read("tests/sample2.jpg")?;
+	let mut metadata  = Metadata::new_from_vec(&file_data, little_exif::filetype::FileExtension::JPEG)?;
+	let thumbnail_ifd = metadata.get_ifd_mut(little_exif::ifd::ExifTagGroup::GENERIC, 1);
+	thumbnail_ifd.set_tag(ExifTag::ThumbnailOffset(vec![0], vec![0xab; 80000]));
+	thumbnail_ifd.set_tag(ExifTag::ThumbnailLength(vec![80000]));
+
+	assert!(metadata.write_to_vec(&mut file_data, little_exif::filetype::FileExtension::JPEG).is_err());
+
+	let options = WriteOptions { oversize_exif_policy: OversizeExifPolicy::RemoveThumbnail, ..WriteOptions::default() };
+	metadata.write_to_vec_with_options(&mut file_data, little_exif::filetype::FileExtension::JPEG, &options)?;
+
+	let read_back = Metadata::new_from_vec(&file_data, little_exif::filetype::FileExtension::JPEG)?;
+	assert!(read_back.get_ifd(little_exif::ifd::ExifTagGroup::GENERIC, 1).is_none());
+	assert!(read_back.get_ifd(little_exif::ifd::ExifTagGroup::EXIF, 0).is_some());
+
+	Ok(())
+}
+
+#[test]
+fn
+jpg_oversize_exif_remove_undef_tags()
+-> Result<(), std::io::Error>
+{
+	let (mut file_data, metadata, options) = oversize_exif_metadata(OversizeExifPolicy::RemoveLargeUndefTags)?;
+	metadata.write_to_vec_with_options(&mut file_data, little_exif::filetype::FileExtension::JPEG, &options)?;
+
+	let read_back = Metadata::new_from_vec(&file_data, little_exif::filetype::FileExtension::JPEG)?;
+	assert!(read_back.get_tag(&ExifTag::MakerNote(Vec::new())).next().is_none());
+	assert_eq!(
+		read_back.get_tag(&ExifTag::ImageDescription(String::new())).next(),
+		Some(&ExifTag::ImageDescription("Oversize".to_string()))
+	);
+
+	// The original struct still has the tag
+	assert!(metadata.get_tag(&ExifTag::MakerNote(Vec::new())).next().is_some());
+
+	Ok(())
+}
+
+#[test]
+fn
+jpg_oversize_exif_multi_segment()
+-> Result<(), std::io::Error>
+{
+	let (mut file_data, metadata, options) = oversize_exif_metadata(OversizeExifPolicy::MultiSegment)?;
+	metadata.write_to_vec_with_options(&mut file_data, little_exif::filetype::FileExtension::JPEG, &options)?;
+
+	// Three consecutive APP1 segments, the first two completely filled
+	assert_eq!(&file_data[2..6],  &[0xff, 0xe1, 0xff, 0xff]);
+	assert_eq!(&file_data[6..12], b"Exif\0\0");
+	assert_eq!(&file_data[65539..65543], &[0xff, 0xe1, 0xff, 0xff]);
+	assert_eq!(&file_data[131076..131078], &[0xff, 0xe1]);
+
+	let read_back = Metadata::new_from_vec(&file_data, little_exif::filetype::FileExtension::JPEG)?;
+	assert_eq!(
+		read_back.get_tag(&ExifTag::MakerNote(Vec::new())).next(),
+		metadata.get_tag(&ExifTag::MakerNote(Vec::new())).next()
+	);
+	assert_eq!(
+		read_back.get_tag(&ExifTag::ImageDescription(String::new())).next(),
+		Some(&ExifTag::ImageDescription("Oversize".to_string()))
+	);
+
+	// The policy is not stored in the file. Writing again with the policy
+	// given replaces all of the segments
+	let length = file_data.len();
+	assert!(read_back.write_to_vec(&mut file_data, little_exif::filetype::FileExtension::JPEG).is_err());
+	read_back.write_to_vec_with_options(&mut file_data, little_exif::filetype::FileExtension::JPEG, &options)?;
+	assert_eq!(file_data.len(), length);
+
+	Ok(())
+}
+
+fn
+mpf_test_images()
+-> Result<Vec<Vec<u8>>, std::io::Error>
+{
+	Ok(vec![read("tests/sample2.jpg")?, read("tests/no_exif.jpeg")?, read("tests/read_sample.jpg")?])
+}
+
+#[test]
+fn
+mpf_read_entries()
+-> Result<(), std::io::Error>
+{
+	let images    = mpf_test_images()?;
+	let file_data = build_mpf_jpg(&images);
+
+	let entries = Metadata::read_mpf_entries(&file_data)?;
+	assert_eq!(entries.len(), 3);
+	assert_eq!(entries[0].get_image_type(), little_exif::mpf::MpImageType::BaselinePrimary);
+	assert!(entries[0].is_representative());
+	assert_eq!(entries[0].get_offset(), 0);
+	assert_eq!(entries[1].get_image_type(), little_exif::mpf::MpImageType::LargeThumbnailVga);
+	assert_eq!(entries[1].get_size() as usize, images[1].len());
+
+	for index in 1..3
+	{
+		assert_eq!(Metadata::extract_mpf_image(&file_data, index)?, images[index]);
+	}
+	assert_eq!(Metadata::extract_mpf_image(&file_data, 0)?.len(), entries[0].get_size() as usize);
+	assert!(Metadata::extract_mpf_image(&file_data, 3).is_err());
+
+	assert_eq!(Metadata::read_mpf_entries(&images[0]).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+	Ok(())
+}
+
+#[test]
+fn
+mpf_primary_changes()
+-> Result<(), std::io::Error>
+{
+	let images        = mpf_test_images()?;
+	let mut file_data = build_mpf_jpg(&images);
+
+	let check_images = |file_data: &[u8]| -> Result<(), std::io::Error> {
+		let entries = Metadata::read_mpf_entries(file_data)?;
+		assert_eq!(Metadata::extract_mpf_image(file_data, 1)?, images[1]);
+		assert_eq!(Metadata::extract_mpf_image(file_data, 2)?, images[2]);
+		assert_eq!(entries[0].get_size() as usize, file_data.len() - images[1].len() - images[2].len());
+		Ok(())
+	};
+
+	// Inserted before the MPF segment
+	let mut metadata = Metadata::new_from_vec(&file_data, little_exif::filetype::FileExtension::JPEG)?;
+	metadata.set_tag(ExifTag::ImageDescription("A much longer description than before".repeat(10)));
+	metadata.write_to_vec(&mut file_data, little_exif::filetype::FileExtension::JPEG)?;
+	check_images(&file_data)?;
+
+	// Inserted and removed after the MPF segment
+	Metadata::write_comments(&[b"Comment".as_slice()], &mut file_data)?;
+	check_images(&file_data)?;
+	Metadata::write_comments(&[], &mut file_data)?;
+	check_images(&file_data)?;
+
+	Metadata::clear_app13_segment(&mut file_data, little_exif::filetype::FileExtension::JPEG)?;
+	check_images(&file_data)?;
+
+	Ok(())
+}
+
+#[test]
+fn
+mpf_sub_image_metadata()
+-> Result<(), std::io::Error>
+{
+	let images        = mpf_test_images()?;
+	let mut file_data = build_mpf_jpg(&images);
+	let primary_data  = Metadata::extract_mpf_image(&file_data, 0)?;
+
+	let mut metadata = Metadata::new_from_mpf_image(&file_data, 2)?;
+
+	let mut new_metadata = Metadata::new();
+	new_metadata.set_tag(ExifTag::ImageDescription("Preview".to_string()));
+	new_metadata.write_to_mpf_image(&mut file_data, 1)?;
+
+	metadata.set_tag(ExifTag::ImageDescription("Third".to_string()));
+	metadata.write_to_mpf_image(&mut file_data, 2)?;
+
+	assert_eq!(
+		Metadata::new_from_mpf_image(&file_data, 1)?.get_tag(&ExifTag::ImageDescription(String::new())).next(),
+		Some(&ExifTag::ImageDescription("Preview".to_string()))
+	);
+	assert_eq!(
+		Metadata::new_from_mpf_image(&file_data, 2)?.get_tag(&ExifTag::ImageDescription(String::new())).next(),
+		Some(&ExifTag::ImageDescription("Third".to_string()))
+	);
+
+	// Only the MPF entries of the primary image changed and the last image
+	// still ends with the file
+	let new_primary_data = Metadata::extract_mpf_image(&file_data, 0)?;
+	assert_eq!(new_primary_data.len(), primary_data.len());
+	assert_eq!(new_primary_data[110..], primary_data[110..]);
+	let entries = Metadata::read_mpf_entries(&file_data)?;
+	assert_eq!(10 + entries[2].get_offset() as usize + entries[2].get_size() as usize, file_data.len());
+
+	// The write options are used for the sub image as well
+	new_metadata.set_tag(ExifTag::MakerNote(vec![0x42u8; 70000]));
+	assert!(new_metadata.write_to_mpf_image(&mut file_data, 1).is_err());
+	let options = WriteOptions { oversize_exif_policy: OversizeExifPolicy::RemoveLargeUndefTags, ..WriteOptions::default() };
+	new_metadata.write_to_mpf_image_with_options(&mut file_data, 1, &options)?;
+	let sub_image_metadata = Metadata::new_from_mpf_image(&file_data, 1)?;
+	assert!(sub_image_metadata.get_tag(&ExifTag::MakerNote(Vec::new())).next().is_none());
+	assert_eq!(
+		sub_image_metadata.get_tag(&ExifTag::ImageDescription(String::new())).next(),
+		Some(&ExifTag::ImageDescription("Preview".to_string()))
+	);
+
+	Ok(())
+}
+
+fn
+google_motion_photo_xmp
+(
+	video_length: usize
+)
+-> Vec<u8>
+{
+	format!(concat!(
+		"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"><rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">",
+		"<rdf:Description rdf:about=\"\" xmlns:GCamera=\"http://ns.google.com/photos/1.0/camera/\" ",
+		"xmlns:Container=\"http://ns.google.com/photos/1.0/container/\" xmlns:Item=\"http://ns.google.com/photos/1.0/container/item/\" ",
+		"GCamera:MotionPhoto=\"1\" GCamera:MotionPhotoVersion=\"1\" GCamera:MotionPhotoPresentationTimestampUs=\"0\">",
+		"<Container:Directory><rdf:Seq>",
+		"<rdf:li rdf:parseType=\"Resource\"><Container:Item Item:Mime=\"image/jpeg\" Item:Semantic=\"Primary\" Item:Length=\"0\" Item:Padding=\"0\"/></rdf:li>",
+		"<rdf:li rdf:parseType=\"Resource\"><Container:Item Item:Mime=\"video/mp4\" Item:Semantic=\"MotionPhoto\" Item:Length=\"{}\"/></rdf:li>",
+		"</rdf:Seq></Container:Directory></rdf:Description></rdf:RDF></x:xmpmeta>"
+	), video_length).into_bytes()
+}
+
+fn
+fake_video
+(
+	length: usize
+)
+-> Vec<u8>
+{
+	let mut video = b"\0\0\0\x18ftypmp42".to_vec();
+	video.extend((0..length - video.len()).map(|index| (index % 251) as u8));
+	video
+}
+
+#[test]
+fn
+motion_photo_google_container()
+-> Result<(), std::io::Error>
+{
+	let original  = read("tests/sample2.jpg")?;
+	let video     = fake_video(5000);
+	assert_eq!(Metadata::detect_motion_photo(&original)?, None);
+	assert_eq!(Metadata::extract_motion_photo(&original).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+	let mut file_data = original.clone();
+	Metadata::write_xmp(&google_motion_photo_xmp(video.len()), &mut file_data, little_exif::filetype::FileExtension::JPEG)?;
+	let without_video = file_data.clone();
+	file_data.extend(&video);
+
+	let motion_photo = Metadata::detect_motion_photo(&file_data)?.unwrap();
+	assert_eq!(motion_photo.get_format(), little_exif::motion_photo::MotionPhotoFormat::GoogleContainer);
+	assert_eq!(motion_photo.get_position(), file_data.len() - video.len());
+	assert_eq!(motion_photo.get_length(), video.len());
+	assert_eq!(Metadata::extract_motion_photo(&file_data)?, video);
+	assert!(Metadata::read_jpg_trailer(&file_data)?.ends_with(&video));
+
+	// Writing EXIF data changes the size of the primary image
+	let mut metadata = Metadata::new_from_vec(&file_data, little_exif::filetype::FileExtension::JPEG)?;
+	metadata.set_tag(ExifTag::ImageDescription("A much longer description than before".repeat(10)));
+	metadata.write_to_vec(&mut file_data, little_exif::filetype::FileExtension::JPEG)?;
+	assert_eq!(Metadata::extract_motion_photo(&file_data)?, video);
+
+	let new_video = fake_video(7000);
+	Metadata::replace_motion_photo(&new_video, &mut file_data)?;
+	assert_eq!(Metadata::extract_motion_photo(&file_data)?, new_video);
+	assert!(file_data.ends_with(&new_video));
+	let xmp = String::from_utf8(Metadata::read_xmp(&file_data, little_exif::filetype::FileExtension::JPEG)?).unwrap();
+	assert!(xmp.contains("Item:Length=\"7000\""));
+
+	Metadata::remove_motion_photo(&mut file_data)?;
+	assert_eq!(Metadata::detect_motion_photo(&file_data)?, None);
+	assert_eq!(Metadata::read_jpg_trailer(&file_data)?, Metadata::read_jpg_trailer(&without_video)?);
+	let xmp = String::from_utf8(Metadata::read_xmp(&file_data, little_exif::filetype::FileExtension::JPEG)?).unwrap();
+	assert!(!xmp.contains("MotionPhoto"));
+	assert!(xmp.contains("Item:Semantic=\"Primary\""));
+
+	Ok(())
+}
+
+#[test]
+fn
+motion_photo_google_micro_video()
+-> Result<(), std::io::Error>
+{
+	let video         = fake_video(3000);
+	let mut file_data = read("tests/sample2.jpg")?;
+	let xmp = format!(concat!(
+		"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"><rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">",
+		"<rdf:Description rdf:about=\"\" xmlns:GCamera=\"http://ns.google.com/photos/1.0/camera/\" ",
+		"GCamera:MicroVideo=\"1\" GCamera:MicroVideoVersion=\"1\" GCamera:MicroVideoOffset=\"{}\"/>",
+		"</rdf:RDF></x:xmpmeta>"
+	), video.len());
+	Metadata::write_xmp(xmp.as_bytes(), &mut file_data, little_exif::filetype::FileExtension::JPEG)?;
+	file_data.extend(&video);
+
+	let motion_photo = Metadata::detect_motion_photo(&file_data)?.unwrap();
+	assert_eq!(motion_photo.get_format(), little_exif::motion_photo::MotionPhotoFormat::GoogleMicroVideo);
+	assert_eq!(Metadata::extract_motion_photo(&file_data)?, video);
+
+	let new_video = fake_video(1000);
+	Metadata::replace_motion_photo(&new_video, &mut file_data)?;
+	assert_eq!(Metadata::extract_motion_photo(&file_data)?, new_video);
+	let xmp = String::from_utf8(Metadata::read_xmp(&file_data, little_exif::filetype::FileExtension::JPEG)?).unwrap();
+	assert!(xmp.contains("1000"));
+
+	Metadata::remove_motion_photo(&mut file_data)?;
+	assert_eq!(Metadata::detect_motion_photo(&file_data)?, None);
+	assert!(!file_data.ends_with(&new_video));
+
+	Ok(())
+}
+
+#[test]
+fn
+motion_photo_samsung_trailer()
+-> Result<(), std::io::Error>
+{
+	let image     = read("tests/sample2.jpg")?;
+	let video     = fake_video(4000);
+	let utc_block = (0x0a01u16, b"Image_UTC_Data".as_slice(), b"1700000000000".as_slice());
+
+	let mut file_data = image.clone();
+	file_data.extend(build_samsung_trailer(&[utc_block, (0x0a30, b"MotionPhoto_Data", &video)]));
+
+	let motion_photo = Metadata::detect_motion_photo(&file_data)?.unwrap();
+	assert_eq!(motion_photo.get_format(), little_exif::motion_photo::MotionPhotoFormat::SamsungTrailer);
+	assert_eq!(Metadata::extract_motion_photo(&file_data)?, video);
+
+	let mut metadata = Metadata::new_from_vec(&file_data, little_exif::filetype::FileExtension::JPEG)?;
+	metadata.set_tag(ExifTag::ImageDescription("Samsung".to_string()));
+	metadata.write_to_vec(&mut file_data, little_exif::filetype::FileExtension::JPEG)?;
+	assert_eq!(Metadata::extract_motion_photo(&file_data)?, video);
+
+	let mut image_data = file_data.clone();
+	image_data.truncate(file_data.len() - Metadata::read_jpg_trailer(&file_data)?.len());
+
+	let new_video = fake_video(6000);
+	Metadata::replace_motion_photo(&new_video, &mut file_data)?;
+	let mut expected = image_data.clone();
+	expected.extend(build_samsung_trailer(&[utc_block, (0x0a30, b"MotionPhoto_Data", &new_video)]));
+	assert_eq!(file_data, expected);
+
+	Metadata::remove_motion_photo(&mut file_data)?;
+	let mut expected = image_data.clone();
+	expected.extend(build_samsung_trailer(&[utc_block]));
+	assert_eq!(file_data, expected);
+	assert_eq!(Metadata::detect_motion_photo(&file_data)?, None);
+
+	// Without any other blocks, the entire trailer gets removed
+	let mut file_data = image.clone();
+	file_data.extend(build_samsung_trailer(&[(0x0a30, b"MotionPhoto_Data", &video)]));
+	Metadata::remove_motion_photo(&mut file_data)?;
+	assert_eq!(file_data, image);
+
+	Ok(())
+}
+
+#[test]
+fn
+png_text_entries()
+-> Result<(), std::io::Error>
+{
+	let mut file_data = read("tests/sample2.png")?;
+	let initial_count = Metadata::read_png_text(&file_data)?.len();
+
+	let mut description = PngTextEntry::new("Description", &"A long description. ".repeat(50));
+	description.format  = PngTextFormat::CompressedText;
+
+	Metadata::write_png_text(&PngTextEntry::new("Title", "Sunset"), &mut file_data)?;
+	Metadata::write_png_text(&PngTextEntry::new("Author", "Jürgen"), &mut file_data)?;
+	Metadata::write_png_text(&description, &mut file_data)?;
+	Metadata::write_png_text(&PngTextEntry::new("Comment", "夕日"), &mut file_data)?;
+	Metadata::write_png_text(&PngTextEntry::new_international("Title", "Sonnenuntergang", "de", "Titel"), &mut file_data)?;
+
+	// Latin-1 encoding of tEXt chunks
+	assert!(file_data.windows(6).any(|window| window == b"J\xfcrgen"));
+
+	let entries = Metadata::read_png_text(&file_data)?;
+	assert_eq!(entries.len(), initial_count + 5);
+	assert_eq!(Metadata::get_png_text("Author", &file_data)?.unwrap().text, "Jürgen");
+	assert_eq!(Metadata::get_png_text("Description", &file_data)?.unwrap(), description);
+
+	let comment = Metadata::get_png_text("Comment", &file_data)?.unwrap();
+	assert_eq!(comment.text,   "夕日");
+	assert_eq!(comment.format, PngTextFormat::InternationalText);
+
+	let german_title = entries.iter().find(|entry| entry.language_tag == "de").unwrap();
+	assert_eq!(german_title.text,               "Sonnenuntergang");
+	assert_eq!(german_title.translated_keyword, "Titel");
+
+	// Replacing only affects the entry with the same language tag
+	let mut new_title = PngTextEntry::new("Title", "Sunrise");
+	new_title.format  = PngTextFormat::CompressedInternationalText;
+	Metadata::write_png_text(&new_title, &mut file_data)?;
+	let titles = Metadata::read_png_text(&file_data)?.into_iter().filter(|entry| entry.keyword == "Title").collect::<Vec<_>>();
+	assert_eq!(titles.len(), 2);
+	assert!(titles.contains(&new_title));
+
+	// Text entries survive writing EXIF data and don't include the XMP/EXIF
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("PNG text".to_string()));
+	metadata.write_to_vec(&mut file_data, little_exif::filetype::FileExtension::PNG { as_zTXt_chunk: true })?;
+	let entries = Metadata::read_png_text(&file_data)?;
+	assert_eq!(entries.len(), initial_count + 5);
+	assert!(entries.iter().all(|entry| entry.keyword != "Raw profile type exif"));
+
+	Metadata::remove_png_text("Title", &mut file_data)?;
+	assert_eq!(Metadata::get_png_text("Title", &file_data)?, None);
+	assert_eq!(Metadata::read_png_text(&file_data)?.len(), initial_count + 3);
+
+	// XMP data in a zTXt chunk stays compressed when it gets replaced
+	let file_extension = little_exif::filetype::FileExtension::PNG { as_zTXt_chunk: false };
+	let xmp_chunk_data = [b"XML:com.adobe.xmp\0\0".as_slice(), &miniz_oxide::deflate::compress_to_vec_zlib(b"<old/>", 8)].concat();
+	file_data.splice(33..33, encode_png_chunk("zTXt", &xmp_chunk_data));
+	Metadata::write_xmp(b"<new/>", &mut file_data, file_extension)?;
+	assert_eq!(Metadata::read_xmp(&file_data, file_extension)?, b"<new/>");
+	assert!(split_png_chunks(&file_data).iter().any(|(name, data)| name == "zTXt" && data[8..].starts_with(b"XML:com.adobe.xmp\0\0")));
+
+	Ok(())
+}
+
+#[test]
+fn
+png_text_invalid_entries()
+-> Result<(), std::io::Error>
+{
+	let mut file_data = read("tests/sample2.png")?;
+	let original      = file_data.clone();
+
+	let mut non_latin1 = PngTextEntry::new("Title", "夕日");
+	non_latin1.format  = PngTextFormat::Text;
+
+	let mut language_without_itxt = PngTextEntry::new_international("Title", "Sunset", "en", "Title");
+	language_without_itxt.format  = PngTextFormat::CompressedText;
+
+	for entry in [
+		PngTextEntry::new("",                      "Empty keyword"),
+		PngTextEntry::new(" Title",                "Leading space"),
+		PngTextEntry::new("Two  spaces",           "Consecutive spaces"),
+		PngTextEntry::new(&"K".repeat(80),         "Too long"),
+		PngTextEntry::new("XML:com.adobe.xmp",     "Reserved"),
+		non_latin1,
+		language_without_itxt,
+	]
+	{
+		let error = Metadata::write_png_text(&entry, &mut file_data).unwrap_err();
+		assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput, "{:?}", entry);
+	}
+
+	assert_eq!(Metadata::remove_png_text("Raw profile type exif", &mut file_data).unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+	assert_eq!(file_data, original);
+
+	// A malformed chunk (here: zTXt with data that can't be inflated) is
+	// skipped when reading the other entries
+	let valid_count = Metadata::read_png_text(&file_data)?.len();
+	Metadata::write_png_text(&PngTextEntry::new("Title", "Sunset"), &mut file_data)?;
+	file_data.splice(33..33, encode_png_chunk("zTXt", b"Broken\0\0not deflated"));
+	let entries = Metadata::read_png_text(&file_data)?;
+	assert_eq!(entries.len(), valid_count + 1);
+	assert!(entries.iter().all(|entry| entry.keyword != "Broken"));
+
+	Ok(())
+}
+
+#[test]
+fn
+apng_metadata()
+-> Result<(), std::io::Error>
+{
+	let file_type = little_exif::filetype::FileExtension::PNG { as_zTXt_chunk: true };
+	let mut file_data = build_apng(&read("tests/sample2.png")?);
+
+	let animation_chunks = |file_data: &[u8]| split_png_chunks(file_data)
+		.into_iter()
+		.filter(|(name, _)| matches!(name.as_str(), "acTL" | "fcTL" | "fdAT"))
+		.collect::<Vec<_>>();
+	let original_animation_chunks = animation_chunks(&file_data);
+	assert_eq!(original_animation_chunks.len(), 4);
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("Animated".to_string()));
+	metadata.write_to_vec(&mut file_data, file_type)?;
+	Metadata::write_png_text(&PngTextEntry::new("Title", "Sticker"), &mut file_data)?;
+
+	// The new chunks precede the first fcTL chunk
+	let chunk_names = split_png_chunks(&file_data).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+	let first_fctl  = chunk_names.iter().position(|name| name == "fcTL").unwrap();
+	let first_idat  = chunk_names.iter().position(|name| name == "IDAT").unwrap();
+	assert!(first_fctl < first_idat);
+	assert!(chunk_names[..first_fctl].contains(&"zTXt".to_string()));
+	assert!(chunk_names[..first_fctl].contains(&"tEXt".to_string()));
+	assert_eq!(animation_chunks(&file_data), original_animation_chunks);
+
+	let read_back = Metadata::new_from_vec(&file_data, file_type)?;
+	assert_eq!(
+		read_back.get_tag(&ExifTag::ImageDescription(String::new())).next(),
+		Some(&ExifTag::ImageDescription("Animated".to_string()))
+	);
+
+	Metadata::clear_metadata(&mut file_data, file_type)?;
+	assert!(Metadata::new_from_vec(&file_data, file_type).is_err());
+	assert_eq!(animation_chunks(&file_data), original_animation_chunks);
+
+	Ok(())
+}
+
+/// Gets the flags and the canvas width and height from the VP8X chunk
+fn
+read_vp8x_info
+(
+	file_data: &[u8]
+)
+-> (u8, u32, u32)
+{
+	assert_eq!(&file_data[12..16], b"VP8X");
+	let width  = u32::from_le_bytes([file_data[24], file_data[25], file_data[26], 0]) + 1;
+	let height = u32::from_le_bytes([file_data[27], file_data[28], file_data[29], 0]) + 1;
+	(file_data[20], width, height)
+}
+
+#[test]
+fn
+webp_simple_to_extended_conversion()
+-> Result<(), std::io::Error>
+{
+	let file_type = little_exif::filetype::FileExtension::WEBP;
+	let metadata  = get_test_metadata()?;
+
+	// Lossy, with the dimensions in the VP8 key frame header changed to a 
+	// non-square canvas (the upper two bits are the scaling)
+	let mut lossy = read("tests/sample2_simple_lossy.webp")?;
+	lossy[26..28].copy_from_slice(&(1000u16 | 0x4000).to_le_bytes());
+	lossy[28..30].copy_from_slice(&600u16.to_le_bytes());
+	metadata.write_to_vec(&mut lossy, file_type)?;
+	assert_eq!(read_vp8x_info(&lossy), (0x08, 1000, 600));
+	assert_eq!(u32::from_le_bytes(lossy[4..8].try_into().unwrap()) as usize + 8, lossy.len());
+	assert_eq!(
+		Metadata::new_from_vec(&lossy, file_type)?.get_tag(&ExifTag::ImageDescription(String::new())).next(),
+		metadata.get_tag(&ExifTag::ImageDescription(String::new())).next()
+	);
+
+	// Lossless, with a 1000x600 canvas that uses alpha
+	let mut lossless = read("tests/sample2_simple_lossless.webp")?;
+	let header = (999u32) | (599 << 14) | (1 << 28);
+	lossless[21..25].copy_from_slice(&header.to_le_bytes());
+	metadata.write_to_vec(&mut lossless, file_type)?;
+	assert_eq!(read_vp8x_info(&lossless), (0x18, 1000, 600));
+
+	Ok(())
+}
+
+#[test]
+fn
+webp_lossless_non_square_canvas()
+-> Result<(), std::io::Error>
+{
+	let file_type = little_exif::filetype::FileExtension::WEBP;
+	let metadata  = get_test_metadata()?;
+
+	// The VP8L header stores the width minus one in the lower 14 bits, 
+	// followed by the height minus one. Both orientations (and the largest
+	// possible size) must end up unswapped as canvas size of the VP8X chunk
+	for (width, height) in [(1000u32, 600u32), (600, 1000), (1, 16384)]
+	{
+		let mut file_data = read("tests/sample2_simple_lossless.webp")?;
+		let header = (width - 1) | ((height - 1) << 14);
+		file_data[21..25].copy_from_slice(&header.to_le_bytes());
+		metadata.write_to_vec(&mut file_data, file_type)?;
+		assert_eq!(read_vp8x_info(&file_data), (0x08, width, height));
+	}
+
+	Ok(())
+}
+
+fn
+check_animated_webp
+(
+	file_data:       &[u8],
+	original_frames: &[(String, Vec<u8>)],
+	with_exif:       bool,
+)
+{
+	let chunks = split_webp_chunks(file_data);
+	let names  = chunks.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+	let frames = chunks.iter().filter(|(name, _)| name == "ANMF").cloned().collect::<Vec<_>>();
+
+	assert_eq!(frames, original_frames);
+	assert_eq!(u32::from_le_bytes([file_data[4], file_data[5], file_data[6], file_data[7]]) as usize + 8, file_data.len());
+
+	if with_exif
+	{
+		assert_eq!(names, ["VP8X", "ANIM", "ANMF", "ANMF", "EXIF", "XMP "]);
+		assert_eq!(file_data[20], 0x02 | 0x04 | 0x08);
+	}
+	else
+	{
+		assert_eq!(names, ["VP8X", "ANIM", "ANMF", "ANMF", "XMP "]);
+		assert_eq!(file_data[20], 0x02 | 0x04);
+	}
+}
+
+#[test]
+fn
+webp_animated_metadata()
+-> Result<(), std::io::Error>
+{
+	let file_type = little_exif::filetype::FileExtension::WEBP;
+	let original  = build_animated_webp()?;
+	let frames    = split_webp_chunks(&original).into_iter().filter(|(name, _)| name == "ANMF").collect::<Vec<_>>();
+	let metadata  = get_test_metadata()?;
+	let description = metadata.get_tag(&ExifTag::ImageDescription(String::new())).next();
+
+	// Vec based
+	let mut file_data = original.clone();
+	metadata.write_to_vec(&mut file_data, file_type)?;
+	check_animated_webp(&file_data, &frames, true);
+	assert_eq!(Metadata::new_from_vec(&file_data, file_type)?.get_tag(&ExifTag::ImageDescription(String::new())).next(), description);
+
+	metadata.write_to_vec(&mut file_data, file_type)?;
+	check_animated_webp(&file_data, &frames, true);
+
+	Metadata::clear_metadata(&mut file_data, file_type)?;
+	check_animated_webp(&file_data, &frames, false);
+	assert_eq!(file_data, original);
+
+	// File based
+	let path = Path::new("tests/animated_copy.webp");
+	std::fs::write(path, &original)?;
+	metadata.write_to_file(path)?;
+	let file_data = read(path)?;
+	check_animated_webp(&file_data, &frames, true);
+	assert_eq!(Metadata::new_from_path(path)?.get_tag(&ExifTag::ImageDescription(String::new())).next(), description);
+
+	Metadata::file_clear_metadata(path)?;
+	assert_eq!(read(path)?, original);
+	remove_file(path)?;
+
+	Ok(())
+}
+
+#[test]
+fn
+webp_xmp()
+-> Result<(), std::io::Error>
+{
+	let file_type = little_exif::filetype::FileExtension::WEBP;
+	let xmp_data  = b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"><rdf:RDF/></x:xmpmeta>".to_vec();
+	let chunk_names = |file_data: &[u8]| split_webp_chunks(file_data).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+
+	// Simple file format gets converted to the extended one
+	let mut file_data = read("tests/sample2_simple_lossless.webp")?;
+	assert!(Metadata::read_xmp(&file_data, file_type).is_err());
+	Metadata::write_xmp(&xmp_data, &mut file_data, file_type)?;
+	assert_eq!(chunk_names(&file_data), ["VP8X", "VP8L", "XMP "]);
+	assert_eq!(file_data[20], 0x04);
+	assert_eq!(Metadata::read_xmp(&file_data, file_type)?, xmp_data);
+
+	// EXIF goes in front of XMP, ICCP right after VP8X
+	get_test_metadata()?.write_to_vec(&mut file_data, file_type)?;
+	Metadata::set_icc_profile(&[1, 2, 3], &mut file_data, file_type)?;
+	assert_eq!(chunk_names(&file_data), ["VP8X", "ICCP", "VP8L", "EXIF", "XMP "]);
+	assert_eq!(file_data[20], 0x20 | 0x08 | 0x04);
+
+	// Replacing the data keeps the order, even with an odd length
+	Metadata::write_xmp(b"<x:xmpmeta/>!", &mut file_data, file_type)?;
+	assert_eq!(chunk_names(&file_data), ["VP8X", "ICCP", "VP8L", "EXIF", "XMP "]);
+	assert_eq!(Metadata::read_xmp(&file_data, file_type)?, b"<x:xmpmeta/>!");
+	assert_eq!(u32::from_le_bytes([file_data[4], file_data[5], file_data[6], file_data[7]]) as usize + 8, file_data.len());
+
+	Metadata::remove_xmp(&mut file_data, file_type)?;
+	assert_eq!(chunk_names(&file_data), ["VP8X", "ICCP", "VP8L", "EXIF"]);
+	assert_eq!(file_data[20], 0x20 | 0x08);
+	assert!(Metadata::read_xmp(&file_data, file_type).is_err());
+	assert!(Metadata::new_from_vec(&file_data, file_type).is_ok());
+
+	// Animations: XMP goes after the frames
+	let mut file_data = build_animated_webp()?;
+	Metadata::write_xmp(&xmp_data, &mut file_data, file_type)?;
+	get_test_metadata()?.write_to_vec(&mut file_data, file_type)?;
+	assert_eq!(chunk_names(&file_data), ["VP8X", "ANIM", "ANMF", "ANMF", "EXIF", "XMP "]);
+	assert_eq!(file_data[20], 0x02 | 0x08 | 0x04);
+	assert_eq!(Metadata::read_xmp(&file_data, file_type)?, xmp_data);
+
+	// File based
+	let path = Path::new("tests/sample2_extended_copy.webp");
+	if path.exists()
+	{
+		remove_file(path)?;
+	}
+	copy("tests/sample2_extended.webp", path)?;
+	Metadata::file_write_xmp(&xmp_data, path)?;
+	assert_eq!(Metadata::file_read_xmp(path)?, xmp_data);
+	assert_eq!(chunk_names(&read(path)?).last().map(String::as_str), Some("XMP "));
+	Metadata::file_remove_xmp(path)?;
+	assert!(Metadata::file_read_xmp(path).is_err());
+	assert_eq!(read(path)?[20] & 0x04, 0);
+	remove_file(path)?;
+
+	Ok(())
+}
+
+#[test]
+fn
+remove_xmp_png()
+-> Result<(), std::io::Error>
+{
+	let file_type     = little_exif::filetype::FileExtension::PNG { as_zTXt_chunk: false };
+	let mut file_data = read("tests/sample2.png")?;
+	let original      = file_data.clone();
+
+	Metadata::write_xmp(b"<x:xmpmeta/>", &mut file_data, file_type)?;
+	assert_eq!(Metadata::read_xmp(&file_data, file_type)?, b"<x:xmpmeta/>");
+	Metadata::remove_xmp(&mut file_data, file_type)?;
+	assert!(Metadata::read_xmp(&file_data, file_type).is_err());
+	assert_eq!(file_data, original);
+
+	Ok(())
+}
+
+fn
+check_heif_description
+(
+	file_data:   &[u8],
+	description: &str,
+)
+-> Result<(), std::io::Error>
+{
+	let metadata = Metadata::new_from_vec(&file_data.to_vec(), little_exif::filetype::FileExtension::HEIF)?;
+	assert_eq!(
+		metadata.get_tag(&ExifTag::ImageDescription(String::new())).next(),
+		Some(&ExifTag::ImageDescription(description.to_string()))
+	);
+	Ok(())
+}
+
+fn
+get_heif_exif_item_data
+(
+	description: &str
+)
+-> Result<Vec<u8>, std::io::Error>
+{
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription(description.to_string()));
+	metadata.as_u8_vec(little_exif::filetype::FileExtension::HEIF)
+}
+
+#[test]
+fn
+heif_exif_in_idat()
+-> Result<(), std::io::Error>
+{
+	let file_type = little_exif::filetype::FileExtension::HEIF;
+	let exif      = get_heif_exif_item_data("in idat")?;
+	let split     = exif.len() as u32 / 2;
+
+	// The EXIF data is split into two extents, with the data of another item
+	// in between
+	let mut idat_data = exif[..split as usize].to_vec();
+	idat_data.extend(b"grid data");
+	idat_data.extend(&exif[split as usize..]);
+
+	let file_data = build_heif(
+		&[
+			TestHeifItem { id: 1, item_type: "hvc1", construction_method: 0, extents: vec![(0, 0, 10)] },
+			TestHeifItem { id: 2, item_type: "Exif", construction_method: 1, extents: vec![(0, 0, split), (0, split + 9, exif.len() as u32 - split)] },
+			TestHeifItem { id: 3, item_type: "grid", construction_method: 1, extents: vec![(0, split, 9)] },
+		],
+		&[("cdsc", 2, vec![1])],
+		&idat_data,
+		b"image data",
+		&[]
+	);
+
+	check_heif_description(&file_data, "in idat")?;
+
+	// Writing moves the EXIF data into a single extent of the idat box
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("a longer description than before".to_string()));
+
+	let mut written = file_data.clone();
+	metadata.write_to_vec(&mut written, file_type)?;
+	check_heif_description(&written, "a longer description than before")?;
+
+	assert_eq!(read_heif_item(&written, 1)?, b"image data");
+	assert_eq!(read_heif_item(&written, 3)?, b"grid data");
+	assert_eq!(find_iso_box(&written, "idat").unwrap().len(), 9 + read_heif_item(&written, 2)?.len());
+
+	// ...and again, now with a single extent
+	metadata.set_tag(ExifTag::ImageDescription("short".to_string()));
+	metadata.write_to_vec(&mut written, file_type)?;
+	check_heif_description(&written, "short")?;
+	assert_eq!(read_heif_item(&written, 1)?, b"image data");
+	assert_eq!(read_heif_item(&written, 3)?, b"grid data");
+
+	Ok(())
+}
+
+#[test]
+fn
+heif_exif_constructed_from_item()
+-> Result<(), std::io::Error>
+{
+	let file_type = little_exif::filetype::FileExtension::HEIF;
+	let exif      = get_heif_exif_item_data("from item")?;
+
+	// Item 3 contains some other data followed by the EXIF data, which is
+	// referenced via an 'iloc' item reference from the EXIF item
+	let mut mdat_data = b"image data".to_vec();
+	mdat_data.extend(b"prefix");
+	mdat_data.extend(&exif);
+
+	let file_data = build_heif(
+		&[
+			TestHeifItem { id: 1, item_type: "hvc1", construction_method: 0, extents: vec![(0, 0, 10)] },
+			TestHeifItem { id: 2, item_type: "Exif", construction_method: 2, extents: vec![(1, 6, exif.len() as u32)] },
+			TestHeifItem { id: 3, item_type: "mime", construction_method: 0, extents: vec![(0, 10, 6 + exif.len() as u32)] },
+		],
+		&[("cdsc", 2, vec![1]), ("iloc", 2, vec![3])],
+		&[],
+		&mdat_data,
+		&[]
+	);
+
+	check_heif_description(&file_data, "from item")?;
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("written".to_string()));
+
+	let mut written = file_data.clone();
+	metadata.write_to_vec(&mut written, file_type)?;
+	check_heif_description(&written, "written")?;
+
+	// The EXIF item is now stored in the file, the other items are unchanged
+	assert_eq!(read_heif_item(&written, 1)?, b"image data");
+	assert_eq!(read_heif_item(&written, 3)?[..6], *b"prefix");
+	assert_eq!(read_heif_item(&written, 3)?[6..], exif);
+	assert!(!find_iso_box(&written, "iref").unwrap().windows(4).any(|window| window == b"iloc"));
+
+	Ok(())
+}
+
+#[test]
+fn
+heif_exif_multiple_extents()
+-> Result<(), std::io::Error>
+{
+	let file_type = little_exif::filetype::FileExtension::HEIF;
+	let exif      = get_heif_exif_item_data("multiple extents")?;
+	let split     = exif.len() as u32 / 3;
+
+	// The image data is located in between the two extents of the EXIF data
+	let mut mdat_data = exif[..split as usize].to_vec();
+	mdat_data.extend(b"image data");
+	mdat_data.extend(&exif[split as usize..]);
+
+	let file_data = build_heif(
+		&[
+			TestHeifItem { id: 1, item_type: "hvc1", construction_method: 0, extents: vec![(0, split, 10)] },
+			TestHeifItem { id: 2, item_type: "Exif", construction_method: 0, extents: vec![(0, 0, split), (0, split + 10, exif.len() as u32 - split)] },
+		],
+		&[("cdsc", 2, vec![1])],
+		&[],
+		&mdat_data,
+		&[]
+	);
+
+	check_heif_description(&file_data, "multiple extents")?;
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("now a single extent".to_string()));
+
+	let mut written = file_data.clone();
+	metadata.write_to_vec(&mut written, file_type)?;
+	check_heif_description(&written, "now a single extent")?;
+	assert_eq!(read_heif_item(&written, 1)?, b"image data");
+
+	// The mdat box only contains the image and the new EXIF data
+	assert_eq!(find_iso_box(&written, "mdat").unwrap().len(), 10 + read_heif_item(&written, 2)?.len());
+
+	Ok(())
+}
+
+#[test]
+fn
+heif_new_exif_item_for_grid_primary()
+-> Result<(), std::io::Error>
+{
+	let file_type = little_exif::filetype::FileExtension::HEIF;
+
+	// The primary item is a grid with ID 7 that is derived from two tiles
+	let file_data = build_heif(
+		&[
+			TestHeifItem { id: 7, item_type: "grid", construction_method: 1, extents: vec![(0, 0, 8)] },
+			TestHeifItem { id: 3, item_type: "hvc1", construction_method: 0, extents: vec![(0, 0, 6)] },
+			TestHeifItem { id: 4, item_type: "hvc1", construction_method: 0, extents: vec![(0, 6, 6)] },
+		],
+		&[("dimg", 7, vec![3, 4])],
+		b"gridinfo",
+		b"tile_atile_b",
+		&[]
+	);
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("new item".to_string()));
+
+	let mut written = file_data.clone();
+	metadata.write_to_vec(&mut written, file_type)?;
+	check_heif_description(&written, "new item")?;
+
+	// The new item uses a fresh ID and describes the primary item
+	assert_eq!(find_iso_box(&written, "cdsc").unwrap(), [0, 8, 0, 1, 0, 7]);
+	assert!(find_iso_box(&written, "infe").is_some());
+	assert_eq!(read_heif_item(&written, 8)?, get_heif_exif_item_data("new item")?);
+	assert_eq!(read_heif_item(&written, 3)?, b"tile_a");
+	assert_eq!(read_heif_item(&written, 4)?, b"tile_b");
+	assert_eq!(read_heif_item(&written, 7)?, b"gridinfo");
+
+	// Writing again replaces the data of the new item
+	metadata.set_tag(ExifTag::ImageDescription("updated".to_string()));
+	metadata.write_to_vec(&mut written, file_type)?;
+	check_heif_description(&written, "updated")?;
+	assert_eq!(read_heif_item(&written, 4)?, b"tile_b");
+
+	Ok(())
+}
+
+#[test]
+fn
+heif_new_exif_item_without_mdat()
+-> Result<(), std::io::Error>
+{
+	let file_type = little_exif::filetype::FileExtension::HEIF;
+
+	// All data is stored in the idat box, so there is no mdat box yet
+	let file_data = build_heif(
+		&[TestHeifItem { id: 2, item_type: "hvc1", construction_method: 1, extents: vec![(0, 0, 10)] }],
+		&[],
+		b"image data",
+		&[],
+		&[]
+	);
+	assert!(find_iso_box(&file_data, "mdat").is_none());
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("new mdat".to_string()));
+
+	let mut written = file_data.clone();
+	metadata.write_to_vec(&mut written, file_type)?;
+	check_heif_description(&written, "new mdat")?;
+
+	// As there was no iref box yet, a new one using 32 bit IDs gets created
+	assert_eq!(find_iso_box(&written, "cdsc").unwrap(), [0, 0, 0, 3, 0, 1, 0, 0, 0, 2]);
+	assert_eq!(find_iso_box(&written, "mdat").unwrap(), get_heif_exif_item_data("new mdat")?);
+	assert_eq!(read_heif_item(&written, 2)?, b"image data");
+
+	Ok(())
+}
+
+#[test]
+fn
+heif_image_item_metadata()
+-> Result<(), std::io::Error>
+{
+	let first_exif = get_heif_exif_item_data("first image")?;
+
+	// Two images, of which only the first one (the primary item) has its own
+	// EXIF item. The tiles of the grid are not listed as image items. 
+	let mut mdat_data = b"image one.image two.".to_vec();
+	mdat_data.extend(&first_exif);
+	mdat_data.extend(b"tile");
+
+	let file_data = build_heif(
+		&[
+			TestHeifItem { id: 1, item_type: "hvc1", construction_method: 0, extents: vec![(0, 0, 10)] },
+			TestHeifItem { id: 2, item_type: "grid", construction_method: 0, extents: vec![(0, 10, 10)] },
+			TestHeifItem { id: 3, item_type: "Exif", construction_method: 0, extents: vec![(0, 20, first_exif.len() as u32)] },
+			TestHeifItem { id: 4, item_type: "hvc1", construction_method: 0, extents: vec![(0, 20 + first_exif.len() as u32, 4)] },
+		],
+		&[("cdsc", 3, vec![1]), ("dimg", 2, vec![4])],
+		&[],
+		&mdat_data,
+		&[(1, 640, 480), (2, 320, 240)]
+	);
+
+	let items = Metadata::read_heif_image_items(&file_data)?;
+	assert_eq!(items.len(), 2);
+	assert_eq!((items[0].item_id, items[0].item_type.as_str(), items[0].width, items[0].height, items[0].is_primary), (1, "hvc1", Some(640), Some(480), true));
+	assert_eq!((items[1].item_id, items[1].item_type.as_str(), items[1].width, items[1].height, items[1].is_primary), (2, "grid", Some(320), Some(240), false));
+
+	let description = |metadata: &Metadata| metadata.get_tag(&ExifTag::ImageDescription(String::new())).next().cloned();
+
+	assert_eq!(description(&Metadata::new_from_heif_image(&file_data, 1)?), Some(ExifTag::ImageDescription("first image".to_string())));
+	assert_eq!(Metadata::new_from_heif_image(&file_data, 2).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+	// Writing to the second image creates a new EXIF item for it
+	let mut written  = file_data.clone();
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::ImageDescription("second image".to_string()));
+	metadata.write_to_heif_image(&mut written, 2)?;
+
+	assert_eq!(description(&Metadata::new_from_heif_image(&written, 2)?), Some(ExifTag::ImageDescription("second image".to_string())));
+	assert_eq!(description(&Metadata::new_from_heif_image(&written, 1)?), Some(ExifTag::ImageDescription("first image".to_string())));
+	check_heif_description(&written, "first image")?;
+
+	// ...while writing to the first image keeps the data of the second one
+	metadata.set_tag(ExifTag::ImageDescription("first image, updated".to_string()));
+	metadata.write_to_heif_image(&mut written, 1)?;
+
+	assert_eq!(description(&Metadata::new_from_heif_image(&written, 1)?), Some(ExifTag::ImageDescription("first image, updated".to_string())));
+	assert_eq!(description(&Metadata::new_from_heif_image(&written, 2)?), Some(ExifTag::ImageDescription("second image".to_string())));
+
+	assert_eq!(read_heif_item(&written, 1)?, b"image one.");
+	assert_eq!(read_heif_item(&written, 2)?, b"image two.");
+	assert_eq!(read_heif_item(&written, 4)?, b"tile");
+
+	// The tile is not an image item on its own
+	assert_eq!(metadata.write_to_heif_image(&mut written, 4).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+	// The EXIF data of the second image does not describe the primary one,
+	// so it is neither read nor overwritten when using the primary item
+	let second_exif = get_heif_exif_item_data("second image")?;
+	let mut mdat_data = b"image one.image two.".to_vec();
+	mdat_data.extend(&second_exif);
+
+	let file_data = build_heif(
+		&[
+			TestHeifItem { id: 1, item_type: "hvc1", construction_method: 0, extents: vec![(0, 0, 10)] },
+			TestHeifItem { id: 2, item_type: "hvc1", construction_method: 0, extents: vec![(0, 10, 10)] },
+			TestHeifItem { id: 3, item_type: "Exif", construction_method: 0, extents: vec![(0, 20, second_exif.len() as u32)] },
+		],
+		&[("cdsc", 3, vec![2])],
+		&[],
+		&mdat_data,
+		&[]
+	);
+	assert!(Metadata::new_from_vec(&file_data, little_exif::filetype::FileExtension::HEIF).is_err());
+
+	let mut written = file_data.clone();
+	metadata.set_tag(ExifTag::ImageDescription("primary image".to_string()));
+	metadata.write_to_vec(&mut written, little_exif::filetype::FileExtension::HEIF)?;
+
+	check_heif_description(&written, "primary image")?;
+	assert_eq!(description(&Metadata::new_from_heif_image(&written, 2)?), Some(ExifTag::ImageDescription("second image".to_string())));
+	assert_eq!(read_heif_item(&written, 3)?, second_exif);
+	assert_eq!(read_heif_item(&written, 4)?, get_heif_exif_item_data("primary image")?);
+
+	Ok(())
+}
+
+#[test]
+fn
+heif_item_properties()
+-> Result<(), std::io::Error>
+{
+	use little_exif::metadata::heif_io::HeifItemProperty;
+	use little_exif::rational::iR64;
+	use little_exif::rational::uR64;
+
+	// Both images share the same ispe property
+	let mut file_data = build_heif(
+		&[
+			TestHeifItem { id: 1, item_type: "hvc1", construction_method: 0, extents: vec![(0, 0, 10)] },
+			TestHeifItem { id: 2, item_type: "hvc1", construction_method: 0, extents: vec![(0, 10, 10)] },
+		],
+		&[],
+		&[],
+		b"image one.image two.",
+		&[(1, 640, 480), (2, 640, 480)]
+	);
+
+	let properties = |file_data: &Vec<u8>, item_id: u32| -> Result<Vec<(HeifItemProperty, bool)>, std::io::Error> {
+		Ok(Metadata::read_heif_item_properties(file_data, item_id)?
+			.into_iter()
+			.map(|associated| (associated.property, associated.essential))
+			.collect())
+	};
+
+	let ispe = |width, height| HeifItemProperty::ImageSpatialExtents { width, height };
+	let nclx = HeifItemProperty::NclxColor { colour_primaries: 1, transfer_characteristics: 13, matrix_coefficients: 6, full_range: true };
+	let icc  = HeifItemProperty::IccColor { restricted: false, icc_profile: b"not really an ICC profile".to_vec() };
+	let clap = HeifItemProperty::CleanAperture { 
+		width:             uR64 { nominator: 600, denominator: 1 },
+		height:            uR64 { nominator: 450, denominator: 1 },
+		horizontal_offset: iR64 { nominator: -5,  denominator: 2 },
+		vertical_offset:   iR64 { nominator: 0,   denominator: 1 },
+	};
+
+	assert_eq!(properties(&file_data, 1)?, vec![(ispe(640, 480), false)]);
+
+	// Transformative properties get appended in the given order, descriptive
+	// ones are inserted before them
+	Metadata::write_heif_item_property(&HeifItemProperty::Rotation { angle: 1 }, true, &mut file_data, 1)?;
+	Metadata::write_heif_item_property(&HeifItemProperty::Mirror { axis: 0 }, true, &mut file_data, 1)?;
+	Metadata::write_heif_item_property(&nclx, false, &mut file_data, 1)?;
+	Metadata::write_heif_item_property(&icc, false, &mut file_data, 1)?;
+	Metadata::write_heif_item_property(&clap, true, &mut file_data, 1)?;
+	assert_eq!(properties(&file_data, 1)?, vec![
+		(ispe(640, 480),                            false),
+		(nclx.clone(),                              false),
+		(icc.clone(),                               false),
+		(HeifItemProperty::Rotation { angle: 1 },   true),
+		(HeifItemProperty::Mirror   { axis:  0 },   true),
+		(clap.clone(),                              true),
+	]);
+
+	// Replacing a property of the same kind keeps its position
+	Metadata::write_heif_item_property(&HeifItemProperty::Rotation { angle: 3 }, true, &mut file_data, 1)?;
+	assert_eq!(properties(&file_data, 1)?[3], (HeifItemProperty::Rotation { angle: 3 }, true));
+	assert_eq!(properties(&file_data, 1)?.len(), 6);
+
+	// The shared ispe property is not changed for the other item
+	Metadata::write_heif_item_property(&ispe(320, 240), false, &mut file_data, 1)?;
+	assert_eq!(properties(&file_data, 1)?[0], (ispe(320, 240), false));
+	assert_eq!(properties(&file_data, 2)?, vec![(ispe(640, 480), false)]);
+
+	// Removing properties that are no longer used by any item
+	Metadata::remove_heif_item_property(&mut file_data, 1, "irot")?;
+	Metadata::remove_heif_item_property(&mut file_data, 2, "ispe")?;
+	assert!(!file_data.windows(4).any(|window| window == b"irot"));
+	assert_eq!(properties(&file_data, 2)?, vec![]);
+	assert_eq!(properties(&file_data, 1)?, vec![
+		(ispe(320, 240),                            false),
+		(nclx,                                      false),
+		(icc,                                       false),
+		(HeifItemProperty::Mirror { axis: 0 },      true),
+		(clap,                                      true),
+	]);
+
+	assert_eq!(read_heif_item(&file_data, 1)?, b"image one.");
+	assert_eq!(read_heif_item(&file_data, 2)?, b"image two.");
+
+	assert_eq!(
+		Metadata::write_heif_item_property(&HeifItemProperty::Rotation { angle: 4 }, true, &mut file_data, 1).unwrap_err().kind(),
+		std::io::ErrorKind::InvalidInput
+	);
+	assert_eq!(Metadata::read_heif_item_properties(&file_data, 9).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+	Ok(())
+}
+
+#[test]
+fn
+sync_xmp_jxl()
+-> Result<(), std::io::Error>
+{
+	sync_xmp_generic("tests/iso_no_exif.jxl", little_exif::filetype::FileExtension::JXL)
+}
+
+#[test]
+fn
+write_brotli_compressed_jxl()
+-> Result<(), std::io::Error>
+{
+	let file_extension = little_exif::filetype::FileExtension::JXL;
+	let mut image_data = read("tests/iso_no_exif.jxl")?;
+
+	let xmp = "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\
+		<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+		<rdf:Description rdf:about=\"\" xmlns:tiff=\"http://ns.adobe.com/tiff/1.0/\" tiff:Make=\"OldMake\"/>\
+		</rdf:RDF>\
+		</x:xmpmeta>";
+	Metadata::write_xmp(xmp.as_bytes(), &mut image_data, file_extension)?;
+	assert!(get_top_level_box_types(&image_data).contains(&"xml ".to_string()));
+
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::Make("NewMake".to_string()));
+	let mut options  = WriteOptions::default();
+	assert_eq!(options.jxl_box_compression, JxlBoxCompression::Uncompressed);
+	options.jxl_box_compression = JxlBoxCompression::Brotli;
+	metadata.write_to_vec_with_options(&mut image_data, file_extension, &options)?;
+
+	// Both the EXIF and the XMP data are now stored in brob boxes
+	let box_types = get_top_level_box_types(&image_data);
+	assert!(box_types.contains(&"brob:Exif".to_string()));
+	assert!(box_types.contains(&"brob:xml ".to_string()));
+	assert!(!box_types.contains(&"Exif".to_string()));
+	assert!(!box_types.contains(&"xml ".to_string()));
+
+	let read_metadata = Metadata::new_from_vec(&image_data, file_extension)?;
+	assert_eq!(read_metadata.get_tag(&ExifTag::Make(String::new())).next(), Some(&ExifTag::Make("NewMake".to_string())));
+	assert_eq!(Metadata::read_xmp(&image_data, file_extension)?, xmp.as_bytes());
+
+	// Writing XMP data keeps the compression of the existing box ...
+	Metadata::write_xmp(b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"/>", &mut image_data, file_extension)?;
+	assert!(get_top_level_box_types(&image_data).contains(&"brob:xml ".to_string()));
+	assert_eq!(Metadata::read_xmp(&image_data, file_extension)?, b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"/>");
+
+	// ... unless it is specified explicitly
+	Metadata::write_jxl_xmp(xmp.as_bytes(), &mut image_data, JxlBoxCompression::Uncompressed)?;
+	let box_types = get_top_level_box_types(&image_data);
+	assert!(box_types.contains(&"xml ".to_string()));
+	assert!(!box_types.contains(&"brob:xml ".to_string()));
+
+	// Writing uncompressed EXIF data does not decompress the XMP data
+	Metadata::write_jxl_xmp(xmp.as_bytes(), &mut image_data, JxlBoxCompression::Brotli)?;
+	options.jxl_box_compression = JxlBoxCompression::Uncompressed;
+	metadata.write_to_vec_with_options(&mut image_data, file_extension, &options)?;
+	let box_types = get_top_level_box_types(&image_data);
+	assert!(box_types.contains(&"Exif".to_string()));
+	assert!(box_types.contains(&"brob:xml ".to_string()));
+
+	Metadata::remove_xmp(&mut image_data, file_extension)?;
+	assert!(Metadata::read_xmp(&image_data, file_extension).is_err());
+	assert!(Metadata::new_from_vec(&image_data, file_extension).is_ok());
+
+	Ok(())
+}
+
+#[test]
+fn
+write_to_vec_naked_jxl()
+-> Result<(), std::io::Error>
+{
+	let file_extension = little_exif::filetype::FileExtension::NAKED_JXL;
+	let codestream     = read("tests/no_exif.jxl")?;
+	let mut image_data = codestream.clone();
+
+	// Nothing to clear in a naked codestream
+	Metadata::clear_metadata(&mut image_data, file_extension)?;
+	assert_eq!(image_data, codestream);
+
+	// Opting out of the conversion leaves the codestream untouched
+	let metadata    = get_test_metadata()?;
+	let mut options = WriteOptions::default();
+	assert!(options.jxl_wrap_codestream);
+	options.jxl_wrap_codestream = false;
+	let error = metadata.write_to_vec_with_options(&mut image_data, file_extension, &options).unwrap_err();
+	assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+	assert_eq!(image_data, codestream);
+
+	// Otherwise, the codestream gets wrapped with the EXIF box in front of it
+	options.jxl_wrap_codestream = true;
+	metadata.write_to_vec_with_options(&mut image_data, file_extension, &options)?;
+	assert_eq!(
+		get_top_level_box_types(&image_data),
+		vec!["JXL ", "ftyp", "Exif", "jxlc"]
+	);
+	assert_eq!(find_iso_box(&image_data, "jxlc").unwrap(), codestream.as_slice());
+
+	let read_metadata = Metadata::new_from_vec(&image_data, little_exif::filetype::FileExtension::JXL)?;
+	assert_eq!(
+		read_metadata.get_tag(&ExifTag::ImageDescription(String::new())).next(),
+		metadata.get_tag(&ExifTag::ImageDescription(String::new())).next()
+	);
+
+	Ok(())
+}
+
+#[test]
+fn
+write_to_vec_jxl_partial_codestream()
+-> Result<(), std::io::Error>
+{
+	let file_extension = little_exif::filetype::FileExtension::JXL;
+
+	// The EXIF box is located between the two jxlp boxes and gets replaced
+	// without changing the layout of the file
+	let mut image_data = read("tests/with_exif.jxl")?;
+	let metadata       = get_test_metadata()?;
+	metadata.write_to_vec(&mut image_data, file_extension)?;
+	assert_eq!(
+		get_top_level_box_types(&image_data),
+		vec!["JXL ", "ftyp", "jxlp", "Exif", "jxlp"]
+	);
+	assert!(Metadata::new_from_vec(&image_data, file_extension)?.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+
+	// The last box may extend to the end of the file by specifying a size of 0
+	let mut image_data = read("tests/iso_no_exif.jxl")?;
+	let jxlc_position  = image_data.len() - 77;
+	assert_eq!(&image_data[jxlc_position+4..jxlc_position+8], b"jxlc");
+	image_data[jxlc_position..jxlc_position+4].copy_from_slice(&[0, 0, 0, 0]);
+
+	metadata.write_to_vec(&mut image_data, file_extension)?;
+	assert_eq!(get_top_level_box_types(&image_data), vec!["JXL ", "ftyp", "Exif", "jxlc"]);
+	assert!(Metadata::new_from_vec(&image_data, file_extension)?.get_tag(&ExifTag::ImageDescription(String::new())).next().is_some());
+
+	Metadata::clear_metadata(&mut image_data, file_extension)?;
+	assert_eq!(get_top_level_box_types(&image_data), vec!["JXL ", "ftyp", "jxlc"]);
+
+	Ok(())
+}
+
+#[test]
+fn
+write_to_vec_jxl_jpeg_reconstruction()
+-> Result<(), std::io::Error>
+{
+	let file_extension = little_exif::filetype::FileExtension::JXL;
+
+	// Insert a (dummy) JPEG reconstruction box in front of the codestream
+	let mut image_data = read("tests/iso_no_exif.jxl")?;
+	let jxlc_position  = image_data.len() - 77;
+	image_data.splice(jxlc_position..jxlc_position, [0, 0, 0, 12, b'j', b'b', b'r', b'd', 1, 2, 3, 4]);
+
+	// By default, the metadata gets written anyway
+	let mut metadata = get_test_metadata()?;
+	let mut options  = WriteOptions::default();
+	assert_eq!(options.jpeg_reconstruction_policy, JpegReconstructionPolicy::Warn);
+	metadata.write_to_vec_with_options(&mut image_data, file_extension, &options)?;
+	assert_eq!(get_top_level_box_types(&image_data), vec!["JXL ", "ftyp", "Exif", "jbrd", "jxlc"]);
+
+	// Writing the same data again is fine, even if it gets compressed
+	options.jpeg_reconstruction_policy = JpegReconstructionPolicy::Error;
+	options.jxl_box_compression        = JxlBoxCompression::Brotli;
+	metadata.write_to_vec_with_options(&mut image_data, file_extension, &options)?;
+	assert_eq!(get_top_level_box_types(&image_data), vec!["JXL ", "ftyp", "brob:Exif", "jbrd", "jxlc"]);
+
+	// Changing the data however is refused and leaves the file untouched
+	let original_data = image_data.clone();
+	metadata.set_tag(ExifTag::ImageDescription("Changed".to_string()));
+	let error = metadata.write_to_vec_with_options(&mut image_data, file_extension, &options).unwrap_err();
+	assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+	assert_eq!(image_data, original_data);
+
+	// ... unless the reconstruction data gets removed
+	options.jpeg_reconstruction_policy = JpegReconstructionPolicy::RemoveReconstructionData;
+	metadata.write_to_vec_with_options(&mut image_data, file_extension, &options)?;
+	assert_eq!(get_top_level_box_types(&image_data), vec!["JXL ", "ftyp", "brob:Exif", "jxlc"]);
+	assert_eq!(
+		Metadata::new_from_vec(&image_data, file_extension)?.get_tag(&ExifTag::ImageDescription(String::new())).next(),
+		Some(&ExifTag::ImageDescription("Changed".to_string()))
+	);
+
+	Ok(())
+}
+
+#[test]
+fn
+read_camera_raw()
+-> Result<(), std::io::Error>
+{
+	use little_exif::filetype::FileExtension;
+
+	let table = [
+		(FileExtension::DNG, "dng", b"II*\0\0\0\0\0".to_vec()),
+		(FileExtension::NEF, "nef", b"MM\0*\0\0\0\0".to_vec()),
+		(FileExtension::ARW, "arw", b"II*\0\0\0\0\0".to_vec()),
+		(FileExtension::PEF, "pef", b"MM\0*\0\0\0\0".to_vec()),
+		(FileExtension::CR2, "cr2", b"II*\0\0\0\0\0CR\x02\0\0\0\0\0".to_vec()),
+		(FileExtension::ORF, "orf", b"IIRO\0\0\0\0".to_vec()),
+		(FileExtension::ORF, "orf", b"MMOR\0\0\0\0".to_vec()),
+		(FileExtension::RW2, "rw2", b"IIU\0\0\0\0\0".to_vec()),
+	];
+
+	for (file_type, extension, header) in table
+	{
+		let file_data = build_test_raw(&header);
+		let path      = format!("tests/raw_sample_copy.{extension}");
+		std::fs::write(&path, &file_data)?;
+
+		for metadata in [
+			Metadata::new_from_vec(&file_data, file_type)?,
+			Metadata::new_from_path(Path::new(&path))?,
+		]
+		{
+			assert_eq!(metadata.get_tag(&ExifTag::Make(String::new())).next(), Some(&ExifTag::Make("Maker".to_string())));
+			assert_eq!(metadata.get_tag(&ExifTag::Model(String::new())).next(), Some(&ExifTag::Model("Raw Model".to_string())));
+			assert_eq!(
+				metadata.get_tag(&ExifTag::DateTimeOriginal(String::new())).next(), 
+				Some(&ExifTag::DateTimeOriginal("2024:05:06 07:08:09".to_string()))
+			);
+			assert_eq!(metadata.get_tag(&ExifTag::LensModel(String::new())).next(), Some(&ExifTag::LensModel("EF50mm f/1.8".to_string())));
+			assert_eq!(metadata.get_tag(&ExifTag::GPSLatitudeRef(String::new())).next(), Some(&ExifTag::GPSLatitudeRef("N".to_string())));
+
+			// The image data is not read
+			assert_eq!(metadata.get_tag_by_hex(0x0111, None).count(), 0);
+			assert_eq!(metadata.get_tag_by_hex(0x014a, None).count(), 1);
+		}
+
+		remove_file(&path)?;
+	}
+
+	// Unknown variants of the TIFF header are rejected
+	let file_data = build_test_raw(b"IIXY\0\0\0\0");
+	assert!(Metadata::new_from_vec(&file_data, FileExtension::ORF).is_err());
+
+	Ok(())
+}
+
+#[test]
+fn
+write_camera_raw_in_place()
+-> Result<(), std::io::Error>
+{
+	use little_exif::filetype::FileExtension;
+
+	for header in [b"IIRO\0\0\0\0".to_vec(), b"MM\0*\0\0\0\0".to_vec()]
+	{
+		let file_data = build_test_raw(&header);
+		let image_data_range = header.len()..header.len()+64;
+
+		// Writing the unchanged metadata does not touch the file at all
+		let mut unchanged_data = file_data.clone();
+		Metadata::new_from_vec(&file_data, FileExtension::NEF)?.write_to_vec(&mut unchanged_data, FileExtension::NEF)?;
+		assert_eq!(unchanged_data, file_data);
+
+		let mut metadata = Metadata::new_from_vec(&file_data, FileExtension::NEF)?;
+		metadata.set_tag(ExifTag::Model("Raw".to_string()));
+		metadata.set_tag(ExifTag::Artist("little_exif".to_string()));
+		metadata.set_tag(ExifTag::LensModel("EF50mm f/1.8 II STM".to_string()));
+		metadata.set_tag(ExifTag::GPSLatitudeRef("S".to_string()));
+		metadata.set_tag(ExifTag::InteroperabilityIndex("R98".to_string()));
+		metadata.remove_tag(ExifTag::DateTimeOriginal(String::new()));
+
+		let mut edited_data = file_data.clone();
+		metadata.write_to_vec(&mut edited_data, FileExtension::NEF)?;
+
+		// The image data stays where it was, the new data gets appended
+		assert!(edited_data.len() > file_data.len());
+		assert_eq!(edited_data[image_data_range.clone()], file_data[image_data_range.clone()]);
+
+		let edited_metadata = Metadata::new_from_vec(&edited_data, FileExtension::NEF)?;
+		assert_eq!(edited_metadata.get_tag(&ExifTag::Make(String::new())).next(), Some(&ExifTag::Make("Maker".to_string())));
+		assert_eq!(edited_metadata.get_tag(&ExifTag::Model(String::new())).next(), Some(&ExifTag::Model("Raw".to_string())));
+		assert_eq!(edited_metadata.get_tag(&ExifTag::Artist(String::new())).next(), Some(&ExifTag::Artist("little_exif".to_string())));
+		assert_eq!(
+			edited_metadata.get_tag(&ExifTag::LensModel(String::new())).next(), 
+			Some(&ExifTag::LensModel("EF50mm f/1.8 II STM".to_string()))
+		);
+		assert_eq!(edited_metadata.get_tag(&ExifTag::GPSLatitudeRef(String::new())).next(), Some(&ExifTag::GPSLatitudeRef("S".to_string())));
+		assert_eq!(
+			edited_metadata.get_tag(&ExifTag::InteroperabilityIndex(String::new())).next(), 
+			Some(&ExifTag::InteroperabilityIndex("R98".to_string()))
+		);
+		assert_eq!(edited_metadata.get_tag(&ExifTag::DateTimeOriginal(String::new())).count(), 0);
+		assert_eq!(edited_metadata.get_tag_by_hex(0x014a, None).count(), 1);
+
+		// Writing the same metadata again changes nothing
+		let mut rewritten_data = edited_data.clone();
+		edited_metadata.write_to_vec(&mut rewritten_data, FileExtension::NEF)?;
+		assert_eq!(rewritten_data, edited_data);
+
+		// Tags in IFD0 get removed as well, except for those that describe
+		// the image data
+		let mut removed_metadata = edited_metadata.clone();
+		removed_metadata.remove_tag(ExifTag::Artist(String::new()));
+		removed_metadata.remove_tag(ExifTag::ImageWidth(Vec::new()));
+
+		let mut removed_data = edited_data.clone();
+		removed_metadata.write_to_vec(&mut removed_data, FileExtension::NEF)?;
+		assert_eq!(removed_data[image_data_range.clone()], file_data[image_data_range]);
+
+		let removed_metadata = Metadata::new_from_vec(&removed_data, FileExtension::NEF)?;
+		assert_eq!(removed_metadata.get_tag(&ExifTag::Artist(String::new())).count(), 0);
+		assert_eq!(removed_metadata.get_tag(&ExifTag::Model(String::new())).next(), Some(&ExifTag::Model("Raw".to_string())));
+		assert_eq!(removed_metadata.get_tag(&ExifTag::ImageWidth(Vec::new())).next(), Some(&ExifTag::ImageWidth(vec![8])));
+		assert_eq!(removed_metadata.get_tag_by_hex(0x014a, None).count(), 1);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn
+write_camera_raw_file_in_place()
+-> Result<(), std::io::Error>
+{
+	let file_data = build_test_raw(b"IIU\0\0\0\0\0");
+	let path      = Path::new("tests/raw_sample_copy_write.rw2");
+	std::fs::write(path, &file_data)?;
+
+	// Tags that are missing in the metadata get removed ...
+	let mut metadata = Metadata::new();
+	metadata.set_tag(ExifTag::Copyright("CC BY 4.0".to_string()));
+	metadata.write_to_file(path)?;
+
+	let edited_metadata = Metadata::new_from_path(path)?;
+	assert_eq!(edited_metadata.get_tag(&ExifTag::Make(String::new())).count(), 0);
+	assert_eq!(edited_metadata.get_tag(&ExifTag::Copyright(String::new())).next(), Some(&ExifTag::Copyright("CC BY 4.0".to_string())));
+
+	// ... except for the ones describing the image data
+	assert_eq!(edited_metadata.get_tag(&ExifTag::ImageWidth(Vec::new())).next(), Some(&ExifTag::ImageWidth(vec![8])));
+	assert_eq!(edited_metadata.get_tag_by_hex(0x014a, None).count(), 1);
+	assert_eq!(read(path)?[8..72], file_data[8..72]);
+
+	// This includes the tags in the SubIFDs
+	assert_eq!(edited_metadata.get_tag(&ExifTag::LensModel(String::new())).count(), 0);
+	assert_eq!(edited_metadata.get_tag(&ExifTag::GPSLatitudeRef(String::new())).count(), 0);
+
+	remove_file(path)?;
+	Ok(())
+}
+
+#[test]
+fn
+read_cr3()
+-> Result<(), std::io::Error>
+{
+	use little_exif::filetype::FileExtension;
+
+	let file_data = build_test_cr3();
+	let path      = Path::new("tests/cr3_sample_copy.cr3");
+	std::fs::write(path, &file_data)?;
+
+	assert_eq!(FileExtension::auto_detect(&mut std::io::Cursor::new(&file_data)), Some(FileExtension::CR3));
+
+	for metadata in [
+		Metadata::new_from_vec(&file_data, FileExtension::CR3)?,
+		Metadata::new_from_path(path)?,
+	]
+	{
+		assert_eq!(metadata.get_tag(&ExifTag::Make(String::new())).next(), Some(&ExifTag::Make("Canon".to_string())));
+		assert_eq!(metadata.get_tag(&ExifTag::Model(String::new())).next(), Some(&ExifTag::Model("EOS R5 II".to_string())));
+		assert_eq!(
+			metadata.get_tag(&ExifTag::DateTimeOriginal(String::new())).next(), 
+			Some(&ExifTag::DateTimeOriginal("2024:05:06 07:08:09".to_string()))
+		);
+		assert_eq!(metadata.get_tag(&ExifTag::GPSLatitudeRef(String::new())).next(), Some(&ExifTag::GPSLatitudeRef("N".to_string())));
+		assert_eq!(
+			metadata.get_tag(&ExifTag::MakerNote(Vec::new())).next(), 
+			Some(&ExifTag::MakerNote(b"II*\0\x08\0\0\0\0\0\0\0\0\0".to_vec()))
+		);
+	}
+
+	remove_file(path)?;
+	Ok(())
+}
+
+#[test]
+fn
+write_cr3()
+-> Result<(), std::io::Error>
+{
+	use little_exif::filetype::FileExtension;
+
+	let file_data = build_test_cr3();
+	let mdat      = find_iso_box(&file_data, "mdat").unwrap().to_vec();
+
+	// Writing the unchanged metadata does not touch the file at all
+	let mut unchanged_data = file_data.clone();
+	Metadata::new_from_vec(&file_data, FileExtension::CR3)?.write_to_vec(&mut unchanged_data, FileExtension::CR3)?;
+	assert_eq!(unchanged_data, file_data);
+
+	// Changes that fit into the CMT boxes are written in place
+	let mut metadata = Metadata::new_from_vec(&file_data, FileExtension::CR3)?;
+	metadata.set_tag(ExifTag::Artist("little_exif".to_string()));
+	metadata.set_tag(ExifTag::GPSLatitudeRef("S".to_string()));
+
+	let mut edited_data = file_data.clone();
+	metadata.write_to_vec(&mut edited_data, FileExtension::CR3)?;
+	assert_eq!(edited_data.len(), file_data.len());
+	assert_eq!(find_iso_box(&edited_data, "mdat").unwrap(), mdat);
+
+	let edited_metadata = Metadata::new_from_vec(&edited_data, FileExtension::CR3)?;
+	assert_eq!(edited_metadata.get_tag(&ExifTag::Model(String::new())).next(), Some(&ExifTag::Model("EOS R5 II".to_string())));
+	assert_eq!(edited_metadata.get_tag(&ExifTag::Artist(String::new())).next(), Some(&ExifTag::Artist("little_exif".to_string())));
+	assert_eq!(edited_metadata.get_tag(&ExifTag::GPSLatitudeRef(String::new())).next(), Some(&ExifTag::GPSLatitudeRef("S".to_string())));
+
+	// Changes that don't fit result in an error, leaving the file as it is
+	metadata.set_tag(ExifTag::LensModel("RF24-105mm F4 L IS USM".to_string()));
+	let mut unfitting_data = edited_data.clone();
+	assert!(metadata.write_to_vec(&mut unfitting_data, FileExtension::CR3).is_err());
+	assert_eq!(unfitting_data, edited_data);
+
+	Ok(())
+}
+
+/// Checks that the chunk offsets in stco point to the chunks in mdat
+fn
+assert_quicktime_chunks
+(
+	file_data: &[u8]
+)
+-> Result<(), std::io::Error>
+{
+	let stco = find_iso_box(file_data, "stco").ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "No stco box"))?;
+	for (index, chunk) in [[0x11; 16], [0x22; 16]].iter().enumerate()
+	{
+		let position = 8 + 4 * index;
+		let offset   = u32::from_be_bytes([stco[position], stco[position+1], stco[position+2], stco[position+3]]) as usize;
+		assert_eq!(&file_data[offset..offset+16], chunk);
+	}
+	Ok(())
+}
+
+#[test]
+fn
+read_quicktime()
+-> Result<(), std::io::Error>
+{
+	use little_exif::filetype::FileExtension;
+	use little_exif::metadata::quicktime_io::QuickTimeMetadata;
+
+	let file_data = build_test_quicktime(b"qt  ", true);
+	let path      = Path::new("tests/quicktime_sample_copy.mov");
+	std::fs::write(path, &file_data)?;
+
+	assert_eq!(FileExtension::auto_detect(&mut std::io::Cursor::new(&file_data)), Some(FileExtension::MOV));
+
+	let expected = QuickTimeMetadata {
+		creation_time:      Some(3_000_000_000),
+		location:           Some("+48.2082+016.3738+171.000/".to_string()),
+		creation_date:      None,
+		content_identifier: Some("8A7B2C1D-TEST".to_string()),
+	};
+	assert_eq!(Metadata::read_quicktime_metadata(&file_data)?, expected);
+	assert_eq!(Metadata::file_read_quicktime_metadata(path)?, expected);
+
+	// MP4 files without any metadata besides mvhd
+	let mp4_data = build_test_quicktime(b"mp42", false);
+	assert_eq!(FileExtension::auto_detect(&mut std::io::Cursor::new(&mp4_data)), Some(FileExtension::MP4));
+	assert_eq!(
+		Metadata::read_quicktime_metadata(&mp4_data)?, 
+		QuickTimeMetadata { creation_time: Some(3_000_000_000), ..Default::default() }
+	);
+
+	// The QuickTime functions are only available for MOV and MP4 files
+	assert!(Metadata::file_read_quicktime_metadata(Path::new("tests/sample2.jpg")).is_err());
+
+	remove_file(path)?;
+	Ok(())
+}
+
+#[test]
+fn
+write_quicktime()
+-> Result<(), std::io::Error>
+{
+	use little_exif::metadata::quicktime_io::QuickTimeMetadata;
+
+	let file_data = build_test_quicktime(b"qt  ", true);
+	let metadata  = Metadata::read_quicktime_metadata(&file_data)?;
+
+	// Writing the unchanged metadata does not touch the file at all
+	let mut unchanged_data = file_data.clone();
+	Metadata::write_quicktime_metadata(&metadata, &mut unchanged_data)?;
+	assert_eq!(unchanged_data, file_data);
+
+	// Changing the size of moov moves mdat, so the chunk offsets get updated
+	let new_metadata = QuickTimeMetadata {
+		creation_time:      Some(3_100_000_000),
+		location:           Some("+40.6892-074.0445/".to_string()),
+		creation_date:      Some("2024-08-28T18:42:55+0200".to_string()),
+		content_identifier: None,
+	};
+
+	let mut edited_data = file_data.clone();
+	Metadata::write_quicktime_metadata(&new_metadata, &mut edited_data)?;
+	assert_ne!(edited_data.len(), file_data.len());
+	assert_eq!(Metadata::read_quicktime_metadata(&edited_data)?, new_metadata);
+	assert_quicktime_chunks(&edited_data)?;
+
+	// Removing the content identifier key moves the location item down
+	let ilst = find_iso_box(&edited_data, "ilst").unwrap();
+	assert_eq!(&ilst[4..8], b"\0\0\0\x01");
+	assert_eq!(find_iso_box(&edited_data, "keys").unwrap()[4..8], 1u32.to_be_bytes());
+
+	// The creation time must fit into the version 0 mvhd
+	let mut too_late = new_metadata.clone();
+	too_late.creation_time = Some(u32::MAX as u64 + 1);
+	let mut unfitting_data = file_data.clone();
+	assert!(Metadata::write_quicktime_metadata(&too_late, &mut unfitting_data).is_err());
+	assert_eq!(unfitting_data, file_data);
+
+	// Files without metadata get the location and date in udta and the
+	// content identifier in a new meta box
+	let mut mp4_data = build_test_quicktime(b"mp42", false);
+	let mp4_metadata = QuickTimeMetadata {
+		creation_time:      None,
+		location:           Some("+48.2082+016.3738/".to_string()),
+		creation_date:      None,
+		content_identifier: Some("8A7B2C1D-TEST".to_string()),
+	};
+	Metadata::write_quicktime_metadata(&mp4_metadata, &mut mp4_data)?;
+	assert_eq!(
+		Metadata::read_quicktime_metadata(&mp4_data)?, 
+		QuickTimeMetadata { creation_time: Some(3_000_000_000), ..mp4_metadata }
+	);
+	assert_quicktime_chunks(&mp4_data)?;
+
+	// Fragmented files may only be edited if moov keeps its size
+	let mut fragmented_data = file_data.clone();
+	fragmented_data.extend(encode_iso_box("moof", None, &[]));
+	let mut same_size_metadata = metadata.clone();
+	same_size_metadata.location = Some("+48.2082+016.3738+172.000/".to_string());
+	assert!(Metadata::write_quicktime_metadata(&new_metadata, &mut fragmented_data).is_err());
+	Metadata::write_quicktime_metadata(&same_size_metadata, &mut fragmented_data)?;
+	assert_eq!(Metadata::read_quicktime_metadata(&fragmented_data)?, same_size_metadata);
+
+	Ok(())
+}
+
+#[test]
+fn
+write_quicktime_file()
+-> Result<(), std::io::Error>
+{
+	let file_data = build_test_quicktime(b"qt  ", true);
+	let path      = Path::new("tests/quicktime_sample_copy2.mov");
+	std::fs::write(path, &file_data)?;
+
+	// If moov keeps its size, it gets overwritten in place without
+	// touching the mdat that follows it
+	let mut metadata = Metadata::file_read_quicktime_metadata(path)?;
+	metadata.location = Some("+48.2082+016.3738+172.000/".to_string());
+	Metadata::file_write_quicktime_metadata(&metadata, path)?;
+
+	let edited_data = read(path)?;
+	assert_eq!(edited_data.len(), file_data.len());
+	assert_eq!(Metadata::read_quicktime_metadata(&edited_data)?, metadata);
+	assert_quicktime_chunks(&edited_data)?;
+
+	metadata.creation_date = Some("2024-08-28T18:42:55+0200".to_string());
+	Metadata::file_write_quicktime_metadata(&metadata, path)?;
+
+	let edited_data = read(path)?;
+	assert_eq!(Metadata::read_quicktime_metadata(&edited_data)?, metadata);
+	assert_quicktime_chunks(&edited_data)?;
+
+	remove_file(path)?;
+	Ok(())
+}